@@ -0,0 +1,148 @@
+// Measures how long every processor and the whole chain take per block
+// Run with `cargo bench`, criterion keeps the previous results around and reports any
+// change against them, so a slowdown shows up right away
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+
+use ret_gain::{
+    ChainSettings, ChaosAttractor, Compressor, Crusher, DcBlocker, Delay, Distortion, EffectChain, Filter, FilterMode,
+    FractalMagic, Gate, Integrator, Limiter, Oversampler, Oversampling, Reverb,
+};
+use ret_gain::test_signals::white_noise;
+
+/// The sample rate every processor runs at
+const SAMPLE_RATE: f32 = 44100.0;
+
+/// The block sizes every benchmark runs at, from a low latency setting to offline rendering
+const BLOCK_SIZES: [usize; 3] = [64, 512, 4096];
+
+/// A block of white noise, so no processor can take a shortcut on silence
+fn noise_block(len: usize) -> Vec<f32> {
+    white_noise(len, 0.5, 0x1234_5678)
+}
+
+/// Benchmark a per-sample processing function at every block size
+// The throughput is set to the block size, so criterion also reports the cost per sample
+fn bench_processor(c: &mut Criterion, name: &str, mut process: impl FnMut(f32) -> f32) {
+    let mut group = c.benchmark_group(name);
+    for block_size in BLOCK_SIZES {
+        let input = noise_block(block_size);
+        let mut block = input.clone();
+        group.throughput(Throughput::Elements(block_size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(block_size), &block_size, |b, _| {
+            b.iter(|| {
+                block.copy_from_slice(&input);
+                for sample in block.iter_mut() {
+                    *sample = process(*sample);
+                }
+                black_box(&block);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn processors(c: &mut Criterion) {
+    let mut distortion = Distortion::new();
+    distortion.set_sample_rate(SAMPLE_RATE);
+    bench_processor(c, "distortion", |sample| distortion.process(0, sample, 10.0));
+
+    let mut filter = Filter::new();
+    filter.set_sample_rate(SAMPLE_RATE);
+    filter.set_mode(FilterMode::LowPass);
+    bench_processor(c, "filter", |sample| filter.process(0, sample, 2000.0, 0.5));
+
+    let mut fractal = FractalMagic::new();
+    fractal.set_sample_rate(SAMPLE_RATE);
+    bench_processor(c, "fractal", |sample| fractal.process(0, sample, 0.7));
+
+    let mut chaos = ChaosAttractor::new();
+    chaos.set_sample_rate(SAMPLE_RATE);
+    bench_processor(c, "chaos_euler", |sample| chaos.process(0, sample, 0.7));
+
+    let mut chaos = ChaosAttractor::new();
+    chaos.set_sample_rate(SAMPLE_RATE);
+    chaos.set_integrator(Integrator::RungeKutta4);
+    bench_processor(c, "chaos_rk4", |sample| chaos.process(0, sample, 0.7));
+
+    let mut crusher = Crusher::new();
+    bench_processor(c, "crusher", |sample| crusher.process(0, sample, 8.0, 4.0));
+
+    let mut gate = Gate::new();
+    gate.set_sample_rate(SAMPLE_RATE);
+    gate.set_threshold_db(-40.0);
+    gate.set_times(1.0, 10.0, 50.0);
+    bench_processor(c, "gate", |sample| sample * gate.process(sample.abs()));
+
+    let mut compressor = Compressor::new();
+    compressor.set_sample_rate(SAMPLE_RATE);
+    compressor.set_curve(-20.0, 4.0);
+    compressor.set_times(5.0, 100.0);
+    bench_processor(c, "compressor", |sample| sample * compressor.process(sample.abs(), 0.0));
+
+    let mut delay = Delay::new();
+    delay.set_sample_rate(SAMPLE_RATE);
+    bench_processor(c, "delay", |sample| delay.process(0, sample, 0.5, 0.5, 0.5, 0.0));
+
+    let mut reverb = Reverb::new();
+    reverb.set_sample_rate(SAMPLE_RATE);
+    bench_processor(c, "reverb", |sample| reverb.process(0, sample, 0.7, 0.5, 0.5));
+
+    let mut dc_blocker = DcBlocker::new();
+    dc_blocker.set_sample_rate(SAMPLE_RATE);
+    bench_processor(c, "dc_blocker", |sample| dc_blocker.process(0, sample));
+
+    let mut limiter = Limiter::new();
+    limiter.set_sample_rate(SAMPLE_RATE);
+    bench_processor(c, "limiter", |sample| {
+        let mut frame = [sample];
+        limiter.process(&mut frame);
+        frame[0]
+    });
+}
+
+/// The whole chain the way the plugin runs it for one channel
+// The effect chain inside the oversampler, with its governor and the coupling, then the crusher,
+// delay, reverb, DC blocker and limiter. This is where any per-sample overhead in the
+// glue between the stages shows up.
+fn chain(c: &mut Criterion) {
+    for oversampling in [Oversampling::Off, Oversampling::X2, Oversampling::X8] {
+        let oversampled_rate = SAMPLE_RATE * oversampling.factor() as f32;
+        let mut oversampler = Oversampler::new(oversampling);
+        let mut effects = EffectChain::new();
+        effects.set_sample_rate(oversampled_rate);
+        effects.filter.set_mode(FilterMode::LowPass);
+        let settings = ChainSettings {
+            drive: 10.0,
+            filter_cutoff: 8000.0,
+            filter_resonance: 0.3,
+            magic: 0.5,
+            chaos: 0.5,
+            coupling: 0.5,
+            ..ChainSettings::default()
+        };
+        let mut crusher = Crusher::new();
+        let mut delay = Delay::new();
+        delay.set_sample_rate(SAMPLE_RATE);
+        let mut reverb = Reverb::new();
+        reverb.set_sample_rate(SAMPLE_RATE);
+        let mut dc_blocker = DcBlocker::new();
+        dc_blocker.set_sample_rate(SAMPLE_RATE);
+        let mut limiter = Limiter::new();
+        limiter.set_sample_rate(SAMPLE_RATE);
+
+        let name = format!("chain_{oversampling:?}").to_lowercase();
+        bench_processor(c, &name, |sample| {
+            let sample = oversampler.process(0, sample, |sample| effects.process(0, sample, &settings).0);
+            let sample = crusher.process(0, sample, 12.0, 1.0);
+            let sample = delay.process(0, sample, 0.4, 0.3, 0.5, effects.chaos_attractor.signal(0));
+            let sample = reverb.process(0, sample, 0.6, 0.5, 0.2);
+            let mut frame = [dc_blocker.process(0, sample)];
+            limiter.process(&mut frame);
+            frame[0]
+        });
+    }
+}
+
+criterion_group!(benches, processors, chain);
+criterion_main!(benches);
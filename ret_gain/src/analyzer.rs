@@ -0,0 +1,585 @@
+// AtomicF32 lets the audio thread share the sample rate with the GUI
+use atomic_float::AtomicF32;
+// realfft computes the FFT of real-valued (audio) signals
+use realfft::num_complex::Complex32;
+use realfft::{RealFftPlanner, RealToComplex};
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Create a connected pair of ring publisher halves that hold `len` items
+// The publisher goes to the audio thread and the subscriber to the GUI. The publisher writes
+// every item into a ring buffer, and after every `interval` items it hands the whole ring to
+// the subscriber, oldest item first. Every view below is built on a pair of these, and only
+// adds how it turns the audio into items and how it reads them back.
+fn ring_publisher<T: Copy + Send>(len: usize, interval: usize, initial: T) -> (RingPublisher<T>, RingSubscriber<T>) {
+    // A triple buffer lets the audio thread publish without ever waiting for the GUI
+    let (input, output) = triple_buffer::TripleBuffer::new(&vec![initial; len]).split();
+    let generation = Arc::new(AtomicUsize::new(0));
+
+    let publisher = RingPublisher {
+        ring: vec![initial; len],
+        write_pos: 0,
+        interval,
+        items_until_publish: interval,
+        input,
+        generation: generation.clone(),
+    };
+    let subscriber = RingSubscriber { output, generation };
+
+    (publisher, subscriber)
+}
+
+/// The audio thread half of a ring publisher
+struct RingPublisher<T: Send> {
+    /// The most recent items, written in a circle
+    ring: Vec<T>,
+    /// Where the next item goes in the ring buffer
+    write_pos: usize,
+    /// How many items are collected between two publishes
+    interval: usize,
+    /// Items left until the ring is published
+    items_until_publish: usize,
+    /// The sending side of the triple buffer
+    input: triple_buffer::Input<Vec<T>>,
+    /// Incremented every time the ring is published
+    generation: Arc<AtomicUsize>,
+}
+
+impl<T: Copy + Send> RingPublisher<T> {
+    /// Add an item to the ring, and publish the ring if enough of them came in since the last time
+    // Both buffers were allocated up front and publishing only swaps the triple buffer's
+    // indices, so this never allocates or blocks and is safe to call from `process()`
+    fn push(&mut self, item: T) {
+        self.ring[self.write_pos] = item;
+        self.write_pos = (self.write_pos + 1) % self.ring.len();
+
+        self.items_until_publish -= 1;
+        if self.items_until_publish == 0 {
+            self.items_until_publish = self.interval;
+
+            // Unroll the ring buffer so the oldest item comes first
+            let published = self.input.input_buffer();
+            let (newest, oldest) = self.ring.split_at(self.write_pos);
+            published[..oldest.len()].copy_from_slice(oldest);
+            published[oldest.len()..].copy_from_slice(newest);
+            self.input.publish();
+
+            self.generation.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The GUI half of a ring publisher
+struct RingSubscriber<T: Send> {
+    /// The receiving side of the triple buffer
+    output: triple_buffer::Output<Vec<T>>,
+    /// Incremented by the audio thread every time the ring is published
+    generation: Arc<AtomicUsize>,
+}
+
+impl<T: Copy + Send> RingSubscriber<T> {
+    /// A counter that changes whenever new items are available, used to trigger redraws
+    fn generation(&self) -> Arc<AtomicUsize> {
+        self.generation.clone()
+    }
+
+    /// The latest published items, oldest first
+    fn read(&mut self) -> &[T] {
+        self.output.read()
+    }
+}
+
+/// The number of samples used for every spectrum measurement
+// 2048 samples gives a frequency resolution of about 21 Hz at 44.1 kHz
+pub const SPECTRUM_WINDOW_SIZE: usize = 2048;
+
+/// How many new samples are collected before a new window is sent to the GUI
+// A 75% overlap between windows keeps the display smooth without too much copying
+const PUBLISH_INTERVAL: usize = SPECTRUM_WINDOW_SIZE / 4;
+
+/// The number of frequency bins produced by the FFT
+const NUM_BINS: usize = SPECTRUM_WINDOW_SIZE / 2 + 1;
+
+/// The floor of the spectrum in decibels, anything quieter is shown at this level
+pub const SPECTRUM_FLOOR_DB: f32 = -120.0;
+
+/// How quickly the displayed spectrum falls back after a peak, per GUI frame (0.0 to 1.0)
+const DISPLAY_DECAY: f32 = 0.7;
+
+/// Create a connected pair of spectrum analyzer halves
+pub fn spectrum_analyzer() -> (SpectrumInput, SpectrumOutput) {
+    let (publisher, subscriber) = ring_publisher(SPECTRUM_WINDOW_SIZE, PUBLISH_INTERVAL, 0.0);
+    let sample_rate = Arc::new(AtomicF32::new(44100.0));
+
+    let mut planner = RealFftPlanner::new();
+    let fft = planner.plan_fft_forward(SPECTRUM_WINDOW_SIZE);
+
+    // A Hann window reduces spectral leakage between bins
+    let window: Vec<f32> = (0..SPECTRUM_WINDOW_SIZE)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / SPECTRUM_WINDOW_SIZE as f32).cos())
+        .collect();
+    // The amplitude of a full scale sine wave after windowing, used to normalize to dBFS
+    let window_gain = window.iter().sum::<f32>() / 2.0;
+
+    let spectrum_input = SpectrumInput {
+        samples: publisher,
+        sample_rate: sample_rate.clone(),
+    };
+    let spectrum_output = SpectrumOutput {
+        samples: subscriber,
+        sample_rate,
+        fft_input: fft.make_input_vec(),
+        fft_output: fft.make_output_vec(),
+        fft,
+        window,
+        window_gain,
+        magnitudes_db: vec![SPECTRUM_FLOOR_DB; NUM_BINS],
+    };
+
+    (spectrum_input, spectrum_output)
+}
+
+/// The audio thread half of the spectrum analyzer
+pub struct SpectrumInput {
+    /// The most recent samples, published as a window every `PUBLISH_INTERVAL` samples
+    samples: RingPublisher<f32>,
+    /// The current sample rate, needed by the GUI to place the bins on the frequency axis
+    sample_rate: Arc<AtomicF32>,
+}
+
+impl SpectrumInput {
+    /// Tell the GUI which sample rate the incoming samples are at
+    pub fn set_sample_rate(&self, sample_rate: f32) {
+        self.sample_rate.store(sample_rate, Ordering::Relaxed);
+    }
+
+    /// Add a sample to the analyzer
+    pub fn push(&mut self, sample: f32) {
+        self.samples.push(sample);
+    }
+}
+
+/// The GUI half of the spectrum analyzer
+// Reads the latest window from the audio thread and turns it into a magnitude spectrum
+pub struct SpectrumOutput {
+    /// The latest window of samples, oldest first
+    samples: RingSubscriber<f32>,
+    /// The sample rate the audio thread is running at
+    sample_rate: Arc<AtomicF32>,
+
+    /// The FFT algorithm, planned once up front
+    fft: Arc<dyn RealToComplex<f32>>,
+    /// Scratch buffer holding the windowed samples
+    fft_input: Vec<f32>,
+    /// Scratch buffer holding the FFT result
+    fft_output: Vec<Complex32>,
+    /// The Hann window applied before the FFT
+    window: Vec<f32>,
+    /// The gain of the window, used to normalize the magnitudes
+    window_gain: f32,
+    /// The smoothed magnitude of every bin in decibels
+    magnitudes_db: Vec<f32>,
+}
+
+impl SpectrumOutput {
+    /// The sample rate the analyzed audio is at
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate.load(Ordering::Relaxed)
+    }
+
+    /// A counter that changes whenever a new window is available, used to trigger redraws
+    pub fn generation(&self) -> Arc<AtomicUsize> {
+        self.samples.generation()
+    }
+
+    /// Analyze the latest window and return the magnitude of every bin in decibels
+    // The result is smoothed with a fast attack and a slower fall back so it's easier to read
+    pub fn compute(&mut self) -> &[f32] {
+        let samples = self.samples.read();
+        for ((input, sample), window) in self.fft_input.iter_mut().zip(samples.iter()).zip(self.window.iter()) {
+            *input = sample * window;
+        }
+
+        // The buffers always have the sizes the FFT was planned with, so this can't fail
+        if self.fft.process(&mut self.fft_input, &mut self.fft_output).is_ok() {
+            for (magnitude_db, bin) in self.magnitudes_db.iter_mut().zip(self.fft_output.iter()) {
+                let new_db = (20.0 * (bin.norm() / self.window_gain).max(1e-7).log10())
+                    .max(SPECTRUM_FLOOR_DB);
+                *magnitude_db = if new_db > *magnitude_db {
+                    new_db
+                } else {
+                    *magnitude_db * DISPLAY_DECAY + new_db * (1.0 - DISPLAY_DECAY)
+                };
+            }
+        }
+
+        &self.magnitudes_db
+    }
+}
+
+/// How long the correlation meter averages over, in milliseconds
+const CORRELATION_TIME_MS: f32 = 300.0;
+
+/// Measures how similar the left and right channels are
+// +1 means both channels are identical (mono), 0 means they are unrelated, and
+// -1 means they cancel out when summed to mono. Anything clearly below zero will
+// sound thin or disappear on mono playback systems.
+pub struct CorrelationMeter {
+    /// Running average of left * right
+    left_right: f32,
+    /// Running average of left * left
+    left_left: f32,
+    /// Running average of right * right
+    right_right: f32,
+    /// Coefficient of the running averages
+    coeff: f32,
+}
+
+impl CorrelationMeter {
+    /// Create a new correlation meter
+    pub fn new() -> Self {
+        let mut meter = Self {
+            left_right: 0.0,
+            left_left: 0.0,
+            right_right: 0.0,
+            coeff: 0.0,
+        };
+        meter.set_sample_rate(44100.0); // Default sample rate, will be updated
+
+        meter
+    }
+
+    /// Set the sample rate for the averaging time
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.coeff = 1.0 - (-1.0 / ((CORRELATION_TIME_MS / 1000.0) * sample_rate)).exp();
+    }
+
+    /// Forget the previous measurements
+    pub fn reset(&mut self) {
+        self.left_right = 0.0;
+        self.left_left = 0.0;
+        self.right_right = 0.0;
+    }
+
+    /// Add a stereo frame to the measurement
+    pub fn push(&mut self, left: f32, right: f32) {
+        self.left_right += (left * right - self.left_right) * self.coeff;
+        self.left_left += (left * left - self.left_left) * self.coeff;
+        self.right_right += (right * right - self.right_right) * self.coeff;
+    }
+
+    /// The current correlation between -1.0 and 1.0
+    // Silence counts as perfectly mono compatible
+    pub fn correlation(&self) -> f32 {
+        let energy = (self.left_left * self.right_right).sqrt();
+        if energy > 1e-10 {
+            (self.left_right / energy).clamp(-1.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// How long the level difference meter averages over, in milliseconds
+// Longer than the correlation meter, so the readout is steady enough to level-match with
+const LEVEL_DIFFERENCE_TIME_MS: f32 = 600.0;
+
+/// Levels below this are treated as silence by the level difference meter
+const LEVEL_DIFFERENCE_FLOOR: f32 = 1e-8;
+
+/// Measures how much louder or quieter the output is than the input
+// Both sides are averaged as RMS levels, so the result is the effective gain the whole
+// chain adds. Bring it to 0 dB with the output gain for a fair comparison with bypass.
+pub struct LevelDifferenceMeter {
+    /// Running average of the squared input
+    input_power: f32,
+    /// Running average of the squared output
+    output_power: f32,
+    /// Coefficient of the running averages
+    coeff: f32,
+}
+
+impl LevelDifferenceMeter {
+    /// Create a new level difference meter
+    pub fn new() -> Self {
+        let mut meter = Self {
+            input_power: 0.0,
+            output_power: 0.0,
+            coeff: 0.0,
+        };
+        meter.set_sample_rate(44100.0); // Default sample rate, will be updated
+
+        meter
+    }
+
+    /// Set the sample rate for the averaging time
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.coeff = 1.0 - (-1.0 / ((LEVEL_DIFFERENCE_TIME_MS / 1000.0) * sample_rate)).exp();
+    }
+
+    /// Forget the previous measurements
+    pub fn reset(&mut self) {
+        self.input_power = 0.0;
+        self.output_power = 0.0;
+    }
+
+    /// Add the mean squared input and output of a frame to the measurement
+    pub fn push(&mut self, input_power: f32, output_power: f32) {
+        self.input_power += (input_power - self.input_power) * self.coeff;
+        self.output_power += (output_power - self.output_power) * self.coeff;
+    }
+
+    /// The output level relative to the input level, in decibels
+    // Reads 0 dB while either side is silent, since there's nothing to compare
+    pub fn difference_db(&self) -> f32 {
+        if self.input_power > LEVEL_DIFFERENCE_FLOOR && self.output_power > LEVEL_DIFFERENCE_FLOOR {
+            10.0 * (self.output_power / self.input_power).log10()
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The number of points of the attractor path shown in the GUI
+const ATTRACTOR_POINTS: usize = 512;
+
+/// Only every n-th state of the attractor is recorded
+// The attractor moves very little between two samples, so this stretches the
+// displayed path over a longer stretch of time
+const ATTRACTOR_DECIMATION: usize = 16;
+
+/// How many new points are collected before the path is sent to the GUI
+const ATTRACTOR_PUBLISH_INTERVAL: usize = 32;
+
+/// Create a connected pair of attractor scope halves
+pub fn attractor_scope() -> (AttractorInput, AttractorOutput) {
+    let (publisher, subscriber) = ring_publisher(ATTRACTOR_POINTS, ATTRACTOR_PUBLISH_INTERVAL, [0.0; 3]);
+
+    let attractor_input = AttractorInput {
+        path: publisher,
+        samples_until_point: ATTRACTOR_DECIMATION,
+    };
+    let attractor_output = AttractorOutput { path: subscriber };
+
+    (attractor_input, attractor_output)
+}
+
+/// The audio thread half of the attractor scope
+// Records the recent path of the chaos effect's Lorenz system
+pub struct AttractorInput {
+    /// The most recent (x, y, z) states, published as a path every `ATTRACTOR_PUBLISH_INTERVAL` points
+    path: RingPublisher<[f32; 3]>,
+    /// Samples left until the next point is recorded
+    samples_until_point: usize,
+}
+
+impl AttractorInput {
+    /// Add the attractor's state for the current sample
+    pub fn push(&mut self, state: [f32; 3]) {
+        self.samples_until_point -= 1;
+        if self.samples_until_point > 0 {
+            return;
+        }
+        self.samples_until_point = ATTRACTOR_DECIMATION;
+
+        self.path.push(state);
+    }
+}
+
+/// The GUI half of the attractor scope
+pub struct AttractorOutput {
+    /// The latest recorded path
+    path: RingSubscriber<[f32; 3]>,
+}
+
+impl AttractorOutput {
+    /// A counter that changes whenever a new path is available, used to trigger redraws
+    pub fn generation(&self) -> Arc<AtomicUsize> {
+        self.path.generation()
+    }
+
+    /// The latest recorded (x, y, z) path, oldest point first
+    pub fn path(&mut self) -> &[[f32; 3]] {
+        self.path.read()
+    }
+}
+
+/// The number of frames the oscilloscope records
+// About 23 ms at 44.1 kHz, twice as long as what's shown so there's room to find a trigger point
+const SCOPE_WINDOW_SIZE: usize = 1024;
+
+/// The number of frames the oscilloscope shows
+pub const SCOPE_DISPLAY_SIZE: usize = SCOPE_WINDOW_SIZE / 2;
+
+/// How many new frames are collected before the recording is sent to the GUI
+const SCOPE_PUBLISH_INTERVAL: usize = SCOPE_WINDOW_SIZE / 2;
+
+/// Create a connected pair of oscilloscope halves
+pub fn oscilloscope() -> (ScopeInput, ScopeOutput) {
+    let (publisher, subscriber) = ring_publisher(SCOPE_WINDOW_SIZE, SCOPE_PUBLISH_INTERVAL, [0.0; 2]);
+
+    let scope_input = ScopeInput { recording: publisher };
+    let scope_output = ScopeOutput { recording: subscriber };
+
+    (scope_input, scope_output)
+}
+
+/// The audio thread half of the oscilloscope
+// Records the input and output waveforms side by side, so they can be drawn on top of each other
+pub struct ScopeInput {
+    /// The most recent (input, output) frames, published every `SCOPE_PUBLISH_INTERVAL` frames
+    recording: RingPublisher<[f32; 2]>,
+}
+
+impl ScopeInput {
+    /// Add the input and output samples of the current frame
+    pub fn push(&mut self, input: f32, output: f32) {
+        self.recording.push([input, output]);
+    }
+}
+
+/// The GUI half of the oscilloscope
+pub struct ScopeOutput {
+    /// The latest recording
+    recording: RingSubscriber<[f32; 2]>,
+}
+
+impl ScopeOutput {
+    /// A counter that changes whenever a new recording is available, used to trigger redraws
+    pub fn generation(&self) -> Arc<AtomicUsize> {
+        self.recording.generation()
+    }
+
+    /// The latest `SCOPE_DISPLAY_SIZE` (input, output) frames, starting at a trigger point
+    // Like the trigger on a hardware scope, the view starts where the input crosses zero on
+    // the way up, so a steady tone stands still instead of scrolling. When the input is
+    // silent the output is used instead, and without any crossing it just starts anywhere.
+    pub fn waveforms(&mut self) -> &[[f32; 2]] {
+        let recording = self.recording.read();
+        let search = &recording[..SCOPE_WINDOW_SIZE - SCOPE_DISPLAY_SIZE];
+        let rising_edge = |side: usize| {
+            search
+                .windows(2)
+                .position(|pair| pair[0][side] <= 0.0 && pair[1][side] > 0.0)
+                .map(|position| position + 1)
+        };
+        let start = rising_edge(0).or_else(|| rising_edge(1)).unwrap_or(0);
+
+        &recording[start..start + SCOPE_DISPLAY_SIZE]
+    }
+}
+
+/// How long every slice of the texture history lasts, in seconds
+const TEXTURE_SLICE_SECONDS: f32 = 0.01;
+
+/// The number of slices the texture history remembers, four seconds worth
+const TEXTURE_SLICES: usize = 400;
+
+/// How many new slices are collected before the history is sent to the GUI
+const TEXTURE_PUBLISH_INTERVAL: usize = 4;
+
+/// A short stretch of the output in the texture history
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextureSlice {
+    /// The lowest output sample in this stretch
+    pub min: f32,
+    /// The highest output sample in this stretch
+    pub max: f32,
+    /// The average chaos signal in this stretch (-1.0 to 1.0)
+    pub chaos: f32,
+}
+
+/// Create a connected pair of texture history halves
+pub fn texture_history() -> (TextureInput, TextureOutput) {
+    let (publisher, subscriber) = ring_publisher(TEXTURE_SLICES, TEXTURE_PUBLISH_INTERVAL, TextureSlice::default());
+
+    let texture_input = TextureInput {
+        history: publisher,
+        slice_len: (TEXTURE_SLICE_SECONDS * 44100.0) as usize, // Default sample rate, will be updated
+        current: TextureSlice { min: f32::MAX, max: f32::MIN, chaos: 0.0 },
+        current_frames: 0,
+    };
+    let texture_output = TextureOutput {
+        history: subscriber,
+        columns: Vec::new(),
+    };
+
+    (texture_input, texture_output)
+}
+
+/// The audio thread half of the texture history
+// Sums up the output waveform and the chaos signal into one slice every 10 ms, so the last
+// few seconds fit into a small buffer no matter the sample rate
+pub struct TextureInput {
+    /// The most recent slices, published every `TEXTURE_PUBLISH_INTERVAL` slices
+    history: RingPublisher<TextureSlice>,
+    /// How many frames every slice covers at the current sample rate
+    slice_len: usize,
+    /// The slice that's currently being collected, with the chaos signal still summed up
+    current: TextureSlice,
+    /// How many frames went into the current slice
+    current_frames: usize,
+}
+
+impl TextureInput {
+    /// Set the sample rate, which decides how many frames go into every slice
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.slice_len = ((TEXTURE_SLICE_SECONDS * sample_rate) as usize).max(1);
+    }
+
+    /// Add the output sample and the chaos signal of the current frame
+    pub fn push(&mut self, output: f32, chaos_signal: f32) {
+        self.current.min = self.current.min.min(output);
+        self.current.max = self.current.max.max(output);
+        self.current.chaos += chaos_signal;
+        self.current_frames += 1;
+        if self.current_frames < self.slice_len {
+            return;
+        }
+
+        self.history.push(TextureSlice {
+            chaos: self.current.chaos / self.current_frames as f32,
+            ..self.current
+        });
+        self.current = TextureSlice { min: f32::MAX, max: f32::MIN, chaos: 0.0 };
+        self.current_frames = 0;
+    }
+}
+
+/// The GUI half of the texture history
+pub struct TextureOutput {
+    /// The latest history
+    history: RingSubscriber<TextureSlice>,
+    /// The history resampled to the width of the display
+    // Only ever touched on the GUI thread, so it can grow when the editor gets wider
+    columns: Vec<TextureSlice>,
+}
+
+impl TextureOutput {
+    /// A counter that changes whenever a new history is available, used to trigger redraws
+    pub fn generation(&self) -> Arc<AtomicUsize> {
+        self.history.generation()
+    }
+
+    /// The latest history resampled to `num_columns` columns, oldest first
+    // When there are fewer columns than slices every column covers a few of them, and keeps
+    // their lowest and highest sample so no peak gets lost. With more columns than slices
+    // the slices are stretched over several columns.
+    pub fn columns(&mut self, num_columns: usize) -> &[TextureSlice] {
+        let history = self.history.read();
+        self.columns.clear();
+        for column in 0..num_columns {
+            let start = column * TEXTURE_SLICES / num_columns;
+            let end = ((column + 1) * TEXTURE_SLICES / num_columns).max(start + 1).min(TEXTURE_SLICES);
+            let slices = &history[start..end];
+            self.columns.push(TextureSlice {
+                min: slices.iter().fold(f32::MAX, |min, slice| min.min(slice.min)),
+                max: slices.iter().fold(f32::MIN, |max, slice| max.max(slice.max)),
+                chaos: slices.iter().map(|slice| slice.chaos).sum::<f32>() / slices.len() as f32,
+            });
+        }
+
+        &self.columns
+    }
+}
@@ -0,0 +1,178 @@
+// The processors one pass through the chain goes through
+use crate::chaos::ChaosAttractor;
+use crate::coupling;
+use crate::distortion::Distortion;
+use crate::filter::Filter;
+use crate::fractal::FractalMagic;
+use crate::governor::StabilityGovernor;
+use crate::ms;
+use crate::precision::Precision;
+use crate::routing::{EffectOrder, Stage, StageSolo};
+
+/// What a sample goes through the effect chain with
+// Plain values the caller works out for every sample, so the chain doesn't know about
+// parameters, smoothing or modulation and can be run on its own
+#[derive(Debug, Clone, Copy)]
+pub struct ChainSettings {
+    /// The order the three stages are applied in
+    pub order: EffectOrder,
+
+    /// Which stages are heard, the others hand on what they were given
+    pub solo: StageSolo,
+
+    /// How hard the distortion is driven (1.0 to 50.0)
+    pub drive: f32,
+
+    /// The tone filter's cutoff in Hz
+    pub filter_cutoff: f32,
+
+    /// The tone filter's resonance (0.0 to 1.0)
+    pub filter_resonance: f32,
+
+    /// The Magic One amount (0.0 to 1.0)
+    pub magic: f32,
+
+    /// The chaos amount (0.0 to 1.0)
+    pub chaos: f32,
+
+    /// How much the fractal and the chaos effect drive each other (0.0 to 1.0), before the governor has its say
+    pub coupling: f32,
+
+    /// The output level of every stage as a gain
+    pub dist_level: f32,
+    pub magic_level: f32,
+    pub chaos_level: f32,
+
+    /// How much of every stage's output is used, from `ms::balance_weights()`, 1.0 outside mid/side mode
+    pub dist_weight: f32,
+    pub magic_weight: f32,
+    pub chaos_weight: f32,
+}
+
+impl Default for ChainSettings {
+    /// The plugin's default settings: the stages in their usual order, and none of them doing much
+    fn default() -> Self {
+        Self {
+            order: EffectOrder::DistortionFractalChaos,
+            solo: StageSolo::default(),
+            drive: 1.0,
+            filter_cutoff: 20000.0,
+            filter_resonance: 0.0,
+            magic: 0.0,
+            chaos: 0.0,
+            coupling: 0.0,
+            dist_level: 1.0,
+            magic_level: 1.0,
+            chaos_level: 1.0,
+            dist_weight: 1.0,
+            magic_weight: 1.0,
+            chaos_weight: 1.0,
+        }
+    }
+}
+
+/// The distortion, Magic One and chaos stages with everything that keeps them together
+// One of these runs the whole chain for the plugin, or one band of it in multiband mode,
+// and the `dsp` feature's `DspChain` runs one too, so they can't drift apart. The
+// processors are public so their modes can be set directly, the chain only takes care of
+// running them in order.
+pub struct EffectChain {
+    /// The distortion stage
+    pub distortion: Distortion,
+
+    /// The tone filter that always follows the distortion
+    pub filter: Filter,
+
+    /// The fractal wavefolder
+    pub fractal_magic: FractalMagic,
+
+    /// The chaos effect
+    pub chaos_attractor: ChaosAttractor,
+
+    /// Reins in the fractal's feedback and the coupling before the two blow up
+    pub governor: StabilityGovernor,
+}
+
+impl EffectChain {
+    /// Create a new effect chain
+    pub fn new() -> Self {
+        Self {
+            distortion: Distortion::new(),
+            filter: Filter::new(),
+            fractal_magic: FractalMagic::new(),
+            chaos_attractor: ChaosAttractor::new(),
+            governor: StabilityGovernor::new(),
+        }
+    }
+
+    /// Set the sample rate of every processor
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.distortion.set_sample_rate(sample_rate);
+        self.filter.set_sample_rate(sample_rate);
+        self.fractal_magic.set_sample_rate(sample_rate);
+        self.chaos_attractor.set_sample_rate(sample_rate);
+        self.governor.set_sample_rate(sample_rate);
+    }
+
+    /// Select the float type the filter, the fractal formula and the chaos systems are calculated with
+    // They're the stages that feed back into themselves, the distortion stays 32-bit either way
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.filter.set_precision(precision);
+        self.fractal_magic.set_precision(precision);
+        self.chaos_attractor.set_precision(precision);
+    }
+
+    /// Clear the state of every processor
+    pub fn reset(&mut self) {
+        self.distortion.reset();
+        self.filter.reset();
+        self.fractal_magic.reset();
+        self.chaos_attractor.reset();
+        self.governor.reset();
+    }
+
+    /// Run a sample of the given channel through the stages
+    // Every channel (up to two) keeps its own state, so call this once per channel and
+    // sample. Returns the processed sample and the stability the governor allowed, from 1.0
+    // when everything runs freely down to 0.0.
+    pub fn process(&mut self, channel: usize, mut sample: f32, settings: &ChainSettings) -> (f32, f32) {
+        // The fractal and chaos effect hear where the other one was before either moves on,
+        // as much as the governor thinks is safe
+        let stability = self.governor.supervise(channel, &self.chaos_attractor, &mut self.fractal_magic);
+        coupling::couple(
+            &mut self.chaos_attractor,
+            &mut self.fractal_magic,
+            channel,
+            settings.coupling * stability,
+        );
+
+        for stage in settings.order.stages() {
+            // Every stage's output at its own level, before it's handed on
+            let stage_output = match stage {
+                Stage::Distortion => {
+                    // The tone filter always follows the distortion to tame its harmonics
+                    let distorted = self.distortion.process(channel, sample, settings.drive);
+                    let filtered =
+                        self.filter.process(channel, distorted, settings.filter_cutoff, settings.filter_resonance);
+                    ms::apply_weight(sample, filtered * settings.dist_level, settings.dist_weight)
+                }
+                Stage::Fractal => {
+                    let folded = self.fractal_magic.process(channel, sample, settings.magic);
+                    ms::apply_weight(sample, folded * settings.magic_level, settings.magic_weight)
+                }
+                Stage::Chaos => {
+                    let modulated = self.chaos_attractor.process(channel, sample, settings.chaos);
+                    ms::apply_weight(sample, modulated * settings.chaos_level, settings.chaos_weight)
+                }
+            };
+
+            // A stage that isn't heard still runs, so un-soloing doesn't start it from
+            // silence, but the next stage gets the signal the stage was given instead
+            if settings.solo.is_heard(stage) {
+                sample = stage_output;
+            }
+        }
+
+        (sample, stability)
+    }
+}
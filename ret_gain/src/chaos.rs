@@ -1,225 +1,1424 @@
+// Import the NIH-plug prelude for the Enum derive macro
+#[cfg(feature = "plugin")]
+use nih_plug::prelude::*;
 // Import the PI constant from Rust's standard library
 use std::f32::consts::PI;
+// Snapshots of the systems are saved with the plugin state
+use serde::{Deserialize, Serialize};
 
-/// A chaotic audio effect based on the Lorenz attractor and other chaotic systems
-// This implements an effect based on chaos theory - specifically the Lorenz attractor
-// The Lorenz attractor is a set of differential equations that create unpredictable but deterministic patterns
-pub struct ChaosAttractor {
-    /// Amount of chaos to apply (0.0 to 1.0)
-    // Controls how much of the effect is applied to the signal
-    chaos_amount: f32,
-    
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+// The secondary modulation can be synced to musical note lengths
+use crate::modulation::NoteDivision;
+// The output goes through the chain's shared soft clipper
+use crate::saturation;
+// The quality tier can run the systems at control rate
+use crate::quality::Quality;
+// The FM branch can shift the input's frequencies instead
+use crate::freq_shift::FrequencyShifter;
+// The systems can be calculated in 32 or 64 bits
+use crate::precision::{DspFloat, Precision};
+
+/// The default rate of the secondary phase modulation, in Hz
+pub const DEFAULT_MOD_RATE_HZ: f32 = 0.44;
+
+/// How many times per second the chaotic systems are advanced
+// The systems run at this fixed rate no matter what the audio sample rate is, so they take
+// exactly the same path at 44.1 kHz, 96 kHz or with oversampling. The output is linearly
+// interpolated between steps. 44.1 kHz matches how earlier versions sounded.
+pub const STEP_RATE: f32 = 44100.0;
+
+/// Time step of one simulation step
+const STEP_DT: f32 = 0.005;
+
+/// How many times fewer steps the continuous systems take at the Eco quality
+// Every step covers this many times as much time instead, so they still move at the same
+// speed, and the interpolation between the steps fills in the samples in between. Even
+// Euler integration stays stable at four times the step size. The logistic map has no time
+// step, fewer iterations would only slow it down, so it always runs at the full rate.
+const ECO_STEP_DIVIDER: f32 = 4.0;
+
+/// How often the systems' parameters evolve, in seconds
+// On a clock of its own rather than every so many simulation steps, so the texture changes
+// at the same moments whatever the sample rate, the chaos rate or the host's block size
+const EVOLUTION_INTERVAL: f64 = 0.01;
+
+/// The length of the free-running evolution's whole pattern, in seconds
+// The three slow oscillations line up again after this long, so wrapping the clock around
+// here doesn't make the parameters jump
+const FREE_EVOLUTION_CYCLE: f64 = 24000.0;
+
+/// The slowest and fastest the systems can run, as a multiple of `STEP_RATE`
+// Slower rates take fewer steps and interpolate between them, faster ones take several
+// steps per sample. The step size stays the same, so the integration stays as stable.
+pub const MIN_RATE: f32 = 0.05;
+pub const MAX_RATE: f32 = 16.0;
+
+/// The cutoff of the color low pass at a color of 0.0, in Hz
+// Slow enough that even a fast chaos rate only comes out as a gentle wobble
+const MIN_COLOR_HZ: f32 = 0.5;
+
+/// The cutoff of the color low pass just below a color of 1.0, in Hz
+// At 1.0 the filter is switched off, so this is only where the sweep ends up
+const MAX_COLOR_HZ: f32 = 20000.0;
+
+/// The most the frequency shift can move the input by, in Hz, either way
+// The shift follows the chaos signal, so at full depth a swing of the signal sweeps the
+// partials up and down by up to this much
+pub const MAX_SHIFT_HZ: f32 = 1000.0;
+
+/// What the chaos effect's FM branch does to the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum FmMode {
+    /// Multiplies the input by a cosine of the secondary phase, which is really another AM. How the chaos effect always sounded.
+    #[cfg_attr(feature = "plugin", id = "classic")]
+    #[cfg_attr(feature = "plugin", name = "Classic")]
+    Classic,
+    /// Shifts every partial of the input up or down as the chaos signal swings, for real inharmonic movement
+    #[cfg_attr(feature = "plugin", id = "shift")]
+    #[cfg_attr(feature = "plugin", name = "Shift")]
+    Shift,
+}
+
+/// The numerical method used to advance the continuous chaotic systems
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum Integrator {
+    /// One derivative per step, cheap and the way the chaos effect always sounded
+    #[cfg_attr(feature = "plugin", id = "euler")]
+    #[cfg_attr(feature = "plugin", name = "Euler")]
+    Euler,
+    /// Fourth-order Runge-Kutta, four derivatives per step and a much more accurate path
+    #[cfg_attr(feature = "plugin", id = "rk4")]
+    #[cfg_attr(feature = "plugin", name = "RK4")]
+    RungeKutta4,
+}
+
+/// How the chaotic systems' parameters drift over time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum Evolution {
+    /// Slow free-running cycles of a few minutes, the way the chaos effect always evolved
+    #[cfg_attr(feature = "plugin", id = "free")]
+    #[cfg_attr(feature = "plugin", name = "Free")]
+    Free,
+    /// One full cycle every 8 bars of the host's tempo
+    #[cfg_attr(feature = "plugin", id = "8bars")]
+    #[cfg_attr(feature = "plugin", name = "8 Bars")]
+    EightBars,
+    /// One full cycle every 16 bars
+    #[cfg_attr(feature = "plugin", id = "16bars")]
+    #[cfg_attr(feature = "plugin", name = "16 Bars")]
+    SixteenBars,
+    /// One full cycle every 32 bars
+    #[cfg_attr(feature = "plugin", id = "32bars")]
+    #[cfg_attr(feature = "plugin", name = "32 Bars")]
+    ThirtyTwoBars,
+}
+
+impl Evolution {
+    /// The length of one evolution cycle in bars, or `None` when it runs freely
+    pub fn bars(self) -> Option<f64> {
+        match self {
+            Evolution::Free => None,
+            Evolution::EightBars => Some(8.0),
+            Evolution::SixteenBars => Some(16.0),
+            Evolution::ThirtyTwoBars => Some(32.0),
+        }
+    }
+}
+
+/// The chaotic system driving the chaos effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum ChaosType {
+    /// The classic butterfly, swings between two lobes at irregular intervals
+    #[cfg_attr(feature = "plugin", id = "lorenz")]
+    #[cfg_attr(feature = "plugin", name = "Lorenz")]
+    Lorenz,
+    /// A smooth spiral with the occasional sudden spike, the most musical of the bunch
+    #[cfg_attr(feature = "plugin", id = "rossler")]
+    #[cfg_attr(feature = "plugin", name = "Rössler")]
+    Rossler,
+    /// The double scroll of Chua's electronic circuit, jumpy and nervous
+    #[cfg_attr(feature = "plugin", id = "chua")]
+    #[cfg_attr(feature = "plugin", name = "Chua")]
+    Chua,
+    /// The logistic map, a stepped sequence that hops between values like a broken sample & hold
+    #[cfg_attr(feature = "plugin", id = "logistic")]
+    #[cfg_attr(feature = "plugin", name = "Logistic Map")]
+    Logistic,
+}
+
+/// A chaotic system that can drive the chaos effect
+// Every system is advanced in small time steps at `STEP_RATE`. The input audio pushes one
+// of its parameters around, so the chaos follows the music, and `evolve()` slowly moves its
+// parameters over time so the effect never sounds quite the same.
+// Continuous systems only describe their equations in `derivatives()`, the integrator in
+// `step()` is shared by all of them. Both are calculated with the float type `F`, see
+// `Precision`. The state is kept in 64 bits either way, but the starting points are worked
+// out in 32 bits, so the 32-bit path starts from exactly where it always has.
+pub trait ChaosSystem {
+    /// Create the system with its standard starting conditions
+    // The decorrelated variant runs with slightly different parameters, so it takes a
+    // completely different path. It's used for the right channel in stereo mode.
+    fn new(decorrelated: bool) -> Self
+    where
+        Self: Sized;
+
+    /// Move the starting point somewhere else within the usual extent of the attractor
+    // `random` are three uniformly random numbers between 0.0 and 1.0
+    fn scatter(&mut self, random: [f32; 3]);
+
+    /// The rates of change of the three state variables at the given point
+    // `input_influence` is the current input sample
+    fn derivatives<F: DspFloat>(&self, state: [F; 3], input_influence: F, chaos_amount: F) -> [F; 3]
+    where
+        Self: Sized;
+
+    /// Move the system to a point in 3D space
+    fn set_state(&mut self, state: [f64; 3]);
+
+    /// How far the state variables may stray from the origin before they get clamped
+    // This keeps the system stable and prevents digital clipping
+    fn limit(&self) -> f32 {
+        100.0
+    }
+
+    /// Advance the system by one time step
+    fn step<F: DspFloat>(&mut self, input_influence: f32, chaos_amount: f32, dt: f32, integrator: Integrator)
+    where
+        Self: Sized,
+    {
+        // Constants are written as f64 and rounded to the float type being used
+        let c = F::from_f64;
+        let state = self.state().map(c);
+        let input_influence = c(input_influence as f64);
+        let chaos_amount = c(chaos_amount as f64);
+        let dt = c(dt as f64);
+        let derivatives = |point: [F; 3]| self.derivatives(point, input_influence, chaos_amount);
+        // The point `scale * dt` along the given direction
+        let offset = |direction: [F; 3], scale: F| {
+            [
+                state[0] + direction[0] * scale * dt,
+                state[1] + direction[1] * scale * dt,
+                state[2] + direction[2] * scale * dt,
+            ]
+        };
+
+        let next = match integrator {
+            // Euler integration: new_value = old_value + (rate_of_change * time_step)
+            // This is the simplest numerical method for solving differential equations
+            Integrator::Euler => offset(derivatives(state), c(1.0)),
+            // Runge-Kutta samples the slope at the start, twice in the middle and at the end
+            // of the step, and moves along a weighted average of the four
+            Integrator::RungeKutta4 => {
+                let k1 = derivatives(state);
+                let k2 = derivatives(offset(k1, c(0.5)));
+                let k3 = derivatives(offset(k2, c(0.5)));
+                let k4 = derivatives(offset(k3, c(1.0)));
+                let average = [
+                    (k1[0] + c(2.0) * k2[0] + c(2.0) * k3[0] + k4[0]) / c(6.0),
+                    (k1[1] + c(2.0) * k2[1] + c(2.0) * k3[1] + k4[1]) / c(6.0),
+                    (k1[2] + c(2.0) * k2[2] + c(2.0) * k3[2] + k4[2]) / c(6.0),
+                ];
+                offset(average, c(1.0))
+            }
+        };
+
+        // Prevent extreme values by clamping
+        let limit = c(self.limit() as f64);
+        self.set_state(next.map(|value| value.clamp(-limit, limit).to_f64()));
+    }
+
+    /// Slowly move the system's parameters around their standard values
+    // `modulation` are three slow oscillations between about -0.5 and 0.5
+    fn evolve(&mut self, modulation: [f32; 3], chaos_amount: f32);
+
+    /// The system's current value as a signal between -1.0 and 1.0
+    fn output(&self) -> f32;
+
+    /// The system's current position in 3D space, for drawing it
+    fn state(&self) -> [f64; 3];
+}
+
+/// The Lorenz system, a simplified model of atmospheric convection
+#[derive(Clone, Copy)]
+pub struct Lorenz {
     /// Lorenz attractor state variables
     // These three variables represent the state of the Lorenz system in 3D space
-    x: f32,  // x coordinate in the Lorenz system
-    y: f32,  // y coordinate in the Lorenz system
-    z: f32,  // z coordinate in the Lorenz system
-    
+    x: f64,  // x coordinate in the Lorenz system
+    y: f64,  // y coordinate in the Lorenz system
+    z: f64,  // z coordinate in the Lorenz system
+
     /// Lorenz system parameters
     // These parameters control the behavior of the Lorenz system
     // Different values create different chaotic behaviors
     sigma: f32,  // Controls how quickly the system reacts to differences in x and y
     rho: f32,    // Related to the onset of chaos (critical value around 24.74)
     beta: f32,   // Related to the size and twist of the Lorenz attractor
-    
-    /// Sample rate for time-based calculations
-    // We need to know the sample rate for proper time-based effects
-    sample_rate: f32,
-    
-    /// Time step for the simulation
-    // Controls how much the Lorenz system advances with each sample
-    // Smaller values give more accurate simulation but require more calculations
-    dt: f32,
-    
-    /// Phase accumulator for secondary modulation
-    // Keeps track of phase for additional modulation effects
-    phase: f32,
-    
-    /// Counter for slow evolution of parameters
-    // Allows the system parameters to evolve slowly over time for continual variation
-    evolution_counter: usize,
+
+    /// Offset on top of the evolving rho parameter
+    // Used to push the right channel's attractor onto a slightly different path in stereo mode
+    rho_offset: f32,
 }
 
-impl ChaosAttractor {
-    /// Create a new chaos attractor effect with the given amount
-    // Constructor for the ChaosAttractor effect
-    pub fn new(chaos_amount: f32) -> Self {
-        // Initialize with standard Lorenz parameters 
-        // These are the classic values that produce the butterfly-shaped attractor
-        let sigma = 10.0;
-        let rho = 28.0;
-        let beta = 8.0 / 3.0;
-        
-        // Create and return a new ChaosAttractor with initial values
+impl ChaosSystem for Lorenz {
+    fn new(decorrelated: bool) -> Self {
+        // Both the starting point and rho are slightly different for the decorrelated variant.
+        // Since the Lorenz system is chaotic, even these tiny differences quickly lead to
+        // completely different paths.
+        let rho_offset = if decorrelated { 0.7 } else { 0.0 };
+        let (x, y, z): (f32, f32, f32) = if decorrelated { (-0.13, 0.12, 0.09) } else { (0.1, 0.1, 0.1) };
+
         Self {
-            chaos_amount,  // Set the amount of chaos effect to apply
             // Start with non-zero values to avoid getting stuck at the origin
             // The origin (0,0,0) is an unstable equilibrium point in the Lorenz system
-            x: 0.1,
-            y: 0.1,
-            z: 0.1,
-            sigma,
-            rho,
-            beta,
-            sample_rate: 44100.0, // Default sample rate, will be updated
-            dt: 0.001, // Time step for numerical integration
-            phase: 0.0, // Start with zero phase
-            evolution_counter: 0, // Start counter at zero
+            x: x.into(),
+            y: y.into(),
+            z: z.into(),
+            // These are the classic values that produce the butterfly-shaped attractor
+            sigma: 10.0,
+            rho: 28.0 + rho_offset,
+            beta: 8.0 / 3.0,
+            rho_offset,
         }
     }
-    
-    /// Set the sample rate for time-based calculations
-    // Updates the sample rate and adjusts dependent parameters
-    pub fn set_sample_rate(&mut self, sample_rate: f32) {
-        self.sample_rate = sample_rate;
-        // Adjust time step based on sample rate to ensure consistent behavior
-        // Higher sample rates need smaller time steps for equivalent simulation speed
-        self.dt = 0.005 * (44100.0 / sample_rate);
+
+    fn scatter(&mut self, [x, y, z]: [f32; 3]) {
+        self.x = f64::from(x * 30.0 - 15.0);
+        self.y = f64::from(y * 30.0 - 15.0);
+        self.z = f64::from(z * 30.0 + 5.0);
     }
-    
-    /// Reset the chaotic system to initial conditions
-    // Resets the state of the Lorenz system to avoid getting stuck or blowing up
-    pub fn reset(&mut self) {
-        // Reset to slightly off-center initial conditions
-        self.x = 0.1;
-        self.y = 0.1;
-        self.z = 0.1;
-        self.phase = 0.0;
-        self.evolution_counter = 0;
-    }
-    
-    /// Update the Lorenz attractor state
-    // This is the heart of the chaos effect - it computes one step of the Lorenz equations
-    // The Lorenz equations are a simplified model of atmospheric convection
-    fn update_lorenz(&mut self, input_influence: f32) {
+
+    /// The Lorenz equations
+    // This is the heart of the chaos effect
+    fn derivatives<F: DspFloat>(&self, [x, y, z]: [F; 3], input_influence: F, chaos_amount: F) -> [F; 3] {
+        let c = F::from_f64;
+
         // Scale the system variables to keep them in a reasonable range
         // Without scaling, the Lorenz system can produce very large values
-        let scale_factor = 0.1;
-        let x_scaled = self.x * scale_factor;
-        let y_scaled = self.y * scale_factor;
-        let z_scaled = self.z * scale_factor;
-        
+        let scale_factor = c(0.1);
+        let x_scaled = x * scale_factor;
+        let y_scaled = y * scale_factor;
+        let z_scaled = z * scale_factor;
+
         // Apply input signal influence to the rho parameter
         // This makes the chaos system responsive to the input audio
-        let rho_mod = self.rho + (input_influence * 5.0 * self.chaos_amount);
-        
+        let rho_mod = c(self.rho as f64) + (input_influence * c(5.0) * chaos_amount);
+
         // Calculate derivatives based on the Lorenz system equations
         // These are the three differential equations that define the Lorenz attractor:
-        let dx = self.sigma * (y_scaled - x_scaled);  // Rate of change for x
+        let dx = c(self.sigma as f64) * (y_scaled - x_scaled);  // Rate of change for x
         let dy = x_scaled * (rho_mod - z_scaled) - y_scaled;  // Rate of change for y
-        let dz = x_scaled * y_scaled - self.beta * z_scaled;  // Rate of change for z
-        
-        // Apply Euler integration to update the state
-        // Euler integration: new_value = old_value + (rate_of_change * time_step)
-        // This is the simplest numerical method for solving differential equations
-        self.x += dx * self.dt;
-        self.y += dy * self.dt;
-        self.z += dz * self.dt;
-        
-        // Prevent extreme values by clamping
-        // This keeps the system stable and prevents digital clipping
-        self.x = self.x.clamp(-100.0, 100.0);  // clamp limits a value to a specified range
-        self.y = self.y.clamp(-100.0, 100.0);
-        self.z = self.z.clamp(-100.0, 100.0);
+        let dz = x_scaled * y_scaled - c(self.beta as f64) * z_scaled;  // Rate of change for z
+
+        [dx, dy, dz]
     }
-    
+
+    fn set_state(&mut self, [x, y, z]: [f64; 3]) {
+        self.x = x;
+        self.y = y;
+        self.z = z;
+    }
+
+    fn evolve(&mut self, [sigma_mod, rho_mod, beta_mod]: [f32; 3], chaos_amount: f32) {
+        // Modulate parameters around their standard values
+        // The chaos_amount scales how much variation is applied
+        self.sigma = 10.0 + (sigma_mod * chaos_amount);
+        self.rho = 28.0 + self.rho_offset + (rho_mod * 5.0 * chaos_amount);
+        self.beta = (8.0 / 3.0) + (beta_mod * chaos_amount);
+    }
+
     /// Get a normalized value from the Lorenz system (between -1.0 and 1.0)
     // Converts the 3D Lorenz state into a single audio signal value
-    fn get_lorenz_output(&self) -> f32 {
+    fn output(&self) -> f32 {
         // Combine the three dimensions into a single signal
         // Normalize each dimension to approximately -1.0 to 1.0 range using tanh
         // tanh naturally limits values to [-1, 1] with a smooth curve
-        let x_norm = (self.x / 30.0).tanh();
-        let y_norm = (self.y / 30.0).tanh();
-        let z_norm = (self.z / 50.0).tanh();
-        
+        let x_norm = (self.x as f32 / 30.0).tanh();
+        let y_norm = (self.y as f32 / 30.0).tanh();
+        let z_norm = (self.z as f32 / 50.0).tanh();
+
         // Mix the three components with different weights
         // This creates a more interesting signal than using just one dimension
         0.5 * x_norm + 0.3 * y_norm + 0.2 * z_norm
     }
-    
-    /// Slowly evolve the Lorenz parameters over time
-    // This prevents the effect from sounding the same over long periods
-    fn evolve_parameters(&mut self) {
-        // Only update occasionally for performance
-        // We don't need to update parameters for every sample
-        if self.evolution_counter % 4000 == 0 {
+
+    fn state(&self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+/// The Rössler system, a spiral in the x-y plane that occasionally shoots up in z
+#[derive(Clone, Copy)]
+pub struct Rossler {
+    /// State variables
+    x: f64,
+    y: f64,
+    z: f64,
+
+    /// Rössler system parameters
+    // c is the one that matters most, chaos sets in above about 4.2
+    a: f32,
+    b: f32,
+    c: f32,
+
+    /// Offset on top of the evolving c parameter, for the decorrelated variant
+    c_offset: f32,
+}
+
+impl ChaosSystem for Rossler {
+    fn new(decorrelated: bool) -> Self {
+        let c_offset = if decorrelated { 0.3 } else { 0.0 };
+        let (x, y, z): (f32, f32, f32) = if decorrelated { (-1.3, 1.1, 0.2) } else { (1.0, 1.0, 0.0) };
+
+        Self {
+            x: x.into(),
+            y: y.into(),
+            z: z.into(),
+            // The parameters Rössler himself studied
+            a: 0.2,
+            b: 0.2,
+            c: 5.7 + c_offset,
+            c_offset,
+        }
+    }
+
+    fn scatter(&mut self, [x, y, z]: [f32; 3]) {
+        self.x = f64::from(x * 16.0 - 8.0);
+        self.y = f64::from(y * 16.0 - 8.0);
+        self.z = f64::from(z * 2.0);
+    }
+
+    fn derivatives<F: DspFloat>(&self, [x, y, z]: [F; 3], input_influence: F, chaos_amount: F) -> [F; 3] {
+        let c = F::from_f64;
+
+        // The input pushes c around, which changes how often the spikes happen
+        let c_mod = c(self.c as f64) + input_influence * c(2.0) * chaos_amount;
+
+        // The three Rössler equations
+        [-y - z, x + c(self.a as f64) * y, c(self.b as f64) + z * (x - c_mod)]
+    }
+
+    fn set_state(&mut self, [x, y, z]: [f64; 3]) {
+        self.x = x;
+        self.y = y;
+        self.z = z;
+    }
+
+    fn evolve(&mut self, [a_mod, c_mod, b_mod]: [f32; 3], chaos_amount: f32) {
+        // a and b only get small nudges, too much of either lets the spiral escape
+        self.a = 0.2 + a_mod * 0.02 * chaos_amount;
+        self.b = 0.2 + b_mod * 0.05 * chaos_amount;
+        self.c = 5.7 + self.c_offset + c_mod * 1.0 * chaos_amount;
+    }
+
+    fn output(&self) -> f32 {
+        // The spiral spans roughly ±12 in x and y, the spikes go up to about 25 in z
+        let x_norm = (self.x as f32 / 12.0).tanh();
+        let y_norm = (self.y as f32 / 12.0).tanh();
+        let z_norm = (self.z as f32 / 25.0).tanh();
+
+        0.5 * x_norm + 0.3 * y_norm + 0.2 * z_norm
+    }
+
+    fn state(&self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+/// Chua's circuit, the first electronic circuit shown to behave chaotically
+#[derive(Clone, Copy)]
+pub struct Chua {
+    /// State variables, the voltages across the two capacitors and the current through the inductor
+    x: f64,
+    y: f64,
+    z: f64,
+
+    /// Circuit parameters
+    alpha: f32,
+    beta: f32,
+
+    /// Offset on top of the evolving alpha parameter, for the decorrelated variant
+    alpha_offset: f32,
+}
+
+impl Chua {
+    /// The slopes of the piecewise linear Chua diode
+    const M0: f64 = -8.0 / 7.0;
+    const M1: f64 = -5.0 / 7.0;
+
+    /// The current through the Chua diode, the circuit's only non-linear part
+    fn diode<F: DspFloat>(x: F) -> F {
+        let c = F::from_f64;
+        c(Self::M1) * x + c(0.5) * (c(Self::M0) - c(Self::M1)) * ((x + c(1.0)).abs() - (x - c(1.0)).abs())
+    }
+}
+
+impl ChaosSystem for Chua {
+    fn new(decorrelated: bool) -> Self {
+        let alpha_offset = if decorrelated { 0.2 } else { 0.0 };
+        let (x, y, z): (f32, f32, f32) = if decorrelated { (-0.12, 0.0, 0.05) } else { (0.1, 0.0, 0.0) };
+
+        Self {
+            x: x.into(),
+            y: y.into(),
+            z: z.into(),
+            // The classic values that produce the double scroll
+            alpha: 15.6 + alpha_offset,
+            beta: 28.0,
+            alpha_offset,
+        }
+    }
+
+    fn scatter(&mut self, [x, y, z]: [f32; 3]) {
+        self.x = f64::from(x * 4.0 - 2.0);
+        self.y = f64::from(y * 0.6 - 0.3);
+        self.z = f64::from(z * 6.0 - 3.0);
+    }
+
+    fn derivatives<F: DspFloat>(&self, [x, y, z]: [F; 3], input_influence: F, chaos_amount: F) -> [F; 3] {
+        let c = F::from_f64;
+
+        // The input pushes alpha around, which makes the jumps between the scrolls more or less likely
+        let alpha_mod = c(self.alpha as f64) + input_influence * c(3.0) * chaos_amount;
+
+        // The three equations of the circuit
+        [alpha_mod * (y - x - Self::diode(x)), x - y + z, -c(self.beta as f64) * y]
+    }
+
+    fn set_state(&mut self, [x, y, z]: [f64; 3]) {
+        self.x = x;
+        self.y = y;
+        self.z = z;
+    }
+
+    fn limit(&self) -> f32 {
+        // The double scroll is much smaller than the other attractors
+        10.0
+    }
+
+    fn evolve(&mut self, [alpha_mod, beta_mod, _]: [f32; 3], chaos_amount: f32) {
+        self.alpha = 15.6 + self.alpha_offset + alpha_mod * 0.6 * chaos_amount;
+        self.beta = 28.0 + beta_mod * 1.0 * chaos_amount;
+    }
+
+    fn output(&self) -> f32 {
+        // The scrolls sit around x = ±1.5, y stays within about ±0.4 and z within ±4
+        let x_norm = (self.x as f32 / 2.0).tanh();
+        let y_norm = (self.y as f32 / 0.4).tanh();
+        let z_norm = (self.z as f32 / 4.0).tanh();
+
+        0.5 * x_norm + 0.3 * y_norm + 0.2 * z_norm
+    }
+
+    fn state(&self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+/// The logistic map x ← r·x·(1 − x), the simplest chaotic system there is
+// Unlike the other systems this is a sequence of separate values instead of a continuous
+// path. It's iterated a couple of hundred times per second and the output glides from one
+// value to the next, otherwise it would just sound like noise.
+#[derive(Clone, Copy)]
+pub struct Logistic {
+    /// The current value, between 0.0 and 1.0
+    x: f64,
+    /// The previous value, the output glides from this one to `x`
+    prev_x: f64,
+
+    /// How far the glide from `prev_x` to `x` has gotten
+    progress: f32,
+
+    /// The growth rate, the map is chaotic between about 3.57 and 4.0
+    r: f32,
+
+    /// Offset on top of the evolving growth rate, for the decorrelated variant
+    r_offset: f32,
+}
+
+impl Logistic {
+    /// How many iterations happen per unit of time
+    // With the fixed time step this comes down to about 220 iterations per second
+    const RATE: f32 = 1.0;
+}
+
+impl ChaosSystem for Logistic {
+    fn new(decorrelated: bool) -> Self {
+        let r_offset = if decorrelated { 0.05 } else { 0.0 };
+        let x: f32 = if decorrelated { 0.37 } else { 0.4 };
+
+        Self {
+            x: x.into(),
+            prev_x: x.into(),
+            progress: 0.0,
+            r: 3.8 + r_offset,
+            r_offset,
+        }
+    }
+
+    fn scatter(&mut self, [x, _, _]: [f32; 3]) {
+        // Stay away from 0 and 1, which the map never leaves
+        self.x = f64::from(0.05 + x * 0.9);
+        self.prev_x = self.x;
+    }
+
+    fn derivatives<F: DspFloat>(&self, _: [F; 3], _: F, _: F) -> [F; 3] {
+        // A map jumps from value to value, there's nothing to integrate. `step()` iterates
+        // the map directly instead.
+        [F::from_f64(0.0); 3]
+    }
+
+    fn set_state(&mut self, [x, _, prev_x]: [f64; 3]) {
+        self.x = x;
+        self.prev_x = prev_x;
+    }
+
+    // The integrator doesn't matter for a map
+    fn step<F: DspFloat>(&mut self, input_influence: f32, chaos_amount: f32, dt: f32, _: Integrator) {
+        self.progress += dt * Self::RATE;
+        if self.progress < 1.0 {
+            return;
+        }
+        self.progress -= 1.0;
+
+        // The input pushes the growth rate around, but it always stays in the chaotic region
+        let r = (self.r + input_influence * 0.2 * chaos_amount).clamp(3.57, 4.0);
+        let c = F::from_f64;
+        let (r, x) = (c(r as f64), c(self.x));
+        self.prev_x = self.x;
+        self.x = (r * x * (c(1.0) - x)).clamp(c(0.0001), c(0.9999)).to_f64();
+    }
+
+    fn evolve(&mut self, [_, r_mod, _]: [f32; 3], chaos_amount: f32) {
+        self.r = 3.8 + self.r_offset + r_mod * 0.3 * chaos_amount;
+    }
+
+    fn output(&self) -> f32 {
+        // Glide from the previous value to the current one, mapped to -1.0 to 1.0
+        let (x, prev_x) = (self.x as f32, self.prev_x as f32);
+        let glided = prev_x + (x - prev_x) * self.progress.min(1.0);
+        glided * 2.0 - 1.0
+    }
+
+    fn state(&self) -> [f64; 3] {
+        // Drawing x against the previous x shows the map's parabola
+        [self.x, 0.0, self.prev_x]
+    }
+}
+
+/// Any of the chaotic systems
+// An enum instead of a `Box<dyn ChaosSystem>` so switching systems doesn't allocate on the
+// audio thread, and the whole state can simply be copied between channels
+#[derive(Clone, Copy)]
+enum AnySystem {
+    Lorenz(Lorenz),
+    Rossler(Rossler),
+    Chua(Chua),
+    Logistic(Logistic),
+}
+
+impl AnySystem {
+    /// Create the selected system with its standard starting conditions
+    fn new(chaos_type: ChaosType, decorrelated: bool) -> Self {
+        match chaos_type {
+            ChaosType::Lorenz => AnySystem::Lorenz(Lorenz::new(decorrelated)),
+            ChaosType::Rossler => AnySystem::Rossler(Rossler::new(decorrelated)),
+            ChaosType::Chua => AnySystem::Chua(Chua::new(decorrelated)),
+            ChaosType::Logistic => AnySystem::Logistic(Logistic::new(decorrelated)),
+        }
+    }
+
+    /// The system as a trait object, so the methods don't all need their own match
+    fn system(&mut self) -> &mut dyn ChaosSystem {
+        match self {
+            AnySystem::Lorenz(system) => system,
+            AnySystem::Rossler(system) => system,
+            AnySystem::Chua(system) => system,
+            AnySystem::Logistic(system) => system,
+        }
+    }
+
+    /// Read-only version of `system()`
+    fn system_ref(&self) -> &dyn ChaosSystem {
+        match self {
+            AnySystem::Lorenz(system) => system,
+            AnySystem::Rossler(system) => system,
+            AnySystem::Chua(system) => system,
+            AnySystem::Logistic(system) => system,
+        }
+    }
+
+    /// Advance the system by one time step, calculated with the float type `F`
+    // `step()` is generic, so it can't be called through `system()`
+    fn step<F: DspFloat>(&mut self, input_influence: f32, chaos_amount: f32, dt: f32, integrator: Integrator) {
+        match self {
+            AnySystem::Lorenz(system) => system.step::<F>(input_influence, chaos_amount, dt, integrator),
+            AnySystem::Rossler(system) => system.step::<F>(input_influence, chaos_amount, dt, integrator),
+            AnySystem::Chua(system) => system.step::<F>(input_influence, chaos_amount, dt, integrator),
+            AnySystem::Logistic(system) => system.step::<F>(input_influence, chaos_amount, dt, integrator),
+        }
+    }
+}
+
+/// Per-channel state of the chaos effect
+// Every audio channel runs its own chaotic system, so the channels don't get
+// coupled together by sharing one attractor
+#[derive(Clone, Copy)]
+struct ChaosState {
+    /// The chaotic system of this channel
+    system: AnySystem,
+
+    /// Phase accumulator for secondary modulation
+    // Keeps track of phase for additional modulation effects. In 64 bits, since adding the
+    // tiny increment to a 32-bit phase rounds it off by about 1e-3 cycles per second at 96 kHz.
+    phase: f64,
+
+    /// How far the free-running evolution is into its pattern, in seconds
+    // Allows the system parameters to evolve slowly over time for continual variation
+    // Moves on by exactly `EVOLUTION_INTERVAL` every time the parameters evolve
+    evolution_time: f64,
+
+    /// The position within the tempo-synced evolution cycle (0.0 to 1.0)
+    // Only used when the evolution is synced to the host's tempo
+    evolution_phase: f32,
+
+    /// The output of the system before its latest step
+    // The effect's output is interpolated between this and the current output
+    prev_output: f32,
+
+    /// Where the current sample lies between the two latest steps
+    // Between -1.0 (at the previous step) and 0.0 (at the latest step). Every sample moves it
+    // forward, and once it gets past 0.0 the system has to take another step.
+    step_position: f32,
+
+    /// How many samples are left until the parameters evolve again
+    // Counted in whole samples, so the evolution lands on the same simulation steps at
+    // every sample rate that's a multiple of `STEP_RATE`. Changing the chaos rate doesn't
+    // change how long its cycles take or knock the synced evolution out of time.
+    evolution_countdown: u32,
+
+    /// The system's output after the color low pass
+    colored_output: f32,
+
+    /// How hard the fractal effect pushes the system, on top of the input, see `coupling::couple()`
+    coupling: f32,
+}
+
+impl ChaosState {
+    /// Create a chaotic system with starting conditions derived from a seed
+    // A seed of 0 gives the standard starting conditions. The decorrelated variant is
+    // used for the right channel in stereo mode.
+    fn new_seeded(chaos_type: ChaosType, seed: u32, decorrelated: bool) -> Self {
+        let mut state = Self {
+            system: AnySystem::new(chaos_type, decorrelated),
+            phase: 0.0, // Start with zero phase
+            evolution_time: 0.0, // Start at the beginning of the pattern
+            evolution_phase: 0.0, // Start at the beginning of the cycle
+            prev_output: 0.0,
+            step_position: 0.0, // Right at the latest step
+            evolution_countdown: 0, // Evolve right away
+            colored_output: 0.0,
+            coupling: 0.0, // Uncoupled
+        };
+        if seed != 0 {
+            state.scatter(seed, decorrelated);
+        }
+        state.prev_output = state.system.system_ref().output();
+        state.colored_output = state.prev_output;
+
+        state
+    }
+
+    /// Move the starting point of the system somewhere depending on the seed
+    fn scatter(&mut self, seed: u32, decorrelated: bool) {
+        // A few rounds of xorshift turn the seed into three starting coordinates
+        let mut rng_state = if decorrelated { seed ^ 0x5BD1_E995 } else { seed };
+        let mut next_random = || {
+            rng_state ^= rng_state << 13;
+            rng_state ^= rng_state >> 17;
+            rng_state ^= rng_state << 5;
+            rng_state as f32 / u32::MAX as f32
+        };
+        self.system.system().scatter([next_random(), next_random(), next_random()]);
+    }
+
+    /// Advance the system by one simulation step of `dt`
+    fn step(&mut self, input_influence: f32, chaos_amount: f32, dt: f32, integrator: Integrator, precision: Precision) {
+        self.prev_output = self.system.system_ref().output();
+        match precision {
+            Precision::Single => self.system.step::<f32>(input_influence, chaos_amount, dt, integrator),
+            Precision::Double => self.system.step::<f64>(input_influence, chaos_amount, dt, integrator),
+        }
+    }
+
+    /// The system's output at the current sample, between the two latest steps
+    fn output(&self) -> f32 {
+        let output = self.system.system_ref().output();
+        self.prev_output + (output - self.prev_output) * (1.0 + self.step_position)
+    }
+
+    /// Slowly evolve the system's parameters over time
+    // This prevents the effect from sounding the same over long periods. Called once every
+    // `EVOLUTION_INTERVAL`, `evolution_rate` is how many tempo-synced evolution cycles pass
+    // per second, or `None` for the free-running evolution.
+    fn evolve_parameters(&mut self, chaos_amount: f32, evolution_rate: Option<f32>) {
+        // Every system decides for itself which of its parameters these move
+        let modulation = if evolution_rate.is_some() {
+            // In sync with the host the oscillations are harmonics of the cycle, so the
+            // whole pattern repeats exactly once per cycle and lines up with song sections
+            let angle = self.evolution_phase * 2.0 * PI;
+            [
+                0.5 * angle.sin(),
+                0.5 * (angle * 2.0).sin(),
+                0.3 * (angle * 3.0).sin(),
+            ]
+        } else {
             // Create slow LFOs (Low Frequency Oscillators) for parameter evolution
             // These create slow, cyclic variations in the parameters
-            let time = (self.evolution_counter as f32) / (self.sample_rate * 120.0); // 2 minute cycle
-            
+            let time = (self.evolution_time / 120.0) as f32; // 2 minute cycle
+
             // Generate three different slowly varying oscillations with different frequencies
-            let sigma_mod = 0.5 * (time * 0.1 * PI).sin();
-            let rho_mod = 0.5 * (time * 0.07 * PI).sin();
-            let beta_mod = 0.3 * (time * 0.05 * PI).sin();
-            
-            // Modulate parameters around their standard values
-            // The chaos_amount scales how much variation is applied
-            self.sigma = 10.0 + (sigma_mod * self.chaos_amount);
-            self.rho = 28.0 + (rho_mod * 5.0 * self.chaos_amount);
-            self.beta = (8.0 / 3.0) + (beta_mod * self.chaos_amount);
-        }
-        
-        // Increment counter and wrap around at a large value
-        // This prevents the counter from overflowing
-        self.evolution_counter = (self.evolution_counter + 1) % (self.sample_rate as usize * 600); // 10 minute cycle
-    }
-    
-    /// Process a single sample through the chaos effect
+            [
+                0.5 * (time * 0.1 * PI).sin(),
+                0.5 * (time * 0.07 * PI).sin(),
+                0.3 * (time * 0.05 * PI).sin(),
+            ]
+        };
+        self.system.system().evolve(modulation, chaos_amount);
+
+        // Both clocks move on by the same interval, however many samples it took
+        if let Some(rate) = evolution_rate {
+            self.evolution_phase = (self.evolution_phase + rate * EVOLUTION_INTERVAL as f32).rem_euclid(1.0);
+        }
+        self.evolution_time = (self.evolution_time + EVOLUTION_INTERVAL) % FREE_EVOLUTION_CYCLE;
+    }
+}
+
+/// Where every channel's system is and how far its evolution has got
+// Saved with the project when the plugin persists its engine state, so reopening it picks
+// the texture up where it was instead of starting the attractor over. The evolved system
+// parameters aren't stored, they follow from the evolution time.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct AttractorSnapshot {
+    /// The system the snapshot was taken of, as its index in `ChaosType`
+    chaos_type: usize,
+
+    /// The (x, y, z) position of every channel's system
+    positions: [[f32; 3]; MAX_CHANNELS],
+
+    /// How far every channel's free-running evolution is into its pattern, in seconds
+    evolution_times: [f64; MAX_CHANNELS],
+}
+
+/// A chaotic audio effect based on the Lorenz attractor and other chaotic systems
+// This implements an effect based on chaos theory. By default it runs the Lorenz attractor,
+// a set of differential equations that create unpredictable but deterministic patterns,
+// but any of the systems in `ChaosType` can drive it.
+// The amount of chaos is passed in with every sample instead of being stored,
+// so a smoothed parameter can change it without any stepping
+pub struct ChaosAttractor {
+    /// One chaotic system per channel
+    channels: [ChaosState; MAX_CHANNELS],
+
+    /// The selected chaotic system
+    chaos_type: ChaosType,
+
+    /// Sample rate for time-based calculations
+    // We need to know the sample rate for proper time-based effects
+    sample_rate: f32,
+
+    /// Whether the right channel runs a decorrelated attractor for a wide stereo image
+    stereo: bool,
+
+    /// When frozen the systems stop moving, locking the modulation in place
+    frozen: bool,
+
+    /// The seed the starting conditions are derived from
+    seed: u32,
+
+    /// The rate of the secondary phase modulation, in Hz
+    mod_rate_hz: f32,
+
+    /// How far the secondary modulation's phase advances per sample, in cycles
+    // Derived from `mod_rate_hz` and the sample rate, so the modulation runs at the same
+    // speed at every sample rate
+    mod_phase_increment: f64,
+
+    /// The numerical method for the continuous systems
+    integrator: Integrator,
+
+    /// The float type the systems are calculated with
+    precision: Precision,
+
+    /// How many simulation steps happen per sample at the normal rate
+    // Below 1.0 at higher sample rates, where the output is interpolated between steps
+    steps_per_sample: f32,
+
+    /// The time step of every simulation step, only longer than `STEP_DT` at the Eco quality
+    step_dt: f32,
+
+    /// The quality tier, which can override the integrator and run the systems at control rate
+    quality: Quality,
+
+    /// How fast the systems run, as a multiple of `STEP_RATE`
+    rate: f32,
+
+    /// How many tempo-synced evolution cycles pass per second
+    // `None` while the evolution runs freely
+    evolution_rate: Option<f32>,
+
+    /// `EVOLUTION_INTERVAL` in samples
+    evolution_interval: u32,
+
+    /// Whether the parameter evolution moves on
+    clock_running: bool,
+
+    /// How much of the chaotic signal's bandwidth gets through, 1.0 is all of it
+    color: f32,
+
+    /// The coefficient of the one-pole low pass that limits the bandwidth
+    // 1.0 lets the signal straight through
+    color_coeff: f32,
+
+    /// The character of the soft clipper on the output, see `saturation::soft_clip()`
+    saturation: f32,
+
+    /// The gain of the input the systems listen to, the audio itself isn't changed by it
+    influence_gain: f32,
+
+    /// What the FM branch does, see `FmMode`
+    fm_mode: FmMode,
+
+    /// How far the chaos signal shifts the input at full chaos amount, in Hz
+    shift_depth: f32,
+
+    /// The frequency shifter the FM branch runs in the Shift mode
+    shifter: FrequencyShifter,
+}
+
+impl ChaosAttractor {
+    /// Create a new chaos attractor effect
+    // Constructor for the ChaosAttractor effect
+    pub fn new() -> Self {
+        // Create and return a new ChaosAttractor with initial values
+        Self {
+            channels: [ChaosState::new_seeded(ChaosType::Lorenz, 0, false); MAX_CHANNELS], // Every channel starts at the same initial conditions
+            chaos_type: ChaosType::Lorenz, // The original chaos effect
+            sample_rate: 44100.0, // Default sample rate, will be updated
+            stereo: false, // Dual-mono by default
+            frozen: false, // Running by default
+            seed: 0, // The standard starting conditions
+            mod_rate_hz: DEFAULT_MOD_RATE_HZ,
+            mod_phase_increment: DEFAULT_MOD_RATE_HZ as f64 / 44100.0,
+            integrator: Integrator::Euler, // The original integration method
+            precision: Precision::Single, // The same math as the rest of the chain
+            steps_per_sample: 1.0, // One step per sample at 44.1 kHz
+            step_dt: STEP_DT,
+            quality: Quality::Normal, // The integrator as it's set, at the full step rate
+            rate: 1.0, // The speed the chaos effect always ran at
+            evolution_rate: None, // Free-running evolution
+            evolution_interval: (EVOLUTION_INTERVAL * 44100.0) as u32,
+            clock_running: true, // Always running unless the plugin follows the transport
+            color: 1.0, // The full bandwidth, like the original chaos effect
+            color_coeff: 1.0,
+            saturation: 0.0, // Plain tanh, like the original chaos effect
+            influence_gain: 1.0, // The input as it comes in
+            fm_mode: FmMode::Classic, // The original FM branch
+            shift_depth: 100.0,
+            shifter: FrequencyShifter::new(),
+        }
+    }
+
+    /// Set the sample rate for time-based calculations
+    // Updates the sample rate and adjusts dependent parameters
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        // The systems keep stepping at their own fixed rate, higher sample rates just
+        // interpolate more samples between the steps
+        self.update_step_rate();
+        self.evolution_interval = ((EVOLUTION_INTERVAL * sample_rate as f64).round() as u32).max(1);
+        self.mod_phase_increment = self.mod_rate_hz as f64 / sample_rate as f64;
+        self.color_coeff = color_coefficient(self.color, sample_rate);
+        self.shifter.set_sample_rate(sample_rate);
+    }
+
+    /// Set how much of the chaotic signal's bandwidth gets through, from 0.0 to 1.0
+    // A one-pole low pass sits between the systems and everything they modulate. Low
+    // colors turn even a fast, rough system into a smooth wobble, 1.0 switches the filter
+    // off for the full audio-rate noise. The coefficient is only worked out again when the
+    // color changes, so a smoothed parameter can call this on every sample.
+    pub fn set_color(&mut self, color: f32) {
+        let color = color.clamp(0.0, 1.0);
+        if color != self.color {
+            self.color = color;
+            self.color_coeff = color_coefficient(color, self.sample_rate);
+        }
+    }
+
+    /// Select what the FM branch does, see `FmMode`
+    // The shifter keeps running in the Classic mode too, so switching back and forth to
+    // compare the two doesn't start it from silence
+    pub fn set_fm_mode(&mut self, mode: FmMode) {
+        self.fm_mode = mode;
+    }
+
+    /// Set how far the chaos signal shifts the input in the Shift mode, in Hz
+    // Cheap enough to call on every sample, so a smoothed parameter can sweep it
+    pub fn set_shift_depth(&mut self, depth_hz: f32) {
+        self.shift_depth = depth_hz.clamp(0.0, MAX_SHIFT_HZ);
+    }
+
+    /// Set the character of the soft clipper on the output, from 0.0 (tanh) to 1.0 (polynomial)
+    pub fn set_saturation(&mut self, character: f32) {
+        self.saturation = character;
+    }
+
+    /// Set how much the input is turned up or down before it pushes the systems around
+    // The plugin's input calibration sets this on every sample, so the systems respond the
+    // same to a quiet and a loud track. The gain only applies to what the systems hear, the
+    // sample that's modulated keeps its level.
+    pub fn set_influence_gain(&mut self, gain: f32) {
+        self.influence_gain = gain;
+    }
+
+    /// Push a channel's system around on top of what the input does
+    // The fractal effect sets this on every sample when the two are coupled. It's added to
+    // the input the system listens to, which for the Lorenz system moves rho.
+    pub fn set_coupling(&mut self, channel: usize, influence: f32) {
+        self.channels[channel].coupling = influence;
+    }
+
+    /// Set how fast the systems run, as a multiple of their normal speed
+    // Cheap enough to call on every sample, so a smoothed parameter can sweep it. Slow
+    // rates turn the chaos into a gentle drift, fast ones push it up into the audio range.
+    pub fn set_rate(&mut self, rate: f32) {
+        self.rate = rate.clamp(MIN_RATE, MAX_RATE);
+    }
+
+    /// Run the secondary phase modulation freely at the given rate in Hz
+    pub fn set_mod_rate(&mut self, rate_hz: f32) {
+        self.mod_rate_hz = rate_hz;
+        self.mod_phase_increment = rate_hz as f64 / self.sample_rate as f64;
+    }
+
+    /// Sync the secondary phase modulation to the host's tempo
+    // Works just like the LFO: `tempo` is in beats per minute, and with a song position
+    // (in quarter notes) the phase locks to it, so the modulation lands in the same spot
+    // every time the song is played
+    pub fn set_synced_mod_rate(&mut self, division: NoteDivision, tempo: f64, pos_beats: Option<f64>) {
+        let cycle_beats = division.beats();
+        self.set_mod_rate((tempo / 60.0 / cycle_beats) as f32);
+
+        if let Some(pos_beats) = pos_beats {
+            let phase = (pos_beats / cycle_beats).rem_euclid(1.0);
+            for state in self.channels.iter_mut() {
+                state.phase = phase;
+            }
+        }
+    }
+
+    /// Let the parameter evolution run freely, in cycles of a few minutes
+    pub fn set_free_evolution(&mut self) {
+        self.evolution_rate = None;
+    }
+
+    /// Sync the parameter evolution to the host's tempo, one cycle every `cycle_beats` beats
+    // Like the secondary modulation, the cycle locks to the song position (in quarter notes)
+    // when there is one, so the texture changes land on the same bars every time
+    pub fn set_synced_evolution(&mut self, cycle_beats: f64, tempo: f64, pos_beats: Option<f64>) {
+        // The evolution runs on its own clock, so the rate doesn't depend on the sample rate
+        self.evolution_rate = Some((tempo / 60.0 / cycle_beats) as f32);
+
+        if let Some(pos_beats) = pos_beats {
+            let phase = (pos_beats / cycle_beats).rem_euclid(1.0) as f32;
+            for state in self.channels.iter_mut() {
+                state.evolution_phase = phase;
+            }
+        }
+    }
+
+    /// Let the parameter evolution move on, or hold it where it is
+    // Unlike freezing, the systems themselves keep moving. The plugin holds the evolution
+    // while the host isn't playing, so a render evolves the same way as the playback it
+    // was auditioned with.
+    pub fn set_clock_running(&mut self, running: bool) {
+        self.clock_running = running;
+    }
+
+    /// Start the parameter evolution over, without touching the systems themselves
+    pub fn reset_clock(&mut self) {
+        for state in self.channels.iter_mut() {
+            state.evolution_time = 0.0;
+            state.evolution_phase = 0.0;
+            state.evolution_countdown = 0;
+        }
+    }
+
+    /// The position of the secondary phase modulation within its cycle (0.0 to 1.0)
+    pub fn mod_phase(&self, channel: usize) -> f32 {
+        self.channels[channel].phase as f32
+    }
+
+    /// Select the chaotic system
+    // Every channel restarts from the new system's starting conditions
+    pub fn set_chaos_type(&mut self, chaos_type: ChaosType) {
+        if chaos_type != self.chaos_type {
+            self.chaos_type = chaos_type;
+            self.update_step_rate();
+            self.reset();
+        }
+    }
+
+    /// Select the quality tier, see `Quality`
+    // Eco runs the continuous systems at control rate with Euler integration, Ultra always
+    // uses Runge-Kutta. The systems carry on from where they are, so switching doesn't click.
+    pub fn set_quality(&mut self, quality: Quality) {
+        if quality != self.quality {
+            self.quality = quality;
+            self.update_step_rate();
+        }
+    }
+
+    /// Work out the step rate and the time step for the sample rate, the system and the quality
+    fn update_step_rate(&mut self) {
+        let divider = if self.quality == Quality::Eco && self.chaos_type != ChaosType::Logistic {
+            ECO_STEP_DIVIDER
+        } else {
+            1.0
+        };
+        self.steps_per_sample = STEP_RATE / divider / self.sample_rate;
+        self.step_dt = STEP_DT * divider;
+    }
+
+    /// Switch between dual-mono and decorrelated stereo attractors
+    // In stereo mode the right channel gets its own slightly different system, so the
+    // modulation moves independently on both sides. Switching back to dual-mono makes the
+    // right channel follow the left channel's attractor again.
+    pub fn set_stereo(&mut self, stereo: bool) {
+        if stereo == self.stereo {
+            return;
+        }
+        self.stereo = stereo;
+
+        if stereo {
+            self.channels[1] = ChaosState::new_seeded(self.chaos_type, self.seed, true);
+        } else {
+            self.channels[1] = self.channels[0];
+        }
+    }
+
+    /// Select the numerical method for the continuous systems
+    // Switching doesn't reset anything, the systems just carry on from where they are
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    /// Select the float type the systems are calculated with
+    // The systems carry on from where they are, so switching doesn't click
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.precision = precision;
+    }
+
+    /// Stop or resume advancing the chaotic systems
+    // While frozen, the systems stay exactly where they are, so the effect keeps its current timbre
+    pub fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    /// Restart the systems from the starting conditions of a new seed
+    // Nothing happens if the seed didn't change
+    pub fn set_seed(&mut self, seed: u32) {
+        if seed != self.seed {
+            self.seed = seed;
+            self.reset();
+        }
+    }
+
+    /// The current (x, y, z) position of the given channel's chaotic system
+    // Used by the GUI to draw the attractor
+    pub fn system_state(&self, channel: usize) -> [f32; 3] {
+        self.channels[channel].system.system_ref().state().map(|value| value as f32)
+    }
+
+    /// How far the given channel's system is out, from 0.0 at the origin to 1.0 at its limit
+    // The largest of the three state variables relative to where they get clamped, which is
+    // what the stability governor watches to keep the system off its limit
+    pub fn magnitude(&self, channel: usize) -> f32 {
+        let system = self.channels[channel].system.system_ref();
+        let largest = system.state().iter().fold(0.0f64, |largest, value| largest.max(value.abs()));
+        largest as f32 / system.limit()
+    }
+
+    /// Take a snapshot of where every channel's system is
+    pub fn snapshot(&self) -> AttractorSnapshot {
+        AttractorSnapshot {
+            chaos_type: self.chaos_type as usize,
+            positions: std::array::from_fn(|channel| self.system_state(channel)),
+            evolution_times: std::array::from_fn(|channel| self.channels[channel].evolution_time),
+        }
+    }
+
+    /// Move every channel's system back to where a snapshot was taken
+    // A snapshot of another system is ignored, its point would be meaningless for this one,
+    // and so is a damaged one. The output picks up right at the restored point, and the
+    // parameters evolve on the next sample to where they were at the restored evolution time.
+    pub fn restore(&mut self, snapshot: &AttractorSnapshot) {
+        let finite = snapshot.positions.iter().flatten().all(|value| value.is_finite())
+            && snapshot.evolution_times.iter().all(|time| time.is_finite());
+        if snapshot.chaos_type != self.chaos_type as usize || !finite {
+            return;
+        }
+
+        for (channel, state) in self.channels.iter_mut().enumerate() {
+            state.system.system().set_state(snapshot.positions[channel].map(f64::from));
+            state.evolution_time = snapshot.evolution_times[channel].rem_euclid(FREE_EVOLUTION_CYCLE);
+            state.evolution_countdown = 0;
+            state.step_position = 0.0;
+            state.prev_output = state.system.system_ref().output();
+            state.colored_output = state.prev_output;
+        }
+    }
+
+    /// The chaotic signal of the given channel (between -1.0 and 1.0) at the latest sample
+    // Other effects use this as a modulation source. It only moves while the chaos
+    // effect is running, with the chaos amount turned down it holds its last value.
+    pub fn signal(&self, channel: usize) -> f32 {
+        self.channels[channel].colored_output
+    }
+
+    /// Reset the chaotic system to initial conditions
+    // Resets the state of the systems to avoid getting stuck or blowing up
+    pub fn reset(&mut self) {
+        // Reset every channel to the starting conditions of the current seed
+        self.channels = [ChaosState::new_seeded(self.chaos_type, self.seed, false); MAX_CHANNELS];
+        if self.stereo {
+            self.channels[1] = ChaosState::new_seeded(self.chaos_type, self.seed, true);
+        }
+        self.shifter.reset();
+    }
+
+    /// Process a single sample of the given channel through the chaos effect
     // This is the main processing function that applies the chaos effect to an audio sample
-    pub fn process(&mut self, sample: f32) -> f32 {
+    // The channel index selects which channel's chaotic system gets updated
+    // `chaos_amount` controls how much of the effect is applied to the signal (0.0 to 1.0)
+    pub fn process(&mut self, channel: usize, sample: f32, chaos_amount: f32) -> f32 {
         // Early exit if the effect is turned off (optimization)
-        if self.chaos_amount <= 0.001 {
+        if chaos_amount <= 0.001 {
             return sample; // Bypass if chaos amount is essentially zero
         }
-        
-        // Update the phase accumulator for secondary modulation
-        // This creates an additional oscillation for modulation effects
-        self.phase += 0.001 * (440.0 / self.sample_rate); 
-        if self.phase > 1.0 {
-            self.phase -= 1.0;  // Wrap phase when it exceeds 1.0
-        }
-        
-        // Update the chaotic system, using the input to influence it
-        // This makes the chaos responsive to the input audio
-        self.update_lorenz(sample);
-        
-        // Evolve parameters slowly over time for continual variation
-        self.evolve_parameters();
-        
-        // Get the chaotic output signal from the Lorenz system
-        let chaos_signal = self.get_lorenz_output();
-        
+
+        // Borrow this channel's state mutably for the rest of the method
+        let state = &mut self.channels[channel];
+
+        // Everything that moves the system forward is skipped while frozen
+        if !self.frozen {
+            // Update the phase accumulator for secondary modulation
+            // This creates an additional oscillation for modulation effects
+            state.phase += self.mod_phase_increment;
+            if state.phase >= 1.0 {
+                state.phase -= 1.0;  // Wrap phase when it exceeds 1.0
+            }
+
+            // Update the chaotic system as many times as this sample needs, using the input
+            // to influence it. This makes the chaos responsive to the input audio.
+            state.step_position += self.steps_per_sample * self.rate;
+            let influence = sample * self.influence_gain + state.coupling;
+            let integrator = self.quality.integrator(self.integrator);
+            while state.step_position > 0.0 {
+                state.step(influence, chaos_amount, self.step_dt, integrator, self.precision);
+                state.step_position -= 1.0;
+            }
+
+            // Evolve parameters slowly over time for continual variation
+            if self.clock_running {
+                if state.evolution_countdown == 0 {
+                    state.evolve_parameters(chaos_amount, self.evolution_rate);
+                    state.evolution_countdown = self.evolution_interval;
+                }
+                state.evolution_countdown -= 1;
+            }
+        }
+
+        // Get the chaotic output signal from the system, through the color low pass
+        // The filter keeps running while frozen, so freezing glides to a stop instead of jumping
+        state.colored_output += (state.output() - state.colored_output) * self.color_coeff;
+        let chaos_signal = state.colored_output;
+
         // Combine the input with the chaotic signal in different ways
-        
+
         // 1. Amplitude modulation (AM) - varies the volume based on the chaos signal
         // Multiplying signals creates amplitude modulation, producing sidebands
-        let am = sample * (1.0 + chaos_signal * self.chaos_amount);
-        
-        // 2. Frequency modulation (FM) via allpass filter with varying delay
-        // This creates frequency modulation effects by varying the phase
-        let phase_mod = (self.phase + chaos_signal * 0.01 * self.chaos_amount) * 2.0 * PI;
-        let fm = sample * phase_mod.cos() * 0.5;
-        
+        let am = sample * (1.0 + chaos_signal * chaos_amount);
+
+        // 2. Frequency modulation (FM)
+        // The shifter runs in both modes so either one can be switched to without a click
+        let shifted = self.shifter.process(channel, sample, chaos_signal * self.shift_depth * chaos_amount);
+        let fm = match self.fm_mode {
+            // Multiplying by a cosine of the secondary phase, nudged by the chaos signal.
+            // Despite the name that's amplitude modulation, the input's frequencies stay put.
+            FmMode::Classic => {
+                let phase_mod = (state.phase as f32 + chaos_signal * 0.01 * chaos_amount) * 2.0 * PI;
+                sample * phase_mod.cos() * 0.5
+            }
+            // The input shifted up or down by as many Hz as the chaos signal says, so its
+            // frequencies actually move
+            FmMode::Shift => shifted * 0.5,
+        };
+
         // 3. Direct addition of shaped chaos
         // Raising to the power of 3 (cubic) adds harmonic content
-        let shaped_chaos = chaos_signal.powf(3.0) * self.chaos_amount * 0.3;
-        
+        let shaped_chaos = chaos_signal.powf(3.0) * chaos_amount * 0.3;
+
         // Mix together based on chaos amount
         // Blend the original signal with the processed signal based on chaos_amount
-        let result = sample * (1.0 - self.chaos_amount) +  // Original (dry) signal
-                     (am * 0.5 + fm * 0.3 + shaped_chaos) * self.chaos_amount;  // Processed (wet) signal
-        
+        let result = sample * (1.0 - chaos_amount) +  // Original (dry) signal
+                     (am * 0.5 + fm * 0.3 + shaped_chaos) * chaos_amount;  // Processed (wet) signal
+
         // Apply soft clipping to prevent extreme output values
         // This prevents the output from getting too loud or distorted
-        soft_clip(result)
+        saturation::soft_clip(result, self.saturation)
+    }
+
+    /// Process a slice of samples of the given channel through the chaos effect
+    // The same as calling `process()` for every sample, so the effect can be used outside
+    // of a plugin. The same chaos amount is used for the whole slice.
+    #[cfg(feature = "dsp")]
+    pub fn process_slice(&mut self, channel: usize, samples: &mut [f32], chaos_amount: f32) {
+        for sample in samples.iter_mut() {
+            *sample = self.process(channel, *sample, chaos_amount);
+        }
+    }
+}
+
+/// The one-pole coefficient of the color low pass for a color at a sample rate
+// The cutoff sweeps exponentially, so every bit of the knob moves it by the same interval
+fn color_coefficient(color: f32, sample_rate: f32) -> f32 {
+    if color >= 1.0 {
+        return 1.0;
     }
+
+    let cutoff = MIN_COLOR_HZ * (MAX_COLOR_HZ / MIN_COLOR_HZ).powf(color);
+    1.0 - (-2.0 * PI * cutoff / sample_rate).exp()
 }
 
-/// Soft clipping function to prevent output from exceeding [-1, 1] too harshly
-// This limits the signal in a musical way to prevent digital distortion
-fn soft_clip(input: f32) -> f32 {
-    // The hyperbolic tangent (tanh) function naturally limits values to [-1, 1]
-    // It has a smooth S-curve shape that sounds more natural than hard clipping
-    input.tanh()
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The sample rates the modulation has to behave the same at
+    const SAMPLE_RATES: [f32; 3] = [44100.0, 48000.0, 96000.0];
+
+    /// Run the effect for `seconds` at `sample_rate` and return the modulation phase
+    fn phase_after(sample_rate: f32, rate_hz: f32, seconds: f32) -> f32 {
+        let mut chaos = ChaosAttractor::new();
+        chaos.set_sample_rate(sample_rate);
+        chaos.set_mod_rate(rate_hz);
+        for _ in 0..(sample_rate * seconds).round() as usize {
+            chaos.process(0, 0.0, 0.5);
+        }
+
+        chaos.mod_phase(0)
+    }
+
+    #[test]
+    fn mod_phase_is_sample_rate_independent() {
+        for rate_hz in [DEFAULT_MOD_RATE_HZ, 1.0, 7.3] {
+            for seconds in [0.25, 0.5, 1.0] {
+                let expected = (rate_hz * seconds).fract();
+                for sample_rate in SAMPLE_RATES {
+                    let phase = phase_after(sample_rate, rate_hz, seconds);
+                    // Compare on the circle, so 0.9999 and 0.0001 count as close
+                    let error = (phase - expected).abs();
+                    assert!(
+                        error.min(1.0 - error) < 1e-3,
+                        "{rate_hz} Hz after {seconds} s at {sample_rate} Hz: {phase}, expected {expected}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn synced_mod_phase_follows_song_position() {
+        for sample_rate in SAMPLE_RATES {
+            let mut chaos = ChaosAttractor::new();
+            chaos.set_sample_rate(sample_rate);
+
+            // Two and a half beats into a one bar cycle
+            chaos.set_synced_mod_rate(NoteDivision::Whole, 120.0, Some(2.5));
+            assert!((chaos.mod_phase(0) - 0.625).abs() < 1e-6);
+            assert!((chaos.mod_phase(1) - 0.625).abs() < 1e-6);
+
+            // A whole note at 120 BPM takes two seconds, so half a second is a quarter of the cycle
+            for _ in 0..(sample_rate * 0.5).round() as usize {
+                chaos.process(0, 0.0, 0.5);
+            }
+            let phase = chaos.mod_phase(0);
+            assert!((phase - 0.875).abs() < 1e-3, "{phase} at {sample_rate} Hz");
+        }
+    }
+
+    #[test]
+    fn trajectory_is_sample_rate_independent() {
+        // At whole multiples of the step rate every step lands exactly on a sample,
+        // so the systems have to end up in exactly the same spot
+        for chaos_type in [ChaosType::Lorenz, ChaosType::Rossler, ChaosType::Chua, ChaosType::Logistic] {
+            for integrator in [Integrator::Euler, Integrator::RungeKutta4] {
+                let state_after_one_second = |sample_rate: f32| {
+                    let mut chaos = ChaosAttractor::new();
+                    chaos.set_sample_rate(sample_rate);
+                    chaos.set_chaos_type(chaos_type);
+                    chaos.set_integrator(integrator);
+                    for _ in 0..sample_rate as usize {
+                        chaos.process(0, 0.0, 0.5);
+                    }
+
+                    chaos.system_state(0)
+                };
+
+                let expected = state_after_one_second(STEP_RATE);
+                for factor in [2.0, 4.0] {
+                    assert_eq!(
+                        state_after_one_second(STEP_RATE * factor),
+                        expected,
+                        "{chaos_type:?} with {integrator:?} at {factor}x"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn evolution_keeps_time_at_any_sample_rate_and_chaos_rate() {
+        for sample_rate in SAMPLE_RATES {
+            for rate in [0.25, 1.0, 4.0] {
+                let mut chaos = ChaosAttractor::new();
+                chaos.set_sample_rate(sample_rate);
+                chaos.set_rate(rate);
+                for _ in 0..(sample_rate * 2.0).round() as usize {
+                    chaos.process(0, 0.0, 0.5);
+                }
+
+                // Two seconds are 200 intervals, whatever the rates
+                let time = chaos.channels[0].evolution_time;
+                assert!((time - 2.0).abs() < 1e-9, "{time} s at {sample_rate} Hz and {rate}x");
+            }
+        }
+    }
+
+    #[test]
+    fn frozen_mod_phase_stays_put() {
+        let mut chaos = ChaosAttractor::new();
+        chaos.set_sample_rate(48000.0);
+        chaos.set_frozen(true);
+        for _ in 0..4800 {
+            chaos.process(0, 0.0, 0.5);
+        }
+
+        assert_eq!(chaos.mod_phase(0), 0.0);
+    }
+}
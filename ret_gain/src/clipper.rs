@@ -0,0 +1,46 @@
+// The clip indicator is shared with the GUI
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A hard clipper that never lets a sample past the ceiling
+// The distortion, fractal and chaos stages all clip softly, which is part of their sound
+// but lets peaks overshoot a little. This is the last stage before the output and cuts
+// everything above the ceiling flat. Whenever it does, the clip indicator lights up and
+// stays lit until the editor clears it, so a single over can't be missed.
+pub struct HardClipper {
+    /// The highest level a sample can have, as a linear gain
+    ceiling: f32,
+
+    /// Set whenever a sample had to be cut, cleared by the GUI
+    clipped: Arc<AtomicBool>,
+}
+
+impl HardClipper {
+    /// Create a clipper with its ceiling at full scale
+    pub fn new() -> Self {
+        Self {
+            ceiling: 1.0,
+            clipped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Set the ceiling as a linear gain
+    pub fn set_ceiling(&mut self, ceiling: f32) {
+        self.ceiling = ceiling;
+    }
+
+    /// The clip indicator, for the GUI to show and clear
+    pub fn clip_indicator(&self) -> Arc<AtomicBool> {
+        self.clipped.clone()
+    }
+
+    /// Clip a single sample to the ceiling
+    pub fn process(&mut self, sample: f32) -> f32 {
+        if sample.abs() <= self.ceiling {
+            return sample;
+        }
+
+        self.clipped.store(true, Ordering::Relaxed);
+        sample.clamp(-self.ceiling, self.ceiling)
+    }
+}
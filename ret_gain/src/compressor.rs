@@ -0,0 +1,118 @@
+// Decibel conversions for the gain computer
+use crate::decibels;
+// The attack and release times are turned into one-pole coefficients like the envelope follower's
+use crate::envelope::smoothing_coeff;
+
+/// How far the chaos signal stretches or squeezes the release time in chaotic release mode
+// The release time is multiplied or divided by up to this factor, three octaves in total
+const CHAOTIC_RELEASE_RANGE: f32 = 2.8;
+
+/// A stereo-linked feed-forward compressor
+// The gain computer looks at the input level in dB and works out how far it's over the
+// threshold, and how much of that the ratio takes away. The gain reduction is smoothed
+// with the attack time when it grows and with the release time when it shrinks.
+// In chaotic release mode the release time is wobbled around by the chaos signal, so
+// the pumping never settles into a regular pattern.
+pub struct Compressor {
+    /// The level where compression starts, in dB
+    threshold_db: f32,
+
+    /// How many dB over the threshold it takes to get 1 dB out
+    ratio: f32,
+
+    /// The current gain reduction in dB (0.0 and up)
+    reduction_db: f32,
+
+    /// Coefficient of the one-pole smoother while the gain reduction grows
+    attack_coeff: f32,
+
+    /// The release time in milliseconds
+    // Kept around because chaotic release mode derives its own coefficient from it
+    release_ms: f32,
+
+    /// Coefficient of the one-pole smoother while the gain reduction shrinks
+    release_coeff: f32,
+
+    /// Whether the chaos signal wobbles the release time
+    chaotic_release: bool,
+
+    /// Sample rate for time-based calculations
+    sample_rate: f32,
+}
+
+impl Compressor {
+    /// Create a new compressor
+    // With the default 1:1 ratio it doesn't do anything
+    pub fn new() -> Self {
+        Self {
+            threshold_db: 0.0,
+            ratio: 1.0,
+            reduction_db: 0.0,
+            attack_coeff: 1.0,
+            release_ms: 100.0,
+            release_coeff: 1.0,
+            chaotic_release: false,
+            sample_rate: 44100.0, // Default sample rate, will be updated
+        }
+    }
+
+    /// Set the sample rate for time-based calculations
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Set the threshold in dB and the ratio
+    pub fn set_curve(&mut self, threshold_db: f32, ratio: f32) {
+        self.threshold_db = threshold_db;
+        self.ratio = ratio.max(1.0);
+    }
+
+    /// Set the attack and release times in milliseconds
+    pub fn set_times(&mut self, attack_ms: f32, release_ms: f32) {
+        self.attack_coeff = smoothing_coeff(attack_ms, self.sample_rate);
+        self.release_ms = release_ms;
+        self.release_coeff = smoothing_coeff(release_ms, self.sample_rate);
+    }
+
+    /// Switch chaotic release mode on or off
+    pub fn set_chaotic_release(&mut self, chaotic_release: bool) {
+        self.chaotic_release = chaotic_release;
+    }
+
+    /// Release all gain reduction
+    pub fn reset(&mut self) {
+        self.reduction_db = 0.0;
+    }
+
+    /// Feed the level of the next frame and return the gain to apply to it
+    // `level` should be the peak of all channels, so both sides are compressed together.
+    // `chaos_signal` (between -1.0 and 1.0) is only used in chaotic release mode.
+    pub fn process(&mut self, level: f32, chaos_signal: f32) -> f32 {
+        // Nothing to do at 1:1, and the smoother is left at rest
+        if self.ratio <= 1.0 && self.reduction_db <= 0.0 {
+            return 1.0;
+        }
+
+        // How much the gain has to come down for this frame
+        let level_db = decibels::gain_to_db(level.max(1e-6));
+        let over_db = (level_db - self.threshold_db).max(0.0);
+        let target_db = over_db * (1.0 - 1.0 / self.ratio);
+
+        let coeff = if target_db > self.reduction_db {
+            self.attack_coeff
+        } else if self.chaotic_release {
+            // The chaos signal stretches and squeezes the release time
+            let release_ms = self.release_ms * CHAOTIC_RELEASE_RANGE.powf(chaos_signal);
+            smoothing_coeff(release_ms, self.sample_rate)
+        } else {
+            self.release_coeff
+        };
+        self.reduction_db += (target_db - self.reduction_db) * coeff;
+        // Snap to zero at the end of the release, so the early exit above can kick in at 1:1
+        if self.reduction_db < 1e-4 {
+            self.reduction_db = 0.0;
+        }
+
+        decibels::db_to_gain(-self.reduction_db)
+    }
+}
@@ -0,0 +1,26 @@
+// The two effects that get coupled
+use crate::chaos::ChaosAttractor;
+use crate::fractal::FractalMagic;
+
+/// How hard the fractal's z pushes the chaotic system at full coupling
+// z stays within about ±2.0, so at full coupling it pushes the system like an input at
+// full scale does, which moves the Lorenz system's rho by up to 5 at full chaos
+const FRACTAL_TO_CHAOS: f32 = 0.5;
+
+/// How far the chaos signal moves the fractal's constant c at full coupling
+// The Julia constant sits right on the edge of the set, a few hundredths either way are
+// enough to turn the orbit from circling into wandering off and back
+const CHAOS_TO_FRACTAL: f32 = 0.05;
+
+/// Let a channel of the fractal and the chaos effect listen to each other
+// The real part of the fractal's z pushes the chaotic system around like the input does,
+// and the chaos signal moves the fractal's constant c, so the two become one coupled
+// dynamical system instead of running side by side. Both are read before either moves on,
+// so each hears where the other was one sample ago, whatever order they run in. At 0.0
+// (`coupling` from 0.0 to 1.0) both run on their own again.
+pub fn couple(chaos: &mut ChaosAttractor, fractal: &mut FractalMagic, channel: usize, coupling: f32) {
+    let z_real = fractal.z_real(channel);
+    let chaos_signal = chaos.signal(channel);
+    chaos.set_coupling(channel, coupling * FRACTAL_TO_CHAOS * z_real);
+    fractal.set_coupling(channel, coupling * CHAOS_TO_FRACTAL * chaos_signal);
+}
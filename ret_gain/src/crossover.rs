@@ -0,0 +1,237 @@
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+// The crossover can be calculated in 32 or 64 bits
+use crate::precision::{DspFloat, Precision};
+
+/// The number of bands the crossover splits the signal into
+pub const NUM_BANDS: usize = 3;
+
+/// The band whose processors run the whole signal while multiband processing is off
+// It's also the band whose chaos signal drives the delay warble and the chaotic release,
+// since the middle of the spectrum is where the chaos effect is heard the most
+#[cfg(feature = "plugin")]
+pub const FULL_BAND: usize = 1;
+
+/// The Q of the Butterworth filters, two of them in a row make a Linkwitz-Riley filter
+const BUTTERWORTH_Q: f64 = std::f64::consts::FRAC_1_SQRT_2;
+
+/// Coefficients of a biquad filter, normalized so a0 is 1.0
+// Stored in 64 bits, after being worked out with the float type the crossover runs with
+#[derive(Clone, Copy, Default)]
+struct BiquadCoefficients {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+}
+
+impl BiquadCoefficients {
+    /// A Butterworth low pass or high pass at `frequency` Hz, from the RBJ cookbook
+    // Worked out with the float type `F`. At a low cutoff and a high sample rate the poles
+    // sit right next to 1.0, and 32 bits can't tell them apart from it well enough.
+    fn butterworth<F: DspFloat>(frequency: f32, sample_rate: f32, high_pass: bool) -> Self {
+        // Constants are written as f64 and rounded to the float type being used
+        let c = F::from_f64;
+
+        // Keep the cutoff below Nyquist, the filter blows up at and above it
+        let frequency = frequency.clamp(10.0, sample_rate * 0.45);
+        let omega = c(2.0) * c(std::f64::consts::PI) * c(frequency as f64) / c(sample_rate as f64);
+        let (sin, cos) = (omega.sin(), omega.cos());
+        let alpha = sin / (c(2.0) * c(BUTTERWORTH_Q));
+        let a0 = c(1.0) + alpha;
+
+        let (b0, b1) = if high_pass {
+            ((c(1.0) + cos) / c(2.0), -(c(1.0) + cos))
+        } else {
+            ((c(1.0) - cos) / c(2.0), c(1.0) - cos)
+        };
+
+        Self {
+            b0: (b0 / a0).to_f64(),
+            b1: (b1 / a0).to_f64(),
+            b2: (b0 / a0).to_f64(),
+            a1: (c(-2.0) * cos / a0).to_f64(),
+            a2: ((c(1.0) - alpha) / a0).to_f64(),
+        }
+    }
+}
+
+/// State of one biquad filter, in transposed direct form II
+// Kept in 64 bits, so switching the precision loses nothing
+#[derive(Clone, Copy, Default)]
+struct BiquadState {
+    s1: f64,
+    s2: f64,
+}
+
+impl BiquadState {
+    fn process<F: DspFloat>(&mut self, coefficients: &BiquadCoefficients, input: F) -> F {
+        let c = F::from_f64;
+        let output = c(coefficients.b0) * input + c(self.s1);
+        self.s1 = (c(coefficients.b1) * input - c(coefficients.a1) * output + c(self.s2)).to_f64();
+        self.s2 = (c(coefficients.b2) * input - c(coefficients.a2) * output).to_f64();
+
+        output
+    }
+}
+
+/// A fourth order Linkwitz-Riley low pass and high pass at the same frequency
+// Each side is two Butterworth filters in a row. The two sides are -6 dB at the crossover
+// frequency and in phase with each other, so they add back up to a flat response.
+#[derive(Clone, Copy, Default)]
+struct LinkwitzRiley {
+    low_pass: [BiquadState; 2],
+    high_pass: [BiquadState; 2],
+}
+
+impl LinkwitzRiley {
+    /// Split a sample into the part below and the part above the crossover frequency
+    fn split<F: DspFloat>(&mut self, coefficients: &CrossoverCoefficients, sample: F) -> (F, F) {
+        let low = self.low_pass[0].process(&coefficients.low_pass, sample);
+        let low = self.low_pass[1].process(&coefficients.low_pass, low);
+        let high = self.high_pass[0].process(&coefficients.high_pass, sample);
+        let high = self.high_pass[1].process(&coefficients.high_pass, high);
+
+        (low, high)
+    }
+}
+
+/// The coefficients of both sides of one crossover point
+#[derive(Clone, Copy, Default)]
+struct CrossoverCoefficients {
+    low_pass: BiquadCoefficients,
+    high_pass: BiquadCoefficients,
+}
+
+impl CrossoverCoefficients {
+    fn new<F: DspFloat>(frequency: f32, sample_rate: f32) -> Self {
+        Self {
+            low_pass: BiquadCoefficients::butterworth::<F>(frequency, sample_rate, false),
+            high_pass: BiquadCoefficients::butterworth::<F>(frequency, sample_rate, true),
+        }
+    }
+}
+
+/// Per-channel state of the crossover
+#[derive(Clone, Copy, Default)]
+struct CrossoverState {
+    /// Splits off the low band
+    low_split: LinkwitzRiley,
+
+    /// Splits what's left into the mid and high bands
+    high_split: LinkwitzRiley,
+
+    /// Puts the low band through the same phase shift as the other two bands
+    low_allpass: LinkwitzRiley,
+}
+
+/// A 3-band Linkwitz-Riley crossover
+// Splits the signal into a low, a mid and a high band, so the effect chain can run on every
+// band with its own amounts. Full-band chaos and folding smear the low end into mud, with
+// the bands split the bass can stay tight while the highs get wrecked. The mid and high
+// bands go through the second crossover, and the low band goes through a matching all-pass
+// (the second crossover's low and high parts added together), so all three bands have the
+// same phase and add back up to a flat response.
+pub struct Crossover {
+    /// One state per channel
+    channels: [CrossoverState; MAX_CHANNELS],
+
+    /// The filters of the crossover between the low and the mid band
+    low_coefficients: CrossoverCoefficients,
+
+    /// The filters of the crossover between the mid and the high band
+    high_coefficients: CrossoverCoefficients,
+
+    /// The crossover frequencies the coefficients were worked out for, in Hz
+    frequencies: (f32, f32),
+
+    /// Sample rate for the filter coefficients
+    sample_rate: f32,
+
+    /// The float type the filters are calculated with
+    precision: Precision,
+}
+
+impl Crossover {
+    /// Create a new crossover at 200 Hz and 3 kHz
+    pub fn new() -> Self {
+        let mut crossover = Self {
+            channels: [CrossoverState::default(); MAX_CHANNELS],
+            low_coefficients: CrossoverCoefficients::default(),
+            high_coefficients: CrossoverCoefficients::default(),
+            frequencies: (200.0, 3000.0),
+            sample_rate: 44100.0, // Default sample rate, will be updated
+            precision: Precision::Single, // The same math as the rest of the chain
+        };
+        crossover.update_coefficients();
+
+        crossover
+    }
+
+    /// Set the sample rate for the filter coefficients
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        if sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            self.update_coefficients();
+        }
+    }
+
+    /// Set the two crossover frequencies in Hz
+    // The coefficients are only worked out again when a frequency changes, so this can be
+    // called with smoothed values for every sample
+    pub fn set_frequencies(&mut self, low_mid: f32, mid_high: f32) {
+        // The mid band never turns inside out
+        let frequencies = (low_mid, mid_high.max(low_mid));
+        if frequencies != self.frequencies {
+            self.frequencies = frequencies;
+            self.update_coefficients();
+        }
+    }
+
+    /// Select the float type the filters are calculated with
+    // The coefficients are worked out again in that type, the filter states carry over
+    pub fn set_precision(&mut self, precision: Precision) {
+        if precision != self.precision {
+            self.precision = precision;
+            self.update_coefficients();
+        }
+    }
+
+    /// Clear the filter states
+    pub fn reset(&mut self) {
+        self.channels = [CrossoverState::default(); MAX_CHANNELS];
+    }
+
+    /// Split a sample of the given channel into the low, mid and high bands
+    // Adding the three bands back up gives the input with an all-pass phase shift
+    pub fn split(&mut self, channel: usize, sample: f32) -> [f32; NUM_BANDS] {
+        match self.precision {
+            Precision::Single => self.split_in::<f32>(channel, sample),
+            Precision::Double => self.split_in::<f64>(channel, sample),
+        }
+    }
+
+    /// `split()`, calculated with the float type `F`
+    fn split_in<F: DspFloat>(&mut self, channel: usize, sample: f32) -> [f32; NUM_BANDS] {
+        let state = &mut self.channels[channel];
+        let sample = F::from_f64(sample as f64);
+        let (low, rest) = state.low_split.split(&self.low_coefficients, sample);
+        let (mid, high) = state.high_split.split(&self.high_coefficients, rest);
+        let (low_low, low_high) = state.low_allpass.split(&self.high_coefficients, low);
+
+        [low_low + low_high, mid, high].map(|band| band.to_f64() as f32)
+    }
+
+    fn update_coefficients(&mut self) {
+        match self.precision {
+            Precision::Single => self.update_coefficients_in::<f32>(),
+            Precision::Double => self.update_coefficients_in::<f64>(),
+        }
+    }
+
+    fn update_coefficients_in<F: DspFloat>(&mut self) {
+        self.low_coefficients = CrossoverCoefficients::new::<F>(self.frequencies.0, self.sample_rate);
+        self.high_coefficients = CrossoverCoefficients::new::<F>(self.frequencies.1, self.sample_rate);
+    }
+}
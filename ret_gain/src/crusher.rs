@@ -0,0 +1,57 @@
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+/// Per-channel state of the sample rate reducer
+#[derive(Clone, Copy, Default)]
+struct CrusherState {
+    /// The sample that's currently being held
+    held: f32,
+
+    /// How far we are towards grabbing the next sample (0.0 to 1.0)
+    phase: f32,
+}
+
+/// A bitcrusher and sample rate reducer
+// This is the lo-fi digital kind of distortion: the sample rate reducer holds every sample
+// for a while, which folds the high frequencies back down as metallic aliasing, and the
+// bit reduction rounds every sample to a coarse grid, which adds gritty quantization noise.
+// It runs after the oversampler on purpose, the aliasing is the whole point.
+pub struct Crusher {
+    /// One state per channel
+    channels: [CrusherState; MAX_CHANNELS],
+}
+
+impl Crusher {
+    /// Create a new crusher
+    pub fn new() -> Self {
+        Self {
+            // Start right at the point of grabbing a new sample
+            channels: [CrusherState { held: 0.0, phase: 1.0 }; MAX_CHANNELS],
+        }
+    }
+
+    /// Forget the held samples
+    pub fn reset(&mut self) {
+        self.channels = [CrusherState { held: 0.0, phase: 1.0 }; MAX_CHANNELS];
+    }
+
+    /// Process a single sample of the given channel through the crusher
+    // `bits` is the bit depth (1.0 to 16.0), fractional values blend smoothly between depths.
+    // `downsample` is how many samples every held sample lasts (1.0 and up), fractional
+    // factors work too since the hold is driven by a phase accumulator.
+    pub fn process(&mut self, channel: usize, sample: f32, bits: f32, downsample: f32) -> f32 {
+        let state = &mut self.channels[channel];
+
+        // Grab a new sample whenever the phase wraps around
+        state.phase += 1.0 / downsample.max(1.0);
+        if state.phase >= 1.0 {
+            state.phase -= state.phase.floor();
+            state.held = sample;
+        }
+
+        // Round the held sample to the grid of the selected bit depth
+        // At one bit only -1, 0 and 1 are left
+        let steps = 2.0f32.powf(bits.clamp(1.0, 16.0) - 1.0);
+        (state.held.clamp(-1.0, 1.0) * steps).round() / steps
+    }
+}
@@ -0,0 +1,62 @@
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+/// The cutoff frequency of the DC blocker, in Hz
+// Low enough to leave the lowest bass notes alone, high enough to settle quickly
+const CUTOFF_HZ: f32 = 10.0;
+
+/// Per-channel state of the DC blocker
+#[derive(Clone, Copy, Default)]
+struct DcBlockerState {
+    /// The previous input sample
+    prev_input: f32,
+
+    /// The previous output sample
+    prev_output: f32,
+}
+
+/// A first-order high pass that removes DC offset
+// Asymmetric folding and the cubic term of the chaos effect push the waveform off center.
+// That offset is inaudible, but it eats headroom and clicks when the plugin is bypassed.
+// The filter subtracts the previous input and feeds back a little less than the previous
+// output, which leaves everything above a few Hz untouched and slowly pulls DC to zero.
+pub struct DcBlocker {
+    /// One state per channel
+    channels: [DcBlockerState; MAX_CHANNELS],
+
+    /// How much of the previous output is fed back (just below 1.0)
+    pole: f32,
+}
+
+impl DcBlocker {
+    /// Create a new DC blocker
+    pub fn new() -> Self {
+        let mut dc_blocker = Self {
+            channels: [DcBlockerState::default(); MAX_CHANNELS],
+            pole: 0.0,
+        };
+        dc_blocker.set_sample_rate(44100.0); // Default sample rate, will be updated
+
+        dc_blocker
+    }
+
+    /// Set the sample rate, which the pole depends on
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.pole = (-2.0 * std::f32::consts::PI * CUTOFF_HZ / sample_rate).exp();
+    }
+
+    /// Forget the previous samples
+    pub fn reset(&mut self) {
+        self.channels = [DcBlockerState::default(); MAX_CHANNELS];
+    }
+
+    /// Process a single sample of the given channel through the DC blocker
+    pub fn process(&mut self, channel: usize, sample: f32) -> f32 {
+        let state = &mut self.channels[channel];
+        let output = sample - state.prev_input + self.pole * state.prev_output;
+        state.prev_input = sample;
+        state.prev_output = output;
+
+        output
+    }
+}
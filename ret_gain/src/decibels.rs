@@ -0,0 +1,23 @@
+// The processors convert between dB and gain without NIH-plug, so they build on their own
+
+/// Anything at or below this many dB is treated as silence
+// The same floor as NIH-plug's `util::MINUS_INFINITY_DB`, so the processors convert exactly
+// like the plugin's parameters do
+const MINUS_INFINITY_DB: f32 = -100.0;
+
+/// `MINUS_INFINITY_DB` as a linear gain
+const MINUS_INFINITY_GAIN: f32 = 1e-5;
+
+/// Convert a level in dB to a linear gain, 0.0 at or below `MINUS_INFINITY_DB`
+pub fn db_to_gain(db: f32) -> f32 {
+    if db > MINUS_INFINITY_DB {
+        10.0f32.powf(db * 0.05)
+    } else {
+        0.0
+    }
+}
+
+/// Convert a linear gain to a level in dB, `MINUS_INFINITY_DB` for silence
+pub fn gain_to_db(gain: f32) -> f32 {
+    gain.max(MINUS_INFINITY_GAIN).log10() * 20.0
+}
@@ -0,0 +1,142 @@
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+// The echoes going back in are saturated by the chain's shared soft clipper
+use crate::saturation;
+
+/// The longest delay time, in milliseconds
+// A whole note at 60 BPM. Synced times that would be longer than this are clamped.
+pub const MAX_DELAY_MS: f32 = 4000.0;
+
+/// How far the chaos signal moves the delay time at full warble, in milliseconds
+// A few milliseconds of movement is enough for a clearly audible pitch wobble
+const MAX_WARBLE_MS: f32 = 6.0;
+
+/// How long the delay time takes to glide to a new setting, in milliseconds
+// Changing the time slides the read position like the tape speed of a tape echo,
+// instead of jumping and clicking
+const TIME_GLIDE_MS: f32 = 80.0;
+
+/// A stereo feedback delay
+// Every channel has its own ring buffer. The read position is fractional and linearly
+// interpolated, so the delay time can move smoothly, both while it glides to a new setting
+// and while the chaos signal wobbles it around.
+pub struct Delay {
+    /// Ring buffers for every channel, allocated by `set_sample_rate()`
+    buffers: [Vec<f32>; MAX_CHANNELS],
+
+    /// The current write position in the ring buffers
+    write_pos: [usize; MAX_CHANNELS],
+
+    /// The delay time that's being glided to, in samples
+    target_delay: f32,
+
+    /// The current delay time of each channel, in samples
+    current_delay: [f32; MAX_CHANNELS],
+
+    /// Coefficient of the one-pole smoother gliding the delay time
+    glide_coeff: f32,
+
+    /// Sample rate for the time calculations
+    sample_rate: f32,
+
+    /// The character of the soft clipper in the feedback path, see `saturation::soft_clip()`
+    saturation: f32,
+}
+
+impl Delay {
+    /// Create a new delay
+    // The buffers are sized for 44.1 kHz until `set_sample_rate()` is called
+    pub fn new() -> Self {
+        let mut delay = Self {
+            buffers: std::array::from_fn(|_| Vec::new()),
+            write_pos: [0; MAX_CHANNELS],
+            target_delay: 1.0,
+            current_delay: [1.0; MAX_CHANNELS],
+            glide_coeff: 1.0,
+            sample_rate: 44100.0,
+            saturation: 0.0, // Plain tanh, like the original feedback path
+        };
+        delay.set_sample_rate(44100.0);
+
+        delay
+    }
+
+    /// Set the sample rate and allocate the ring buffers for it
+    // Every channel's buffer holds four seconds plus the warble at this rate, about 3 MB at
+    // 192 kHz, so they're never resized on the audio thread
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+
+        // Room for the longest delay time plus the warble on top, and the interpolation
+        let max_samples = ((MAX_DELAY_MS + MAX_WARBLE_MS) / 1000.0 * sample_rate).ceil() as usize + 2;
+        self.buffers = std::array::from_fn(|_| vec![0.0; max_samples]);
+        self.write_pos = [0; MAX_CHANNELS];
+        self.glide_coeff = 1.0 - (-1.0 / ((TIME_GLIDE_MS / 1000.0) * sample_rate)).exp();
+    }
+
+    /// Set the delay time in milliseconds
+    // Clamped to `MAX_DELAY_MS`
+    pub fn set_time_ms(&mut self, time_ms: f32) {
+        self.target_delay = (time_ms.clamp(0.0, MAX_DELAY_MS) / 1000.0 * self.sample_rate).max(1.0);
+    }
+
+    /// Set the delay time to a note length at the given tempo
+    // `beats` is the length in quarter notes, `tempo` is in beats per minute
+    pub fn set_synced_time(&mut self, beats: f64, tempo: f64) {
+        self.set_time_ms((beats * 60_000.0 / tempo) as f32);
+    }
+
+    /// Set the character of the soft clipper in the feedback path, from 0.0 (tanh) to 1.0 (polynomial)
+    pub fn set_saturation(&mut self, character: f32) {
+        self.saturation = character;
+    }
+
+    /// Clear the echoes and jump straight to the current delay time
+    pub fn reset(&mut self) {
+        for buffer in self.buffers.iter_mut() {
+            buffer.fill(0.0);
+        }
+        self.current_delay = [self.target_delay; MAX_CHANNELS];
+    }
+
+    /// Process a single sample of the given channel through the delay
+    // `feedback` is how much of the echo goes back into the delay line (0.0 to just below 1.0)
+    // `mix` blends the input with the echoes, and `warble` is how much `chaos_signal`
+    // (between -1.0 and 1.0) moves the delay time around
+    pub fn process(
+        &mut self,
+        channel: usize,
+        sample: f32,
+        feedback: f32,
+        mix: f32,
+        warble: f32,
+        chaos_signal: f32,
+    ) -> f32 {
+        // Glide towards the selected delay time
+        let current_delay = &mut self.current_delay[channel];
+        *current_delay += (self.target_delay - *current_delay) * self.glide_coeff;
+
+        // The chaos signal wobbles the read position like a worn out tape transport
+        let warble_samples = chaos_signal * warble * (MAX_WARBLE_MS / 1000.0) * self.sample_rate;
+
+        let buffer = &mut self.buffers[channel];
+        let len = buffer.len();
+        let write_pos = self.write_pos[channel];
+        let delay = (*current_delay + warble_samples).clamp(1.0, (len - 2) as f32);
+
+        // Read between the two samples around the fractional delay time
+        let read_pos = write_pos as f32 + len as f32 - delay;
+        let index = read_pos as usize;
+        let fraction = read_pos - index as f32;
+        let a = buffer[index % len];
+        let b = buffer[(index + 1) % len];
+        let delayed = a + (b - a) * fraction;
+
+        // The echoes that go back in are softly saturated, so high feedback settings
+        // get gritty instead of running away
+        buffer[write_pos] = sample + saturation::soft_clip(delayed * feedback, self.saturation);
+        self.write_pos[channel] = (write_pos + 1) % len;
+
+        sample * (1.0 - mix) + delayed * mix
+    }
+}
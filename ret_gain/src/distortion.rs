@@ -1,50 +1,300 @@
 // Import the NIH-plug prelude for audio processing types and traits
+#[cfg(feature = "plugin")]
 use nih_plug::prelude::*;
 
-/// A simple distortion effect
-// This struct implements a basic waveshaping distortion effect
-pub struct Distortion {
-    /// The amount of distortion to apply (1.0 = none, >1.0 = more distortion)
-    // The drive parameter controls how much the signal is pushed before distortion
-    // Higher values create more harmonics and a more aggressive sound
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+/// Cutoff of the DC blocker after the waveshaper, in Hz
+// Low enough to leave the bass alone, high enough to settle quickly after a drive change
+const DC_BLOCKER_FREQ: f32 = 10.0;
+
+/// The peak level of the sine the loudness estimate is based on, -12 dBFS
+// Roughly where a mixed track sits going into an insert effect
+const LOUDNESS_REFERENCE_LEVEL: f32 = 0.25;
+
+/// How many points of the reference sine the loudness estimate looks at
+const LOUDNESS_POINTS: usize = 32;
+
+/// The range the automatic gain compensation stays within, as linear gains
+// -24 dB to +12 dB, so a shaper that mutes the reference sine can't turn the output up forever
+const MIN_COMPENSATION: f32 = 0.063;
+const MAX_COMPENSATION: f32 = 4.0;
+
+/// The highest drive, as a ratio, about 34 dB
+#[cfg(feature = "plugin")]
+pub const MAX_DRIVE: f32 = 50.0;
+
+/// How the distortion stage treats its level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum DriveModel {
+    /// The drive pushes the signal into the waveshaper and the level is made up afterwards,
+    /// so turning it up adds distortion without adding much loudness
+    #[cfg_attr(feature = "plugin", id = "compensated")]
+    #[cfg_attr(feature = "plugin", name = "Compensated")]
+    Compensated,
+    /// The drive pushes the signal into the waveshaper and nothing else, so more drive is
+    /// also louder. How projects from before the compensation sound.
+    #[cfg_attr(feature = "plugin", id = "legacy")]
+    #[cfg_attr(feature = "plugin", name = "Legacy")]
+    Legacy,
+}
+
+/// The waveshaping algorithm used by the distortion stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum DistortionMode {
+    /// Smooth, symmetric saturation
+    #[cfg_attr(feature = "plugin", id = "tanh")]
+    #[cfg_attr(feature = "plugin", name = "Tanh")]
+    Tanh,
+    /// Flat tops at ±1, the harshest of the bunch
+    #[cfg_attr(feature = "plugin", id = "hard-clip")]
+    #[cfg_attr(feature = "plugin", name = "Hard Clip")]
+    HardClip,
+    /// The classic x - x³/3 soft clipper
+    #[cfg_attr(feature = "plugin", id = "cubic")]
+    #[cfg_attr(feature = "plugin", name = "Cubic")]
+    Cubic,
+    /// Clips the positive half earlier than the negative half, which adds even harmonics
+    #[cfg_attr(feature = "plugin", id = "diode")]
+    #[cfg_attr(feature = "plugin", name = "Diode")]
+    Diode,
+    /// Folds everything above ±1 back down instead of clipping it
+    #[cfg_attr(feature = "plugin", id = "foldback")]
+    #[cfg_attr(feature = "plugin", name = "Foldback")]
+    Foldback,
+    /// Reduces the bit depth, more drive means fewer bits
+    #[cfg_attr(feature = "plugin", id = "bitcrush")]
+    #[cfg_attr(feature = "plugin", name = "Bitcrush")]
+    Bitcrush,
+}
+
+/// Per-channel state of the DC blocker
+#[derive(Clone, Copy, Default)]
+struct DcBlockerState {
+    /// The previous input sample
+    prev_input: f32,
+    /// The previous output sample
+    prev_output: f32,
+}
+
+/// A gain compensation worked out for one drive
+// Every channel keeps its own, since mid/side and the chaos modulation give the channels
+// different drives and they'd keep throwing out each other's otherwise
+#[derive(Clone, Copy)]
+struct CachedCompensation {
+    /// The drive the compensation was worked out for, NaN when there's nothing cached
     drive: f32,
+    /// The gain that undoes the loudness the waveshaper adds at that drive
+    gain: f32,
+}
+
+impl CachedCompensation {
+    const EMPTY: Self = Self { drive: f32::NAN, gain: 1.0 };
+
+    /// The compensation for the drive, only worked out again when the drive changed
+    fn get(&mut self, mode: DistortionMode, drive: f32) -> f32 {
+        if drive != self.drive {
+            self.drive = drive;
+            self.gain = (1.0 / loudness_estimate(mode, drive)).clamp(MIN_COMPENSATION, MAX_COMPENSATION);
+        }
+
+        self.gain
+    }
+}
+
+/// A bank of waveshaping distortion effects
+// This struct implements several waveshapers that share a drive control
+// Like the gain processor, the drive amount is passed in with every sample,
+// so a smoothed parameter can change it without any stepping
+// Asymmetric shapers like the diode add a DC offset to the signal, so every mode
+// goes through a gentle DC blocker afterwards
+pub struct Distortion {
+    /// The currently selected waveshaper
+    mode: DistortionMode,
+
+    /// One DC blocker per channel
+    dc_blockers: [DcBlockerState; MAX_CHANNELS],
+
+    /// Pole of the DC blocker, depends on the sample rate
+    dc_coeff: f32,
+
+    /// The cached gain compensation for `gain_compensation()`
+    compensation: CachedCompensation,
+
+    /// Whether the level is made up after the waveshaper
+    model: DriveModel,
+
+    /// The cached gain compensation of every channel, for the compensated model
+    channel_compensations: [CachedCompensation; MAX_CHANNELS],
 }
 
 impl Distortion {
-    /// Create a new distortion effect with the given drive amount
+    /// Create a new distortion effect
     // Constructor for the Distortion effect
     // Notice how in Rust we use 'Self' (capital S) to refer to the current type within an impl block
-    pub fn new(drive: f32) -> Self {
-        // Create a new instance with the specified drive amount
-        // This syntax is creating a struct with named fields
-        Self { drive }  // Shorthand for drive: drive
+    pub fn new() -> Self {
+        let mut distortion = Self {
+            mode: DistortionMode::Tanh,
+            dc_blockers: [DcBlockerState::default(); MAX_CHANNELS],
+            dc_coeff: 0.0,
+            compensation: CachedCompensation::EMPTY,
+            model: DriveModel::Legacy, // Plain waveshaping, the plugin picks the model itself
+            channel_compensations: [CachedCompensation::EMPTY; MAX_CHANNELS],
+        };
+        distortion.set_sample_rate(44100.0); // Default sample rate, will be updated
+
+        distortion
     }
 
-    
-    /// Process a single sample through the distortion algorithm
+    /// Set the sample rate for the DC blocker
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.dc_coeff = (-2.0 * std::f32::consts::PI * DC_BLOCKER_FREQ / sample_rate).exp();
+    }
+
+    /// Select the waveshaping algorithm
+    pub fn set_mode(&mut self, mode: DistortionMode) {
+        if mode != self.mode {
+            self.mode = mode;
+            // Every shaper adds a different amount of loudness
+            self.compensation = CachedCompensation::EMPTY;
+            self.channel_compensations = [CachedCompensation::EMPTY; MAX_CHANNELS];
+        }
+    }
+
+    /// Select whether the level is made up after the waveshaper
+    pub fn set_model(&mut self, model: DriveModel) {
+        self.model = model;
+    }
+
+    /// The gain that undoes the loudness the current waveshaper adds at this drive
+    // Working it out takes a few dozen waveshaper calls, so the result is kept until the
+    // drive or the mode changes. While the drive parameter is smoothing that's once per
+    // sample, and not at all once it has settled.
+    pub fn gain_compensation(&mut self, drive: f32) -> f32 {
+        self.compensation.get(self.mode, drive)
+    }
+
+    /// Clear the DC blocker state
+    pub fn reset(&mut self) {
+        self.dc_blockers = [DcBlockerState::default(); MAX_CHANNELS];
+    }
+
+    /// Process a single sample of the given channel through the distortion algorithm
     // This is where the actual distortion effect happens
-    // &self means this method takes an immutable reference to the struct instance
-    pub fn process(&self, sample: f32) -> f32 {
-        // Simple tanh distortion with drive control
-        // 1. Multiply the input sample by the drive amount (makes signal stronger)
-        // 2. Apply the hyperbolic tangent function (tanh) which "clips" the signal in a smooth way
-        // This creates a "soft clipping" effect - a key part of many distortion/overdrive effects
-        (sample * self.drive).tanh()
-    }
-    
+    // The drive controls how much the signal is pushed before distortion (1.0 = none, >1.0 = more distortion)
+    pub fn process(&mut self, channel: usize, sample: f32, drive: f32) -> f32 {
+        let shaped = match self.model {
+            // The same estimate as the plugin's auto gain, but right after the shaper
+            DriveModel::Compensated => {
+                shape(self.mode, sample, drive) * self.channel_compensations[channel].get(self.mode, drive)
+            }
+            DriveModel::Legacy => shape(self.mode, sample, drive),
+        };
+
+        // Remove any DC offset the waveshaper added
+        // y[n] = x[n] - x[n-1] + R * y[n-1]
+        let dc_blocker = &mut self.dc_blockers[channel];
+        let output = shaped - dc_blocker.prev_input + self.dc_coeff * dc_blocker.prev_output;
+        dc_blocker.prev_input = shaped;
+        dc_blocker.prev_output = output;
+
+        output
+    }
+
     /// Process a buffer of samples through the distortion effect
     // This method processes an entire buffer of audio at once
     // This is a convenience method for processing multiple samples
-    pub fn process_buffer(&self, buffer: &mut Buffer) {
+    // The same drive is used for the whole buffer
+    #[cfg(feature = "plugin")]
+    pub fn process_buffer(&mut self, buffer: &mut Buffer, drive: f32) {
         // Iterate through each set of samples across all channels
         for channel_samples in buffer.iter_samples() {
-            // For each sample in the current frame
-            for sample in channel_samples {
+            // For each sample in the current frame, together with its channel index
+            for (channel, sample) in channel_samples.into_iter().enumerate() {
                 // Apply distortion and write the result back to the same location
                 // The * before sample is dereferencing the pointer to modify the original value
                 // This is a key difference from JavaScript/Python - we're modifying the original data
-                *sample = self.process(*sample);
+                *sample = self.process(channel, *sample, drive);
             }
         }
     }
-}
\ No newline at end of file
+
+    /// Process a slice of samples of the given channel through the distortion effect
+    // The same as `process_buffer()`, but for plain slices, so the distortion can be used
+    // outside of a plugin. The same drive is used for the whole slice.
+    #[cfg(feature = "dsp")]
+    pub fn process_slice(&mut self, channel: usize, samples: &mut [f32], drive: f32) {
+        for sample in samples.iter_mut() {
+            *sample = self.process(channel, *sample, drive);
+        }
+    }
+}
+
+/// Estimate how much louder a waveshaper makes the signal at a given drive
+// Runs one period of a sine at `LOUDNESS_REFERENCE_LEVEL` through the shaper and compares
+// the RMS levels of what goes in and what comes out. The DC offset of the asymmetric
+// shapers is left out, since the DC blocker removes it anyway. Returns 1.0 when the shaper
+// doesn't change the level, more than that when it makes the signal louder.
+pub fn loudness_estimate(mode: DistortionMode, drive: f32) -> f32 {
+    let mut sum = 0.0;
+    let mut sum_squares = 0.0;
+    for point in 0..LOUDNESS_POINTS {
+        let phase = (point as f32 + 0.5) / LOUDNESS_POINTS as f32;
+        let sample = LOUDNESS_REFERENCE_LEVEL * (std::f32::consts::TAU * phase).sin();
+        let shaped = shape(mode, sample, drive);
+        sum += shaped;
+        sum_squares += shaped * shaped;
+    }
+
+    let mean = sum / LOUDNESS_POINTS as f32;
+    let output_rms = (sum_squares / LOUDNESS_POINTS as f32 - mean * mean).max(0.0).sqrt();
+    let input_rms = LOUDNESS_REFERENCE_LEVEL * std::f32::consts::FRAC_1_SQRT_2;
+
+    (output_rms / input_rms).max(1e-6)
+}
+
+/// Apply one of the waveshapers to a sample
+// Every shaper maps 0.0 to 0.0, so silence stays silent
+fn shape(mode: DistortionMode, sample: f32, drive: f32) -> f32 {
+    // Multiply the input sample by the drive amount (makes signal stronger)
+    let driven = sample * drive;
+
+    match mode {
+        // The hyperbolic tangent (tanh) "clips" the signal in a smooth way
+        DistortionMode::Tanh => driven.tanh(),
+        // Simply cut off everything outside of ±1
+        DistortionMode::HardClip => driven.clamp(-1.0, 1.0),
+        // x - x³/3 reaches its peak of 2/3 at x = 1, so scale it back up to ±1
+        DistortionMode::Cubic => {
+            let x = driven.clamp(-1.0, 1.0);
+            1.5 * (x - x * x * x / 3.0)
+        }
+        // The positive half saturates twice as hard as the negative half
+        DistortionMode::Diode => {
+            if driven >= 0.0 {
+                1.0 - (-2.0 * driven).exp()
+            } else {
+                -1.0 + driven.exp()
+            }
+        }
+        // Reflect the signal at ±1 until it fits, which works for any amount of drive
+        DistortionMode::Foldback => {
+            // Map to a triangle wave with a period of 4 that passes through the origin
+            let folded = (driven + 1.0).rem_euclid(4.0);
+            if folded < 2.0 {
+                folded - 1.0
+            } else {
+                3.0 - folded
+            }
+        }
+        // Fewer quantization steps the more drive there is, from 16 bits down to 1 bit
+        DistortionMode::Bitcrush => {
+            let bits = (16.0 - (drive - 1.0) * (15.0 / 49.0)).clamp(1.0, 16.0);
+            let steps = 2.0f32.powf(bits - 1.0);
+            (sample.clamp(-1.0, 1.0) * steps).round() / steps
+        }
+    }
+}
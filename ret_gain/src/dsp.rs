@@ -0,0 +1,54 @@
+// The effect chain without any plugin around it, enabled with the `dsp` feature
+// Everything in here works on plain `&mut [f32]` slices instead of NIH-plug's `Buffer`,
+// so the algorithms can be used in any Rust project, like a game's audio engine or an
+// offline renderer
+use crate::chain::{ChainSettings, EffectChain};
+
+/// The distortion, Magic One and chaos stages in a configurable order
+// This is the same chain the plugin runs, minus the oversampling, the dry/wet latency
+// compensation and everything after the mix. The settings are plain public fields, set
+// them between calls to `process_slice()`.
+pub struct DspChain {
+    /// The stages themselves, set their modes directly, e.g. `chain.effects.distortion.set_mode()`
+    pub effects: EffectChain,
+
+    /// The order, amounts and levels the stages run with
+    pub settings: ChainSettings,
+
+    /// How much of the processed signal is mixed in (0.0 is dry, 1.0 is fully processed)
+    pub mix: f32,
+}
+
+impl DspChain {
+    /// Create a chain for the given sample rate with the plugin's default settings
+    pub fn new(sample_rate: f32) -> Self {
+        let mut chain = Self {
+            effects: EffectChain::new(),
+            settings: ChainSettings::default(),
+            mix: 1.0,
+        };
+        chain.set_sample_rate(sample_rate);
+
+        chain
+    }
+
+    /// Set the sample rate of every stage
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.effects.set_sample_rate(sample_rate);
+    }
+
+    /// Clear the state of every stage
+    pub fn reset(&mut self) {
+        self.effects.reset();
+    }
+
+    /// Process a slice of samples of the given channel through the whole chain
+    // Every channel (up to two) keeps its own state, so call this once per channel
+    pub fn process_slice(&mut self, channel: usize, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            let dry = *sample;
+            let (wet, _) = self.effects.process(channel, dry, &self.settings);
+            *sample = dry * (1.0 - self.mix) + wet * self.mix;
+        }
+    }
+}
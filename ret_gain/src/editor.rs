@@ -1,28 +1,49 @@
 use atomic_float::AtomicF32;
-use nih_plug::prelude::{util, Editor};
+use nih_plug::prelude::{nih_log, util, AsyncExecutor, Editor, GuiContext, Param, ParamPtr, ParamSetter};
 use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::vizia::vg;
 use nih_plug_vizia::widgets::*;
 use nih_plug_vizia::{assets, create_vizia_editor, ViziaState, ViziaTheming};
-use std::sync::atomic::Ordering;
-use std::sync::Arc;
-use std::time::Duration;
-
-use crate::plugin::RetardedGainParams;
-
-// More refined color palette - professional but still distinctive
-const BACKGROUND_COLOR: Color = Color::rgb(0x18, 0x18, 0x1E); // Dark background with slight blue tint
-const TEXT_COLOR: Color = Color::rgb(0xE8, 0xE9, 0xF3); // Soft white
-const ACCENT_COLOR: Color = Color::rgb(0xFF, 0x1A, 0x8C); // Softened pink
-const SECONDARY_COLOR: Color = Color::rgb(0x0A, 0xD8, 0xE9); // Cyan for contrast
-const MAGIC_COLOR: Color = Color::rgb(0x9B, 0x59, 0xB6); // Purple for the magic slider
-const CHAOS_COLOR: Color = Color::rgb(0x1A, 0xBC, 0x9C); // Teal/turquoise for the chaos slider
-const KNOB_BG_COLOR: Color = Color::rgb(0x22, 0x22, 0x2A); // Slight contrast for controls
-const PANEL_BG: Color = Color::rgb(0x20, 0x20, 0x28); // Panel background
-
-// Semi-transparent colors
-const BORDER_COLOR: Color = Color::rgba(0xFF, 0x1A, 0x8C, 0x30); // Very subtle borders
-const METER_BG_COLOR: Color = Color::rgba(0x0A, 0x0A, 0x10, 0x80); // Dark meter background
-const TEXT_SECONDARY: Color = Color::rgba(0xE8, 0xE9, 0xF3, 0x70); // Secondary text
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::analyzer::{AttractorOutput, ScopeOutput, SpectrumOutput, TextureOutput, SCOPE_DISPLAY_SIZE, SPECTRUM_FLOOR_DB, SPECTRUM_WINDOW_SIZE};
+use crate::history::{History, Snapshot};
+use crate::metering::{LoudnessReadings, MeterRange, PeakReadings, ProcessingActivity};
+use crate::export::{ExportState, ExportStatus, TextureExport};
+use crate::plugin::{RetardedGain, RetardedGainParams, Task};
+use crate::midi::{self, MIDI_TARGETS};
+use crate::macros::{MAPPINGS_PER_MACRO, NUM_MACROS};
+use crate::modulation::ModulationRings;
+use crate::morph::{self, MorphSlot, MORPH_TARGETS};
+use crate::presets::{self, Preset, PresetLock};
+use crate::randomize::{self, RandomPatch};
+
+mod gesture; // Begin/end gestures that the custom widgets send to the host
+pub(crate) mod layout; // The editor's tabs and how far each of them is scrolled
+pub(crate) mod theme; // Light and dark color themes
+mod widgets; // Custom widgets like the rotary knob
+use gesture::ParamGesture;
+use layout::EditorTab;
+use theme::{TextSize, Theme, ACCENT_COLORS};
+use widgets::{Knob, KnobExt, KnobModulation};
+
+// Spectrum analyzer display range
+const SPECTRUM_MIN_FREQ: f32 = 20.0;
+const SPECTRUM_MAX_FREQ: f32 = 20_000.0;
+const SPECTRUM_MAX_DB: f32 = 0.0;
+const SPECTRUM_MIN_DB: f32 = -90.0;
+
+/// How tall the tabs' scrollable area is, in logical pixels
+// Every tab scrolls within it, and so do the meters after its controls, so new controls don't
+// make the window any taller
+const TAB_VIEW_HEIGHT: f32 = 460.0;
+
+/// How many recent positions the XY pad's trail remembers
+const XY_TRAIL_LENGTH: usize = 48;
 
 // Get version directly from Cargo.toml
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -30,22 +51,1509 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 #[derive(Lens)]
 struct Data {
     params: Arc<RetardedGainParams>,
-    peak_meter: Arc<AtomicF32>,
+    /// Used to restore parameter values on undo without going through the widgets
+    gui_context: Arc<dyn GuiContext>,
+    /// Peak level going into the effect chain
+    input_peak_meter: PeakReadings,
+    /// Peak level at the output
+    peak_meter: PeakReadings,
+    /// Loudness of the untouched input
+    input_loudness: LoudnessReadings,
+    /// Loudness of the output
+    output_loudness: LoudnessReadings,
+    /// The output level relative to the input level in dB
+    level_difference: Arc<AtomicF32>,
+    /// The magic amount after envelope modulation
+    modulated_magic: Arc<AtomicF32>,
+    /// The chaos amount after LFO and envelope modulation
+    modulated_chaos: Arc<AtomicF32>,
+    /// How much of the feedback and the coupling the stability governors left, 1.0 is all of it
+    stability: Arc<AtomicF32>,
+    /// The correlation between the left and right output channels
+    correlation: Arc<AtomicF32>,
+    /// How much of the realtime budget the audio thread uses, 1.0 is all of it
+    cpu_load: Arc<AtomicF32>,
+    /// When the audio thread processed its latest block
+    activity: Arc<ProcessingActivity>,
+    /// Whether the hard clipper had to cut a sample since it was last cleared
+    hard_clipped: Arc<AtomicBool>,
+    /// How the latest texture export went
+    export_status: Arc<ExportStatus>,
+    /// Hands the texture export to a background thread
+    async_executor: AsyncExecutor<RetardedGain>,
+    /// Changes every time the audio thread publishes a new spectrum window
+    spectrum_generation: Arc<AtomicUsize>,
+    /// Changes every time the audio thread publishes a new attractor path
+    attractor_generation: Arc<AtomicUsize>,
+    /// Changes every time the audio thread publishes a new oscilloscope recording
+    scope_generation: Arc<AtomicUsize>,
+    /// Changes every time the audio thread publishes a new texture history
+    texture_generation: Arc<AtomicUsize>,
+
+    /// All presets shown in the preset browser, factory presets first
+    presets: Vec<Preset>,
+    /// The names of the presets, used to populate the dropdown
+    preset_names: Vec<String>,
+    /// The name of the last loaded or saved preset
+    current_preset: String,
+    /// The name typed into the preset name textbox
+    new_preset_name: String,
+
+    /// The MIDI learn target the GUI is waiting on
+    midi_learning: Option<usize>,
+    /// Feedback about MIDI learn shown in the footer
+    midi_status: String,
+
+    /// Undo and redo stacks for changes made in the editor
+    history: History,
+
+    /// Locked parameters are left alone by the dice
+    lock_drive: bool,
+    lock_magic: bool,
+    lock_chaos: bool,
+    /// The seed of the last roll of the dice, as shown in the seed textbox
+    random_seed: String,
+
+    /// The names of the parameters a macro can drive, "None" first
+    macro_target_names: Vec<String>,
+
+    /// The colors the editor is drawn with, worked out from the saved theme settings
+    theme: Theme,
+    /// Changes every time the theme changes, so the editor is built again with the new colors
+    theme_generation: usize,
+
+    /// Whether the preferences are shown in the footer
+    prefs_open: bool,
+
+    /// The tab the controls are shown from
+    tab: EditorTab,
+
+    /// The parameter a value is being typed in for, the popup is open while there is one
+    value_entry: Option<ValueEntry>,
+    /// What's typed into the popup's textbox
+    value_entry_text: String,
+    /// Whether the last value typed in didn't parse
+    value_entry_invalid: bool,
+}
+
+/// The parameter the value entry popup was opened for
+#[derive(Clone)]
+struct ValueEntry {
+    param_ptr: ParamPtr,
+    /// The parameter's name, shown as the popup's title
+    name: String,
+    /// The parameter's value when the popup was opened, with its unit
+    current: String,
+    /// What was already typed to open the popup, it goes into the textbox first
+    typed: String,
+    /// How far down the popup sits, in logical pixels, so it opens next to where it was asked for
+    top: f32,
+}
+
+/// Events sent by the preset browser
+enum PresetEvent {
+    /// Load the preset at this index in `Data::presets`
+    Load(usize),
+    /// The preset name textbox was edited
+    SetName(String),
+    /// Save the current parameter values as a user preset
+    Save,
+    /// Keep a parameter at its current value while browsing presets, or stop keeping it
+    ToggleLock(PresetLock),
+}
+
+/// Events sent by the A/B comparison buttons
+enum AbEvent {
+    /// Switch between the A and B settings
+    Switch,
+    /// Copy the current settings to the other slot
+    Copy,
+}
+
+/// Events sent by the morph controls
+enum MorphEvent {
+    /// Store the current settings in a morph snapshot
+    Store(MorphSlot),
+}
+
+/// Events sent by the macro mapping matrix, the first two fields pick the macro and its mapping
+enum MacroEvent {
+    /// Point a mapping at a target, given as an index in `MORPH_TARGETS`, or clear it
+    SetTarget(usize, usize, Option<usize>),
+    /// Set how far a mapping moves its target (-1.0 to 1.0)
+    SetRange(usize, usize, f32),
+    /// Step a mapping to the next curve
+    CycleCurve(usize, usize),
+}
+
+/// Events sent by the MIDI learn handling
+enum MidiLearnEvent {
+    /// A slider was right-clicked: start learning, cancel learning, or remove its mapping
+    Toggle(&'static str),
+    /// The audio thread received or learned a CC
+    Sync,
+}
+
+/// Events sent by the chaos controls
+enum ChaosEvent {
+    /// Restart the Lorenz systems from new random starting conditions
+    Reseed,
+}
+
+/// Events sent by the theme controls in the footer
+enum ThemeEvent {
+    /// Switch between the light and the dark theme
+    ToggleMode,
+    /// Use one of `ACCENT_COLORS` as the accent color
+    SetAccent([u8; 3]),
+}
+
+/// The UI scales the preferences offer
+// The resize handle can still pick anything in between
+const UI_SCALES: [f64; 6] = [0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+
+/// Events sent by the tab bar and the tabs' scroll view
+enum LayoutEvent {
+    /// Show the controls on another tab
+    SelectTab(EditorTab),
+    /// The current tab was scrolled, from 0.0 at the top to 1.0 at the bottom
+    Scroll(f32),
+}
+
+/// Events sent by the preferences in the footer
+enum PrefsEvent {
+    /// Show or hide the preferences
+    Toggle,
+    /// Scale the whole editor, like dragging the resize handle
+    SetScale(f64),
+    /// Draw the text at one of the text sizes
+    SetTextSize(TextSize),
+}
+
+/// Events sent by the value entry popup and the widgets that open it
+pub(crate) enum ValueEntryEvent {
+    /// Open the popup for a parameter, with whatever was already typed to open it
+    Open(ParamPtr, String),
+    /// The popup's textbox was edited
+    SetText(String),
+    /// Set the parameter to what was typed in and close the popup
+    Submit,
+    /// Close the popup without changing anything
+    Cancel,
+}
+
+/// Clears the peak hold and the clip indicator of one of the meters, or the hard clipper's
+#[derive(Clone, Copy)]
+enum MeterEvent {
+    ClearInput,
+    ClearOutput,
+    ClearHardClip,
+}
+
+/// Events sent by the texture export
+enum ExportEvent {
+    /// Render the chaos and fractal effects with the current settings and save them
+    Render,
+}
+
+/// The parameters the dice can change
+#[derive(Clone, Copy)]
+enum RandomTarget {
+    Drive,
+    Magic,
+    Chaos,
+}
+
+/// Events sent by the randomizer
+enum RandomizeEvent {
+    /// Roll the dice with a new seed
+    Roll,
+    /// A seed was typed in, roll with that one
+    SetSeed(String),
+    /// Lock or unlock a parameter
+    ToggleLock(RandomTarget),
+}
+
+/// A button that locks a parameter so the dice leave it alone
+// Dimmed while locked
+fn lock_button(cx: &mut Context, name: &'static str, target: RandomTarget, locked: impl Lens<Target = bool>) {
+    Binding::new(cx, locked, move |cx, locked| {
+        let theme = Data::theme.get(cx);
+        let color = if locked.get(cx) { theme.text_secondary } else { theme.mix };
+        Button::new(
+            cx,
+            move |cx| cx.emit(RandomizeEvent::ToggleLock(target)),
+            move |cx| Label::new(cx, name).font_size(theme.text_size(12.0)).color(color),
+        )
+        .width(Percentage(12.0))
+        .height(Pixels(20.0))
+        .top(Pixels(5.0))
+        .left(Pixels(6.0));
+    });
+}
+
+/// A button that keeps a parameter at its current value while browsing presets
+// Lit while locked, the other way round than the dice's locks, since this one keeps something
+fn preset_lock_button(cx: &mut Context, lock: PresetLock) {
+    let locked = Data::params.map(move |params| {
+        params.preset_locks.read().map(|locks| locks.is_locked(lock)).unwrap_or(false)
+    });
+    Binding::new(cx, locked, move |cx, locked| {
+        let theme = Data::theme.get(cx);
+        let color = if locked.get(cx) { theme.accent } else { theme.text_secondary };
+        Button::new(
+            cx,
+            move |cx| cx.emit(PresetEvent::ToggleLock(lock)),
+            move |cx| Label::new(cx, lock.label()).font_size(theme.text_size(12.0)).color(color),
+        )
+        .width(Percentage(12.0))
+        .height(Pixels(20.0))
+        .top(Pixels(5.0))
+        .left(Pixels(6.0));
+    });
+}
+
+/// A tab in the tab bar, lit while it's the one shown
+fn tab_button(cx: &mut Context, tab: EditorTab) {
+    Binding::new(cx, Data::tab.map(move |current| *current == tab), move |cx, current| {
+        let theme = Data::theme.get(cx);
+        let color = if current.get(cx) { theme.accent } else { theme.text_secondary };
+        Button::new(
+            cx,
+            move |cx| cx.emit(LayoutEvent::SelectTab(tab)),
+            move |cx| Label::new(cx, tab.label()).font_size(theme.text_size(12.0)).color(color),
+        )
+        .width(Stretch(1.0))
+        .height(Pixels(20.0))
+        .top(Pixels(5.0))
+        .left(Pixels(6.0))
+        .child_space(Stretch(1.0));
+    });
+}
+
+/// One row of the macro matrix: the target, range and curve of one of a macro's mappings
+fn macro_mapping_row(cx: &mut Context, macro_index: usize, slot: usize) {
+    let theme = Data::theme.get(cx);
+    HStack::new(cx, move |cx| {
+        Label::new(cx, &format!("M{} \u{2192} {}", macro_index + 1, slot + 1))
+            .font_size(theme.text_size(13.0))
+            .color(theme.text_secondary)
+            .width(Percentage(15.0))
+            .child_space(Stretch(1.0));
+
+        // The parameter this mapping drives
+        Dropdown::new(
+            cx,
+            move |cx| {
+                Label::new(
+                    cx,
+                    Data::params.map(move |params| {
+                        params
+                            .macro_mappings
+                            .target(macro_index, slot)
+                            .and_then(|target| morph::target_param(params, target))
+                            .map(|param| param.name().to_string())
+                            .unwrap_or_else(|| String::from("None"))
+                    }),
+                )
+                .font_size(theme.text_size(12.0))
+                .color(theme.text)
+            },
+            move |cx| {
+                List::new(cx, Data::macro_target_names, move |cx, index, name| {
+                    Label::new(cx, name)
+                        .font_size(theme.text_size(12.0))
+                        .color(theme.text)
+                        .width(Stretch(1.0))
+                        .on_press(move |cx| {
+                            // The first entry is "None", the rest are the targets in order
+                            cx.emit(MacroEvent::SetTarget(macro_index, slot, index.checked_sub(1)));
+                            cx.emit(PopupEvent::Close);
+                        });
+                })
+                .background_color(theme.knob_bg);
+            },
+        )
+        .width(Percentage(32.0))
+        .height(Pixels(20.0))
+        .top(Pixels(5.0));
+
+        // How far it moves the target, the middle of the slider doesn't move it at all
+        Slider::new(
+            cx,
+            Data::params.map(move |params| (params.macro_mappings.range(macro_index, slot) + 1.0) / 2.0),
+        )
+        .on_changing(move |cx, value| cx.emit(MacroEvent::SetRange(macro_index, slot, value * 2.0 - 1.0)))
+        .width(Percentage(22.0))
+        .height(Pixels(20.0))
+        .top(Pixels(5.0))
+        .left(Pixels(6.0))
+        .color(theme.mix);
+
+        Label::new(
+            cx,
+            Data::params.map(move |params| format!("{:+.0}%", params.macro_mappings.range(macro_index, slot) * 100.0)),
+        )
+        .font_size(theme.text_size(12.0))
+        .color(theme.mix)
+        .width(Percentage(12.0))
+        .top(Pixels(5.0))
+        .left(Pixels(6.0));
+
+        // Click to step through the curves
+        Button::new(
+            cx,
+            move |cx| cx.emit(MacroEvent::CycleCurve(macro_index, slot)),
+            move |cx| {
+                Label::new(
+                    cx,
+                    Data::params.map(move |params| params.macro_mappings.curve(macro_index, slot).label().to_string()),
+                )
+                .font_size(theme.text_size(12.0))
+                .color(theme.mix)
+            },
+        )
+        .width(Percentage(12.0))
+        .height(Pixels(20.0))
+        .top(Pixels(5.0))
+        .left(Pixels(6.0));
+    })
+    .height(Pixels(30.0))
+    .child_left(Pixels(15.0))
+    .child_right(Pixels(15.0))
+    .width(Percentage(95.0))
+    .background_color(theme.panel_bg)
+    .border_color(theme.border)
+    .border_width(Pixels(1.0))
+    .border_radius(Pixels(4.0))
+    .bottom(Pixels(8.0));
+}
+
+/// Toggles MIDI learn for a parameter when its slider is right-clicked
+// The host's own context menu for a parameter (VST3's IComponentHandler3, CLAP's
+// context-menu extension) would be the other candidate for the right click, but nih-plug's
+// wrappers don't implement either of them and `GuiContext` has no way to open one, so the
+// right click stays with MIDI learn until nih-plug supports them
+fn midi_learn_on_right_click(id: &'static str) -> impl Fn(&mut EventContext, MouseButton) + Send + Sync {
+    move |cx, button| {
+        if button == MouseButton::Right {
+            cx.emit(MidiLearnEvent::Toggle(id));
+        }
+    }
+}
+
+/// The name of a peak meter above it, with its peak hold and clip indicator on the right
+// Both stay until they're clicked, so a short over can't be missed
+fn meter_header(cx: &mut Context, name: &'static str, readings: impl Lens<Target = PeakReadings>, clear: MeterEvent) {
+    Binding::new(
+        cx,
+        readings.map(|readings| {
+            let hold = util::gain_to_db(readings.hold.load(Ordering::Relaxed));
+            ((hold * 10.0).round() / 10.0, readings.clipped.load(Ordering::Relaxed))
+        }),
+        move |cx, state| {
+            let (hold, clipped) = state.get(cx);
+            let theme = Data::theme.get(cx);
+            HStack::new(cx, |cx| {
+                Label::new(cx, name)
+                    .font_size(theme.text_size(14.0))
+                    .color(theme.secondary);
+
+                // A silent meter holds nothing worth showing
+                let hold_text = if hold > util::MINUS_INFINITY_DB {
+                    format!("PEAK {hold:.1} dB")
+                } else {
+                    String::from("PEAK --")
+                };
+                Label::new(cx, &hold_text)
+                    .font_size(theme.text_size(12.0))
+                    .left(Stretch(1.0))
+                    .color(theme.text_secondary)
+                    .on_mouse_down(move |cx, _| cx.emit(clear));
+
+                // Lights up once a true peak went over 0 dBFS
+                Label::new(cx, "CLIP")
+                    .font_size(theme.text_size(12.0))
+                    .left(Pixels(8.0))
+                    .color(if clipped { theme.accent } else { theme.text_secondary })
+                    .on_mouse_down(move |cx, _| cx.emit(clear));
+            })
+            .height(Pixels(18.0))
+            .width(Percentage(90.0))
+            .bottom(Pixels(4.0));
+        },
+    );
 }
 
-impl Model for Data {}
+/// A peak meter's level as a bar, with the levels of the selected meter range underneath
+// Built again when the range changes, so the bar and the labels always agree
+fn level_meter(cx: &mut Context, readings: impl Lens<Target = PeakReadings> + Copy) {
+    Binding::new(
+        cx,
+        Data::params.map(|params| params.meter_range.value() == MeterRange::Extended),
+        move |cx, _| {
+            let theme = Data::theme.get(cx);
+            let (min_db, max_db) = Data::params.map(|params| params.meter_range.value().db_range()).get(cx);
+
+            Binding::new(
+                cx,
+                readings.map(|readings| {
+                    let level = util::gain_to_db(readings.level.load(Ordering::Relaxed));
+                    (level * 10.0).round() / 10.0
+                }),
+                move |cx, level| {
+                    let fill = ((level.get(cx) - min_db) / (max_db - min_db)).clamp(0.0, 1.0);
+                    HStack::new(cx, |cx| {
+                        Element::new(cx)
+                            .width(Percentage(fill * 100.0))
+                            .height(Stretch(1.0))
+                            .background_color(theme.secondary);
+                    })
+                    .height(Pixels(12.0))
+                    .width(Percentage(90.0))
+                    .background_color(theme.meter_bg)
+                    .bottom(Pixels(2.0));
+                },
+            );
+
+            // The bottom, the middle and the top of the range
+            HStack::new(cx, |cx| {
+                for (index, db) in [min_db, (min_db + max_db) / 2.0, max_db].into_iter().enumerate() {
+                    let label = if db > 0.0 { format!("+{db:.0}") } else { format!("{db:.0}") };
+                    Label::new(cx, &label)
+                        .font_size(theme.text_size(10.0))
+                        .color(theme.text_secondary)
+                        .left(if index == 0 { Pixels(0.0) } else { Stretch(1.0) });
+                }
+            })
+            .height(Pixels(12.0))
+            .width(Percentage(90.0))
+            .bottom(Pixels(6.0));
+        },
+    );
+}
+
+/// The short-term and integrated readings of a loudness meter, like "IN -18.3 / -19.0 LUFS"
+// Shows "--" for a reading while there's nothing to measure yet
+fn loudness_text(name: &str, loudness: &LoudnessReadings) -> String {
+    let reading = |value: &AtomicF32| {
+        let value = value.load(Ordering::Relaxed);
+        if value.is_finite() {
+            format!("{value:.1}")
+        } else {
+            String::from("--")
+        }
+    };
+
+    format!("{name} {} / {} LUFS", reading(&loudness.short_term), reading(&loudness.integrated))
+}
+
+impl Data {
+    /// Send a preset's values to the host through nih-plug's parameter setter
+    // Just like when a slider is dragged, so the host sees every change
+    // All parameters are changed in one gesture, so loading a preset is a single undo step
+    fn apply_preset(&self, cx: &mut EventContext, preset: &Preset) {
+        gesture::set_all(cx, &preset.normalized_values(&self.params));
+    }
+
+    /// Go back to a snapshot from the undo history
+    // This talks to the GuiContext directly instead of emitting RawParamEvents, so
+    // restoring a snapshot doesn't get recorded as a new gesture
+    fn restore(&self, snapshot: &Snapshot) {
+        let values = snapshot.values.normalized_values(&self.params);
+        // SAFETY: The parameter pointers come straight from the params object, which outlives the editor
+        unsafe {
+            for &(param_ptr, _) in &values {
+                self.gui_context.raw_begin_set_parameter(param_ptr);
+            }
+            for &(param_ptr, normalized) in &values {
+                self.gui_context.raw_set_parameter_normalized(param_ptr, normalized);
+            }
+            for &(param_ptr, _) in &values {
+                self.gui_context.raw_end_set_parameter(param_ptr);
+            }
+        }
+        self.params.chaos_seed.store(snapshot.chaos_seed, Ordering::Relaxed);
+    }
+
+    /// Set the unlocked parameters to a roll of the dice
+    // Goes through the GuiContext's parameter setter, so the host records it like any other change
+    fn apply_roll(&mut self, seed: u32) {
+        let RandomPatch { drive, magic, chaos } = randomize::roll(seed);
+        self.history.push(Snapshot::capture(&self.params));
+
+        let setter = ParamSetter::new(self.gui_context.as_ref());
+        let targets = [
+            (&self.params.drive, drive, self.lock_drive),
+            (&self.params.magic, magic, self.lock_magic),
+            (&self.params.chaos, chaos, self.lock_chaos),
+        ];
+        for (param, value, locked) in targets {
+            if locked {
+                continue;
+            }
+            setter.begin_set_parameter(param);
+            setter.set_parameter(param, value);
+            setter.end_set_parameter(param);
+        }
+
+        self.random_seed = randomize::format_seed(seed);
+    }
+
+    /// Rebuild the preset list from the factory presets and the presets on disk
+    fn reload_presets(&mut self) {
+        self.presets = presets::factory_presets();
+        self.presets.extend(presets::load_user_presets());
+        self.preset_names = self.presets.iter().map(|preset| preset.name.clone()).collect();
+    }
+}
+
+impl Model for Data {
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        // Every gesture from the widgets passes by here on its way to the host
+        event.map(|param_event, _| match param_event {
+            RawParamEvent::BeginSetParameter(_) => self.history.begin_gesture(Snapshot::capture(&self.params)),
+            RawParamEvent::EndSetParameter(_) => self.history.end_gesture(Snapshot::capture(&self.params)),
+            _ => {}
+        });
+
+        // Ctrl+Z undoes, Ctrl+Shift+Z redoes (Cmd instead of Ctrl on macOS)
+        event.map(|window_event, meta| {
+            if let WindowEvent::KeyDown(Code::KeyZ, _) = window_event {
+                let modifiers = *cx.modifiers();
+                if !modifiers.intersects(Modifiers::CTRL | Modifiers::LOGO) {
+                    return;
+                }
+
+                let current = Snapshot::capture(&self.params);
+                let snapshot = if modifiers.contains(Modifiers::SHIFT) {
+                    self.history.redo(current)
+                } else {
+                    self.history.undo(current)
+                };
+                if let Some(snapshot) = snapshot {
+                    self.restore(&snapshot);
+                }
+                meta.consume();
+            }
+        });
+
+        event.map(|preset_event, _| match preset_event {
+            PresetEvent::Load(index) => {
+                let Some(preset) = self.presets.get(*index) else {
+                    return;
+                };
+
+                // The locked parameters are left out, so they keep their current values
+                let locks = self.params.preset_locks.read().map(|locks| *locks).unwrap_or_default();
+                self.apply_preset(cx, &preset.without_locked(locks));
+                self.current_preset = preset.name.clone();
+            }
+            PresetEvent::ToggleLock(lock) => {
+                if let Ok(mut locks) = self.params.preset_locks.write() {
+                    locks.toggle(*lock);
+                }
+            }
+            PresetEvent::SetName(name) => self.new_preset_name = name.clone(),
+            PresetEvent::Save => {
+                let name = match self.new_preset_name.trim() {
+                    "" => String::from("User Preset"),
+                    name => name.to_string(),
+                };
+
+                let preset = Preset::from_params(name, &self.params);
+                match presets::save_user_preset(&preset) {
+                    Ok(_) => {
+                        self.current_preset = preset.name;
+                        self.reload_presets();
+                    }
+                    Err(err) => nih_log!("Could not save preset: {}", err),
+                }
+            }
+        });
+
+        event.map(|ab_event, _| {
+            let current = Preset::from_params(String::from("A/B"), &self.params);
+            let Ok(mut ab_comparison) = self.params.ab_comparison.write() else {
+                return;
+            };
+            match ab_event {
+                AbEvent::Switch => {
+                    // The first switch leaves the settings alone, so B starts out as a copy of A
+                    if let Some(other) = ab_comparison.switch(current) {
+                        drop(ab_comparison);
+                        self.apply_preset(cx, &other);
+                    }
+                }
+                AbEvent::Copy => ab_comparison.copy_to_inactive(current),
+            }
+        });
+
+        event.map(|theme_event, _| {
+            let Ok(mut settings) = self.params.editor_theme.write() else {
+                return;
+            };
+            match theme_event {
+                ThemeEvent::ToggleMode => settings.mode = settings.mode.toggled(),
+                ThemeEvent::SetAccent(accent) => settings.accent = Some(*accent),
+            }
+            self.theme = Theme::from_settings(&settings);
+            self.theme_generation += 1;
+        });
+
+        event.map(|prefs_event, _| match prefs_event {
+            PrefsEvent::Toggle => self.prefs_open = !self.prefs_open,
+            // The scale is part of the editor state, so nih-plug saves it and resizes the window
+            PrefsEvent::SetScale(scale) => cx.set_user_scale_factor(*scale),
+            PrefsEvent::SetTextSize(text_size) => {
+                let Ok(mut settings) = self.params.editor_theme.write() else {
+                    return;
+                };
+                settings.text_size = *text_size;
+                self.theme = Theme::from_settings(&settings);
+                self.theme_generation += 1;
+            }
+        });
+
+        event.map(|value_entry_event, meta| match value_entry_event {
+            ValueEntryEvent::Open(param_ptr, typed) => {
+                let param_ptr = *param_ptr;
+                // SAFETY: The parameter pointers come straight from the params object, which outlives the editor
+                let (name, current) = unsafe {
+                    (
+                        param_ptr.name().to_string(),
+                        param_ptr.normalized_value_to_string(param_ptr.unmodulated_normalized_value(), true),
+                    )
+                };
+                // Right below the cursor, a bit above it so the textbox ends up under it
+                let top = (cx.mouse().cursory / cx.scale_factor() - 40.0).max(10.0);
+                self.value_entry_text = current.clone();
+                self.value_entry_invalid = false;
+                self.value_entry = Some(ValueEntry { param_ptr, name, current, typed: typed.clone(), top });
+                meta.consume();
+            }
+            ValueEntryEvent::SetText(text) => {
+                self.value_entry_text = text.clone();
+                self.value_entry_invalid = false;
+            }
+            ValueEntryEvent::Submit => {
+                let Some(entry) = &self.value_entry else {
+                    return;
+                };
+
+                // The parameter's own string to value conversion, so dB, Hz and percentages all
+                // parse the way they're shown. Anything it can't read keeps the popup open.
+                // SAFETY: See above
+                let normalized = unsafe { entry.param_ptr.string_to_normalized_value(self.value_entry_text.trim()) };
+                match normalized {
+                    Some(normalized) => {
+                        // A gesture of its own, so the host records it and it's one undo step
+                        gesture::set_all(cx, &[(entry.param_ptr, normalized)]);
+                        self.value_entry = None;
+                    }
+                    None => self.value_entry_invalid = true,
+                }
+            }
+            ValueEntryEvent::Cancel => self.value_entry = None,
+        });
+
+        // Escape closes the value entry popup
+        event.map(|window_event, _| {
+            if let WindowEvent::KeyDown(Code::Escape, _) = window_event {
+                self.value_entry = None;
+            }
+        });
+
+        // The tab and how far every tab is scrolled are saved with the plugin state, so the
+        // editor opens again where it was left
+        event.map(|layout_event, _| {
+            let Ok(mut layout) = self.params.editor_layout.write() else {
+                return;
+            };
+            match *layout_event {
+                LayoutEvent::SelectTab(tab) => {
+                    layout.tab = tab;
+                    self.tab = tab;
+                }
+                LayoutEvent::Scroll(scroll) => layout.set_scroll(self.tab, scroll),
+            }
+        });
+
+        event.map(|morph_event, _| match morph_event {
+            MorphEvent::Store(slot) => self.params.morph_snapshots.store(*slot, &self.params),
+        });
+
+        event.map(|macro_event, _| {
+            let mappings = &self.params.macro_mappings;
+            match *macro_event {
+                MacroEvent::SetTarget(macro_index, slot, target) => mappings.set_target(macro_index, slot, target),
+                MacroEvent::SetRange(macro_index, slot, range) => mappings.set_range(macro_index, slot, range),
+                MacroEvent::CycleCurve(macro_index, slot) => {
+                    mappings.set_curve(macro_index, slot, mappings.curve(macro_index, slot).next())
+                }
+            }
+        });
+
+        event.map(|meter_event, _| match meter_event {
+            MeterEvent::ClearInput => self.input_peak_meter.clear(),
+            MeterEvent::ClearOutput => self.peak_meter.clear(),
+            MeterEvent::ClearHardClip => self.hard_clipped.store(false, Ordering::Relaxed),
+        });
+
+        event.map(|export_event, _| match export_event {
+            ExportEvent::Render => {
+                // One export at a time, a second click while rendering does nothing
+                if self.export_status.state() == ExportState::Rendering {
+                    return;
+                }
+
+                self.export_status.set(ExportState::Rendering);
+                self.async_executor
+                    .execute_background(Task::ExportTexture(TextureExport::from_params(&self.params)));
+            }
+        });
+
+        event.map(|chaos_event, _| match chaos_event {
+            ChaosEvent::Reseed => {
+                // Seed 0 is reserved for the standard starting conditions, new seeds are never 0
+                let seed = randomize::new_seed();
 
-// Adjusted window size to accommodate the new control
+                // The host doesn't know about the seed, so this is the only way to get it back
+                self.history.push(Snapshot::capture(&self.params));
+                self.params.chaos_seed.store(seed, Ordering::Relaxed);
+            }
+        });
+
+        event.map(|randomize_event, _| match randomize_event {
+            RandomizeEvent::Roll => self.apply_roll(randomize::new_seed()),
+            RandomizeEvent::SetSeed(text) => {
+                // Anything that isn't a hexadecimal seed is ignored
+                if let Some(seed) = randomize::parse_seed(text) {
+                    self.apply_roll(seed);
+                }
+            }
+            RandomizeEvent::ToggleLock(target) => {
+                let lock = match target {
+                    RandomTarget::Drive => &mut self.lock_drive,
+                    RandomTarget::Magic => &mut self.lock_magic,
+                    RandomTarget::Chaos => &mut self.lock_chaos,
+                };
+                *lock = !*lock;
+            }
+        });
+
+        event.map(|midi_event, _| match midi_event {
+            MidiLearnEvent::Toggle(id) => {
+                let Some(target) = midi::target_index(id) else {
+                    return;
+                };
+                let midi_learn = &self.params.midi_learn;
+                let name = midi::target_param(&self.params, target).map_or("", |param| param.name());
+
+                if midi_learn.armed() == Some(target) {
+                    midi_learn.disarm();
+                    self.midi_learning = None;
+                    self.midi_status.clear();
+                } else if midi_learn.mapped_cc(target).is_some() {
+                    midi_learn.clear(target);
+                    self.midi_status = format!("{name}: MIDI mapping removed");
+                } else {
+                    midi_learn.arm(target);
+                    self.midi_learning = Some(target);
+                    self.midi_status = format!("MIDI LEARN: move a controller for {name}");
+                }
+            }
+            MidiLearnEvent::Sync => {
+                // Pass the CC values on to the host, so it sees the same value the audio thread uses
+                for target in 0..MIDI_TARGETS.len() {
+                    let (Some(value), Some(param)) = (
+                        self.params.midi_learn.take_pending(target),
+                        midi::target_param(&self.params, target),
+                    ) else {
+                        continue;
+                    };
+                    gesture::set_all(cx, &[(param.as_ptr(), value)]);
+                }
+
+                // Report a finished learn
+                if let Some(target) = self.midi_learning {
+                    if self.params.midi_learn.armed().is_none() {
+                        self.midi_learning = None;
+                        if let (Some(cc), Some(param)) = (
+                            self.params.midi_learn.mapped_cc(target),
+                            midi::target_param(&self.params, target),
+                        ) {
+                            self.midi_status = format!("{} \u{2192} CC {cc}", param.name());
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+// The controls and the meters scroll within the tabs, so new ones don't need a taller window
+// This is the size at a scale of 100%, which leaves room for a 1080p screen's taskbar and the
+// host's title bar even at 125%. Dragging the resize handle scales it
 pub(crate) fn default_state() -> Arc<ViziaState> {
-    ViziaState::new(|| (380, 320)) // Increase height for the new chaos slider
+    ViziaState::new(|| (380, 764))
+}
+
+/// Draws the magnitude spectrum of the plugin's output on a logarithmic frequency axis
+struct SpectrumView {
+    spectrum: Arc<Mutex<SpectrumOutput>>,
+    theme: Theme,
+}
+
+impl SpectrumView {
+    fn new(cx: &mut Context, spectrum: Arc<Mutex<SpectrumOutput>>) -> Handle<Self> {
+        Self {
+            spectrum,
+            theme: Data::theme.get(cx),
+        }
+        .build(cx, |_| {})
+    }
+}
+
+impl View for SpectrumView {
+    fn element(&self) -> Option<&'static str> {
+        Some("spectrum-analyzer")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w == 0.0 || bounds.h == 0.0 {
+            return;
+        }
+
+        let mut background = vg::Path::new();
+        background.rect(bounds.x, bounds.y, bounds.w, bounds.h);
+        canvas.fill_path(&background, &vg::Paint::color(self.theme.meter_bg.into()));
+
+        let mut spectrum = self.spectrum.lock().unwrap();
+        let bin_width = spectrum.sample_rate() / SPECTRUM_WINDOW_SIZE as f32;
+        let log_range = (SPECTRUM_MAX_FREQ / SPECTRUM_MIN_FREQ).ln();
+
+        let mut path = vg::Path::new();
+        let mut first_point = true;
+        for (bin, magnitude_db) in spectrum.compute().iter().enumerate().skip(1) {
+            let frequency = bin as f32 * bin_width;
+            if !(SPECTRUM_MIN_FREQ..=SPECTRUM_MAX_FREQ).contains(&frequency) {
+                continue;
+            }
+
+            let x = bounds.x + bounds.w * (frequency / SPECTRUM_MIN_FREQ).ln() / log_range;
+            let level = (magnitude_db.max(SPECTRUM_FLOOR_DB) - SPECTRUM_MIN_DB)
+                / (SPECTRUM_MAX_DB - SPECTRUM_MIN_DB);
+            let y = bounds.y + bounds.h * (1.0 - level.clamp(0.0, 1.0));
+
+            if first_point {
+                path.move_to(x, y);
+                first_point = false;
+            } else {
+                path.line_to(x, y);
+            }
+        }
+
+        canvas.stroke_path(
+            &path,
+            &vg::Paint::color(self.theme.secondary.into()).with_line_width(1.5 * cx.scale_factor()),
+        );
+    }
+}
+
+/// A two-dimensional pad controlling the magic (x) and chaos (y) amounts at once
+// Dragging the handle sets both parameters in a single gesture. A fading trail shows
+// where the handle has been recently, whether it was moved by the mouse or by automation.
+struct XyPad {
+    params: Arc<RetardedGainParams>,
+    /// The gesture on both parameters, running while the handle is being dragged
+    gesture: ParamGesture<2>,
+    /// Recent normalized (magic, chaos) positions, oldest first
+    // Drawing only has shared access to the view, hence the RefCell
+    trail: RefCell<VecDeque<(f32, f32)>>,
+    theme: Theme,
+}
+
+impl XyPad {
+    fn new(cx: &mut Context, params: Arc<RetardedGainParams>) -> Handle<Self> {
+        Self {
+            gesture: ParamGesture::new([params.magic.as_ptr(), params.chaos.as_ptr()]),
+            params,
+            trail: RefCell::new(VecDeque::with_capacity(XY_TRAIL_LENGTH)),
+            theme: Data::theme.get(cx),
+        }
+        .build(cx, |_| {})
+        // Redraw whenever either parameter changes, including through automation
+        .bind(
+            Data::params.map(|params| {
+                (
+                    params.magic.unmodulated_normalized_value(),
+                    params.chaos.unmodulated_normalized_value(),
+                )
+            }),
+            |mut handle, _| handle.needs_redraw(),
+        )
+    }
+
+    /// Set both parameters from a cursor position in window coordinates
+    fn set_from_cursor(&mut self, cx: &mut EventContext, x: f32, y: f32) {
+        let bounds = cx.bounds();
+        if bounds.w == 0.0 || bounds.h == 0.0 {
+            return;
+        }
+
+        // Chaos goes up the pad, so the y axis is flipped
+        let magic = ((x - bounds.x) / bounds.w).clamp(0.0, 1.0);
+        let chaos = (1.0 - (y - bounds.y) / bounds.h).clamp(0.0, 1.0);
+        self.gesture.set(cx, 0, magic);
+        self.gesture.set(cx, 1, chaos);
+    }
+}
+
+impl View for XyPad {
+    fn element(&self) -> Option<&'static str> {
+        Some("xy-pad")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|window_event, meta| match *window_event {
+            WindowEvent::MouseDown(MouseButton::Left) => {
+                cx.capture();
+                cx.set_active(true);
+
+                // Both parameters are changed as part of the same gesture
+                self.gesture.begin(cx);
+                let (x, y) = (cx.mouse().cursorx, cx.mouse().cursory);
+                self.set_from_cursor(cx, x, y);
+                meta.consume();
+            }
+            WindowEvent::MouseMove(x, y) => {
+                if self.gesture.is_active() {
+                    self.set_from_cursor(cx, x, y);
+                }
+            }
+            WindowEvent::MouseUp(MouseButton::Left) => {
+                if self.gesture.is_active() {
+                    cx.release();
+                    cx.set_active(false);
+
+                    self.gesture.end(cx);
+                    meta.consume();
+                }
+            }
+            _ => {}
+        });
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w == 0.0 || bounds.h == 0.0 {
+            return;
+        }
+        let scale = cx.scale_factor();
+
+        let mut background = vg::Path::new();
+        background.rect(bounds.x, bounds.y, bounds.w, bounds.h);
+        canvas.fill_path(&background, &vg::Paint::color(self.theme.meter_bg.into()));
+
+        // Crosshair through the middle of the pad
+        let mut grid = vg::Path::new();
+        grid.move_to(bounds.x + bounds.w / 2.0, bounds.y);
+        grid.line_to(bounds.x + bounds.w / 2.0, bounds.y + bounds.h);
+        grid.move_to(bounds.x, bounds.y + bounds.h / 2.0);
+        grid.line_to(bounds.x + bounds.w, bounds.y + bounds.h / 2.0);
+        canvas.stroke_path(&grid, &vg::Paint::color(self.theme.border.into()).with_line_width(scale));
+
+        // Remember the current position if it moved since the last redraw
+        let position = (
+            self.params.magic.unmodulated_normalized_value(),
+            self.params.chaos.unmodulated_normalized_value(),
+        );
+        let mut trail = self.trail.borrow_mut();
+        if trail.back() != Some(&position) {
+            if trail.len() == XY_TRAIL_LENGTH {
+                trail.pop_front();
+            }
+            trail.push_back(position);
+        }
+
+        let to_point = |(magic, chaos): (f32, f32)| {
+            (bounds.x + magic * bounds.w, bounds.y + (1.0 - chaos) * bounds.h)
+        };
+
+        // Older trail points are smaller and more transparent
+        for (index, &point) in trail.iter().enumerate() {
+            let age = (index + 1) as f32 / trail.len() as f32;
+            let (x, y) = to_point(point);
+            let mut dot = vg::Path::new();
+            dot.circle(x, y, (1.0 + 2.0 * age) * scale);
+            let mut color: vg::Color = self.theme.chaos.into();
+            color.set_alphaf(0.6 * age);
+            canvas.fill_path(&dot, &vg::Paint::color(color));
+        }
+
+        // The handle itself
+        let (x, y) = to_point(position);
+        let mut handle = vg::Path::new();
+        handle.circle(x, y, 6.0 * scale);
+        canvas.fill_path(&handle, &vg::Paint::color(self.theme.magic.into()));
+        canvas.stroke_path(&handle, &vg::Paint::color(self.theme.text.into()).with_line_width(1.5 * scale));
+    }
+}
+
+/// Draws a 2D projection of the chaos effect's attractor
+// The x and z coordinates are used, which shows the familiar butterfly shape of the Lorenz system
+// The path is scaled to fit its own extent, so it stays visible as the parameters evolve
+struct AttractorView {
+    attractor: Arc<Mutex<AttractorOutput>>,
+    theme: Theme,
+}
+
+impl AttractorView {
+    fn new(cx: &mut Context, attractor: Arc<Mutex<AttractorOutput>>) -> Handle<Self> {
+        Self {
+            attractor,
+            theme: Data::theme.get(cx),
+        }
+        .build(cx, |_| {})
+    }
+}
+
+impl View for AttractorView {
+    fn element(&self) -> Option<&'static str> {
+        Some("attractor-view")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w == 0.0 || bounds.h == 0.0 {
+            return;
+        }
+
+        let mut background = vg::Path::new();
+        background.rect(bounds.x, bounds.y, bounds.w, bounds.h);
+        canvas.fill_path(&background, &vg::Paint::color(self.theme.meter_bg.into()));
+
+        let mut attractor = self.attractor.lock().unwrap();
+        let path = attractor.path();
+
+        // Find the extent of the path so it can be fitted into the view
+        let (mut min_x, mut max_x, mut min_z, mut max_z) = (f32::MAX, f32::MIN, f32::MAX, f32::MIN);
+        for &[x, _, z] in path {
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+            min_z = min_z.min(z);
+            max_z = max_z.max(z);
+        }
+        // A tiny range means the attractor isn't moving (chaos is off), so don't blow it up
+        let range_x = (max_x - min_x).max(1.0);
+        let range_z = (max_z - min_z).max(1.0);
+
+        let padding = 4.0 * cx.scale_factor();
+        let to_point = |x: f32, z: f32| {
+            (
+                bounds.x + padding + (x - min_x) / range_x * (bounds.w - 2.0 * padding),
+                bounds.y + bounds.h - padding - (z - min_z) / range_z * (bounds.h - 2.0 * padding),
+            )
+        };
+
+        let mut line = vg::Path::new();
+        for (index, &[x, _, z]) in path.iter().enumerate() {
+            let (x, y) = to_point(x, z);
+            if index == 0 {
+                line.move_to(x, y);
+            } else {
+                line.line_to(x, y);
+            }
+        }
+        canvas.stroke_path(
+            &line,
+            &vg::Paint::color(self.theme.chaos.into()).with_line_width(cx.scale_factor()),
+        );
+
+        // Mark the newest point
+        if let Some(&[x, _, z]) = path.last() {
+            let (x, y) = to_point(x, z);
+            let mut head = vg::Path::new();
+            head.circle(x, y, 2.5 * cx.scale_factor());
+            canvas.fill_path(&head, &vg::Paint::color(self.theme.text.into()));
+        }
+    }
+}
+
+/// Draws the input and output waveforms on top of each other
+// The input is drawn faintly behind the output, so it's easy to see how the wavefolder
+// bends the shape and how the chaos effect pushes the level around
+struct ScopeView {
+    scope: Arc<Mutex<ScopeOutput>>,
+    theme: Theme,
+}
+
+impl ScopeView {
+    fn new(cx: &mut Context, scope: Arc<Mutex<ScopeOutput>>) -> Handle<Self> {
+        Self {
+            scope,
+            theme: Data::theme.get(cx),
+        }
+        .build(cx, |_| {})
+    }
+}
+
+impl View for ScopeView {
+    fn element(&self) -> Option<&'static str> {
+        Some("oscilloscope")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w == 0.0 || bounds.h == 0.0 {
+            return;
+        }
+
+        let mut background = vg::Path::new();
+        background.rect(bounds.x, bounds.y, bounds.w, bounds.h);
+        canvas.fill_path(&background, &vg::Paint::color(self.theme.meter_bg.into()));
+
+        // The zero line
+        let center_y = bounds.y + bounds.h / 2.0;
+        let mut zero_line = vg::Path::new();
+        zero_line.move_to(bounds.x, center_y);
+        zero_line.line_to(bounds.x + bounds.w, center_y);
+        canvas.stroke_path(&zero_line, &vg::Paint::color(self.theme.border.into()).with_line_width(cx.scale_factor()));
+
+        let mut scope = self.scope.lock().unwrap();
+        let waveforms = scope.waveforms();
+
+        // Full scale reaches the top and bottom edges, anything louder is clipped to them
+        let step = bounds.w / (SCOPE_DISPLAY_SIZE - 1) as f32;
+        let to_y = |sample: f32| center_y - sample.clamp(-1.0, 1.0) * bounds.h / 2.0;
+        for (side, color, line_width) in [(0, self.theme.text_secondary, 1.0), (1, self.theme.secondary, 1.5)] {
+            let mut line = vg::Path::new();
+            for (index, frame) in waveforms.iter().enumerate() {
+                let (x, y) = (bounds.x + index as f32 * step, to_y(frame[side]));
+                if index == 0 {
+                    line.move_to(x, y);
+                } else {
+                    line.line_to(x, y);
+                }
+            }
+            canvas.stroke_path(
+                &line,
+                &vg::Paint::color(color.into()).with_line_width(line_width * cx.scale_factor()),
+            );
+        }
+    }
+}
+
+/// The color of the texture history for a chaos signal value
+// Goes from the magic purple at -1.0 through the chaos teal at 0.0 to the mix orange at 1.0
+fn texture_color(theme: &Theme, chaos: f32) -> vg::Color {
+    let (from, to, t): (vg::Color, vg::Color, f32) = if chaos < 0.0 {
+        (theme.magic.into(), theme.chaos.into(), chaos + 1.0)
+    } else {
+        (theme.chaos.into(), theme.mix.into(), chaos)
+    };
+    let t = t.clamp(0.0, 1.0);
+
+    vg::Color::rgbf(
+        from.r + (to.r - from.r) * t,
+        from.g + (to.g - from.g) * t,
+        from.b + (to.b - from.b) * t,
+    )
+}
+
+/// Draws the last few seconds of the output waveform, colored by the chaos signal
+// Every column shows the output's lowest and highest sample over a short stretch of time,
+// with a faint band behind it in the same color. When the chaos system's evolution moves
+// into a different timbre the colors shift, so it's easy to see when it happens.
+struct TextureView {
+    texture: Arc<Mutex<TextureOutput>>,
+    theme: Theme,
+}
+
+impl TextureView {
+    fn new(cx: &mut Context, texture: Arc<Mutex<TextureOutput>>) -> Handle<Self> {
+        Self {
+            texture,
+            theme: Data::theme.get(cx),
+        }
+        .build(cx, |_| {})
+    }
+}
+
+impl View for TextureView {
+    fn element(&self) -> Option<&'static str> {
+        Some("texture-history")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w == 0.0 || bounds.h == 0.0 {
+            return;
+        }
+
+        let mut background = vg::Path::new();
+        background.rect(bounds.x, bounds.y, bounds.w, bounds.h);
+        canvas.fill_path(&background, &vg::Paint::color(self.theme.meter_bg.into()));
+
+        // One column for every logical pixel, resampled from the history on the GUI thread
+        let num_columns = ((bounds.w / cx.scale_factor()).round() as usize).max(1);
+        let mut texture = self.texture.lock().unwrap();
+        let columns = texture.columns(num_columns);
+
+        let column_width = bounds.w / num_columns as f32;
+        let center_y = bounds.y + bounds.h / 2.0;
+        let to_y = |sample: f32| center_y - sample.clamp(-1.0, 1.0) * bounds.h / 2.0;
+        for (index, column) in columns.iter().enumerate() {
+            let x = bounds.x + index as f32 * column_width;
+            let color = texture_color(&self.theme, column.chaos);
+
+            let mut band = vg::Path::new();
+            band.rect(x, bounds.y, column_width, bounds.h);
+            let mut band_color = color;
+            band_color.a = 0.15;
+            canvas.fill_path(&band, &vg::Paint::color(band_color));
+
+            // At least a pixel high, so silence still shows its color as a line
+            let (top, bottom) = (to_y(column.max), to_y(column.min));
+            let height = (bottom - top).max(cx.scale_factor());
+            let mut waveform = vg::Path::new();
+            waveform.rect(x, top.min(center_y - height / 2.0), column_width, height);
+            canvas.fill_path(&waveform, &vg::Paint::color(color));
+        }
+    }
+}
+
+/// The gear that opens the preferences
+// Drawn instead of taken from a font, since the editor's font has no gear in it
+struct GearIcon {
+    color: Color,
+    theme: Theme,
+}
+
+impl GearIcon {
+    fn new(cx: &mut Context, color: Color) -> Handle<Self> {
+        Self {
+            color,
+            theme: Data::theme.get(cx),
+        }
+        .build(cx, |_| {})
+    }
+}
+
+impl View for GearIcon {
+    fn element(&self) -> Option<&'static str> {
+        Some("gear-icon")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        let (center_x, center_y) = (bounds.x + bounds.w / 2.0, bounds.y + bounds.h / 2.0);
+        let outer = bounds.w.min(bounds.h) / 2.0;
+        let inner = outer * 0.72;
+
+        // Eight teeth around the rim, every tooth a slice of the outer circle
+        let teeth = 8;
+        let mut gear = vg::Path::new();
+        for step in 0..teeth * 4 {
+            let angle = step as f32 / (teeth * 4) as f32 * 2.0 * PI;
+            let radius = if (step / 2) % 2 == 0 { outer } else { inner };
+            let (x, y) = (center_x + radius * angle.cos(), center_y + radius * angle.sin());
+            if step == 0 {
+                gear.move_to(x, y);
+            } else {
+                gear.line_to(x, y);
+            }
+        }
+        gear.close();
+        canvas.fill_path(&gear, &vg::Paint::color(self.color.into()));
+
+        // The hole in the middle, in the color of the footer behind it
+        let mut hole = vg::Path::new();
+        hole.circle(center_x, center_y, outer * 0.3);
+        canvas.fill_path(&hole, &vg::Paint::color(self.theme.background.into()));
+    }
+}
+
+/// The pencil on the buttons that open the value entry popup
+// Drawn like the gear, the editor's font has no pencil either
+struct EditIcon {
+    color: Color,
+}
+
+impl EditIcon {
+    fn new(cx: &mut Context, color: Color) -> Handle<Self> {
+        Self { color }.build(cx, |_| {})
+    }
+}
+
+impl View for EditIcon {
+    fn element(&self) -> Option<&'static str> {
+        Some("edit-icon")
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        let size = bounds.w.min(bounds.h);
+        let (left, bottom) = (bounds.x + (bounds.w - size) / 2.0, bounds.y + (bounds.h + size) / 2.0);
+
+        // A pencil lying from the bottom left to the top right, the tip in the bottom left
+        // corner. `along` runs up the pencil, `across` is half its thickness to either side.
+        let along = |distance: f32| (left + distance * size, bottom - distance * size);
+        let across = 0.12 * size;
+        let (tip_x, tip_y) = along(0.0);
+        let (base_x, base_y) = along(0.3);
+        let (end_x, end_y) = along(0.85);
+
+        let mut pencil = vg::Path::new();
+        pencil.move_to(tip_x, tip_y);
+        pencil.line_to(base_x + across, base_y + across);
+        pencil.line_to(end_x + across, end_y + across);
+        pencil.line_to(end_x - across, end_y - across);
+        pencil.line_to(base_x - across, base_y - across);
+        pencil.close();
+        canvas.fill_path(&pencil, &vg::Paint::color(self.color.into()));
+    }
+}
+
+/// A pencil button that opens the value entry popup for a parameter
+fn edit_button(cx: &mut Context, param_ptr: ParamPtr, color: Color) {
+    Button::new(
+        cx,
+        move |cx| cx.emit(ValueEntryEvent::Open(param_ptr, String::new())),
+        move |cx| EditIcon::new(cx, color).width(Pixels(12.0)).height(Pixels(12.0)),
+    )
+    .width(Pixels(24.0))
+    .height(Pixels(20.0))
+    .top(Pixels(5.0))
+    .left(Pixels(6.0))
+    .child_space(Stretch(1.0));
+}
+
+/// The popup for typing in an exact value, on top of a dimmed editor
+// Opened by a knob when a number is typed right after double-clicking it, or by the pencil
+// next to the gain knobs. Enter or SET sets the value, Escape or CANCEL closes it again.
+fn value_entry_popup(cx: &mut Context) {
+    Binding::new(cx, Data::value_entry.map(Option::is_some), |cx, open| {
+        if !open.get(cx) {
+            return;
+        }
+        let Some(entry) = Data::value_entry.get(cx) else {
+            return;
+        };
+        let theme = Data::theme.get(cx);
+
+        VStack::new(cx, |cx| {
+            VStack::new(cx, |cx| {
+                Label::new(cx, &entry.name.to_uppercase())
+                    .font_size(theme.text_size(14.0))
+                    .color(theme.secondary);
+                Label::new(cx, &format!("Currently {}", entry.current))
+                    .font_size(theme.text_size(11.0))
+                    .color(theme.text_secondary)
+                    .top(Pixels(2.0));
+
+                // Starts out with the current value selected, so typing replaces it. Whatever
+                // was typed to open the popup replaces it right away.
+                let typed = entry.typed.clone();
+                Textbox::new(cx, Data::value_entry_text)
+                    .on_edit(|cx, text| cx.emit(ValueEntryEvent::SetText(text)))
+                    .on_submit(|cx, _, success| {
+                        if success {
+                            cx.emit(ValueEntryEvent::Submit);
+                        }
+                    })
+                    .on_build(move |cx| {
+                        cx.emit(TextEvent::StartEdit);
+                        cx.emit(TextEvent::SelectAll);
+                        if !typed.is_empty() {
+                            cx.emit(TextEvent::InsertText(typed.clone()));
+                        }
+                    })
+                    .width(Stretch(1.0))
+                    .height(Pixels(22.0))
+                    .top(Pixels(8.0))
+                    .font_size(theme.text_size(13.0))
+                    .color(theme.text);
+
+                HStack::new(cx, |cx| {
+                    Binding::new(cx, Data::value_entry_invalid, move |cx, invalid| {
+                        if invalid.get(cx) {
+                            Label::new(cx, "Not a value")
+                                .font_size(theme.text_size(11.0))
+                                .color(theme.text_secondary)
+                                .top(Pixels(4.0));
+                        }
+                    });
+
+                    Button::new(
+                        cx,
+                        |cx| cx.emit(ValueEntryEvent::Cancel),
+                        move |cx| Label::new(cx, "CANCEL").font_size(theme.text_size(12.0)).color(theme.text_secondary),
+                    )
+                    .height(Pixels(22.0))
+                    .left(Stretch(1.0));
+
+                    Button::new(
+                        cx,
+                        |cx| cx.emit(ValueEntryEvent::Submit),
+                        move |cx| Label::new(cx, "SET").font_size(theme.text_size(12.0)).color(theme.accent),
+                    )
+                    .height(Pixels(22.0))
+                    .left(Pixels(6.0));
+                })
+                .height(Pixels(22.0))
+                .top(Pixels(8.0));
+            })
+            .width(Pixels(260.0))
+            .height(Auto)
+            .child_space(Pixels(12.0))
+            .top(Pixels(entry.top))
+            .left(Stretch(1.0))
+            .right(Stretch(1.0))
+            .background_color(theme.knob_bg)
+            .border_color(theme.border)
+            .border_width(Pixels(1.0))
+            .border_radius(Pixels(4.0));
+        })
+        // Covers the whole editor, so nothing underneath can be touched while it's open
+        .position_type(PositionType::SelfDirected)
+        .left(Pixels(0.0))
+        .top(Pixels(0.0))
+        .width(Percentage(100.0))
+        .height(Percentage(100.0))
+        .z_index(200)
+        .background_color(Color::rgba(0, 0, 0, 0x90));
+    });
 }
 
+// Every meter and display has its own shared value
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn create(
     params: Arc<RetardedGainParams>,
-    peak_meter: Arc<AtomicF32>,
+    input_peak_meter: PeakReadings,
+    peak_meter: PeakReadings,
+    input_loudness: LoudnessReadings,
+    output_loudness: LoudnessReadings,
+    level_difference: Arc<AtomicF32>,
+    modulated_magic: Arc<AtomicF32>,
+    modulated_chaos: Arc<AtomicF32>,
+    stability: Arc<AtomicF32>,
+    modulation_rings: Arc<ModulationRings>,
+    correlation: Arc<AtomicF32>,
+    cpu_load: Arc<AtomicF32>,
+    activity: Arc<ProcessingActivity>,
+    hard_clipped: Arc<AtomicBool>,
+    export_status: Arc<ExportStatus>,
+    async_executor: AsyncExecutor<RetardedGain>,
+    spectrum: Arc<Mutex<SpectrumOutput>>,
+    attractor: Arc<Mutex<AttractorOutput>>,
+    scope: Arc<Mutex<ScopeOutput>>,
+    texture: Arc<Mutex<TextureOutput>>,
     editor_state: Arc<ViziaState>,
 ) -> Option<Box<dyn Editor>> {
-    create_vizia_editor(editor_state, ViziaTheming::Custom, move |cx, _| {
+    create_vizia_editor(editor_state, ViziaTheming::Custom, move |cx, gui_context| {
         // Register fonts
         assets::register_noto_sans_thin(cx);
         assets::register_noto_sans_light(cx);
@@ -54,190 +1562,2752 @@ pub(crate) fn create(
         // Set the default font
         cx.set_default_font(&[assets::NOTO_SANS]);
         
-        Data {
+        let mut data = Data {
             params: params.clone(),
+            gui_context: gui_context.clone(),
+            input_peak_meter: input_peak_meter.clone(),
             peak_meter: peak_meter.clone(),
-        }
-        .build(cx);
+            input_loudness: input_loudness.clone(),
+            output_loudness: output_loudness.clone(),
+            level_difference: level_difference.clone(),
+            modulated_magic: modulated_magic.clone(),
+            modulated_chaos: modulated_chaos.clone(),
+            stability: stability.clone(),
+            correlation: correlation.clone(),
+            cpu_load: cpu_load.clone(),
+            activity: activity.clone(),
+            hard_clipped: hard_clipped.clone(),
+            export_status: export_status.clone(),
+            async_executor: async_executor.clone(),
+            spectrum_generation: spectrum.lock().unwrap().generation(),
+            attractor_generation: attractor.lock().unwrap().generation(),
+            scope_generation: scope.lock().unwrap().generation(),
+            texture_generation: texture.lock().unwrap().generation(),
+            presets: Vec::new(),
+            preset_names: Vec::new(),
+            current_preset: String::from("Presets"),
+            new_preset_name: String::new(),
+            midi_learning: None,
+            midi_status: String::new(),
+            history: History::new(),
+            lock_drive: false,
+            lock_magic: false,
+            lock_chaos: false,
+            random_seed: String::from("--------"),
+            macro_target_names: std::iter::once(String::from("None"))
+                .chain((0..MORPH_TARGETS.len()).filter_map(|target| {
+                    morph::target_param(&params, target).map(|param| param.name().to_string())
+                }))
+                .collect(),
+            theme: params
+                .editor_theme
+                .read()
+                .map(|settings| Theme::from_settings(&settings))
+                .unwrap_or(Theme::DARK),
+            theme_generation: 0,
+            prefs_open: false,
+            tab: params.editor_layout.read().map(|layout| layout.tab).unwrap_or_default(),
+            value_entry: None,
+            value_entry_text: String::new(),
+            value_entry_invalid: false,
+        };
+        data.reload_presets();
+        data.build(cx);
+        // Every knob looks for its own ring in here
+        KnobModulation(modulation_rings.clone()).build(cx);
 
-        // Main container
-        VStack::new(cx, |cx| {
-            // Header with plugin name and brand - better aligned
-            HStack::new(cx, |cx| {
-                VStack::new(cx, |cx| {
-                    Label::new(cx, "R3T4RD3D G41N")
-                        .font_size(22.0)
-                        .color(TEXT_COLOR);
+        // The whole editor is built again whenever the theme changes, so every widget picks up the new colors
+        let params = params.clone();
+        let spectrum = spectrum.clone();
+        let attractor = attractor.clone();
+        let scope = scope.clone();
+        let texture = texture.clone();
+        Binding::new(cx, Data::theme_generation, move |cx, _| {
+            let theme = Data::theme.get(cx);
+
+            // Main container
+            VStack::new(cx, |cx| {
+                // Header with plugin name and brand - better aligned
+                HStack::new(cx, |cx| {
+                    VStack::new(cx, |cx| {
+                        Label::new(cx, "R3T4RD3D G41N")
+                            .font_size(theme.text_size(22.0))
+                            .color(theme.text);
                         
              
+                    })
+                    .child_space(Stretch(1.0));
                 })
-                .child_space(Stretch(1.0));
-            })
-            .height(Pixels(42.0))
-            .child_left(Pixels(15.0))
-            .child_right(Pixels(15.0))
-            .width(Percentage(95.0))
-            .background_color(KNOB_BG_COLOR)
-            .border_color(BORDER_COLOR)
-            .border_width(Pixels(1.0))
-            .border_radius(Pixels(4.0))
-            .bottom(Pixels(10.0));
+                .height(Pixels(42.0))
+                .child_left(Pixels(15.0))
+                .child_right(Pixels(15.0))
+                .width(Percentage(95.0))
+                .background_color(theme.knob_bg)
+                .border_color(theme.border)
+                .border_width(Pixels(1.0))
+                .border_radius(Pixels(4.0))
+                .bottom(Pixels(10.0));
             
-            // Main controls section with better organization
-            VStack::new(cx, |cx| {
-                // GAIN with better positioned elements
+                // Preset browser - load factory/user presets and save new ones
                 HStack::new(cx, |cx| {
-                    Label::new(cx, "GAIN")
-                        .font_size(14.0)
-                        .color(SECONDARY_COLOR)
-                        .width(Percentage(15.0))
-                        .child_space(Stretch(1.0));
-                        
-                    ParamSlider::new(cx, Data::params, |params| &params.gain)
-                        .width(Percentage(75.0))
-                        .height(Pixels(20.0))
-                        .color(SECONDARY_COLOR)
-                        .top(Pixels(5.0))
-                        .font_size(13.0);
+                    Dropdown::new(
+                        cx,
+                        move |cx| {
+                            Label::new(cx, Data::current_preset)
+                                .font_size(theme.text_size(13.0))
+                                .color(theme.text)
+                        },
+                        move |cx| {
+                            List::new(cx, Data::preset_names, move |cx, index, name| {
+                                Label::new(cx, name)
+                                    .font_size(theme.text_size(13.0))
+                                    .color(theme.text)
+                                    .width(Stretch(1.0))
+                                    .on_press(move |cx| {
+                                        cx.emit(PresetEvent::Load(index));
+                                        cx.emit(PopupEvent::Close);
+                                    });
+                            })
+                            .background_color(theme.knob_bg);
+                        },
+                    )
+                    .width(Percentage(34.0))
+                    .height(Pixels(22.0));
+
+                    Textbox::new(cx, Data::new_preset_name)
+                        .on_edit(|cx, text| cx.emit(PresetEvent::SetName(text)))
+                        .width(Percentage(22.0))
+                        .height(Pixels(22.0))
+                        .left(Pixels(6.0))
+                        .font_size(theme.text_size(13.0))
+                        .color(theme.text);
+
+                    Button::new(
+                        cx,
+                        |cx| cx.emit(PresetEvent::Save),
+                        move |cx| Label::new(cx, "SAVE").font_size(theme.text_size(12.0)).color(theme.accent),
+                    )
+                    .height(Pixels(22.0))
+                    .left(Pixels(6.0));
+
+                    // A/B comparison - shows the active slot, click to switch to the other one
+                    Button::new(
+                        cx,
+                        |cx| cx.emit(AbEvent::Switch),
+                        move |cx| {
+                            Label::new(
+                                cx,
+                                Data::params.map(|params| {
+                                    params
+                                        .ab_comparison
+                                        .read()
+                                        .map(|ab_comparison| ab_comparison.active.label())
+                                        .unwrap_or("A")
+                                        .to_string()
+                                }),
+                            )
+                            .font_size(theme.text_size(12.0))
+                            .color(theme.secondary)
+                        },
+                    )
+                    .height(Pixels(22.0))
+                    .left(Pixels(6.0));
+
+                    // Copy the current settings into the other slot
+                    Button::new(
+                        cx,
+                        |cx| cx.emit(AbEvent::Copy),
+                        move |cx| {
+                            Label::new(
+                                cx,
+                                Data::params.map(|params| {
+                                    let active = params
+                                        .ab_comparison
+                                        .read()
+                                        .map(|ab_comparison| ab_comparison.active)
+                                        .unwrap_or_default();
+                                    format!("{}\u{2192}{}", active.label(), active.other().label())
+                                }),
+                            )
+                            .font_size(theme.text_size(12.0))
+                            .color(theme.secondary)
+                        },
+                    )
+                    .height(Pixels(22.0))
+                    .left(Pixels(6.0));
                 })
                 .height(Pixels(30.0))
                 .child_left(Pixels(15.0))
                 .child_right(Pixels(15.0))
+                .child_top(Pixels(4.0))
                 .width(Percentage(95.0))
-                .background_color(PANEL_BG)
-                .border_color(BORDER_COLOR)
+                .background_color(theme.panel_bg)
+                .border_color(theme.border)
                 .border_width(Pixels(1.0))
                 .border_radius(Pixels(4.0))
                 .bottom(Pixels(8.0));
-        
-                // DISTORTION with better alignment
+
+                // KEEP - parameters that stay where they are while browsing presets
                 HStack::new(cx, |cx| {
-                    Label::new(cx, "DIST")
-                        .font_size(14.0)
-                        .color(ACCENT_COLOR)
+                    Label::new(cx, "KEEP")
+                        .font_size(theme.text_size(14.0))
+                        .color(theme.accent)
                         .width(Percentage(15.0))
                         .child_space(Stretch(1.0));
-                        
-                    ParamSlider::new(cx, Data::params, |params| &params.drive)
-                        .width(Percentage(75.0))
-                        .height(Pixels(20.0))
-                        .top(Pixels(5.0))
-                        .color(ACCENT_COLOR)
-                        .font_size(13.0);
+
+                    for lock in PresetLock::ALL {
+                        preset_lock_button(cx, lock);
+                    }
                 })
                 .height(Pixels(30.0))
                 .child_left(Pixels(15.0))
                 .child_right(Pixels(15.0))
                 .width(Percentage(95.0))
-                .background_color(PANEL_BG)
-                .border_color(BORDER_COLOR)
+                .background_color(theme.panel_bg)
+                .border_color(theme.border)
                 .border_width(Pixels(1.0))
                 .border_radius(Pixels(4.0))
                 .bottom(Pixels(8.0));
-                
-                // MAGIC ONE - new slider for fractal algorithm
+            
+                // TABS - the controls are split over three tabs, the meters show at the end of every one
                 HStack::new(cx, |cx| {
-                    Label::new(cx, "MAGIC")
-                        .font_size(14.0)
-                        .color(MAGIC_COLOR)
-                        .width(Percentage(15.0))
-                        .child_space(Stretch(1.0));
-                        
-                    ParamSlider::new(cx, Data::params, |params| &params.magic)
-                        .width(Percentage(75.0))
-                        .height(Pixels(20.0))
-                        .top(Pixels(5.0))
-                        .color(MAGIC_COLOR)
-                        .font_size(13.0);
+                    for tab in EditorTab::ALL {
+                        tab_button(cx, tab);
+                    }
                 })
                 .height(Pixels(30.0))
                 .child_left(Pixels(15.0))
                 .child_right(Pixels(15.0))
                 .width(Percentage(95.0))
-                .background_color(PANEL_BG)
-                .border_color(BORDER_COLOR)
+                .background_color(theme.panel_bg)
+                .border_color(theme.border)
                 .border_width(Pixels(1.0))
                 .border_radius(Pixels(4.0))
                 .bottom(Pixels(8.0));
 
-                // CHAOS - new slider for Lorenz attractor
+                // The current tab, scrolled to where it was left, with the meters after its controls
+                // Built again when another tab is picked, so only one tab's widgets exist at a time
+                let params = params.clone();
+                let spectrum = spectrum.clone();
+                let attractor = attractor.clone();
+                let scope = scope.clone();
+                let texture = texture.clone();
+                Binding::new(cx, Data::tab.map(|tab| tab.index()), move |cx, index| {
+                    let tab = EditorTab::ALL[index.get(cx)];
+                    let scroll = params.editor_layout.read().map(|layout| layout.scroll(tab)).unwrap_or(0.0);
+                    let params = params.clone();
+                    let spectrum = spectrum.clone();
+                    let attractor = attractor.clone();
+                    let scope = scope.clone();
+                    let texture = texture.clone();
+                    ScrollView::new(cx, 0.0, scroll, false, true, move |cx| {
+                        VStack::new(cx, |cx| match tab {
+                            EditorTab::Main => {
+                                // INPUT GAIN to trim the level going into the chain
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "IN")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.input_gain)
+                                        .on_mouse_down(midi_learn_on_right_click("input_gain"))
+                                        .width(Percentage(66.0))
+                                        .height(Pixels(20.0))
+                                        .color(theme.secondary)
+                                        .top(Pixels(5.0))
+                                        .font_size(theme.text_size(13.0));
+
+                                    // Type in an exact gain in dB
+                                    let input_gain = Data::params.map(|params| params.input_gain.as_ptr()).get(cx);
+                                    edit_button(cx, input_gain, theme.secondary);
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // GATE - threshold, attack, hold and release of the noise gate before the chain
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "GATE")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.gate_threshold)
+                                        .width(Percentage(30.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.gate_attack)
+                                        .width(Percentage(14.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.gate_hold)
+                                        .width(Percentage(14.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.gate_release)
+                                        .width(Percentage(14.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // OUTPUT GAIN with better positioned elements
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "OUT")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.output_gain)
+                                        .on_mouse_down(midi_learn_on_right_click("gain"))
+                                        .width(Percentage(42.0))
+                                        .height(Pixels(20.0))
+                                        .color(theme.secondary)
+                                        .top(Pixels(5.0))
+                                        .font_size(theme.text_size(13.0));
+
+                                    let output_gain = Data::params.map(|params| params.output_gain.as_ptr()).get(cx);
+                                    edit_button(cx, output_gain, theme.secondary);
+
+                                    // Turns the output down as the drive goes up
+                                    ParamButton::new(cx, Data::params, |params| &params.auto_gain)
+                                        .with_label("AUTO GAIN")
+                                        .width(Percentage(23.0))
+                                        .left(Pixels(6.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // STEREO - pan, balance and polarity of the output
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "STEREO")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.output_pan)
+                                        .width(Percentage(27.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.output_balance)
+                                        .width(Percentage(27.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // Flip the polarity of one channel
+                                    ParamButton::new(cx, Data::params, |params| &params.invert_left)
+                                        .with_label("\u{00D8} L")
+                                        .width(Percentage(9.0))
+                                        .left(Pixels(6.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.invert_right)
+                                        .with_label("\u{00D8} R")
+                                        .width(Percentage(9.0))
+                                        .left(Pixels(6.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // WIDTH - how wide the output's stereo image is
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "WIDTH")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.stereo_width)
+                                        .width(Percentage(80.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // DISTORTION - drive, drive model and waveshaper mode
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "DIST")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.accent)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.drive)
+                                        .on_mouse_down(midi_learn_on_right_click("drive"))
+                                        .width(Percentage(30.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(13.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.drive_model)
+                                        .width(Percentage(20.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(13.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.dist_mode)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // FILTER - tone filter after the distortion
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "TONE")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.accent)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.filter_mode)
+                                        .width(Percentage(22.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.filter_cutoff)
+                                        .on_mouse_down(midi_learn_on_right_click("filter_cutoff"))
+                                        .width(Percentage(28.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.filter_resonance)
+                                        .on_mouse_down(midi_learn_on_right_click("filter_resonance"))
+                                        .width(Percentage(22.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // CRUSH - on/off switch, bit depth and sample rate reduction of the bitcrusher
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "CRUSH")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.accent)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.crusher)
+                                        .with_label("ON")
+                                        .width(Percentage(16.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.crusher_bits)
+                                        .width(Percentage(28.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.crusher_downsample)
+                                        .width(Percentage(28.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // MAGIC ONE - new slider for fractal algorithm
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "MAGIC")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.magic)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    VStack::new(cx, |cx| {
+                                        let modulated_magic = Data::modulated_magic.get(cx);
+                                        Knob::new(cx, Data::params, |params| &params.magic)
+                                            .modulation(modulated_magic)
+                                            .on_mouse_down(midi_learn_on_right_click("magic"))
+                                            .height(Pixels(20.0))
+                                            .color(theme.magic)
+                                            .font_size(theme.text_size(13.0));
+
+                                        // Thin bar under the slider showing the magic amount after envelope modulation
+                                        Binding::new(
+                                            cx,
+                                            Data::modulated_magic.map(|magic| magic.load(Ordering::Relaxed)),
+                                            move |cx, magic| {
+                                                Element::new(cx)
+                                                    .width(Percentage(magic.get(cx) * 100.0))
+                                                    .height(Pixels(2.0))
+                                                    .top(Pixels(1.0))
+                                                    .background_color(theme.magic);
+                                            },
+                                        );
+                                    })
+                                    .width(Percentage(75.0))
+                                    .height(Pixels(23.0))
+                                    .top(Pixels(5.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // CHAOS - new slider for Lorenz attractor
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "CHAOS")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    VStack::new(cx, |cx| {
+                                        let modulated_chaos = Data::modulated_chaos.get(cx);
+                                        Knob::new(cx, Data::params, |params| &params.chaos)
+                                            .modulation(modulated_chaos)
+                                            .on_mouse_down(midi_learn_on_right_click("chaos"))
+                                            .height(Pixels(20.0))
+                                            .color(theme.chaos)
+                                            .font_size(theme.text_size(13.0));
+
+                                        // Thin bar under the slider showing the chaos amount after LFO and envelope modulation
+                                        Binding::new(
+                                            cx,
+                                            Data::modulated_chaos.map(|chaos| chaos.load(Ordering::Relaxed)),
+                                            move |cx, chaos| {
+                                                Element::new(cx)
+                                                    .width(Percentage(chaos.get(cx) * 100.0))
+                                                    .height(Pixels(2.0))
+                                                    .top(Pixels(1.0))
+                                                    .background_color(theme.chaos);
+                                            },
+                                        );
+                                    })
+                                    .width(Percentage(38.0))
+                                    .height(Pixels(23.0))
+                                    .top(Pixels(5.0));
+
+                                    // From a smooth wobble to harsh noise
+                                    Knob::new(cx, Data::params, |params| &params.chaos_color)
+                                        .width(Percentage(18.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // Decorrelated left and right attractors for a wide stereo image
+                                    ParamButton::new(cx, Data::params, |params| &params.chaos_stereo)
+                                        .with_label("WIDE")
+                                        .width(Percentage(17.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // FM - the chaos effect's FM branch, the classic cosine or a real frequency shift
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "FM")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    // Classic or Shift, to A/B the two
+                                    ParamSlider::new(cx, Data::params, |params| &params.chaos_fm_mode)
+                                        .width(Percentage(22.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    // How far the chaos signal shifts the input, only heard in the Shift mode
+                                    Knob::new(cx, Data::params, |params| &params.chaos_shift)
+                                        .width(Percentage(53.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // COUPLE - let the fractal and the chaos effect push each other around
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "COUPLE")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.coupling)
+                                        .width(Percentage(56.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // Lights up while the stability governor holds the feedback and the coupling back
+                                    Binding::new(
+                                        cx,
+                                        Data::stability.map(|stability| (stability.load(Ordering::Relaxed) * 10.0).round() / 10.0),
+                                        move |cx, stability| {
+                                            let holding = stability.get(cx) < 1.0;
+                                            Label::new(cx, "GOVERN")
+                                                .font_size(theme.text_size(12.0))
+                                                .width(Percentage(20.0))
+                                                .height(Pixels(20.0))
+                                                .top(Pixels(5.0))
+                                                .left(Pixels(6.0))
+                                                .child_space(Stretch(1.0))
+                                                .border_radius(Pixels(4.0))
+                                                .background_color(if holding { theme.chaos } else { theme.meter_bg })
+                                                .color(if holding { theme.background } else { theme.text_secondary });
+                                        },
+                                    );
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // DICE - randomize drive, magic and chaos, with locks for the ones to keep
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "DICE")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.mix)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Button::new(
+                                        cx,
+                                        |cx| cx.emit(RandomizeEvent::Roll),
+                                        move |cx| Label::new(cx, "ROLL").font_size(theme.text_size(12.0)).color(theme.mix),
+                                    )
+                                    .width(Percentage(18.0))
+                                    .height(Pixels(20.0))
+                                    .top(Pixels(5.0));
+
+                                    // Lit parameters get randomized, dimmed ones are locked
+                                    lock_button(cx, "DRV", RandomTarget::Drive, Data::lock_drive);
+                                    lock_button(cx, "MAG", RandomTarget::Magic, Data::lock_magic);
+                                    lock_button(cx, "CHS", RandomTarget::Chaos, Data::lock_chaos);
+
+                                    // The seed of the last roll, type a seed in to recall it
+                                    Textbox::new(cx, Data::random_seed)
+                                        .on_submit(|cx, text, _| cx.emit(RandomizeEvent::SetSeed(text)))
+                                        .width(Stretch(1.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .font_size(theme.text_size(12.0))
+                                        .color(theme.text);
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // XY PAD - magic and chaos performed together, next to the attractor they drive
+                                HStack::new(cx, |cx| {
+                                    VStack::new(cx, |cx| {
+                                        HStack::new(cx, |cx| {
+                                            Label::new(cx, "MAGIC \u{2192}")
+                                                .font_size(theme.text_size(12.0))
+                                                .color(theme.magic);
+                                            Label::new(cx, "CHAOS \u{2191}")
+                                                .font_size(theme.text_size(12.0))
+                                                .left(Stretch(1.0))
+                                                .color(theme.chaos);
+                                        })
+                                        .height(Pixels(16.0))
+                                        .bottom(Pixels(4.0));
+
+                                        XyPad::new(cx, params.clone())
+                                            .height(Pixels(110.0));
+                                    })
+                                    .width(Percentage(48.0));
+
+                                    // ATTRACTOR path of the chaos effect
+                                    VStack::new(cx, |cx| {
+                                        Label::new(cx, "ATTRACTOR")
+                                            .font_size(theme.text_size(12.0))
+                                            .color(theme.chaos)
+                                            .height(Pixels(16.0))
+                                            .bottom(Pixels(4.0));
+
+                                        // Rebuild the view whenever a new path arrives so it gets redrawn
+                                        let attractor = attractor.clone();
+                                        Binding::new(
+                                            cx,
+                                            Data::attractor_generation.map(|generation| generation.load(Ordering::Relaxed)),
+                                            move |cx, _| {
+                                                AttractorView::new(cx, attractor.clone())
+                                                    .height(Pixels(110.0));
+                                            },
+                                        );
+                                    })
+                                    .width(Percentage(48.0))
+                                    .left(Stretch(1.0));
+                                })
+                                .height(Pixels(140.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .child_top(Pixels(4.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // MIX - dry/wet balance for the whole chain
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "MIX")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.mix)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.mix)
+                                        .on_mouse_down(midi_learn_on_right_click("mix"))
+                                        .width(Percentage(75.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.mix)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // COMP - threshold and ratio of the compressor after the mix
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "COMP")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.comp)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.comp_threshold)
+                                        .width(Percentage(36.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.comp)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.comp_ratio)
+                                        .width(Percentage(36.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.comp)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // COMP TIMES - attack and release, with the release optionally wobbled by the chaos signal
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "TIMES")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.comp)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.comp_attack)
+                                        .width(Percentage(26.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.comp)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.comp_release)
+                                        .width(Percentage(26.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.comp)
+                                        .font_size(theme.text_size(13.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.comp_chaotic_release)
+                                        .with_label("CHAOTIC")
+                                        .width(Percentage(20.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.comp)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // DELAY - delay time, free in milliseconds or synced to the host
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "DELAY")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.delay)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.delay_sync)
+                                        .width(Percentage(16.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.delay)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.delay_time)
+                                        .width(Percentage(28.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.delay)
+                                        .font_size(theme.text_size(13.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.delay_division)
+                                        .width(Percentage(28.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.delay)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // ECHO - feedback, chaos warble and mix of the delay
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "ECHO")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.delay)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.delay_feedback)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.delay)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // How much the chaos signal wobbles the delay time
+                                    Knob::new(cx, Data::params, |params| &params.delay_warble)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.delay)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.delay_mix)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.delay)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // REVERB - size, damping and mix of the reverb at the end of the chain
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "REVERB")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.reverb)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.reverb_size)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.reverb)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.reverb_damping)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.reverb)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.reverb_mix)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.reverb)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // TILT - darken or brighten everything the chain puts out
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "TILT")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.tilt)
+                                        .width(Percentage(76.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // SPECTRUM ANALYZER of the output signal
+                                VStack::new(cx, |cx| {
+                                    Label::new(cx, "SPECTRUM")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .bottom(Pixels(4.0));
+
+                                    // Rebuild the view whenever a new window arrives so it gets redrawn
+                                    let spectrum = spectrum.clone();
+                                    Binding::new(
+                                        cx,
+                                        Data::spectrum_generation.map(|generation| generation.load(Ordering::Relaxed)),
+                                        move |cx, _| {
+                                            SpectrumView::new(cx, spectrum.clone())
+                                                .height(Pixels(64.0))
+                                                .width(Percentage(90.0));
+                                        },
+                                    );
+                                })
+                                .height(Pixels(90.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .child_top(Pixels(4.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // OSCILLOSCOPE with the input behind the output
+                                VStack::new(cx, |cx| {
+                                    HStack::new(cx, |cx| {
+                                        Label::new(cx, "SCOPE")
+                                            .font_size(theme.text_size(14.0))
+                                            .color(theme.secondary);
+                                        Label::new(cx, "IN")
+                                            .font_size(theme.text_size(12.0))
+                                            .left(Stretch(1.0))
+                                            .color(theme.text_secondary);
+                                        Label::new(cx, "OUT")
+                                            .font_size(theme.text_size(12.0))
+                                            .left(Pixels(8.0))
+                                            .color(theme.secondary);
+                                    })
+                                    .height(Pixels(18.0))
+                                    .width(Percentage(90.0))
+                                    .bottom(Pixels(4.0));
+
+                                    // Rebuild the view whenever a new recording arrives so it gets redrawn
+                                    let scope = scope.clone();
+                                    Binding::new(
+                                        cx,
+                                        Data::scope_generation.map(|generation| generation.load(Ordering::Relaxed)),
+                                        move |cx, _| {
+                                            ScopeView::new(cx, scope.clone())
+                                                .height(Pixels(64.0))
+                                                .width(Percentage(90.0));
+                                        },
+                                    );
+                                })
+                                .height(Pixels(94.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .child_top(Pixels(4.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // TEXTURE - the output over the last few seconds, colored by the chaos signal
+                                VStack::new(cx, |cx| {
+                                    HStack::new(cx, |cx| {
+                                        Label::new(cx, "TEXTURE")
+                                            .font_size(theme.text_size(14.0))
+                                            .color(theme.chaos);
+                                        Label::new(cx, "4 S")
+                                            .font_size(theme.text_size(12.0))
+                                            .left(Stretch(1.0))
+                                            .color(theme.text_secondary);
+                                    })
+                                    .height(Pixels(18.0))
+                                    .width(Percentage(90.0))
+                                    .bottom(Pixels(4.0));
+
+                                    // Rebuild the view whenever a new history arrives so it gets redrawn
+                                    let texture = texture.clone();
+                                    Binding::new(
+                                        cx,
+                                        Data::texture_generation.map(|generation| generation.load(Ordering::Relaxed)),
+                                        move |cx, _| {
+                                            TextureView::new(cx, texture.clone())
+                                                .height(Pixels(64.0))
+                                                .width(Percentage(90.0));
+                                        },
+                                    );
+                                })
+                                .height(Pixels(94.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .child_top(Pixels(4.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+                            }
+                            EditorTab::Modulation => {
+                                // MORPH - blends everything between two stored snapshots
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "MORPH")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.morph)
+                                        .width(Percentage(43.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // Store the current settings as one end of the morph, a dot marks stored snapshots
+                                    for (slot, name) in [(MorphSlot::A, "A"), (MorphSlot::B, "B")] {
+                                        Button::new(
+                                            cx,
+                                            move |cx| cx.emit(MorphEvent::Store(slot)),
+                                            move |cx| {
+                                                Label::new(
+                                                    cx,
+                                                    Data::params.map(move |params| {
+                                                        if params.morph_snapshots.is_stored(slot) {
+                                                            format!("SET {name} \u{25CF}")
+                                                        } else {
+                                                            format!("SET {name}")
+                                                        }
+                                                    }),
+                                                )
+                                                .font_size(theme.text_size(12.0))
+                                                .color(theme.secondary)
+                                            },
+                                        )
+                                        .width(Percentage(15.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0));
+                                    }
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // MACRO - two knobs that each drive up to three parameters at once
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "MACRO")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.mix)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.macro_1)
+                                        .width(Percentage(37.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.mix)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.macro_2)
+                                        .width(Percentage(37.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.mix)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // The macro matrix, one row for every mapping
+                                for macro_index in 0..NUM_MACROS {
+                                    for slot in 0..MAPPINGS_PER_MACRO {
+                                        macro_mapping_row(cx, macro_index, slot);
+                                    }
+                                }
+
+                                // GEN - play an oscillator through the chain instead of the input, its pitch set by the chaos
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "GEN")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.generator)
+                                        .with_label("ON")
+                                        .width(Percentage(12.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    // The pitch with the chaos signal at rest
+                                    Knob::new(cx, Data::params, |params| &params.generator_pitch)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // How far the chaos moves the pitch
+                                    Knob::new(cx, Data::params, |params| &params.generator_range)
+                                        .width(Percentage(20.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.generator_scale)
+                                        .width(Percentage(18.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // NOTES - play the generator from a MIDI keyboard, gliding from note to note
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "NOTES")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.generator_midi)
+                                        .with_label("MIDI")
+                                        .width(Percentage(14.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.generator_glide)
+                                        .width(Percentage(30.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // LFO - depth and shape of the chaos modulation
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "LFO")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.lfo_depth)
+                                        .on_mouse_down(midi_learn_on_right_click("lfo_depth"))
+                                        .width(Percentage(45.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.lfo_shape)
+                                        .width(Percentage(28.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // LFO RATE - free rate in Hz, or a note division when synced to the host
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "RATE")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.lfo_sync)
+                                        .width(Percentage(16.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.lfo_rate)
+                                        .width(Percentage(28.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.lfo_division)
+                                        .width(Percentage(28.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // PHASE - rate of the chaos effect's secondary phase modulation, free or synced
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "PHASE")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.chaos_mod_sync)
+                                        .width(Percentage(16.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.chaos_mod_rate)
+                                        .width(Percentage(28.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.chaos_mod_division)
+                                        .width(Percentage(28.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // EVOLVE - the slow drift of the chaotic systems, free or locked to a number of bars, how
+                                // fast they run, whether they restart with the host's transport, and whether they
+                                // only move while it plays
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "EVOLVE")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.chaos_evolution)
+                                        .width(Percentage(20.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    // From a slow drift to audio rate
+                                    Knob::new(cx, Data::params, |params| &params.chaos_rate)
+                                        .width(Percentage(18.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // Restarts the chaos and fractal effects when the host starts playing or jumps
+                                    ParamButton::new(cx, Data::params, |params| &params.transport_reset)
+                                        .with_label("AUTO RESET")
+                                        .width(Percentage(19.0))
+                                        .left(Pixels(6.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    // Holds the fractal LFO and the chaos evolution while the host is stopped
+                                    ParamButton::new(cx, Data::params, |params| &params.transport_clock)
+                                        .with_label("PLAY CLOCK")
+                                        .width(Percentage(19.0))
+                                        .left(Pixels(6.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // ENV - attack and release of the input envelope follower
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "ENV")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.env_attack)
+                                        .width(Percentage(36.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.env_release)
+                                        .width(Percentage(36.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // ENV DEPTH - how much the envelope pushes the magic and chaos amounts
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "DEPTH")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.env_magic_depth)
+                                        .on_mouse_down(midi_learn_on_right_click("env_magic_depth"))
+                                        .width(Percentage(36.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.magic)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.env_chaos_depth)
+                                        .on_mouse_down(midi_learn_on_right_click("env_chaos_depth"))
+                                        .width(Percentage(36.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // CH MOD - how far the chaos signal moves the drive, the output gain and the filter cutoff
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "CH MOD")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.chaos_to_drive)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.chaos_to_gain)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.chaos_to_cutoff)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // BURST - chaos bursts fired by the hits in the input
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "BURST")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.burst_amount)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.burst_sensitivity)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.burst_decay)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+                            }
+                            EditorTab::Advanced => {
+                                // CAL - how the level the chaos and fractal effects listen to is calibrated
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "CAL")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.calibration)
+                                        .width(Percentage(30.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // Only used with the manual calibration
+                                    Knob::new(cx, Data::params, |params| &params.calibration_trim)
+                                        .width(Percentage(42.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.secondary)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // CLIP - the curve of the soft clippers in the chaos, the fractal and the delay's feedback
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "CLIP")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.accent)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.clip_character)
+                                        .width(Percentage(72.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // FORMULA - the fractal's iteration formula, the engine and the smoother's lookahead
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "FORMULA")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.magic)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.magic_mode)
+                                        .width(Percentage(37.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.magic)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // On the samples or on the spectrum, the spectral engine adds latency
+                                    ParamSlider::new(cx, Data::params, |params| &params.magic_engine)
+                                        .width(Percentage(15.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.magic)
+                                        .font_size(theme.text_size(12.0));
+
+                                    // Lookahead for the fractal smoother, adds latency when it's on
+                                    Knob::new(cx, Data::params, |params| &params.magic_lookahead)
+                                        .width(Percentage(22.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.magic)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // FOLD - how the fractal's wavefolder anti-aliases its folds
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "FOLD")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.magic)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.magic_fold_quality)
+                                        .width(Percentage(40.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.magic)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // SYSTEM - pick the chaotic system and its integrator, freeze it or restart it somewhere else
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "SYSTEM")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.chaos_system)
+                                        .width(Percentage(26.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    // Euler or RK4
+                                    ParamSlider::new(cx, Data::params, |params| &params.chaos_integrator)
+                                        .width(Percentage(14.0))
+                                        .left(Pixels(6.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    // Locks the chaos modulation to its current timbre
+                                    ParamButton::new(cx, Data::params, |params| &params.chaos_freeze)
+                                        .with_label("FREEZE")
+                                        .width(Percentage(17.0))
+                                        .left(Pixels(6.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    // Random new starting conditions for the chaotic systems
+                                    Button::new(
+                                        cx,
+                                        |cx| cx.emit(ChaosEvent::Reseed),
+                                        move |cx| Label::new(cx, "RE-SEED").font_size(theme.text_size(12.0)).color(theme.chaos),
+                                    )
+                                    .width(Percentage(17.0))
+                                    .height(Pixels(20.0))
+                                    .top(Pixels(5.0))
+                                    .left(Pixels(6.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // EXPORT - render the chaos and fractal effects without any input to a WAV file
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "EXPORT")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.export_length)
+                                        .width(Percentage(30.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Button::new(
+                                        cx,
+                                        |cx| cx.emit(ExportEvent::Render),
+                                        move |cx| Label::new(cx, "RENDER WAV").font_size(theme.text_size(12.0)).color(theme.chaos),
+                                    )
+                                    .width(Percentage(26.0))
+                                    .height(Pixels(20.0))
+                                    .top(Pixels(5.0))
+                                    .left(Pixels(6.0));
+
+                                    // Rendering, saved or failed, the file goes to the export directory
+                                    Label::new(cx, Data::export_status.map(|status| status.label().to_string()))
+                                        .font_size(theme.text_size(12.0))
+                                        .color(theme.text_secondary)
+                                        .width(Percentage(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // STATE - save where the chaos and fractal effects are with the project
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "STATE")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.chaos)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    // Reopening the project resumes the exact texture instead of starting over
+                                    ParamButton::new(cx, Data::params, |params| &params.persist_engine_state)
+                                        .with_label("PERSIST ENGINE STATE")
+                                        .width(Percentage(40.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.chaos)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // ORDER - the order of the distortion, fractal and chaos stages
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "ORDER")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.text_secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.routing)
+                                        .width(Percentage(75.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // LEVELS - output trims of the distortion, fractal and chaos stages
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "LEVELS")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.text_secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.dist_level)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.magic_level)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.chaos_level)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // SOLO - hear only the soloed stages, the others pass the signal through
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "SOLO")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.text_secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.solo_distortion)
+                                        .with_label("DIST")
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.solo_fractal)
+                                        .with_label("MAGIC")
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.solo_chaos)
+                                        .with_label("CHAOS")
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // CHANNELS - linked, dual mono or mid/side, and how much the side gets in mid/side mode
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "CHANNELS")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.text_secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.channel_mode)
+                                        .width(Percentage(36.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(13.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.side_amount)
+                                        .width(Percentage(36.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(13.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // M/S BAL - whether every stage goes on the mid, the side or both in mid/side mode
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "M/S BAL")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.text_secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.dist_ms_balance)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.magic_ms_balance)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.chaos_ms_balance)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // BANDS - multiband on/off and the two crossover frequencies
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "BANDS")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.text_secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.multiband)
+                                        .with_label("MULTI")
+                                        .width(Percentage(20.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.crossover_low)
+                                        .width(Percentage(26.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.crossover_high)
+                                        .width(Percentage(26.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // AMOUNT - how much of the drive, magic and chaos amounts every band gets
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "AMOUNT")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.text_secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.low_band_amount)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.mid_band_amount)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.high_band_amount)
+                                        .width(Percentage(24.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // OVERSAMPLING - quality selector and precision for the effect chain
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "OS")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.text_secondary)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamSlider::new(cx, Data::params, |params| &params.oversampling)
+                                        .width(Percentage(30.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // Eco, Normal or Ultra, overrides the oversampling above unless it's Normal
+                                    ParamSlider::new(cx, Data::params, |params| &params.quality)
+                                        .width(Percentage(24.0))
+                                        .left(Pixels(6.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // 32-bit or 64-bit math for the stages that feed back into themselves
+                                    ParamSlider::new(cx, Data::params, |params| &params.precision)
+                                        .width(Percentage(20.0))
+                                        .left(Pixels(6.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.text_secondary)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // LIMITER - on/off switch and ceiling for the safety limiter
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "LIMIT")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.accent)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.limiter)
+                                        .width(Percentage(20.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.limiter_ceiling)
+                                        .width(Percentage(33.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // Keep the limiter's latency while it's off, so switching it doesn't shift the timing
+                                    ParamButton::new(cx, Data::params, |params| &params.constant_latency)
+                                        .with_label("CONST LAT")
+                                        .width(Percentage(20.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(12.0));
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+
+                                // CLIPPER - on/off switch and ceiling for the hard clipper, with its clip indicator
+                                HStack::new(cx, |cx| {
+                                    Label::new(cx, "CLIPPER")
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.accent)
+                                        .width(Percentage(15.0))
+                                        .child_space(Stretch(1.0));
+
+                                    ParamButton::new(cx, Data::params, |params| &params.hard_clip)
+                                        .width(Percentage(20.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(12.0));
+
+                                    Knob::new(cx, Data::params, |params| &params.hard_clip_ceiling)
+                                        .width(Percentage(33.0))
+                                        .height(Pixels(20.0))
+                                        .top(Pixels(5.0))
+                                        .left(Pixels(6.0))
+                                        .color(theme.accent)
+                                        .font_size(theme.text_size(13.0));
+
+                                    // Stays lit after the clipper cut a sample, until it's clicked
+                                    Binding::new(
+                                        cx,
+                                        Data::hard_clipped.map(|clipped| clipped.load(Ordering::Relaxed)),
+                                        move |cx, clipped| {
+                                            let clipped = clipped.get(cx);
+                                            Label::new(cx, "CLIP")
+                                                .font_size(theme.text_size(12.0))
+                                                .width(Percentage(20.0))
+                                                .height(Pixels(20.0))
+                                                .top(Pixels(5.0))
+                                                .left(Pixels(6.0))
+                                                .child_space(Stretch(1.0))
+                                                .border_radius(Pixels(4.0))
+                                                .background_color(if clipped { theme.accent } else { theme.meter_bg })
+                                                .color(if clipped { theme.background } else { theme.text_secondary })
+                                                .on_mouse_down(|cx, _| cx.emit(MeterEvent::ClearHardClip));
+                                        },
+                                    );
+                                })
+                                .height(Pixels(30.0))
+                                .child_left(Pixels(15.0))
+                                .child_right(Pixels(15.0))
+                                .width(Percentage(95.0))
+                                .background_color(theme.panel_bg)
+                                .border_color(theme.border)
+                                .border_width(Pixels(1.0))
+                                .border_radius(Pixels(4.0))
+                                .bottom(Pixels(8.0));
+                            }
+                        })
+                        .child_top(Pixels(0.0))
+                        .width(Percentage(100.0))
+                        .height(Auto);
+
+                        // INPUT and OUTPUT METERS for gain staging
+                        VStack::new(cx, |cx| {
+                            // How the meters move, the hold and the clip indicators see every peak either way
+                            HStack::new(cx, |cx| {
+                                Label::new(cx, "BALLISTICS")
+                                    .font_size(theme.text_size(12.0))
+                                    .color(theme.text_secondary)
+                                    .width(Percentage(40.0));
+
+                                ParamSlider::new(cx, Data::params, |params| &params.meter_ballistics)
+                                    .width(Percentage(60.0))
+                                    .height(Pixels(18.0))
+                                    .color(theme.secondary)
+                                    .font_size(theme.text_size(12.0));
+                            })
+                            .height(Pixels(18.0))
+                            .width(Percentage(90.0))
+                            .bottom(Pixels(8.0));
+
+                            // The levels the meters show, and how quickly the digital ballistics fall back
+                            HStack::new(cx, |cx| {
+                                Label::new(cx, "RANGE")
+                                    .font_size(theme.text_size(12.0))
+                                    .color(theme.text_secondary)
+                                    .width(Percentage(20.0));
+
+                                ParamSlider::new(cx, Data::params, |params| &params.meter_range)
+                                    .width(Percentage(35.0))
+                                    .height(Pixels(18.0))
+                                    .color(theme.secondary)
+                                    .font_size(theme.text_size(12.0));
+
+                                Label::new(cx, "DECAY")
+                                    .font_size(theme.text_size(12.0))
+                                    .color(theme.text_secondary)
+                                    .width(Percentage(17.0))
+                                    .left(Pixels(6.0));
+
+                                Knob::new(cx, Data::params, |params| &params.meter_decay)
+                                    .width(Percentage(28.0))
+                                    .height(Pixels(18.0))
+                                    .color(theme.secondary)
+                                    .font_size(theme.text_size(12.0));
+                            })
+                            .height(Pixels(18.0))
+                            .width(Percentage(90.0))
+                            .bottom(Pixels(8.0));
+
+                            meter_header(cx, "INPUT LEVEL", Data::input_peak_meter, MeterEvent::ClearInput);
+                            level_meter(cx, Data::input_peak_meter);
+
+                            meter_header(cx, "OUTPUT LEVEL", Data::peak_meter, MeterEvent::ClearOutput);
+                            level_meter(cx, Data::peak_meter);
+
+                            // Short-term and integrated loudness of the input and the output
+                            // Match the integrated readings with the output gain for a fair comparison against bypass
+                            Binding::new(
+                                cx,
+                                Data::input_loudness.map(|loudness| loudness_text("IN", loudness)),
+                                move |cx, text| {
+                                    Label::new(cx, &text.get(cx))
+                                        .font_size(theme.text_size(13.0))
+                                        .color(theme.secondary)
+                                        .bottom(Pixels(2.0));
+                                },
+                            );
+                            Binding::new(
+                                cx,
+                                Data::output_loudness.map(|loudness| loudness_text("OUT", loudness)),
+                                move |cx, text| {
+                                    Label::new(cx, &text.get(cx))
+                                        .font_size(theme.text_size(13.0))
+                                        .color(theme.secondary)
+                                        .bottom(Pixels(6.0));
+                                },
+                            );
+
+                            // Stereo correlation, from -1 (out of phase) on the left to +1 (mono) on the right
+                            // Anything left of the center will lose energy when summed to mono
+                            Label::new(cx, "CORRELATION")
+                                .font_size(theme.text_size(14.0))
+                                .color(theme.secondary)
+                                .bottom(Pixels(4.0));
+
+                            Binding::new(
+                                cx,
+                                Data::correlation.map(|correlation| correlation.load(Ordering::Relaxed)),
+                                move |cx, correlation| {
+                                    let correlation = correlation.get(cx);
+                                    // Out of phase material is shown in the accent color as a warning
+                                    let color = if correlation < 0.0 { theme.accent } else { theme.chaos };
+
+                                    HStack::new(cx, |cx| {
+                                        Element::new(cx)
+                                            .left(Percentage((correlation + 1.0) * 50.0 - 1.5))
+                                            .width(Percentage(3.0))
+                                            .height(Stretch(1.0))
+                                            .background_color(color);
+                                    })
+                                    .height(Pixels(12.0))
+                                    .width(Percentage(90.0))
+                                    .background_color(theme.meter_bg)
+                                    .bottom(Pixels(6.0));
+                                },
+                            );
+
+                            // How much louder the output is than the input, from -24 dB on the left to +24 dB on the right
+                            // Bring this to the center with the output gain to compare the chain against bypass fairly
+                            Binding::new(
+                                cx,
+                                Data::level_difference
+                                    .map(|difference| (difference.load(Ordering::Relaxed) * 10.0).round() / 10.0),
+                                move |cx, difference| {
+                                    let difference = difference.get(cx);
+
+                                    Label::new(cx, &format!("DIFFERENCE {difference:+.1} dB"))
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .bottom(Pixels(4.0));
+
+                                    // The bar grows from the center towards the side of the difference
+                                    let width = (difference.abs() / 24.0).min(1.0) * 50.0;
+                                    let left = if difference < 0.0 { 50.0 - width } else { 50.0 };
+                                    HStack::new(cx, |cx| {
+                                        Element::new(cx)
+                                            .left(Percentage(left))
+                                            .width(Percentage(width))
+                                            .height(Stretch(1.0))
+                                            .background_color(theme.mix);
+                                    })
+                                    .height(Pixels(12.0))
+                                    .width(Percentage(90.0))
+                                    .background_color(theme.meter_bg)
+                                    .bottom(Pixels(6.0));
+                                },
+                            );
+
+                            // Whether the host is running the plugin, so stale readings don't pass for live ones
+                            // A soft bypass keeps everything running, a hard bypass or a suspended plugin doesn't
+                            Binding::new(cx, Data::activity.map(|activity| activity.is_active()), move |cx, active| {
+                                let active = active.get(cx);
+                                Binding::new(cx, Data::params.map(|params| params.bypass.value()), move |cx, bypassed| {
+                                    let (text, color) = match (active, bypassed.get(cx)) {
+                                        (false, _) => ("IDLE", theme.meter_bg),
+                                        (true, true) => ("BYPASSED", theme.accent),
+                                        (true, false) => ("PROCESSING", theme.chaos),
+                                    };
+                                    HStack::new(cx, |cx| {
+                                        Element::new(cx)
+                                            .width(Pixels(8.0))
+                                            .height(Pixels(8.0))
+                                            .top(Pixels(4.0))
+                                            .border_radius(Pixels(4.0))
+                                            .background_color(color);
+                                        Label::new(cx, text)
+                                            .font_size(theme.text_size(12.0))
+                                            .color(theme.text_secondary)
+                                            .left(Pixels(6.0));
+                                    })
+                                    .height(Pixels(16.0))
+                                    .bottom(Pixels(6.0));
+                                });
+                            });
+
+                            // How much of the realtime budget the plugin uses, at 100% the audio drops out
+                            // Heavy chaos settings with 8x oversampling are the usual suspects
+                            Binding::new(
+                                cx,
+                                Data::cpu_load.map(|load| (load.load(Ordering::Relaxed) * 1000.0).round() / 10.0),
+                                move |cx, load| {
+                                    let load = load.get(cx);
+                                    // Close to the limit is shown in the accent color as a warning
+                                    let color = if load > 80.0 { theme.accent } else { theme.chaos };
+
+                                    Label::new(cx, &format!("CPU {load:.1}%"))
+                                        .font_size(theme.text_size(14.0))
+                                        .color(theme.secondary)
+                                        .bottom(Pixels(4.0));
+
+                                    HStack::new(cx, |cx| {
+                                        Element::new(cx)
+                                            .width(Percentage(load.min(100.0)))
+                                            .height(Stretch(1.0))
+                                            .background_color(color);
+                                    })
+                                    .height(Pixels(12.0))
+                                    .width(Percentage(90.0))
+                                    .background_color(theme.meter_bg);
+                                },
+                            );
+                        })
+                        .height(Pixels(342.0))
+                        .child_left(Pixels(15.0))
+                        .child_right(Pixels(15.0))
+                        .width(Percentage(95.0))
+                        .background_color(theme.panel_bg)
+                        .border_color(theme.border)
+                        .border_width(Pixels(1.0))
+                        .border_radius(Pixels(4.0));
+                    })
+                    .on_scroll(|cx, _, y| cx.emit(LayoutEvent::Scroll(y)))
+                    .width(Percentage(100.0))
+                    .height(Pixels(TAB_VIEW_HEIGHT))
+                    .bottom(Pixels(8.0));
+                });
+            
+                // Footer with version info
                 HStack::new(cx, |cx| {
-                    Label::new(cx, "CHAOS")
-                        .font_size(14.0)
-                        .color(CHAOS_COLOR)
-                        .width(Percentage(15.0))
-                        .child_space(Stretch(1.0));
-                        
-                    ParamSlider::new(cx, Data::params, |params| &params.chaos)
-                        .width(Percentage(75.0))
-                        .height(Pixels(20.0))
-                        .top(Pixels(5.0))
-                        .color(CHAOS_COLOR)
-                        .font_size(13.0);
+                    Label::new(cx, &format!("v{VERSION}"))
+                        .color(theme.text_secondary)
+                        .font_size(theme.text_size(11.0));
+                    
+                    Label::new(cx, "Mark Gemesi - weblabstudio.hu © 2025")
+                        .left(Pixels(10.0))
+                        .color(theme.text_secondary)
+                        .font_size(theme.text_size(11.0));
+
+                    // MIDI learn feedback, right-click a slider to learn a controller
+                    Label::new(cx, Data::midi_status)
+                        .left(Stretch(1.0))
+                        .color(theme.secondary)
+                        .font_size(theme.text_size(11.0));
+
+                    // Check for new CCs whenever the audio thread received one
+                    Binding::new(
+                        cx,
+                        Data::params.map(|params| params.midi_learn.generation()),
+                        |cx, _| cx.emit(MidiLearnEvent::Sync),
+                    );
                 })
-                .height(Pixels(30.0))
+                .height(Pixels(24.0))
                 .child_left(Pixels(15.0))
                 .child_right(Pixels(15.0))
-                .width(Percentage(95.0))
-                .background_color(PANEL_BG)
-                .border_color(BORDER_COLOR)
-                .border_width(Pixels(1.0))
-                .border_radius(Pixels(4.0))
-                .bottom(Pixels(8.0));
+                .top(Pixels(8.0))
+                .width(Percentage(100.0));
 
-                // OUTPUT METER with improved styling
-                VStack::new(cx, |cx| {
-                    Label::new(cx, "OUTPUT LEVEL")
-                        .font_size(14.0)
-                        .color(SECONDARY_COLOR)
-                        .bottom(Pixels(4.0));
-                    
-                    // Improved peak meter
-                    PeakMeter::new(
+                // Theme toggle and accent color picker, both saved with the plugin state
+                HStack::new(cx, |cx| {
+                    Label::new(cx, "THEME")
+                        .color(theme.text_secondary)
+                        .font_size(theme.text_size(11.0))
+                        .top(Pixels(4.0));
+
+                    // Shows the current theme, click to switch to the other one
+                    Button::new(
                         cx,
-                        Data::peak_meter
-                            .map(|peak_meter| util::gain_to_db(peak_meter.load(Ordering::Relaxed))),
-                        Some(Duration::from_millis(600))
+                        |cx| cx.emit(ThemeEvent::ToggleMode),
+                        move |cx| {
+                            Label::new(
+                                cx,
+                                Data::params.map(|params| {
+                                    params
+                                        .editor_theme
+                                        .read()
+                                        .map(|settings| settings.mode)
+                                        .unwrap_or_default()
+                                        .label()
+                                        .to_string()
+                                }),
+                            )
+                            .font_size(theme.text_size(11.0))
+                            .color(theme.accent)
+                        },
                     )
-                    .height(Pixels(12.0))
-                    .width(Percentage(90.0))
-                    .background_color(METER_BG_COLOR)
-                    .color(SECONDARY_COLOR);
+                    .height(Pixels(20.0))
+                    .left(Pixels(8.0));
+
+                    // One swatch for every accent color, the current one is outlined
+                    for accent in ACCENT_COLORS {
+                        let [r, g, b] = accent;
+                        let color = Color::rgb(r, g, b);
+                        Element::new(cx)
+                            .width(Pixels(12.0))
+                            .height(Pixels(12.0))
+                            .top(Pixels(4.0))
+                            .left(Pixels(8.0))
+                            .border_radius(Pixels(6.0))
+                            .background_color(color)
+                            .border_color(theme.text)
+                            .border_width(Pixels(if theme.accent == color { 1.5 } else { 0.0 }))
+                            .on_mouse_down(move |cx, _| cx.emit(ThemeEvent::SetAccent(accent)));
+                    }
+
+                    // Opens the preferences below
+                    Button::new(
+                        cx,
+                        |cx| cx.emit(PrefsEvent::Toggle),
+                        move |cx| GearIcon::new(cx, theme.text_secondary).width(Pixels(14.0)).height(Pixels(14.0)),
+                    )
+                    .height(Pixels(20.0))
+                    .left(Stretch(1.0));
                 })
-                .height(Pixels(48.0))
+                .height(Pixels(24.0))
                 .child_left(Pixels(15.0))
                 .child_right(Pixels(15.0))
-                .width(Percentage(95.0))
-                .background_color(PANEL_BG)
-                .border_color(BORDER_COLOR)
-                .border_width(Pixels(1.0))
-                .border_radius(Pixels(4.0));
-            })
-            .child_top(Pixels(0.0))
-            .width(Percentage(100.0))
-            .height(Pixels(210.0)); // Increased height for the new chaos slider
-            
-            // Footer with version info
-            HStack::new(cx, |cx| {
-                Label::new(cx, &format!("v{VERSION}"))
-                    .color(TEXT_SECONDARY)
-                    .font_size(11.0);
-                    
-                Label::new(cx, "Mark Gemesi - weblabstudio.hu © 2025")
-                    .left(Pixels(10.0))
-                    .color(TEXT_SECONDARY)
-                    .font_size(11.0);
+                .width(Percentage(100.0));
+
+                // Preferences - the UI scale and the text size, both saved with the plugin state
+                // The editor is sized to show them, so opening them doesn't push anything out of view
+                Binding::new(cx, Data::prefs_open, move |cx, prefs_open| {
+                    if !prefs_open.get(cx) {
+                        return;
+                    }
+
+                    HStack::new(cx, |cx| {
+                        Label::new(cx, "SCALE")
+                            .color(theme.text_secondary)
+                            .font_size(theme.text_size(11.0))
+                            .width(Pixels(40.0))
+                            .top(Pixels(4.0));
+
+                        // The current scale is lit, it's the same one the resize handle changes
+                        for scale in UI_SCALES {
+                            let current = Data::params.map(move |params| {
+                                (params.editor_state.user_scale_factor() - scale).abs() < 0.01
+                            });
+                            Binding::new(cx, current, move |cx, current| {
+                                let color = if current.get(cx) { theme.accent } else { theme.text_secondary };
+                                Button::new(
+                                    cx,
+                                    move |cx| cx.emit(PrefsEvent::SetScale(scale)),
+                                    move |cx| {
+                                        Label::new(cx, &format!("{:.0}%", scale * 100.0))
+                                            .font_size(theme.text_size(11.0))
+                                            .color(color)
+                                    },
+                                )
+                                .height(Pixels(20.0))
+                                .left(Pixels(6.0));
+                            });
+                        }
+                    })
+                    .height(Pixels(24.0))
+                    .child_left(Pixels(15.0))
+                    .child_right(Pixels(15.0))
+                    .width(Percentage(100.0));
+
+                    HStack::new(cx, |cx| {
+                        Label::new(cx, "TEXT")
+                            .color(theme.text_secondary)
+                            .font_size(theme.text_size(11.0))
+                            .width(Pixels(40.0))
+                            .top(Pixels(4.0));
+
+                        // Bigger text on top of the scale, the whole editor is built again with it
+                        for text_size in TextSize::ALL {
+                            let color = if theme.text_scale == text_size.scale() { theme.accent } else { theme.text_secondary };
+                            Button::new(
+                                cx,
+                                move |cx| cx.emit(PrefsEvent::SetTextSize(text_size)),
+                                move |cx| Label::new(cx, text_size.label()).font_size(theme.text_size(11.0)).color(color),
+                            )
+                            .height(Pixels(20.0))
+                            .left(Pixels(6.0));
+                        }
+                    })
+                    .height(Pixels(24.0))
+                    .child_left(Pixels(15.0))
+                    .child_right(Pixels(15.0))
+                    .width(Percentage(100.0));
+                });
             })
-            .height(Pixels(24.0))
-            .child_left(Pixels(15.0))
-            .child_right(Pixels(15.0))
-            .top(Pixels(8.0))
-            .width(Percentage(100.0));
-        })
-        .background_color(BACKGROUND_COLOR)
-        .child_top(Pixels(10.0))
-        .child_bottom(Pixels(10.0))
-        .child_left(Pixels(10.0))
-        .child_right(Pixels(10.0));
+            .background_color(theme.background)
+            .child_top(Pixels(10.0))
+            .child_bottom(Pixels(10.0))
+            .child_left(Pixels(10.0))
+            .child_right(Pixels(10.0));
+
+            // On top of everything but the resize handle
+            value_entry_popup(cx);
+        });
+
+        // Drag the bottom right corner to scale the whole editor up or down
+        // This changes vizia's user scale factor, so every size in the layout and the custom
+        // views (which multiply their line widths by `scale_factor()`) scales along with it.
+        // The scale is part of the editor state, so it's saved with the plugin state.
+        // It's created last so it sits on top of everything else.
+        ResizeHandle::new(cx);
     })
 }
\ No newline at end of file
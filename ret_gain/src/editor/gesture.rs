@@ -0,0 +1,74 @@
+use nih_plug::prelude::ParamPtr;
+use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::widgets::RawParamEvent;
+
+/// A gesture on one or more parameters, started and ended by a custom widget
+// Hosts only record automation while a parameter is between a begin and an end, and the
+// undo history turns everything in between into a single step. This keeps track of
+// whether the gesture is running, so a widget can't begin it twice or end it without
+// beginning it first, which some hosts get confused by.
+pub struct ParamGesture<const N: usize> {
+    /// The parameters the gesture changes
+    params: [ParamPtr; N],
+
+    /// Whether the gesture has begun and not ended yet
+    active: bool,
+}
+
+impl<const N: usize> ParamGesture<N> {
+    /// A gesture on these parameters, nothing is sent to the host until it begins
+    pub fn new(params: [ParamPtr; N]) -> Self {
+        Self { params, active: false }
+    }
+
+    /// Whether the gesture has begun and not ended yet
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Tell the host that the widget starts changing its parameters
+    pub fn begin(&mut self, cx: &mut EventContext) {
+        if self.active {
+            return;
+        }
+
+        self.active = true;
+        for &param_ptr in &self.params {
+            cx.emit(RawParamEvent::BeginSetParameter(param_ptr));
+        }
+    }
+
+    /// Set one of the parameters, by its index in the array passed to `new()`
+    // Outside of a gesture the host might not record the change, so this begins one if needed
+    pub fn set(&mut self, cx: &mut EventContext, index: usize, normalized_value: f32) {
+        self.begin(cx);
+        cx.emit(RawParamEvent::SetParameterNormalized(self.params[index], normalized_value));
+    }
+
+    /// Tell the host that the widget is done changing its parameters
+    pub fn end(&mut self, cx: &mut EventContext) {
+        if !self.active {
+            return;
+        }
+
+        self.active = false;
+        for &param_ptr in &self.params {
+            cx.emit(RawParamEvent::EndSetParameter(param_ptr));
+        }
+    }
+}
+
+/// Set any number of parameters in one gesture, like when a preset is loaded
+// Every parameter begins before the first one changes, so the host and the undo history
+// see the whole change as one step
+pub fn set_all(cx: &mut EventContext, values: &[(ParamPtr, f32)]) {
+    for &(param_ptr, _) in values {
+        cx.emit(RawParamEvent::BeginSetParameter(param_ptr));
+    }
+    for &(param_ptr, normalized_value) in values {
+        cx.emit(RawParamEvent::SetParameterNormalized(param_ptr, normalized_value));
+    }
+    for &(param_ptr, _) in values {
+        cx.emit(RawParamEvent::EndSetParameter(param_ptr));
+    }
+}
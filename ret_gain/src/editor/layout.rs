@@ -0,0 +1,57 @@
+// The layout is saved with the plugin state
+use serde::{Deserialize, Serialize};
+
+/// The tabs the editor's controls are split over
+// Only the current tab is built, so the window stays the same size however many controls
+// the chain grows. The meters and the footer are shown below every tab.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EditorTab {
+    /// The gain staging, the effects themselves and the displays
+    #[default]
+    Main,
+    /// Everything that moves the effects around: morph, macros, LFOs, envelopes and the generator
+    Modulation,
+    /// Routing, multiband, the engine's inner settings and the safety stages
+    Advanced,
+}
+
+impl EditorTab {
+    /// Every tab, in the order the tab bar shows them
+    pub const ALL: [EditorTab; 3] = [EditorTab::Main, EditorTab::Modulation, EditorTab::Advanced];
+
+    /// Where the tab is in `ALL`
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The name shown in the tab bar
+    pub fn label(self) -> &'static str {
+        match self {
+            EditorTab::Main => "MAIN",
+            EditorTab::Modulation => "MODULATION",
+            EditorTab::Advanced => "ADVANCED",
+        }
+    }
+}
+
+/// The editor's tabs as they're saved with the plugin state
+// States from before the tabs don't have it and open on the main tab, scrolled to the top
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LayoutSettings {
+    /// The tab that was open last
+    pub tab: EditorTab,
+    /// How far every tab was scrolled down, from 0.0 at the top to 1.0 at the bottom
+    scroll: [f32; EditorTab::ALL.len()],
+}
+
+impl LayoutSettings {
+    /// How far a tab was scrolled down when it was last open
+    pub fn scroll(&self, tab: EditorTab) -> f32 {
+        self.scroll[tab.index()]
+    }
+
+    /// Remember how far a tab is scrolled down
+    pub fn set_scroll(&mut self, tab: EditorTab, scroll: f32) {
+        self.scroll[tab.index()] = scroll.clamp(0.0, 1.0);
+    }
+}
@@ -0,0 +1,188 @@
+use nih_plug_vizia::vizia::prelude::*;
+// The theme settings are saved with the plugin state
+use serde::{Deserialize, Serialize};
+
+/// The accent colors the footer offers, the first one is the dark theme's own pink
+pub const ACCENT_COLORS: [[u8; 3]; 6] = [
+    [0xFF, 0x1A, 0x8C], // Pink
+    [0xFF, 0x6B, 0x1A], // Orange
+    [0xF1, 0xC4, 0x0F], // Yellow
+    [0x2E, 0xCC, 0x71], // Green
+    [0x1A, 0x8C, 0xFF], // Blue
+    [0xA5, 0x69, 0xFF], // Violet
+];
+
+/// Whether the editor is drawn light on dark or dark on light
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ThemeMode {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl ThemeMode {
+    /// The other mode, for the toggle in the footer
+    pub fn toggled(self) -> Self {
+        match self {
+            ThemeMode::Dark => ThemeMode::Light,
+            ThemeMode::Light => ThemeMode::Dark,
+        }
+    }
+
+    /// The name shown on the toggle
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeMode::Dark => "DARK",
+            ThemeMode::Light => "LIGHT",
+        }
+    }
+}
+
+/// How big the editor's text is drawn, on top of the UI scale
+// The labels were laid out at 10 to 14 pixels, which is hard to read on large screens even
+// with the whole editor scaled up, so the text can grow on its own
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextSize {
+    #[default]
+    Normal,
+    Large,
+    Larger,
+}
+
+impl TextSize {
+    /// Every text size, in the order the preferences show them
+    pub const ALL: [TextSize; 3] = [TextSize::Normal, TextSize::Large, TextSize::Larger];
+
+    /// How much bigger the text is drawn than the layout's own sizes
+    pub fn scale(self) -> f32 {
+        match self {
+            TextSize::Normal => 1.0,
+            TextSize::Large => 1.15,
+            TextSize::Larger => 1.3,
+        }
+    }
+
+    /// The name shown in the preferences
+    pub fn label(self) -> &'static str {
+        match self {
+            TextSize::Normal => "100%",
+            TextSize::Large => "115%",
+            TextSize::Larger => "130%",
+        }
+    }
+}
+
+/// The theme as it's saved with the plugin state
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ThemeSettings {
+    /// Light or dark
+    pub mode: ThemeMode,
+    /// The accent color picked in the footer, `None` keeps the mode's own accent
+    pub accent: Option<[u8; 3]>,
+    /// The text size picked in the preferences
+    // States from before the setting don't have it and get the normal size
+    #[serde(default)]
+    pub text_size: TextSize,
+}
+
+/// The colors the editor is drawn with
+// Every widget takes its colors from here instead of from constants, so the whole editor
+// can switch themes. The editor is rebuilt when the theme changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// Behind everything
+    pub background: Color,
+    /// Labels and values
+    pub text: Color,
+    /// Headings and the main highlights
+    pub accent: Color,
+    /// Contrast to the accent, used for the meters and the stereo controls
+    pub secondary: Color,
+    /// The magic controls
+    pub magic: Color,
+    /// The chaos controls
+    pub chaos: Color,
+    /// The dry/wet mix and the randomizer
+    pub mix: Color,
+    /// The compressor
+    pub comp: Color,
+    /// The delay
+    pub delay: Color,
+    /// The reverb
+    pub reverb: Color,
+    /// Slight contrast for controls and dropdown lists
+    pub knob_bg: Color,
+    /// The background of every row
+    pub panel_bg: Color,
+    /// Very subtle borders around the rows
+    pub border: Color,
+    /// Behind the meters and displays
+    pub meter_bg: Color,
+    /// Less important text
+    pub text_secondary: Color,
+    /// How much bigger the text is drawn, from the text size preference
+    pub text_scale: f32,
+}
+
+impl Theme {
+    /// The original look, light text on a dark background with a slight blue tint
+    pub const DARK: Theme = Theme {
+        background: Color::rgb(0x18, 0x18, 0x1E),
+        text: Color::rgb(0xE8, 0xE9, 0xF3),
+        accent: Color::rgb(0xFF, 0x1A, 0x8C),
+        secondary: Color::rgb(0x0A, 0xD8, 0xE9),
+        magic: Color::rgb(0x9B, 0x59, 0xB6),
+        chaos: Color::rgb(0x1A, 0xBC, 0x9C),
+        mix: Color::rgb(0xF3, 0x9C, 0x12),
+        comp: Color::rgb(0xE7, 0x4C, 0x3C),
+        delay: Color::rgb(0x34, 0x98, 0xDB),
+        reverb: Color::rgb(0x85, 0xC1, 0xE9),
+        knob_bg: Color::rgb(0x22, 0x22, 0x2A),
+        panel_bg: Color::rgb(0x20, 0x20, 0x28),
+        border: Color::rgba(0xFF, 0x1A, 0x8C, 0x30),
+        meter_bg: Color::rgba(0x0A, 0x0A, 0x10, 0x80),
+        text_secondary: Color::rgba(0xE8, 0xE9, 0xF3, 0x70),
+        text_scale: 1.0,
+    };
+
+    /// Dark text on a pale background, with deeper colors so they still stand out
+    pub const LIGHT: Theme = Theme {
+        background: Color::rgb(0xEC, 0xEC, 0xF2),
+        text: Color::rgb(0x1E, 0x1E, 0x26),
+        accent: Color::rgb(0xD1, 0x00, 0x6B),
+        secondary: Color::rgb(0x00, 0x86, 0x9C),
+        magic: Color::rgb(0x7D, 0x3C, 0x98),
+        chaos: Color::rgb(0x11, 0x87, 0x70),
+        mix: Color::rgb(0xC2, 0x6F, 0x00),
+        comp: Color::rgb(0xC0, 0x39, 0x2B),
+        delay: Color::rgb(0x21, 0x6F, 0xA8),
+        reverb: Color::rgb(0x4A, 0x8B, 0xBF),
+        knob_bg: Color::rgb(0xDE, 0xDE, 0xE8),
+        panel_bg: Color::rgb(0xF8, 0xF8, 0xFC),
+        border: Color::rgba(0xD1, 0x00, 0x6B, 0x40),
+        meter_bg: Color::rgba(0xC8, 0xC8, 0xD4, 0x80),
+        text_secondary: Color::rgba(0x1E, 0x1E, 0x26, 0x80),
+        text_scale: 1.0,
+    };
+
+    /// The colors for saved theme settings
+    // A picked accent color replaces the accent and tints the borders, like the mode's own accent does
+    pub fn from_settings(settings: &ThemeSettings) -> Self {
+        let (mut theme, border_alpha) = match settings.mode {
+            ThemeMode::Dark => (Self::DARK, 0x30),
+            ThemeMode::Light => (Self::LIGHT, 0x40),
+        };
+        if let Some([r, g, b]) = settings.accent {
+            theme.accent = Color::rgb(r, g, b);
+            theme.border = Color::rgba(r, g, b, border_alpha);
+        }
+        theme.text_scale = settings.text_size.scale();
+
+        theme
+    }
+
+    /// A font size from the layout, grown by the text size preference
+    pub fn text_size(&self, size: f32) -> f32 {
+        size * self.text_scale
+    }
+}
@@ -0,0 +1,6 @@
+// Custom widgets for the editor
+mod knob; // Rotary knob for continuous parameters
+mod tooltip; // Popup with extra details while the cursor is over a widget
+
+pub use knob::{Knob, KnobExt, KnobModulation};
+pub use tooltip::Tooltip;
@@ -0,0 +1,479 @@
+use atomic_float::AtomicF32;
+use nih_plug::prelude::{Param, ParamFlags, ParamPtr};
+use nih_plug_vizia::vizia::prelude::*;
+use nih_plug_vizia::vizia::vg;
+use nih_plug_vizia::widgets::param_base::ParamWidgetBase;
+use std::f32::consts::PI;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use super::Tooltip;
+use crate::editor::gesture::ParamGesture;
+use crate::editor::ValueEntryEvent;
+use crate::modulation::ModulationRings;
+
+/// How far the mouse has to move to sweep the whole range, in logical pixels
+const DRAG_RANGE_PIXELS: f32 = 200.0;
+
+/// How much slower the knob turns while Shift, Ctrl or Cmd is held down
+const FINE_DRAG_FACTOR: f32 = 0.1;
+
+/// Where the knob's arc starts, straight down is 90 degrees
+// The arc sweeps 270 degrees, leaving a gap at the bottom like a hardware knob
+const START_ANGLE: f32 = 0.75 * PI;
+const SWEEP_ANGLE: f32 = 1.5 * PI;
+
+/// How far the value is pushed to the right to make room for the knob, in logical pixels
+// The knob is as wide as the widget is tall, this fits the 20 pixel high controls in the
+// editor's rows with a small gap
+const LABEL_OFFSET: f32 = 24.0;
+
+/// How far the audio thread has to move a parameter before its ring shows up, normalized
+// Keeps the ring from flickering on and off while a knob's own smoothing catches up
+const RING_THRESHOLD: f32 = 0.005;
+
+/// The modulation rings as a model, so every knob can find its own without being told
+// Built once at the root of the editor. Knobs for parameters the audio thread can't move,
+// or in views without this model, simply don't draw a ring.
+pub struct KnobModulation(pub Arc<ModulationRings>);
+
+impl Model for KnobModulation {}
+
+/// Where a drag started, so the knob moves relative to it instead of jumping
+#[derive(Debug, Clone, Copy)]
+struct DragStatus {
+    /// The cursor's y position when the drag started, or when a fine modifier was last pressed or released
+    start_y: f32,
+    /// The normalized value at that point
+    start_value: f32,
+    /// Whether a fine modifier was held down at that point
+    fine: bool,
+}
+
+/// Whether the modifiers ask for fine adjustments
+// Shift like in the sliders, and Ctrl or Cmd like in most other plugins, so whatever
+// people's fingers are used to works
+fn is_fine(modifiers: &Modifiers) -> bool {
+    modifiers.intersects(Modifiers::SHIFT | Modifiers::CTRL | Modifiers::LOGO)
+}
+
+enum KnobEvent {
+    /// Close the text entry without changing the value
+    CancelTextInput,
+    /// Set the parameter from what was typed into the text entry
+    TextInput(String),
+}
+
+/// A rotary knob for a parameter, with its value shown next to it
+// Drag up and down or scroll to turn it, hold Shift, Ctrl or Cmd for fine adjustments,
+// double-click to reset it to its default value, and Alt+click to type in a value. Typing a
+// number right after the double-click opens the editor's value entry popup with it. The arc takes the widget's font
+// color, so `.color()` works the same way it does for `ParamSlider`. While the cursor is
+// over the knob a tooltip shows the exact value, whether the host can automate it, and
+// how far it's currently being modulated. When the audio thread moves the parameter away
+// from the knob's setting, a thin ring inside the arc shows where to.
+#[derive(Lens)]
+pub struct Knob {
+    /// Connects the knob to its parameter
+    param_base: ParamWidgetBase,
+
+    /// The parameter itself, for the value entry popup
+    param_ptr: ParamPtr,
+
+    /// The gesture on the parameter, running while the knob is being dragged
+    gesture: ParamGesture<1>,
+
+    /// Whether the value is currently being typed in
+    text_input_active: bool,
+
+    /// Whether the knob was just double-clicked, typing a number now opens the value entry popup
+    // Until the cursor leaves the knob or anything else is clicked
+    typing_armed: bool,
+
+    /// The current drag, if there is one
+    drag_status: Option<DragStatus>,
+
+    /// Whether the cursor is over the knob, the tooltip is shown while it is
+    hovered: bool,
+
+    /// Scrolled lines that haven't turned the knob yet
+    // Trackpads scroll by fractions of a line, the knob only moves once a whole line adds up
+    scrolled_lines: f32,
+
+    /// The parameter's value after the plugin's own modulation, for the tooltip
+    // Set with `KnobExt::modulation`, the host's modulation is shown either way
+    modulation: Option<Arc<AtomicF32>>,
+
+    /// The shared modulation rings and the index of this knob's ring among them
+    ring: Option<(Arc<ModulationRings>, usize)>,
+}
+
+impl Knob {
+    /// Create a knob for the parameter `params_to_param` picks out of `params`
+    pub fn new<L, Params, P, FMap>(cx: &mut Context, params: L, params_to_param: FMap) -> Handle<Self>
+    where
+        L: Lens<Target = Params> + Clone,
+        Params: 'static,
+        P: Param<Plain = f32> + 'static,
+        FMap: Fn(&Params) -> &P + Copy + 'static,
+    {
+        let param_ptr = params.map(move |params| params_to_param(params).as_ptr()).get(cx);
+        let ring = cx.data::<KnobModulation>().and_then(|KnobModulation(rings)| {
+            rings.index_of(param_ptr).map(|index| (rings.clone(), index))
+        });
+        Self {
+            param_base: ParamWidgetBase::new(cx, params.clone(), params_to_param),
+            param_ptr,
+            gesture: ParamGesture::new([param_ptr]),
+            text_input_active: false,
+            typing_armed: false,
+            drag_status: None,
+            hovered: false,
+            scrolled_lines: 0.0,
+            modulation: None,
+            ring,
+        }
+        .build(
+            cx,
+            ParamWidgetBase::build_view(params.clone(), params_to_param, move |cx, param_data| {
+                let display_value_lens = param_data.make_lens(|param| {
+                    param.normalized_value_to_string(param.unmodulated_normalized_value(), true)
+                });
+
+                // The value sits to the right of the knob, and turns into a text box for typing
+                Binding::new(cx, Knob::text_input_active, move |cx, text_input_active| {
+                    if text_input_active.get(cx) {
+                        Textbox::new(cx, display_value_lens)
+                            .on_submit(|cx, string, success| {
+                                if success {
+                                    cx.emit(KnobEvent::TextInput(string))
+                                } else {
+                                    cx.emit(KnobEvent::CancelTextInput)
+                                }
+                            })
+                            .on_build(|cx| {
+                                cx.emit(TextEvent::StartEdit);
+                                cx.emit(TextEvent::SelectAll);
+                            })
+                            .width(Stretch(1.0))
+                            .height(Stretch(1.0))
+                            .child_left(Pixels(2.0))
+                            .child_top(Stretch(1.0))
+                            .child_bottom(Stretch(1.0));
+                    } else {
+                        Label::new(cx, display_value_lens)
+                            .width(Stretch(1.0))
+                            .height(Stretch(1.0))
+                            .child_top(Stretch(1.0))
+                            .child_bottom(Stretch(1.0))
+                            .hoverable(false);
+                    }
+                });
+
+                // The lines of the tooltip, all of them follow the parameter while it's shown
+                let name_lens = param_data.make_lens(|param| param.name().to_owned());
+                let exact_value_lens = param_data.make_lens(exact_value_text);
+                let automation_lens = param_data.make_lens(automation_text);
+                let host_modulation_lens = param_data.make_lens(host_modulation_text);
+                Binding::new(cx, Knob::hovered, move |cx, hovered| {
+                    if !hovered.get(cx) {
+                        return;
+                    }
+
+                    Tooltip::new(cx, |cx| {
+                        Label::new(cx, name_lens).font_size(13.0).hoverable(false);
+                        Label::new(cx, exact_value_lens).font_size(12.0).hoverable(false);
+                        Label::new(cx, automation_lens).font_size(11.0).hoverable(false);
+                        Label::new(cx, host_modulation_lens).font_size(11.0).hoverable(false);
+                        // The plugin's own modulation, only for knobs that have it
+                        let modulation_lens = Knob::modulation.map(|modulation| {
+                            modulation
+                                .as_ref()
+                                .map(|modulated| format!("Modulated to {:.1}%", modulated.load(Ordering::Relaxed) * 100.0))
+                                .unwrap_or_default()
+                        });
+                        Binding::new(cx, modulation_lens, |cx, text| {
+                            let text = text.get(cx);
+                            if !text.is_empty() {
+                                Label::new(cx, &text).font_size(11.0).hoverable(false);
+                            }
+                        });
+                    });
+                });
+            }),
+        )
+        // The arc is drawn by the knob itself, so it needs a redraw whenever the value changes,
+        // including through automation
+        .bind(
+            params.map(move |params| params_to_param(params).unmodulated_normalized_value()),
+            |mut handle, _| handle.needs_redraw(),
+        )
+        // And whenever the audio thread moves the parameter somewhere else
+        .bind(Knob::ring.map(|ring| ring.as_ref().map(|(rings, index)| rings.value(*index))), |mut handle, _| {
+            handle.needs_redraw()
+        })
+        // Make room for the knob on the left
+        .child_left(Pixels(LABEL_OFFSET))
+    }
+
+    /// Set the parameter to a normalized value as part of the current gesture
+    fn set_normalized_value(&self, cx: &mut EventContext, normalized_value: f32) {
+        self.param_base.set_normalized_value(cx, normalized_value.clamp(0.0, 1.0));
+    }
+
+    /// Set the parameter to a normalized value in a gesture of its own
+    fn set_normalized_value_once(&mut self, cx: &mut EventContext, normalized_value: f32) {
+        self.gesture.begin(cx);
+        self.set_normalized_value(cx, normalized_value);
+        self.gesture.end(cx);
+    }
+}
+
+/// Extra settings for a `Knob`
+pub trait KnobExt {
+    /// Show the parameter's value after the plugin's own modulation in the tooltip
+    // `modulated` holds the plain value the audio thread actually uses. The tooltip shows it
+    // as a percentage, so this only makes sense for parameters that go from 0 to 1.
+    fn modulation(self, modulated: Arc<AtomicF32>) -> Self;
+}
+
+impl KnobExt for Handle<'_, Knob> {
+    fn modulation(self, modulated: Arc<AtomicF32>) -> Self {
+        self.modify(|knob| knob.modulation = Some(modulated))
+    }
+}
+
+/// The tooltip line with the parameter's value, both formatted and at full precision
+fn exact_value_text<P: Param<Plain = f32>>(param: &P) -> String {
+    format!(
+        "{} ({})",
+        param.normalized_value_to_string(param.unmodulated_normalized_value(), true),
+        param.unmodulated_plain_value()
+    )
+}
+
+/// The tooltip line saying whether the host can automate the parameter
+fn automation_text<P: Param>(param: &P) -> String {
+    if param.flags().contains(ParamFlags::NON_AUTOMATABLE) {
+        String::from("Not automatable")
+    } else {
+        String::from("Automatable")
+    }
+}
+
+/// The tooltip line with the host's modulation offset, as a share of the full range
+// Only CLAP hosts modulate parameters without touching their automation, everywhere else
+// this stays at no modulation
+fn host_modulation_text<P: Param>(param: &P) -> String {
+    let offset = param.modulated_normalized_value() - param.unmodulated_normalized_value();
+    if offset.abs() < 1e-4 {
+        String::from("No host modulation")
+    } else {
+        format!("Host modulation {:+.1}%", offset * 100.0)
+    }
+}
+
+impl View for Knob {
+    fn element(&self) -> Option<&'static str> {
+        Some("knob")
+    }
+
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|knob_event, meta| match knob_event {
+            KnobEvent::CancelTextInput => {
+                self.text_input_active = false;
+                cx.set_active(false);
+                meta.consume();
+            }
+            KnobEvent::TextInput(string) => {
+                if let Some(normalized_value) = self.param_base.string_to_normalized_value(string) {
+                    self.set_normalized_value_once(cx, normalized_value);
+                }
+                self.text_input_active = false;
+                meta.consume();
+            }
+        });
+
+        event.map(|window_event, meta| match *window_event {
+            // The value label and the tooltip don't take the hover, so these only fire for the
+            // knob itself
+            WindowEvent::MouseEnter if meta.target == cx.current() => {
+                self.hovered = true;
+            }
+            WindowEvent::MouseLeave if meta.target == cx.current() => {
+                self.hovered = false;
+                self.typing_armed = false;
+            }
+            WindowEvent::MouseDown(MouseButton::Left) => {
+                self.typing_armed = false;
+                if self.text_input_active {
+                    return;
+                }
+
+                if cx.modifiers().contains(Modifiers::ALT) {
+                    // Typing in a value
+                    self.text_input_active = true;
+                    cx.set_active(true);
+                } else {
+                    // Dragging the knob, the whole drag is one gesture
+                    cx.capture();
+                    cx.set_active(true);
+                    self.gesture.begin(cx);
+                    self.drag_status = Some(DragStatus {
+                        start_y: cx.mouse().cursory,
+                        start_value: self.param_base.unmodulated_normalized_value(),
+                        fine: is_fine(cx.modifiers()),
+                    });
+                }
+                meta.consume();
+            }
+            WindowEvent::MouseDoubleClick(MouseButton::Left) => {
+                // The first click of the double-click started a drag, which is ended here
+                if self.drag_status.take().is_some() {
+                    cx.release();
+                    cx.set_active(false);
+                    self.gesture.end(cx);
+                }
+                self.set_normalized_value_once(cx, self.param_base.default_normalized_value());
+
+                // The keys come here now, in case a value gets typed in
+                cx.focus();
+                self.typing_armed = true;
+                meta.consume();
+            }
+            WindowEvent::CharInput(c) => {
+                if !self.typing_armed || self.text_input_active {
+                    return;
+                }
+
+                // Only what can start a number, so a shortcut doesn't open the popup
+                if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') {
+                    self.typing_armed = false;
+                    cx.emit(ValueEntryEvent::Open(self.param_ptr, c.to_string()));
+                    meta.consume();
+                }
+            }
+            WindowEvent::MouseUp(MouseButton::Left) => {
+                if self.drag_status.take().is_some() {
+                    cx.release();
+                    cx.set_active(false);
+                    self.gesture.end(cx);
+                    meta.consume();
+                }
+            }
+            WindowEvent::MouseMove(_, y) => {
+                let Some(mut status) = self.drag_status else {
+                    return;
+                };
+
+                // Pressing or releasing a fine modifier mid-drag continues from the current
+                // value, so the knob doesn't jump when the speed changes
+                let fine = is_fine(cx.modifiers());
+                if fine != status.fine {
+                    status = DragStatus {
+                        start_y: y,
+                        start_value: self.param_base.unmodulated_normalized_value(),
+                        fine,
+                    };
+                    self.drag_status = Some(status);
+                }
+
+                // Dragging up turns the knob up
+                let speed = if fine { FINE_DRAG_FACTOR } else { 1.0 };
+                let delta = (status.start_y - y) / (DRAG_RANGE_PIXELS * cx.scale_factor()) * speed;
+                self.set_normalized_value(cx, status.start_value + delta);
+            }
+            WindowEvent::MouseScroll(_, scroll_y) => {
+                if self.text_input_active || self.drag_status.is_some() {
+                    return;
+                }
+
+                // Every line is one of the parameter's own steps, so stepped parameters land
+                // on their steps and the fine modifiers take smaller ones
+                self.scrolled_lines += scroll_y;
+                let fine = is_fine(cx.modifiers());
+                let mut normalized_value = self.param_base.unmodulated_normalized_value();
+                while self.scrolled_lines >= 1.0 {
+                    normalized_value = self.param_base.next_normalized_step(normalized_value, fine);
+                    self.scrolled_lines -= 1.0;
+                }
+                while self.scrolled_lines <= -1.0 {
+                    normalized_value = self.param_base.previous_normalized_step(normalized_value, fine);
+                    self.scrolled_lines += 1.0;
+                }
+                if normalized_value != self.param_base.unmodulated_normalized_value() {
+                    self.set_normalized_value_once(cx, normalized_value);
+                }
+                meta.consume();
+            }
+            _ => {}
+        });
+    }
+
+    fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+        let bounds = cx.bounds();
+        if bounds.w == 0.0 || bounds.h == 0.0 {
+            return;
+        }
+        let scale = cx.scale_factor();
+        let color: vg::Color = cx.font_color().into();
+
+        // The knob sits in a square on the left side of the widget
+        let line_width = 2.5 * scale;
+        let radius = bounds.h / 2.0 - line_width;
+        let (center_x, center_y) = (bounds.x + bounds.h / 2.0, bounds.y + bounds.h / 2.0);
+        let value = self.param_base.unmodulated_normalized_value();
+        let value_angle = START_ANGLE + SWEEP_ANGLE * value;
+
+        // The full range as a dim track
+        let mut track = vg::Path::new();
+        track.arc(center_x, center_y, radius, START_ANGLE, START_ANGLE + SWEEP_ANGLE, vg::Solidity::Hole);
+        let mut dim_color = color;
+        dim_color.a = 0.25;
+        canvas.stroke_path(&track, &vg::Paint::color(dim_color).with_line_width(line_width));
+
+        // The current value as a bright arc on top of it
+        if value > 0.0 {
+            let mut arc = vg::Path::new();
+            arc.arc(center_x, center_y, radius, START_ANGLE, value_angle, vg::Solidity::Hole);
+            canvas.stroke_path(&arc, &vg::Paint::color(color).with_line_width(line_width));
+        }
+
+        // A pointer from the center to the current value
+        let mut pointer = vg::Path::new();
+        pointer.move_to(center_x, center_y);
+        pointer.line_to(
+            center_x + value_angle.cos() * radius,
+            center_y + value_angle.sin() * radius,
+        );
+        canvas.stroke_path(&pointer, &vg::Paint::color(color).with_line_width(1.5 * scale));
+
+        // Where the audio thread moved the value to, as a thin ring inside the arc from the
+        // knob's own setting to the modulated value, with a dot at the modulated end
+        let modulated = self.ring.as_ref().map(|(rings, index)| rings.value(*index));
+        if let Some(modulated) = modulated.filter(|modulated| (modulated - value).abs() > RING_THRESHOLD) {
+            let modulated_angle = START_ANGLE + SWEEP_ANGLE * modulated.clamp(0.0, 1.0);
+            let ring_radius = radius - 3.0 * scale;
+            let mut ring = vg::Path::new();
+            ring.arc(
+                center_x,
+                center_y,
+                ring_radius,
+                value_angle.min(modulated_angle),
+                value_angle.max(modulated_angle),
+                vg::Solidity::Hole,
+            );
+            let mut ring_color = color;
+            ring_color.a = 0.7;
+            canvas.stroke_path(&ring, &vg::Paint::color(ring_color).with_line_width(1.5 * scale));
+
+            let mut dot = vg::Path::new();
+            dot.circle(
+                center_x + modulated_angle.cos() * ring_radius,
+                center_y + modulated_angle.sin() * ring_radius,
+                1.5 * scale,
+            );
+            canvas.fill_path(&dot, &vg::Paint::color(color));
+        }
+    }
+}
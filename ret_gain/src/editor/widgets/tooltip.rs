@@ -0,0 +1,49 @@
+use nih_plug_vizia::vizia::prelude::*;
+
+/// Background of the popup, a bit lighter than the panels so it stands out on top of them
+const TOOLTIP_BG_COLOR: Color = Color::rgb(0x2C, 0x2C, 0x36);
+
+/// Border of the popup
+const TOOLTIP_BORDER_COLOR: Color = Color::rgba(0xE8, 0xE9, 0xF3, 0x40);
+
+/// A small popup floating above the widget it's built in
+// It's taken out of the layout, so it doesn't push the widget's other children around, and
+// drawn on top of the rows around it. The contents are built by the closure, usually a few
+// lines of text. Build it while the widget is hovered and drop it again when the cursor
+// leaves, see `Knob` for an example.
+pub struct Tooltip;
+
+impl Tooltip {
+    /// Create a tooltip with the contents built by `content`
+    pub fn new<F>(cx: &mut Context, content: F) -> Handle<Self>
+    where
+        F: FnOnce(&mut Context),
+    {
+        Self.build(cx, |cx| {
+            VStack::new(cx, content)
+                .width(Auto)
+                .height(Auto)
+                .child_space(Pixels(5.0))
+                .row_between(Pixels(2.0));
+        })
+        // Sits right above the top left corner of the widget
+        .position_type(PositionType::SelfDirected)
+        .left(Pixels(0.0))
+        .bottom(Percentage(100.0))
+        .width(Auto)
+        .height(Auto)
+        .z_index(100)
+        .background_color(TOOLTIP_BG_COLOR)
+        .border_color(TOOLTIP_BORDER_COLOR)
+        .border_width(Pixels(1.0))
+        .border_radius(Pixels(3.0))
+        // Never gets in the way of the cursor, or the widget underneath would lose its hover
+        .hoverable(false)
+    }
+}
+
+impl View for Tooltip {
+    fn element(&self) -> Option<&'static str> {
+        Some("tooltip")
+    }
+}
@@ -0,0 +1,35 @@
+// The snapshots are saved as JSON together with the plugin state
+use serde::{Deserialize, Serialize};
+
+use crate::chain::EffectChain;
+use crate::chaos::AttractorSnapshot;
+use crate::crossover::NUM_BANDS;
+use crate::fractal::FractalSnapshot;
+
+/// Where every chaos system and fractal iteration of the chain is
+// One snapshot per band, the full band one included, so a multiband project resumes with
+// every band where it was. Only taken while the plugin persists its engine state.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct EngineState {
+    chaos: [AttractorSnapshot; NUM_BANDS],
+    fractal: [FractalSnapshot; NUM_BANDS],
+}
+
+impl EngineState {
+    /// Take a snapshot of every chaos and fractal instance
+    pub fn capture(chains: &[EffectChain; NUM_BANDS]) -> Self {
+        Self {
+            chaos: std::array::from_fn(|band| chains[band].chaos_attractor.snapshot()),
+            fractal: std::array::from_fn(|band| chains[band].fractal_magic.snapshot()),
+        }
+    }
+
+    /// Move every chaos and fractal instance back to where the snapshot was taken
+    // The instances check for themselves whether a snapshot still fits their settings
+    pub fn restore(&self, chains: &mut [EffectChain; NUM_BANDS]) {
+        for ((chain, chaos), fractal) in chains.iter_mut().zip(&self.chaos).zip(&self.fractal) {
+            chain.chaos_attractor.restore(chaos);
+            chain.fractal_magic.restore(fractal);
+        }
+    }
+}
@@ -0,0 +1,335 @@
+// Import the NIH-plug prelude for the Enum derive macro
+#[cfg(feature = "plugin")]
+use nih_plug::prelude::*;
+// The transient threshold is worked out in dB
+#[cfg(feature = "plugin")]
+use crate::decibels;
+
+/// An attack/release envelope follower used as a modulation source
+// The follower tracks the level of the input signal, rising with the attack time
+// and falling with the release time. Its output can push the effect amounts up
+// when the input gets louder, so the effects respond to how hard something is played.
+pub struct EnvelopeFollower {
+    /// The current envelope level (0.0 and up, follows the input's peak level)
+    envelope: f32,
+
+    /// Coefficient of the one-pole smoother while the level is rising
+    attack_coeff: f32,
+
+    /// Coefficient of the one-pole smoother while the level is falling
+    release_coeff: f32,
+
+    /// Sample rate for time-based calculations
+    sample_rate: f32,
+}
+
+impl EnvelopeFollower {
+    /// Create a new envelope follower
+    pub fn new() -> Self {
+        Self {
+            envelope: 0.0,
+            attack_coeff: 1.0,
+            release_coeff: 1.0,
+            sample_rate: 44100.0, // Default sample rate, will be updated
+        }
+    }
+
+    /// Set the sample rate for time-based calculations
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Set the attack and release times in milliseconds
+    // These are the times it takes to move about 63% of the way to a new level
+    pub fn set_times(&mut self, attack_ms: f32, release_ms: f32) {
+        self.attack_coeff = smoothing_coeff(attack_ms, self.sample_rate);
+        self.release_coeff = smoothing_coeff(release_ms, self.sample_rate);
+    }
+
+    /// Start from silence again
+    pub fn reset(&mut self) {
+        self.envelope = 0.0;
+    }
+
+    /// Feed the level of the next frame and return the new envelope value
+    // `level` should be the peak of all channels, so the envelope is the same for every channel
+    pub fn process(&mut self, level: f32) -> f32 {
+        let coeff = if level > self.envelope {
+            self.attack_coeff
+        } else {
+            self.release_coeff
+        };
+        self.envelope += (level - self.envelope) * coeff;
+
+        self.envelope
+    }
+}
+
+/// How quickly the fast follower of the transient detector reacts, in milliseconds
+#[cfg(feature = "plugin")]
+const TRANSIENT_FAST_ATTACK_MS: f32 = 0.5;
+#[cfg(feature = "plugin")]
+const TRANSIENT_FAST_RELEASE_MS: f32 = 20.0;
+
+/// How quickly the slow follower of the transient detector reacts, in milliseconds
+// It lags behind the fast one on every hit, that lag is what the detector looks for
+#[cfg(feature = "plugin")]
+const TRANSIENT_SLOW_ATTACK_MS: f32 = 30.0;
+#[cfg(feature = "plugin")]
+const TRANSIENT_SLOW_RELEASE_MS: f32 = 300.0;
+
+/// How long the transient detector waits after a hit before it can fire again, in milliseconds
+// Keeps a single drum hit with a ragged attack from firing a few times in a row
+#[cfg(feature = "plugin")]
+const TRANSIENT_HOLDOFF_MS: f32 = 50.0;
+
+/// How quickly a burst rises after a hit, in milliseconds
+// Short enough to land right on the hit, long enough not to click
+#[cfg(feature = "plugin")]
+const BURST_ATTACK_MS: f32 = 1.0;
+
+/// Below this level nothing counts as a hit, so the noise floor can't fire the detector
+#[cfg(feature = "plugin")]
+const TRANSIENT_FLOOR: f32 = 0.001; // -60 dB
+
+/// Fires on every hit in the input and gives out a burst that fades away after it
+// Two envelope followers track the input, a fast one that jumps up with every hit and a
+// slow one that lags behind it. A hit is detected when the fast follower gets far enough
+// above the slow one, the sensitivity sets how far that is. Every hit starts a burst that
+// rises to 1.0 within a millisecond and then decays with the decay time, which can push
+// the chaos amount up only on the hits instead of all the time.
+#[cfg(feature = "plugin")]
+pub struct TransientDetector {
+    /// The level of the fast follower
+    fast: f32,
+
+    /// The level of the slow follower
+    slow: f32,
+
+    /// How far the fast follower has to get above the slow one to count as a hit, as a ratio
+    threshold: f32,
+
+    /// The current burst level (0.0 to 1.0)
+    burst: f32,
+
+    /// Whether the burst is still rising towards 1.0 after a hit
+    rising: bool,
+
+    /// How many more samples until the detector can fire again
+    holdoff_counter: usize,
+
+    /// The hold-off time in samples
+    holdoff_samples: usize,
+
+    /// Coefficients of the fast and slow followers, rising and falling
+    fast_attack_coeff: f32,
+    fast_release_coeff: f32,
+    slow_attack_coeff: f32,
+    slow_release_coeff: f32,
+
+    /// Coefficient of the one-pole smoother while the burst rises
+    burst_attack_coeff: f32,
+
+    /// How much of the burst is left after every sample while it decays
+    decay_factor: f32,
+
+    /// Sample rate for time-based calculations
+    sample_rate: f32,
+}
+
+#[cfg(feature = "plugin")]
+impl TransientDetector {
+    /// Create a new transient detector
+    pub fn new() -> Self {
+        let mut detector = Self {
+            fast: 0.0,
+            slow: 0.0,
+            threshold: 2.0,
+            burst: 0.0,
+            rising: false,
+            holdoff_counter: 0,
+            holdoff_samples: 0,
+            fast_attack_coeff: 1.0,
+            fast_release_coeff: 1.0,
+            slow_attack_coeff: 1.0,
+            slow_release_coeff: 1.0,
+            burst_attack_coeff: 1.0,
+            decay_factor: 0.0,
+            sample_rate: 44100.0, // Default sample rate, will be updated
+        };
+        detector.set_sample_rate(detector.sample_rate);
+
+        detector
+    }
+
+    /// Set the sample rate for time-based calculations
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.fast_attack_coeff = smoothing_coeff(TRANSIENT_FAST_ATTACK_MS, sample_rate);
+        self.fast_release_coeff = smoothing_coeff(TRANSIENT_FAST_RELEASE_MS, sample_rate);
+        self.slow_attack_coeff = smoothing_coeff(TRANSIENT_SLOW_ATTACK_MS, sample_rate);
+        self.slow_release_coeff = smoothing_coeff(TRANSIENT_SLOW_RELEASE_MS, sample_rate);
+        self.burst_attack_coeff = smoothing_coeff(BURST_ATTACK_MS, sample_rate);
+        self.holdoff_samples = ((TRANSIENT_HOLDOFF_MS / 1000.0) * sample_rate).round() as usize;
+    }
+
+    /// Set how easily a hit is detected (0.0 to 1.0) and how long a burst takes to fade, in milliseconds
+    // At the lowest sensitivity the fast follower has to jump 12 dB above the slow one, at
+    // the highest 2 dB is enough
+    pub fn set_params(&mut self, sensitivity: f32, decay_ms: f32) {
+        self.threshold = decibels::db_to_gain(12.0 - 10.0 * sensitivity.clamp(0.0, 1.0));
+        // The burst falls to about 37% of its level after the decay time
+        self.decay_factor = (-1.0 / ((decay_ms / 1000.0) * self.sample_rate)).exp();
+    }
+
+    /// Start from silence again
+    pub fn reset(&mut self) {
+        self.fast = 0.0;
+        self.slow = 0.0;
+        self.burst = 0.0;
+        self.rising = false;
+        self.holdoff_counter = 0;
+    }
+
+    /// Feed the level of the next frame and return the burst level (0.0 to 1.0)
+    // `level` should be the peak of all channels, so a hit on either side fires the burst
+    pub fn process(&mut self, level: f32) -> f32 {
+        let fast_coeff = if level > self.fast { self.fast_attack_coeff } else { self.fast_release_coeff };
+        self.fast += (level - self.fast) * fast_coeff;
+        let slow_coeff = if level > self.slow { self.slow_attack_coeff } else { self.slow_release_coeff };
+        self.slow += (level - self.slow) * slow_coeff;
+
+        self.holdoff_counter = self.holdoff_counter.saturating_sub(1);
+        let hit = self.fast > TRANSIENT_FLOOR && self.fast > self.slow * self.threshold;
+        if hit && self.holdoff_counter == 0 {
+            self.rising = true;
+            self.holdoff_counter = self.holdoff_samples;
+        }
+
+        if self.rising {
+            self.burst += (1.0 - self.burst) * self.burst_attack_coeff;
+            if self.burst >= 0.99 {
+                self.rising = false;
+            }
+        } else {
+            self.burst *= self.decay_factor;
+        }
+
+        self.burst
+    }
+}
+
+/// The RMS level the input calibration brings the chaos and fractal influence to, -18 dBFS
+// The level the effects were tuned at, so a calibrated input responds like they always did
+// at a healthy level
+pub const CALIBRATION_REFERENCE: f32 = 0.126;
+
+/// The most the calibration turns the influence up or down, ±24 dB
+#[cfg(feature = "plugin")]
+pub const MAX_CALIBRATION_DB: f32 = 24.0;
+const MAX_CALIBRATION_GAIN: f32 = 15.85;
+
+/// How long the automatic calibration takes to settle on a new input level, in milliseconds
+// Slow on purpose, it follows the level of the track and not of every note, or the effects
+// would respond the same to soft and hard playing
+const CALIBRATION_TIME_MS: f32 = 3000.0;
+
+/// How quickly the calibration notices that the input went quiet, in milliseconds
+// Everything the slow measurement takes in before it notices counts as part of the level,
+// so this is short, a pause moves the gain by less than 0.2 dB. The power of even a 20 Hz
+// sine doesn't dip below the floor at this speed.
+const CALIBRATION_PRESENCE_MS: f32 = 10.0;
+
+/// Below this RMS level the input counts as silence and the calibration holds its gain, -60 dB
+// Otherwise it would turn the noise floor up to the reference level in every pause
+const CALIBRATION_FLOOR: f32 = 0.001;
+
+/// How the level that drives the chaos and fractal effects is calibrated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum Calibration {
+    /// The input drives them at whatever level it comes in. How projects from before the
+    /// calibration sound.
+    #[cfg_attr(feature = "plugin", id = "off")]
+    #[cfg_attr(feature = "plugin", name = "Off")]
+    Off,
+    /// The input's level is measured and brought to the reference level
+    #[cfg_attr(feature = "plugin", id = "auto")]
+    #[cfg_attr(feature = "plugin", name = "Auto")]
+    Auto,
+    /// The trim sets how much the input is turned up or down, for when the automatic
+    /// calibration guesses wrong
+    #[cfg_attr(feature = "plugin", id = "manual")]
+    #[cfg_attr(feature = "plugin", name = "Manual")]
+    Manual,
+}
+
+/// Measures the input level and works out the gain that brings it to `CALIBRATION_REFERENCE`
+// The gain isn't applied to the audio, only to the copy of the input the Lorenz system and
+// the fractal formula listen to. That way a track coming in at -18 dBFS and one coming in at
+// 0 dBFS push the effects around the same amount, while the audio itself keeps its level.
+pub struct InputCalibration {
+    /// The slowly smoothed mean square of the input
+    mean_square: f32,
+
+    /// A faster smoothed mean square, for telling whether there's any input at all
+    presence: f32,
+
+    /// Coefficient of the slow smoother that sets the gain
+    coeff: f32,
+
+    /// Coefficient of the fast smoother that detects silence
+    presence_coeff: f32,
+
+    /// The latest calibration gain
+    gain: f32,
+}
+
+impl InputCalibration {
+    /// Create a calibration that starts out at the reference level, with a gain of 1.0
+    pub fn new() -> Self {
+        let mut calibration = Self {
+            mean_square: CALIBRATION_REFERENCE * CALIBRATION_REFERENCE,
+            presence: 0.0,
+            coeff: 1.0,
+            presence_coeff: 1.0,
+            gain: 1.0,
+        };
+        calibration.set_sample_rate(44100.0); // Default sample rate, will be updated
+
+        calibration
+    }
+
+    /// Set the sample rate for the smoothers
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.coeff = smoothing_coeff(CALIBRATION_TIME_MS, sample_rate);
+        self.presence_coeff = smoothing_coeff(CALIBRATION_PRESENCE_MS, sample_rate);
+    }
+
+    /// Start over at the reference level
+    pub fn reset(&mut self) {
+        self.mean_square = CALIBRATION_REFERENCE * CALIBRATION_REFERENCE;
+        self.presence = 0.0;
+        self.gain = 1.0;
+    }
+
+    /// Feed the power of the next frame and return the calibration gain
+    // `power` is the mean of the squared samples of all channels. While the input is silent
+    // the measurement stands still, so the gain is where it was when the music comes back.
+    pub fn process(&mut self, power: f32) -> f32 {
+        self.presence += (power - self.presence) * self.presence_coeff;
+        if self.presence > CALIBRATION_FLOOR * CALIBRATION_FLOOR {
+            self.mean_square += (power - self.mean_square) * self.coeff;
+            self.gain = (CALIBRATION_REFERENCE / self.mean_square.max(1e-12).sqrt())
+                .clamp(1.0 / MAX_CALIBRATION_GAIN, MAX_CALIBRATION_GAIN);
+        }
+
+        self.gain
+    }
+}
+
+/// The one-pole coefficient for the given time constant in milliseconds
+// Shared with the gate and the compressor, which smooth their levels the same way
+pub(crate) fn smoothing_coeff(time_ms: f32, sample_rate: f32) -> f32 {
+    1.0 - (-1.0 / ((time_ms / 1000.0) * sample_rate)).exp()
+}
@@ -0,0 +1,258 @@
+// The exported parameter values are read straight from the plugin's parameters
+use nih_plug::prelude::*;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::chaos::{ChaosAttractor, ChaosType, Integrator};
+use crate::coupling;
+use crate::governor::StabilityGovernor;
+use crate::dc_blocker::DcBlocker;
+use crate::fractal::{FoldQuality, FractalMagic, MagicEngine, MagicMode};
+use crate::plugin::RetardedGainParams;
+use crate::precision::Precision;
+use crate::quality::Quality;
+use crate::MAX_CHANNELS;
+
+/// The sample rate exported textures are rendered at
+// The chaos effect sounds the same at every sample rate, so this doesn't have to follow the host
+pub const EXPORT_SAMPLE_RATE: u32 = 48000;
+
+/// The longest texture that can be exported, in seconds
+pub const MAX_EXPORT_SECONDS: i32 = 60;
+
+/// The peak level exported textures are normalized to, -1 dBFS
+const EXPORT_PEAK: f32 = 0.891;
+
+/// What the GUI shows about the latest export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportState {
+    /// Nothing has been exported yet
+    Idle,
+    /// The background task is still rendering
+    Rendering,
+    /// The latest export was written to the export directory
+    Done,
+    /// The latest export couldn't be written
+    Failed,
+}
+
+/// The state of the latest export, shared between the background task and the GUI
+// Lives in the plugin rather than the editor, so closing the editor in the middle of an
+// export doesn't lose track of it
+pub struct ExportStatus {
+    state: AtomicU8,
+}
+
+impl ExportStatus {
+    pub fn new() -> Self {
+        Self {
+            state: AtomicU8::new(ExportState::Idle as u8),
+        }
+    }
+
+    /// The state of the latest export
+    pub fn state(&self) -> ExportState {
+        match self.state.load(Ordering::Relaxed) {
+            1 => ExportState::Rendering,
+            2 => ExportState::Done,
+            3 => ExportState::Failed,
+            _ => ExportState::Idle,
+        }
+    }
+
+    /// Move on to another state
+    pub fn set(&self, state: ExportState) {
+        self.state.store(state as u8, Ordering::Relaxed);
+    }
+
+    /// The text the GUI shows next to the export button
+    pub fn label(&self) -> &'static str {
+        match self.state() {
+            ExportState::Idle => "",
+            ExportState::Rendering => "Rendering...",
+            ExportState::Done => "Saved",
+            ExportState::Failed => "Failed",
+        }
+    }
+}
+
+/// The settings of one texture export
+// Copied out of the parameters when the button is clicked, so the background task never
+// touches the parameters while the audio thread is using them, and turning a knob during
+// the export doesn't change the file halfway through
+#[derive(Debug, Clone, Copy)]
+pub struct TextureExport {
+    /// The length of the texture, in seconds
+    pub seconds: i32,
+    pub chaos: f32,
+    pub chaos_type: ChaosType,
+    pub integrator: Integrator,
+    pub chaos_rate: f32,
+    pub chaos_color: f32,
+    pub chaos_stereo: bool,
+    pub chaos_seed: u32,
+    pub magic: f32,
+    pub magic_mode: MagicMode,
+    pub magic_engine: MagicEngine,
+    pub magic_fold_quality: FoldQuality,
+    pub clip_character: f32,
+    pub coupling: f32,
+    pub quality: Quality,
+    pub precision: Precision,
+}
+
+impl TextureExport {
+    /// Take the current settings of the chaos and fractal effects
+    pub fn from_params(params: &RetardedGainParams) -> Self {
+        Self {
+            seconds: params.export_length.value(),
+            chaos: params.chaos.value(),
+            chaos_type: params.chaos_system.value(),
+            integrator: params.chaos_integrator.value(),
+            chaos_rate: params.chaos_rate.value(),
+            chaos_color: params.chaos_color.value(),
+            chaos_stereo: params.chaos_stereo.value(),
+            chaos_seed: params.chaos_seed.load(Ordering::Relaxed),
+            magic: params.magic.value(),
+            magic_mode: params.magic_mode.value(),
+            magic_engine: params.magic_engine.value(),
+            magic_fold_quality: params.magic_fold_quality.value(),
+            clip_character: params.clip_character.value(),
+            coupling: params.coupling.value(),
+            quality: params.quality.value(),
+            precision: params.precision.value(),
+        }
+    }
+
+    /// Render the texture, interleaved stereo at `EXPORT_SAMPLE_RATE`
+    // With no input the chaos effect only puts out its own shaped signal, which then goes
+    // through the fractal, just like a silent track through the plugin. The result is
+    // normalized, since the raw generator is rather quiet, and the DC the cubic chaos term
+    // leaves behind is taken out.
+    pub fn render(&self) -> Vec<f32> {
+        let sample_rate = EXPORT_SAMPLE_RATE as f32;
+        let mut chaos = ChaosAttractor::new();
+        chaos.set_sample_rate(sample_rate);
+        chaos.set_chaos_type(self.chaos_type);
+        chaos.set_integrator(self.integrator);
+        chaos.set_rate(self.chaos_rate);
+        chaos.set_color(self.chaos_color);
+        chaos.set_stereo(self.chaos_stereo);
+        chaos.set_seed(self.chaos_seed);
+        chaos.set_saturation(self.clip_character);
+        chaos.set_quality(self.quality);
+        chaos.set_precision(self.precision);
+
+        let mut fractal = FractalMagic::new();
+        fractal.set_sample_rate(sample_rate);
+        fractal.set_mode(self.magic_mode);
+        fractal.set_precision(self.precision);
+        fractal.set_engine(self.magic_engine);
+        fractal.set_fold_quality(self.magic_fold_quality);
+        fractal.set_saturation(self.clip_character);
+        fractal.set_quality(self.quality);
+
+        let mut governor = StabilityGovernor::new();
+        governor.set_sample_rate(sample_rate);
+
+        let mut dc_blocker = DcBlocker::new();
+        dc_blocker.set_sample_rate(sample_rate);
+
+        // The spectral engine is late, so it renders a little longer and the start is dropped
+        let latency = fractal.latency();
+        let frames = self.seconds.clamp(1, MAX_EXPORT_SECONDS) as usize * EXPORT_SAMPLE_RATE as usize;
+        let mut samples = Vec::with_capacity(frames * MAX_CHANNELS);
+        for frame in 0..frames + latency {
+            for channel in 0..MAX_CHANNELS {
+                let stability = governor.supervise(channel, &chaos, &mut fractal);
+                coupling::couple(&mut chaos, &mut fractal, channel, self.coupling * stability);
+                let generated = chaos.process(channel, 0.0, self.chaos);
+                let shaped = fractal.process(channel, generated, self.magic);
+                let output = dc_blocker.process(channel, shaped);
+                if frame >= latency {
+                    samples.push(output);
+                }
+            }
+        }
+
+        let peak = samples.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        if peak > 1e-6 {
+            let gain = EXPORT_PEAK / peak;
+            samples.iter_mut().for_each(|sample| *sample *= gain);
+        }
+
+        samples
+    }
+}
+
+/// The directory exported textures are saved in
+// Next to the user's music if the platform has a folder for it, e.g. `~/Music/ret_gain` on
+// Linux, otherwise in the plugin's config directory
+pub fn export_dir() -> Option<PathBuf> {
+    dirs::audio_dir()
+        .map(|dir| dir.join("ret_gain"))
+        .or_else(|| dirs::config_dir().map(|dir| dir.join("ret_gain").join("exports")))
+}
+
+/// Render a texture and save it as a WAV file in the export directory
+// Runs on a background thread, since a minute of audio takes a while to render. Returns
+// the path of the written file.
+pub fn export_texture(export: &TextureExport) -> std::io::Result<PathBuf> {
+    let dir = export_dir().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "no directory to export to on this platform")
+    })?;
+    fs::create_dir_all(&dir)?;
+
+    // The seconds since 1970 keep the file names apart without a dialog asking for one
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let path = dir.join(format!("Chaos Texture {timestamp}.wav"));
+
+    write_wav(&path, &export.render(), MAX_CHANNELS as u16, EXPORT_SAMPLE_RATE)?;
+
+    Ok(path)
+}
+
+/// Write interleaved samples to a 32-bit float WAV file
+// The format is simple enough that it's not worth a dependency: a RIFF header, a format
+// chunk, the fact chunk that non-PCM formats need, and the samples
+fn write_wav(path: &Path, samples: &[f32], channels: u16, sample_rate: u32) -> std::io::Result<()> {
+    const FORMAT_IEEE_FLOAT: u16 = 3;
+    const BYTES_PER_SAMPLE: u32 = 4;
+
+    let data_size = samples.len() as u32 * BYTES_PER_SAMPLE;
+    let frames = samples.len() as u32 / channels as u32;
+    let block_align = channels as u32 * BYTES_PER_SAMPLE;
+
+    let mut file = BufWriter::new(File::create(path)?);
+    file.write_all(b"RIFF")?;
+    // Everything after this field: "WAVE", the fmt chunk, the fact chunk and the data chunk
+    file.write_all(&(4 + (8 + 16) + (8 + 4) + (8 + data_size)).to_le_bytes())?;
+    file.write_all(b"WAVE")?;
+
+    file.write_all(b"fmt ")?;
+    file.write_all(&16u32.to_le_bytes())?;
+    file.write_all(&FORMAT_IEEE_FLOAT.to_le_bytes())?;
+    file.write_all(&channels.to_le_bytes())?;
+    file.write_all(&sample_rate.to_le_bytes())?;
+    file.write_all(&(sample_rate * block_align).to_le_bytes())?;
+    file.write_all(&(block_align as u16).to_le_bytes())?;
+    file.write_all(&(BYTES_PER_SAMPLE as u16 * 8).to_le_bytes())?;
+
+    file.write_all(b"fact")?;
+    file.write_all(&4u32.to_le_bytes())?;
+    file.write_all(&frames.to_le_bytes())?;
+
+    file.write_all(b"data")?;
+    file.write_all(&data_size.to_le_bytes())?;
+    for sample in samples {
+        file.write_all(&sample.to_le_bytes())?;
+    }
+
+    file.flush()
+}
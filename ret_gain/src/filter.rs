@@ -0,0 +1,148 @@
+// Import the NIH-plug prelude for the Enum derive macro
+#[cfg(feature = "plugin")]
+use nih_plug::prelude::*;
+// Import PI constant from the standard library
+use std::f64::consts::PI;
+
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+// The filter can be calculated in 32 or 64 bits
+use crate::precision::{DspFloat, Precision};
+
+/// Which output of the state variable filter is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum FilterMode {
+    /// The filter is bypassed
+    #[cfg_attr(feature = "plugin", id = "off")]
+    #[cfg_attr(feature = "plugin", name = "Off")]
+    Off,
+    /// Tames the fizzy highs of heavy distortion
+    #[cfg_attr(feature = "plugin", id = "lowpass")]
+    #[cfg_attr(feature = "plugin", name = "Low Pass")]
+    LowPass,
+    /// Removes the mud below the cutoff
+    #[cfg_attr(feature = "plugin", id = "highpass")]
+    #[cfg_attr(feature = "plugin", name = "High Pass")]
+    HighPass,
+    /// Only keeps a band around the cutoff, for telephone and wah-like sounds
+    #[cfg_attr(feature = "plugin", id = "bandpass")]
+    #[cfg_attr(feature = "plugin", name = "Band Pass")]
+    BandPass,
+}
+
+/// Per-channel state of the filter
+#[derive(Clone, Copy, Default)]
+struct FilterState {
+    /// The two integrator states of the filter
+    // Kept in 64 bits, so switching the precision loses nothing
+    ic1eq: f64,
+    ic2eq: f64,
+}
+
+/// A state variable filter used as a tone control after the distortion
+// This is the trapezoidal ("zero delay feedback") state variable filter from Andrew Simper.
+// Unlike the classic Chamberlin version it stays stable at any cutoff and resonance,
+// and the cutoff can be changed every sample without zipper noise.
+pub struct Filter {
+    /// The selected filter output
+    mode: FilterMode,
+
+    /// One filter state per channel
+    channels: [FilterState; MAX_CHANNELS],
+
+    /// Sample rate for the cutoff calculations
+    sample_rate: f32,
+
+    /// The float type the filter is calculated with
+    precision: Precision,
+}
+
+impl Filter {
+    /// Create a new filter
+    pub fn new() -> Self {
+        Self {
+            mode: FilterMode::Off,
+            channels: [FilterState::default(); MAX_CHANNELS],
+            sample_rate: 44100.0, // Default sample rate, will be updated
+            precision: Precision::Single, // The same math as the rest of the chain
+        }
+    }
+
+    /// Set the sample rate for the cutoff calculations
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Select the filter output
+    // The filter state is cleared when going from off to on, so old state doesn't cause a click
+    pub fn set_mode(&mut self, mode: FilterMode) {
+        if self.mode == FilterMode::Off && mode != FilterMode::Off {
+            self.reset();
+        }
+        self.mode = mode;
+    }
+
+    /// Select the float type the filter is calculated with
+    // The state carries over, so switching doesn't click
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.precision = precision;
+    }
+
+    /// Clear the filter state of every channel
+    pub fn reset(&mut self) {
+        self.channels = [FilterState::default(); MAX_CHANNELS];
+    }
+
+    /// Filter a single sample of the given channel
+    // `cutoff` is in Hz and `resonance` goes from 0.0 (no peak) to 1.0 (strong peak)
+    // Both are passed in with every sample so their smoothing is sample-accurate
+    pub fn process(&mut self, channel: usize, sample: f32, cutoff: f32, resonance: f32) -> f32 {
+        if self.mode == FilterMode::Off {
+            return sample;
+        }
+
+        // Keep the cutoff safely below Nyquist, where the tan() below blows up
+        let cutoff = cutoff.min(self.sample_rate * 0.49);
+
+        let state = &mut self.channels[channel];
+        match self.precision {
+            Precision::Single => tick::<f32>(state, self.mode, sample, cutoff, self.sample_rate, resonance),
+            Precision::Double => tick::<f64>(state, self.mode, sample, cutoff, self.sample_rate, resonance),
+        }
+    }
+}
+
+/// Run one sample through a channel's filter, calculated with the float type `F`
+fn tick<F: DspFloat>(state: &mut FilterState, mode: FilterMode, sample: f32, cutoff: f32, sample_rate: f32, resonance: f32) -> f32 {
+    // Constants are written as f64 and rounded to the float type being used
+    let c = F::from_f64;
+    let sample = c(sample as f64);
+    let ic1eq = c(state.ic1eq);
+    let ic2eq = c(state.ic2eq);
+
+    // Filter coefficients
+    // k is 1/Q, going from 2.0 (Q = 0.5) down to 0.1 (Q = 10)
+    let g = (c(PI) * c(cutoff as f64) / c(sample_rate as f64)).tan();
+    let k = c(2.0) - c(1.9) * c(resonance as f64);
+    let a1 = c(1.0) / (c(1.0) + g * (g + k));
+    let a2 = g * a1;
+    let a3 = g * a2;
+
+    // Run the two integrators
+    let v3 = sample - ic2eq;
+    let v1 = a1 * ic1eq + a2 * v3;
+    let v2 = ic2eq + a2 * ic1eq + a3 * v3;
+    state.ic1eq = (c(2.0) * v1 - ic1eq).to_f64();
+    state.ic2eq = (c(2.0) * v2 - ic2eq).to_f64();
+
+    // Pick the requested output
+    let output = match mode {
+        FilterMode::Off => sample,
+        FilterMode::LowPass => v2,
+        FilterMode::HighPass => sample - k * v1 - v2,
+        FilterMode::BandPass => v1,
+    };
+
+    output.to_f64() as f32
+}
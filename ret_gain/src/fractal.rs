@@ -1,52 +1,290 @@
 // Import the NIH-plug prelude for audio processing types and traits
+#[cfg(feature = "plugin")]
 use nih_plug::prelude::*;
 // Import PI constant from the standard library
 use std::f32::consts::PI;
+// Snapshots of the fractal state are saved with the plugin state
+use serde::{Deserialize, Serialize};
 
-/// A complex fractal-based audio effect that combines fractal patterns with non-linear wave-shaping
-// This struct implements a creative effect based on fractal mathematics
-pub struct FractalMagic {
-    /// The amount of "magic" to apply (0.0 to 1.0)
-    // Controls how much of the effect is applied to the signal
-    magic_amount: f32,
-    
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+// The lookahead is a plain delay line
+use crate::latency::CompensationDelay;
+// The output goes through the chain's shared soft clipper
+use crate::saturation;
+// The quality tier can override the fold quality
+use crate::quality::Quality;
+// The formula can be calculated in 32 or 64 bits
+use crate::precision::{DspFloat, Precision};
+
+// The STFT version of the effect lives in its own file
+mod spectral;
+use spectral::SpectralMagic;
+// So does the anti-aliased wavefolder
+mod fold;
+pub use fold::{FoldQuality, WaveFolder};
+
+/// The longest lookahead the release smoother can use, in milliseconds
+#[cfg(feature = "plugin")]
+pub const MAX_LOOKAHEAD_MS: f32 = 5.0;
+
+/// How far either part of z may get from the origin before it's pulled back in
+// Past 2.0 the orbit of z² + c runs off to infinity for every c the formulas use
+const ESCAPE_LIMIT: f64 = 2.0;
+
+/// The iteration formula used by the fractal effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum MagicMode {
+    /// z = z² + c with a c that only wobbles a little around a fixed point, the original sound
+    #[cfg_attr(feature = "plugin", id = "julia")]
+    #[cfg_attr(feature = "plugin", name = "Julia")]
+    Julia,
+    /// z = z² + c where c follows the input directly, much more aggressive and input-dependent
+    #[cfg_attr(feature = "plugin", id = "mandelbrot")]
+    #[cfg_attr(feature = "plugin", name = "Mandelbrot")]
+    Mandelbrot,
+    /// Like the Julia set, but z is folded into the positive quadrant before squaring, which sounds rougher
+    #[cfg_attr(feature = "plugin", id = "burning-ship")]
+    #[cfg_attr(feature = "plugin", name = "Burning Ship")]
+    BurningShip,
+    /// Newton's method for z³ = 1, the input keeps knocking z between the three roots
+    #[cfg_attr(feature = "plugin", id = "newton")]
+    #[cfg_attr(feature = "plugin", name = "Newton")]
+    Newton,
+}
+
+/// Whether the fractal works on the samples or on the spectrum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum MagicEngine {
+    /// Every sample goes through the formula, the original waveshaping sound
+    #[cfg_attr(feature = "plugin", id = "time")]
+    #[cfg_attr(feature = "plugin", name = "Time")]
+    Time,
+    /// Every frequency bin goes through the Julia formula, which filters and smears the
+    /// spectrum instead of distorting the waveform. Adds `FFT_SIZE` samples of latency.
+    #[cfg_attr(feature = "plugin", id = "spectral")]
+    #[cfg_attr(feature = "plugin", name = "Spectral")]
+    Spectral,
+}
+
+/// The latency of the spectral engine, in samples at the rate the effect runs at
+pub const SPECTRAL_LATENCY_SAMPLES: usize = spectral::LATENCY_SAMPLES;
+
+/// Per-channel state of the fractal effect
+// Every audio channel gets its own copy of this, so the left and right channels
+// evolve independently instead of feeding interleaved samples into one fractal
+#[derive(Clone, Copy, Default)]
+struct FractalState {
     /// Internal state for creating evolving patterns
     // These track the state of our fractal calculation, similar to complex numbers
     // In fractal math, complex numbers (with real and imaginary parts) are common
-    z_real: f32,  // Real part of our complex number z
-    z_imag: f32,  // Imaginary part of our complex number z
-    
-    /// Sample rate for time-based calculations
-    // We need to know the sample rate to create time-based effects properly
-    sample_rate: f32,
+    // Always stored as 64-bit floats, 32-bit values fit into them without any change
+    z_real: f64,  // Real part of our complex number z
+    z_imag: f64,  // Imaginary part of our complex number z
     
     /// Sample counter for evolving patterns
     // Keeps track of how many samples we've processed for time-based evolution
     sample_counter: usize,  // usize is an unsigned integer sized for the platform (32 or 64 bit)
     
-    /// Smoothing factor for release/decay
-    // Controls how quickly the effect decays when input decreases
-    release_smoothing: f32,
-    
     /// Previous output value for smoothing
     // Used to create smooth transitions between processed samples
     prev_output: f32,
+
+    /// How many more samples the release speeds up for, because a transient is on its way
+    transient_countdown: usize,
+
+    /// The fractal's latest modulation signal, the same one that's fed back
+    modulation: f32,
+
+    /// How far the chaos effect moves the constant c, see `coupling::couple()`
+    coupling: f32,
+
+    /// Whether z got past `ESCAPE_LIMIT` on the latest sample and had to be pulled back in
+    escaped: bool,
+
+    /// How much of the feedback the stability governor took away, from 0.0 to 1.0
+    feedback_cut: f32,
+}
+
+/// Where every channel's fractal iteration is, for saving with the project
+// Saved together with the chaos systems when the plugin persists its engine state
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct FractalSnapshot {
+    /// The formula the snapshot was taken of, as its index in `MagicMode`
+    mode: usize,
+
+    /// The complex z of every channel, real and imaginary part
+    z: [[f64; 2]; MAX_CHANNELS],
+
+    /// Where every channel's slow LFO is, in samples
+    lfo_positions: [usize; MAX_CHANNELS],
+}
+
+/// A complex fractal-based audio effect that combines fractal patterns with non-linear wave-shaping
+// This struct implements a creative effect based on fractal mathematics
+// The amount of "magic" is passed in with every sample instead of being stored,
+// so a smoothed parameter can change it without any stepping
+pub struct FractalMagic {
+    /// One fractal state per channel
+    channels: [FractalState; MAX_CHANNELS],
+
+    /// The selected iteration formula
+    mode: MagicMode,
+
+    /// Whether the samples or the spectrum go through the formula
+    engine: MagicEngine,
+
+    /// The spectral engine, only running while it's selected
+    // It always uses the Julia formula in 32 bits, the formula and precision only apply to samples
+    spectral: SpectralMagic,
+
+    /// The float type the formula is calculated with
+    precision: Precision,
+    
+    /// Sample rate for time-based calculations
+    // We need to know the sample rate to create time-based effects properly
+    sample_rate: f32,
+    
+    /// Smoothing factor for release/decay
+    // Controls how quickly the effect decays when input decreases
+    release_smoothing: f32,
+
+    /// Delays the signal going into the release smoother
+    // The smoother then knows what's coming up before it gets there. Without this the slow
+    // release is still holding on to the previous peak when a transient hits, which clips it.
+    lookahead_delay: CompensationDelay,
+
+    /// The lookahead in samples, 0 when it's off
+    lookahead: usize,
+
+    /// The release smoothing used while a transient is coming up
+    // Fast enough to get the old tail out of the way within the lookahead time
+    lookahead_release: f32,
+
+    /// Whether the sample counter behind the slow LFO moves on
+    clock_running: bool,
+
+    /// The character of the soft clipper on the output, see `saturation::soft_clip()`
+    saturation: f32,
+
+    /// The wavefolder that adds the harmonics, with its own history for the anti-aliasing
+    folder: WaveFolder,
+
+    /// The fold quality that's chosen, the quality tier decides whether the folder uses it
+    fold_quality: FoldQuality,
+
+    /// The quality tier, see `Quality`
+    quality: Quality,
+
+    /// The gain of the input the formula listens to, the audio itself isn't changed by it
+    influence_gain: f32,
 }
 
 impl FractalMagic {
-    /// Create a new fractal magic effect with the given amount
+    /// Create a new fractal magic effect
     // Constructor for the FractalMagic effect
-    pub fn new(magic_amount: f32) -> Self {
+    pub fn new() -> Self {
         // Create and return a new instance with initial values
         Self {
-            magic_amount,          // The amount of effect to apply
-            z_real: 0.0,           // Start with a zero state
-            z_imag: 0.0,           // Start with a zero state
+            channels: [FractalState::default(); MAX_CHANNELS], // Start every channel with a zero state
+            mode: MagicMode::Julia, // The original fractal
+            engine: MagicEngine::Time, // Straight on the samples
+            spectral: SpectralMagic::new(), // Allocates its FFT buffers up front
+            precision: Precision::Single, // The same math as the rest of the chain
             sample_rate: 44100.0,  // Default sample rate, will be updated later
-            sample_counter: 0,     // Start with counter at 0
             release_smoothing: 0.9995, // High value for smooth release (close to 1.0)
-            prev_output: 0.0,      // Start with previous output at 0
+            lookahead_delay: CompensationDelay::new(0), // Allocated by set_max_lookahead()
+            lookahead: 0, // No lookahead and no latency by default
+            lookahead_release: 0.0,
+            clock_running: true, // Always running unless the plugin follows the transport
+            saturation: 0.0, // Plain tanh, like the original fractal
+            folder: WaveFolder::new(), // The plain fold, the plugin picks the quality itself
+            fold_quality: FoldQuality::Off,
+            quality: Quality::Normal, // The fold quality as it's chosen
+            influence_gain: 1.0, // The input as it comes in
+        }
+    }
+
+    /// Allocate room for a lookahead of up to `max_samples`
+    // This allocates, so it's called from initialize() and never on the audio thread
+    pub fn set_max_lookahead(&mut self, max_samples: usize) {
+        self.lookahead_delay = CompensationDelay::new(max_samples);
+        self.lookahead_delay.set_delay(self.lookahead);
+    }
+
+    /// Select the iteration formula
+    // The fractal state is cleared on a change, since a z that's fine for one formula can blow up another
+    pub fn set_mode(&mut self, mode: MagicMode) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.channels = [FractalState::default(); MAX_CHANNELS];
+        }
+    }
+
+    /// Select whether the samples or the spectrum go through the formula
+    // The spectral engine starts from silence, so no half-finished frames from the last
+    // time it ran come out
+    pub fn set_engine(&mut self, engine: MagicEngine) {
+        if engine != self.engine {
+            self.engine = engine;
+            self.spectral.reset();
+        }
+    }
+
+    /// The latency of the selected engine, in samples at the rate the effect runs at
+    pub fn latency(&self) -> usize {
+        match self.engine {
+            MagicEngine::Time => self.lookahead,
+            MagicEngine::Spectral => SPECTRAL_LATENCY_SAMPLES,
+        }
+    }
+
+    /// Set the character of the soft clipper on the output, from 0.0 (tanh) to 1.0 (polynomial)
+    // Both engines clip with it
+    pub fn set_saturation(&mut self, character: f32) {
+        self.saturation = character;
+        self.spectral.set_saturation(character);
+    }
+
+    /// Set how much the input is turned up or down before it steers the formula
+    // The plugin's input calibration sets this on every sample. Only the time engine's
+    // formula listens to it, the fold and the spectral engine work on the audio itself.
+    pub fn set_influence_gain(&mut self, gain: f32) {
+        self.influence_gain = gain;
+    }
+
+    /// Select how the wavefolder anti-aliases its folds
+    // The folder's history carries over, so switching doesn't click
+    pub fn set_fold_quality(&mut self, quality: FoldQuality) {
+        self.fold_quality = quality;
+        self.folder.set_quality(self.quality.fold_quality(quality));
+    }
+
+    /// Select the quality tier, which overrides the fold quality at Eco and Ultra
+    pub fn set_quality(&mut self, quality: Quality) {
+        self.quality = quality;
+        self.folder.set_quality(quality.fold_quality(self.fold_quality));
+    }
+
+    /// Select the float type the formula is calculated with
+    // The state carries over, so switching doesn't click
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.precision = precision;
+    }
+
+    /// Set the lookahead in samples at the rate the effect runs at, 0 turns it off
+    // The effect's output is delayed by this much, which the plugin reports as latency
+    pub fn set_lookahead(&mut self, samples: usize) {
+        if samples == self.lookahead {
+            return;
         }
+
+        self.lookahead = samples;
+        self.lookahead_delay.set_delay(samples);
+        // Decays to about 2% within the lookahead window
+        self.lookahead_release = (-4.0 / samples.max(1) as f32).exp();
     }
 
     /// Set the sample rate for time-based calculations
@@ -61,50 +299,135 @@ impl FractalMagic {
         self.release_smoothing = 0.9995f32.powf(44100.0 / sample_rate);
     }
 
+    /// Let the sample counter behind the slow LFO move on, or hold it where it is
+    // The plugin holds it while the host isn't playing, so a render starts the LFO at the
+    // same point as the playback it was auditioned with
+    pub fn set_clock_running(&mut self, running: bool) {
+        self.clock_running = running;
+    }
+
+    /// Take a snapshot of where every channel's iteration is
+    pub fn snapshot(&self) -> FractalSnapshot {
+        FractalSnapshot {
+            mode: self.mode as usize,
+            z: std::array::from_fn(|channel| [self.channels[channel].z_real, self.channels[channel].z_imag]),
+            lfo_positions: std::array::from_fn(|channel| self.channels[channel].sample_counter),
+        }
+    }
+
+    /// Move every channel's iteration back to where a snapshot was taken
+    // Like `set_mode()`, a z from another formula could blow this one up, so a snapshot of
+    // another formula is ignored, and so is a damaged one
+    pub fn restore(&mut self, snapshot: &FractalSnapshot) {
+        if snapshot.mode != self.mode as usize || !snapshot.z.iter().flatten().all(|value| value.is_finite()) {
+            return;
+        }
+
+        for (channel, state) in self.channels.iter_mut().enumerate() {
+            [state.z_real, state.z_imag] = snapshot.z[channel];
+            state.sample_counter = snapshot.lfo_positions[channel];
+        }
+    }
+
+    /// Start the sample counter behind the slow LFO over, without touching anything else
+    pub fn reset_clock(&mut self) {
+        for state in self.channels.iter_mut() {
+            state.sample_counter = 0;
+        }
+    }
+
+    /// The real part of a channel's z, where the iteration currently is
+    // Around -2.0 to 2.0, it's scaled back whenever it gets further out
+    pub fn z_real(&self, channel: usize) -> f32 {
+        self.channels[channel].z_real as f32
+    }
+
+    /// Move the constant c of a channel's formula, on top of what the input does to it
+    // The chaos effect sets this on every sample when the two are coupled. Only the real
+    // part of c moves, and the Newton formula has no c, so it doesn't listen to it.
+    pub fn set_coupling(&mut self, channel: usize, c_offset: f32) {
+        self.channels[channel].coupling = c_offset;
+    }
+
+    /// Whether a channel's z got away on the latest sample and had to be pulled back in
+    // The stability governor counts how often this happens to tell how close the loop is to blowing up
+    pub fn escaped(&self, channel: usize) -> bool {
+        self.channels[channel].escaped
+    }
+
+    /// Take away part of a channel's feedback, from 0.0 (all of it stays) to 1.0 (none of it)
+    // Set by the stability governor on every sample, see `StabilityGovernor::supervise()`
+    pub fn set_feedback_cut(&mut self, channel: usize, cut: f32) {
+        self.channels[channel].feedback_cut = cut.clamp(0.0, 1.0);
+    }
+
+    /// The fractal's latest modulation signal of a channel, from -1.0 to 1.0
+    // It's what the formula put out before it was mixed with the folded signal, and 0.0 while
+    // the effect is off or the spectral engine is running, which has no such signal
+    pub fn modulation(&self, channel: usize) -> f32 {
+        self.channels[channel].modulation
+    }
+
     /// Reset the internal state
     // Clears the internal state of the effect
     pub fn reset(&mut self) {
-        self.z_real = 0.0;
-        self.z_imag = 0.0;
-        self.sample_counter = 0;
-        self.prev_output = 0.0;
+        self.channels = [FractalState::default(); MAX_CHANNELS];
+        self.lookahead_delay.reset();
+        self.spectral.reset();
+        self.folder.reset();
     }
     
-    /// Process a single sample through the fractal magic algorithm
+    /// Process a single sample of the given channel through the fractal magic algorithm
     // This is where the magic happens! The main DSP method.
-    pub fn process(&mut self, sample: f32) -> f32 {
+    // The channel index selects which channel's fractal state gets updated
+    // `magic_amount` controls how much of the effect is applied to the signal (0.0 to 1.0)
+    pub fn process(&mut self, channel: usize, sample: f32, magic_amount: f32) -> f32 {
+        // The spectral engine has a fixed latency, it keeps running even without magic
+        if self.engine == MagicEngine::Spectral {
+            self.channels[channel].modulation = 0.0;
+            self.channels[channel].escaped = false;
+            self.folder.hold(channel, sample);
+            return self.spectral.process(channel, sample, magic_amount);
+        }
+
         // Early exit if the effect is turned off (optimization)
-        if self.magic_amount <= 0.001 {
-            return sample; // Bypass if magic amount is essentially zero
+        // The lookahead delay still runs, so the latency doesn't change with the magic amount
+        if magic_amount <= 0.001 {
+            self.channels[channel].modulation = 0.0;
+            self.channels[channel].escaped = false;
+            // The folder picks up from the current input once the magic comes back
+            self.folder.hold(channel, sample);
+            return self.lookahead_delay.process(channel, sample); // Bypass if magic amount is essentially zero
         }
 
         // Scale the magic amount for different aspects of the effect
         // Each aspect of the effect responds differently to the magic amount
-        let fractal_strength = self.magic_amount * 2.0; // Reduced from 2.5
-        let fold_strength = self.magic_amount * 2.5;    // Reduced from 3.0
-        let feedback_amount = self.magic_amount * 0.4;  // Reduced from 0.7
+        let fractal_strength = magic_amount * 2.0; // Reduced from 2.5
+        let fold_strength = magic_amount * 2.5;    // Reduced from 3.0
+        let feedback_amount = magic_amount * 0.4;  // Reduced from 0.7
         
-        // Update the fractal state - using a modified Julia set iteration
-        // The Julia set is a famous fractal in mathematics
-        // The input sample modulates the fractal parameters for audio-responsive behavior
-        let c_real = 0.285 + 0.01 * (sample * fractal_strength).sin();
-        let c_imag = 0.01 + 0.01 * (sample * fractal_strength).cos();
+        // Borrow this channel's state mutably for the rest of the method
+        let state = &mut self.channels[channel];
+        let feedback_amount = feedback_amount * (1.0 - state.feedback_cut); // What the governor left of it
         
-        // Store the current z values temporarily
-        let temp_real = self.z_real;
-        let temp_imag = self.z_imag;
-        
-        // z = z² + c + sample_influence
-        // This is the core of the Julia set fractal formula, with audio input
-        // For complex number z², we calculate (a+bi)² = a² - b² + 2abi
-        self.z_real = temp_real * temp_real - temp_imag * temp_imag + c_real + sample * 0.1;
-        self.z_imag = 2.0 * temp_real * temp_imag + c_imag;
+        // Update the fractal state with the selected formula
+        // The input sample modulates the fractal parameters for audio-responsive behavior
+        let influence = sample * self.influence_gain;
+        let fractal_value = match self.precision {
+            Precision::Single => iterate::<f32>(state, self.mode, influence, fractal_strength),
+            Precision::Double => iterate::<f64>(state, self.mode, influence, fractal_strength),
+        };
         
-        // Better state management to prevent explosions
-        // If the values get too large, scale them back to prevent the effect from getting out of control
-        if self.z_real.abs() > 2.0 || self.z_imag.abs() > 2.0 {
-            self.z_real *= 0.5;
-            self.z_imag *= 0.5;
+        // The last line of defence against explosions, the stability governor keeps the
+        // feedback and the coupling from getting here in the first place. A z that got away
+        // is scaled back so its larger part lands on 1.0, which is what halving did to a z
+        // that only just got out, and a far-out z is back in on the same sample.
+        let escape = state.z_real.abs().max(state.z_imag.abs());
+        state.escaped = escape > ESCAPE_LIMIT;
+        if state.escaped {
+            let scale = 0.5 * ESCAPE_LIMIT / escape;
+            state.z_real *= scale;
+            state.z_imag *= scale;
         }
         
         // Add slow LFO modulation based on sample count
@@ -113,7 +436,7 @@ impl FractalMagic {
         
         // Calculate the phase of the LFO based on sample count and rate
         // This converts our sample counter to a phase angle for the sine wave
-        let lfo_phase = (self.sample_counter as f32 / self.sample_rate) * lfo_freq * 2.0 * PI;
+        let lfo_phase = (state.sample_counter as f32 / self.sample_rate) * lfo_freq * 2.0 * PI;
         
         // Calculate the actual LFO value using sine
         let lfo_value = lfo_phase.sin() * 0.1; // Reduced amplitude from 0.2
@@ -121,90 +444,171 @@ impl FractalMagic {
         // Wave folding for harmonic richness
         // Wave folding is a technique that "folds" the waveform back on itself,
         // creating interesting harmonics (frequencies not in the original sound)
-        let folded = wave_fold(sample + lfo_value, fold_strength);
+        // With anti-aliasing on, the harmonics above Nyquist don't fold back down as noise
+        let folded = self.folder.process(channel, sample + lfo_value, fold_strength);
         
         // Combine original, fractal modulation, and folded signal
         // This blends the dry signal with the processed signal based on magic_amount
-        let result = sample * (1.0 - self.magic_amount) +  // Dry signal
-                     (self.z_real * 0.2 * fractal_strength + folded) * self.magic_amount; // Wet signal
+        let result = sample * (1.0 - magic_amount) +  // Dry signal
+                     (fractal_value * 0.2 * fractal_strength + folded) * magic_amount; // Wet signal
         
         // Apply feedback with tanh limiting and reduced feedback
         // Feedback means feeding part of the output back into the algorithm
         // tanh limits the feedback to prevent it from growing out of control
-        let with_feedback = result + feedback_amount * self.z_real.tanh();
+        state.modulation = fractal_value.tanh();
+        let with_feedback = result + feedback_amount * state.modulation;
+
+        // With lookahead, the smoother works on a delayed copy and sees the new value coming
+        // A value bigger than what's currently being held is a transient, so the release
+        // speeds up until it arrives
+        let upcoming = with_feedback;
+        let with_feedback = self.lookahead_delay.process(channel, upcoming);
+        if self.lookahead > 0 && upcoming.abs() > state.prev_output.abs() {
+            state.transient_countdown = self.lookahead;
+        }
+        let release_smoothing = if state.transient_countdown > 0 {
+            state.transient_countdown -= 1;
+            self.lookahead_release.min(self.release_smoothing)
+        } else {
+            self.release_smoothing
+        };
         
         // Apply smoothing for better release behavior
         // Fast attack, slow release is a common pattern in audio effects
-        let smoothed = if with_feedback.abs() > self.prev_output.abs() {
+        let smoothed = if with_feedback.abs() > state.prev_output.abs() {
             // Fast attack - immediately jump to new value when it's larger
             with_feedback
         } else {
             // Smooth release - gradually decrease when value gets smaller
             // This is a weighted average between new and previous values
-            with_feedback * (1.0 - self.release_smoothing) + self.prev_output * self.release_smoothing
+            with_feedback * (1.0 - release_smoothing) + state.prev_output * release_smoothing
         };
         
         // Hard limit to ensure output stays in bounds
         // This prevents the effect from producing samples that are too loud
-        let limited = soft_clip(smoothed);
+        let limited = saturation::soft_clip(smoothed, self.saturation);
         
         // Increment counter for time-based modulation
         // The modulo (%) operator ensures the counter wraps around after 1 minute
-        self.sample_counter = (self.sample_counter + 1) % (self.sample_rate as usize * 60); // Reset after 1 minute
+        if self.clock_running {
+            state.sample_counter = (state.sample_counter + 1) % (self.sample_rate as usize * 60); // Reset after 1 minute
+        }
         
         // Store for next iteration - this is used for smoothing
-        self.prev_output = limited;
+        state.prev_output = limited;
         
         // Return the processed sample
         limited
     }
     
     /// Process a buffer of samples through the fractal magic effect
-    // Convenience method to process an entire buffer at once
-    pub fn process_buffer(&mut self, buffer: &mut Buffer) {
+    // Convenience method to process an entire buffer at once with a fixed amount
+    #[cfg(feature = "plugin")]
+    pub fn process_buffer(&mut self, buffer: &mut Buffer, magic_amount: f32) {
         // Iterate through each set of samples across all channels
         for channel_samples in buffer.iter_samples() {
-            // For each sample in the current frame
-            for sample in channel_samples {
+            // For each sample in the current frame, together with its channel index
+            for (channel, sample) in channel_samples.into_iter().enumerate() {
                 // Process the sample and write back to the buffer in-place
-                *sample = self.process(*sample);
+                *sample = self.process(channel, *sample, magic_amount);
             }
         }
     }
-}
 
-/// Wave folding function that creates harmonic content when the signal exceeds a threshold
-// This is a separate function (not a method) that implements the wavefolder algorithm
-// In Rust, functions don't need to be part of a struct/class
-fn wave_fold(input: f32, fold_amount: f32) -> f32 {
-    // Early exit if no folding is needed
-    if fold_amount <= 0.0 {
-        return input;
-    }
-    
-    // The threshold after which folding begins to occur
-    // As fold_amount increases, the threshold decreases, creating more folding
-    let threshold = 1.0 / fold_amount;
-    
-    // Simple folding algorithm that reflects the signal when it exceeds the threshold
-    // This creates a "fold" in the waveform, adding harmonics
-    if input > threshold {
-        // Reflect around the threshold line (like a mirror)
-        return 2.0 * threshold - input;
-    } else if input < -threshold {
-        // Reflect around the negative threshold line
-        return -2.0 * threshold - input;
+    /// Process a slice of samples of the given channel through the fractal magic effect
+    // The same as `process_buffer()`, but for plain slices, so the effect can be used
+    // outside of a plugin
+    #[cfg(feature = "dsp")]
+    pub fn process_slice(&mut self, channel: usize, samples: &mut [f32], magic_amount: f32) {
+        for sample in samples.iter_mut() {
+            *sample = self.process(channel, *sample, magic_amount);
+        }
     }
-    
-    // If within thresholds, return the original input
-    input
 }
 
-/// Soft clipper to ensure output stays within reasonable bounds
-// Another utility function that prevents the output from getting too loud
-fn soft_clip(input: f32) -> f32 {
-    // Hyperbolic tangent provides a smooth, musical limiting
-    // tanh approaches ±1 as the input approaches ±∞, creating a smooth curve
-    // This sounds more musical than hard clipping, which would create harsh distortion
-    input.tanh()
-}
\ No newline at end of file
+/// Run one iteration of the selected fractal formula on a channel's state
+// Returns the value that gets mixed into the signal, which is the real part of z for
+// everything but the Newton fractal. `F` is the float type the formula is calculated
+// with, the state is only rounded to it on the way in, so switching loses nothing.
+fn iterate<F: DspFloat>(state: &mut FractalState, mode: MagicMode, sample: f32, fractal_strength: f32) -> f32 {
+    // Constants are written as f64 and rounded to the float type being used
+    let c = F::from_f64;
+    let sample = c(sample as f64);
+    let fractal_strength = c(fractal_strength as f64);
+
+    // Store the current z values temporarily
+    let temp_real = c(state.z_real);
+    let temp_imag = c(state.z_imag);
+
+    // Where the chaos effect moved c to, 0.0 unless the two are coupled
+    let coupling = c(state.coupling as f64);
+
+    let (z_real, z_imag, output) = match mode {
+        MagicMode::Julia | MagicMode::BurningShip => {
+            // The Julia set is a famous fractal in mathematics
+            // The input only nudges c a little, so z keeps circling the same shape
+            let c_real = c(0.285) + c(0.01) * (sample * fractal_strength).sin() + coupling;
+            let c_imag = c(0.01) + c(0.01) * (sample * fractal_strength).cos();
+
+            // The Burning Ship takes the absolute values first, which breaks the symmetry
+            let (a, b) = if mode == MagicMode::BurningShip {
+                (temp_real.abs(), temp_imag.abs())
+            } else {
+                (temp_real, temp_imag)
+            };
+
+            // z = z² + c + sample_influence
+            // This is the core of the Julia set fractal formula, with audio input
+            // For complex number z², we calculate (a+bi)² = a² - b² + 2abi
+            let z_real = a * a - b * b + c_real + sample * c(0.1);
+            let z_imag = c(2.0) * a * b + c_imag;
+
+            (z_real, z_imag, z_real)
+        }
+        MagicMode::Mandelbrot => {
+            // c comes straight from the input, sweeping across the Mandelbrot set's main bulb
+            // Loud input pushes c outside of the set, where z runs away until it's scaled back
+            let c_real = c(-0.4) + c(0.5) * sample * fractal_strength + coupling;
+            let c_imag = c(0.3) * (sample * fractal_strength * c(std::f64::consts::PI)).sin();
+
+            let z_real = temp_real * temp_real - temp_imag * temp_imag + c_real;
+            let z_imag = c(2.0) * temp_real * temp_imag + c_imag;
+
+            (z_real, z_imag, z_real)
+        }
+        MagicMode::Newton => {
+            // The input knocks z around first, otherwise it would settle on a root and stay there
+            let z_real = temp_real + sample * c(0.5) * fractal_strength;
+            let z_imag = temp_imag + c(0.05);
+
+            // z ← z - (z³ - 1) / (3z²)
+            // Written out for complex numbers: z³ - 1 and 3z², then the complex division
+            let z2_real = z_real * z_real - z_imag * z_imag;
+            let z2_imag = c(2.0) * z_real * z_imag;
+            let num_real = z2_real * z_real - z2_imag * z_imag - c(1.0);
+            let num_imag = z2_real * z_imag + z2_imag * z_real;
+            let den_real = c(3.0) * z2_real;
+            let den_imag = c(3.0) * z2_imag;
+            let den = den_real * den_real + den_imag * den_imag;
+
+            // z² is zero right at the origin, where the step is undefined
+            let (step_real, step_imag) = if den > c(1e-6) {
+                (
+                    (num_real * den_real + num_imag * den_imag) / den,
+                    (num_imag * den_real - num_real * den_imag) / den,
+                )
+            } else {
+                (c(0.0), c(0.0))
+            };
+
+            // The roots themselves are constants, so the size of the step is what's heard
+            // It jumps whenever the input pushes z towards a different root
+            (z_real - step_real, z_imag - step_imag, (c(2.0) * step_real).clamp(c(-2.0), c(2.0)))
+        }
+    };
+
+    state.z_real = z_real.to_f64();
+    state.z_imag = z_imag.to_f64();
+
+    output.to_f64() as f32
+}
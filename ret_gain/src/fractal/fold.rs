@@ -0,0 +1,173 @@
+// Import the NIH-plug prelude for the Enum derive macro
+#[cfg(feature = "plugin")]
+use nih_plug::prelude::*;
+
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+/// Below this difference between two inputs the antiderivative quotients are ill-conditioned
+// They divide by the difference, so for nearly equal inputs the rounding error of the
+// antiderivatives would be blown up into a click. The folder is evaluated at the midpoint
+// instead, which is what the quotient tends to anyway.
+const ILL_CONDITIONED: f64 = 1e-5;
+
+/// How hard the wavefolder fights the aliasing its folds cause
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum FoldQuality {
+    /// The plain fold, every corner aliases. How projects from before the setting sound.
+    #[cfg_attr(feature = "plugin", id = "off")]
+    #[cfg_attr(feature = "plugin", name = "Off")]
+    Off,
+    /// First-order antiderivative anti-aliasing, half a sample late
+    #[cfg_attr(feature = "plugin", id = "adaa-1")]
+    #[cfg_attr(feature = "plugin", name = "ADAA 1st")]
+    FirstOrder,
+    /// Second-order antiderivative anti-aliasing, a sample late and a bit more CPU
+    #[cfg_attr(feature = "plugin", id = "adaa-2")]
+    #[cfg_attr(feature = "plugin", name = "ADAA 2nd")]
+    SecondOrder,
+}
+
+/// The wavefolder behind Magic One, with optional antiderivative anti-aliasing (ADAA)
+// The plain fold has sharp corners, and every corner throws harmonics far above Nyquist that
+// fold back down as inharmonic aliasing. ADAA doesn't evaluate the folder at the samples, it
+// averages it over the straight line between the last two (or three) inputs, using the
+// folder's antiderivatives, which takes the corners off just enough. The math runs in 64 bits,
+// since the antiderivatives are subtracted from each other.
+pub struct WaveFolder {
+    /// The previous two inputs of every channel, the latest one first
+    history: [[f64; 2]; MAX_CHANNELS],
+
+    /// How the folds are anti-aliased
+    quality: FoldQuality,
+}
+
+impl WaveFolder {
+    /// Create a wavefolder with the plain fold
+    pub fn new() -> Self {
+        Self {
+            history: [[0.0; 2]; MAX_CHANNELS],
+            quality: FoldQuality::Off,
+        }
+    }
+
+    /// Select how the folds are anti-aliased
+    pub fn set_quality(&mut self, quality: FoldQuality) {
+        self.quality = quality;
+    }
+
+    /// Forget the previous inputs
+    pub fn reset(&mut self) {
+        self.history = [[0.0; 2]; MAX_CHANNELS];
+    }
+
+    /// Start the history over at a level, so the anti-aliasing picks up from there without a jump
+    // Called while the fold isn't used, so it doesn't average over stale inputs once it is again
+    pub fn hold(&mut self, channel: usize, input: f32) {
+        self.history[channel] = [input as f64; 2];
+    }
+
+    /// Fold a sample of the given channel
+    // Everything above `1.0 / fold_amount` is reflected back down, so more fold amount folds
+    // earlier. The threshold can change on every sample, the antiderivatives of the previous
+    // inputs are always taken with the current one.
+    pub fn process(&mut self, channel: usize, input: f32, fold_amount: f32) -> f32 {
+        if fold_amount <= 0.0 {
+            self.hold(channel, input);
+            return input;
+        }
+
+        let threshold = 1.0 / fold_amount as f64;
+        let x0 = input as f64;
+        let [x1, x2] = self.history[channel];
+        self.history[channel] = [x0, x1];
+
+        let output = match self.quality {
+            FoldQuality::Off => fold(x0, threshold),
+            FoldQuality::FirstOrder => first_order(x0, x1, threshold),
+            FoldQuality::SecondOrder => second_order(x0, x1, x2, threshold),
+        };
+
+        output as f32
+    }
+}
+
+/// The plain fold: a straight line up to the threshold, reflected back down beyond it
+fn fold(x: f64, threshold: f64) -> f64 {
+    if x > threshold {
+        2.0 * threshold - x
+    } else if x < -threshold {
+        -2.0 * threshold - x
+    } else {
+        x
+    }
+}
+
+/// The antiderivative of `fold()`, zero at the origin
+// The fold is odd, so this is even
+fn fold_ad1(x: f64, threshold: f64) -> f64 {
+    let magnitude = x.abs();
+    if magnitude <= threshold {
+        x * x / 2.0
+    } else {
+        2.0 * threshold * magnitude - x * x / 2.0 - threshold * threshold
+    }
+}
+
+/// The antiderivative of `fold_ad1()`, zero at the origin
+// Odd again, so only the positive half has to be worked out
+fn fold_ad2(x: f64, threshold: f64) -> f64 {
+    let magnitude = x.abs();
+    let positive = if magnitude <= threshold {
+        magnitude * magnitude * magnitude / 6.0
+    } else {
+        threshold * magnitude * magnitude - magnitude * magnitude * magnitude / 6.0
+            - threshold * threshold * magnitude
+            + threshold * threshold * threshold / 3.0
+    };
+
+    positive.copysign(x)
+}
+
+/// The average of the fold over the line from `x1` to `x0`
+fn first_order(x0: f64, x1: f64, threshold: f64) -> f64 {
+    let difference = x0 - x1;
+    if difference.abs() < ILL_CONDITIONED {
+        fold((x0 + x1) / 2.0, threshold)
+    } else {
+        (fold_ad1(x0, threshold) - fold_ad1(x1, threshold)) / difference
+    }
+}
+
+/// The average of `fold_ad1()` over the line from `x1` to `x0`
+fn first_order_ad1(x0: f64, x1: f64, threshold: f64) -> f64 {
+    let difference = x0 - x1;
+    if difference.abs() < ILL_CONDITIONED {
+        fold_ad1((x0 + x1) / 2.0, threshold)
+    } else {
+        (fold_ad2(x0, threshold) - fold_ad2(x1, threshold)) / difference
+    }
+}
+
+/// The fold averaged with a triangular kernel over the last three inputs
+// The second-order form from Bilbao, Esqueda, Parker and Välimäki, "Antiderivative
+// Antialiasing for Memoryless Nonlinearities" (2017). When `x0` and `x2` are nearly the same
+// the difference of the two averages is taken around their midpoint instead.
+fn second_order(x0: f64, x1: f64, x2: f64, threshold: f64) -> f64 {
+    let outer_difference = x0 - x2;
+    if outer_difference.abs() >= ILL_CONDITIONED {
+        return 2.0 * (first_order_ad1(x0, x1, threshold) - first_order_ad1(x1, x2, threshold))
+            / outer_difference;
+    }
+
+    let midpoint = (x0 + x2) / 2.0;
+    let inner_difference = midpoint - x1;
+    if inner_difference.abs() < ILL_CONDITIONED {
+        fold((midpoint + x1) / 2.0, threshold)
+    } else {
+        2.0 / inner_difference
+            * (fold_ad1(midpoint, threshold)
+                + (fold_ad2(x1, threshold) - fold_ad2(midpoint, threshold)) / inner_difference)
+    }
+}
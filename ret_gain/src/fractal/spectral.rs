@@ -0,0 +1,266 @@
+// realfft computes the FFT of real-valued (audio) signals
+use realfft::num_complex::Complex32;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+// The output goes through the chain's shared soft clipper
+use crate::saturation;
+
+/// The length of every STFT frame, in samples at the rate the effect runs at
+// About 23 ms at 44.1 kHz, enough resolution to pull the low end apart without smearing
+// drums into mush
+pub const FFT_SIZE: usize = 1024;
+
+/// How far apart the frames start, a quarter of a frame so they overlap by 75%
+const HOP_SIZE: usize = FFT_SIZE / 4;
+
+/// The number of frequency bins the FFT produces
+const NUM_BINS: usize = FFT_SIZE / 2 + 1;
+
+/// The latency of the spectral engine, in samples at the rate the effect runs at
+// A sample only comes out once every frame it's a part of has been through the FFT
+pub const LATENCY_SAMPLES: usize = FFT_SIZE;
+
+/// How many times the Julia formula is applied to every bin at full magic
+const MAX_ITERATIONS: usize = 8;
+
+/// How much of the previous frame's magnitude a bin keeps at full magic
+// This is what smears the spectrum over time, higher values hold notes for longer
+const MAX_SMEAR: f32 = 0.85;
+
+/// How far c moves along its orbit with every frame, in radians
+const C_ORBIT_STEP: f32 = 2.0 * PI / 400.0;
+
+/// Per-channel state of the spectral engine
+struct SpectralChannel {
+    /// The most recent `FFT_SIZE` input samples, oldest first
+    input: Vec<f32>,
+
+    /// The overlap-added output of the frames, the first `HOP_SIZE` samples are complete
+    accumulator: Vec<f32>,
+
+    /// The finished output samples, played back while the next hop is collected
+    output: Vec<f32>,
+
+    /// Where in the current hop the next sample goes
+    position: usize,
+
+    /// The magnitude of every bin after the previous frame, for the smearing
+    magnitudes: Vec<f32>,
+
+    /// Where the Julia constant is on its orbit, in radians
+    c_phase: f32,
+}
+
+impl SpectralChannel {
+    fn new() -> Self {
+        Self {
+            input: vec![0.0; FFT_SIZE],
+            accumulator: vec![0.0; FFT_SIZE],
+            output: vec![0.0; HOP_SIZE],
+            position: 0,
+            magnitudes: vec![0.0; NUM_BINS],
+            c_phase: 0.0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.input.fill(0.0);
+        self.accumulator.fill(0.0);
+        self.output.fill(0.0);
+        self.position = 0;
+        self.magnitudes.fill(0.0);
+        self.c_phase = 0.0;
+    }
+}
+
+/// The fractal effect in the frequency domain
+// The signal is cut into overlapping frames, and every frequency bin of every frame is
+// fed into the Julia formula as a starting point. Loud bins start far from the origin,
+// quiet ones close to it. Bins whose orbit stays bounded belong to the Julia set and get
+// louder, bins that escape quickly get quieter, which filters the signal with the shape
+// of the fractal. The angle the orbit ends up at rotates the bin's phase, and every bin
+// keeps some of its previous magnitude, which smears the sound over time. The constant c
+// slowly circles the time-domain Julia set's constant, so the filter keeps moving.
+pub struct SpectralMagic {
+    /// One state per channel
+    channels: [SpectralChannel; MAX_CHANNELS],
+
+    /// The character of the soft clipper on the output
+    saturation: f32,
+
+    /// The forward FFT, planned once up front
+    fft: Arc<dyn RealToComplex<f32>>,
+
+    /// The inverse FFT, planned once up front
+    ifft: Arc<dyn ComplexToReal<f32>>,
+
+    /// Scratch buffer holding the windowed frame, and the inverse FFT's result
+    frame: Vec<f32>,
+
+    /// Scratch buffer holding the spectrum of the frame
+    spectrum: Vec<Complex32>,
+
+    /// Scratch space for the forward FFT, so it never allocates on the audio thread
+    fft_scratch: Vec<Complex32>,
+
+    /// Scratch space for the inverse FFT
+    ifft_scratch: Vec<Complex32>,
+
+    /// A square root Hann window, applied before the FFT and again after the inverse FFT
+    // Together that's a Hann window, and Hann windows a quarter frame apart add up to a
+    // constant, so with no magic the output is the input, just delayed
+    window: Vec<f32>,
+}
+
+impl SpectralMagic {
+    /// Create the spectral engine, this allocates all of its buffers
+    pub fn new() -> Self {
+        let mut planner = RealFftPlanner::new();
+        let fft = planner.plan_fft_forward(FFT_SIZE);
+        let ifft = planner.plan_fft_inverse(FFT_SIZE);
+        let window = (0..FFT_SIZE)
+            .map(|i| (0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos()).sqrt())
+            .collect();
+
+        Self {
+            channels: std::array::from_fn(|_| SpectralChannel::new()),
+            saturation: 0.0,
+            frame: fft.make_input_vec(),
+            spectrum: fft.make_output_vec(),
+            fft_scratch: fft.make_scratch_vec(),
+            ifft_scratch: ifft.make_scratch_vec(),
+            fft,
+            ifft,
+            window,
+        }
+    }
+
+    /// Clear every channel
+    pub fn reset(&mut self) {
+        self.channels.iter_mut().for_each(SpectralChannel::reset);
+    }
+
+    /// Set the character of the soft clipper on the output, see `saturation::soft_clip()`
+    pub fn set_saturation(&mut self, character: f32) {
+        self.saturation = character;
+    }
+
+    /// Process a single sample of the given channel, the output is `LATENCY_SAMPLES` late
+    // The magic amount is picked up once per hop, at the start of every new frame
+    pub fn process(&mut self, channel: usize, sample: f32, magic_amount: f32) -> f32 {
+        let state = &mut self.channels[channel];
+        state.input[FFT_SIZE - HOP_SIZE + state.position] = sample;
+        let output = state.output[state.position];
+
+        state.position += 1;
+        if state.position == HOP_SIZE {
+            state.position = 0;
+            self.process_frame(channel, magic_amount);
+        }
+
+        // Clipped as much as the time-domain engine at full magic, and not at all without it
+        output + (saturation::soft_clip(output, self.saturation) - output) * magic_amount.clamp(0.0, 1.0)
+    }
+
+    /// Run the most recent frame of a channel through the FFT and the fractal
+    fn process_frame(&mut self, channel: usize, magic_amount: f32) {
+        let state = &mut self.channels[channel];
+        for ((frame, input), window) in self.frame.iter_mut().zip(&state.input).zip(&self.window) {
+            *frame = input * window;
+        }
+
+        // The buffers always have the sizes the FFTs were planned with, so these can't fail
+        let transformed = self
+            .fft
+            .process_with_scratch(&mut self.frame, &mut self.spectrum, &mut self.fft_scratch)
+            .is_ok();
+        if transformed {
+            shape_spectrum(&mut self.spectrum, state, magic_amount);
+
+            // The inverse FFT insists on real DC and Nyquist bins
+            self.spectrum[0].im = 0.0;
+            self.spectrum[NUM_BINS - 1].im = 0.0;
+            if self
+                .ifft
+                .process_with_scratch(&mut self.spectrum, &mut self.frame, &mut self.ifft_scratch)
+                .is_err()
+            {
+                self.frame.fill(0.0);
+            }
+        } else {
+            self.frame.fill(0.0);
+        }
+
+        // The FFTs scale by the frame size, and the overlapping Hann windows add up to 2
+        let scale = 1.0 / (2.0 * FFT_SIZE as f32);
+        for ((accumulated, frame), window) in state.accumulator.iter_mut().zip(&self.frame).zip(&self.window) {
+            *accumulated += frame * window * scale;
+        }
+
+        // The first hop has been through all of its frames now, the rest moves up
+        state.output.copy_from_slice(&state.accumulator[..HOP_SIZE]);
+        state.accumulator.copy_within(HOP_SIZE.., 0);
+        state.accumulator[FFT_SIZE - HOP_SIZE..].fill(0.0);
+        state.input.copy_within(HOP_SIZE.., 0);
+    }
+}
+
+/// Filter, rotate and smear the bins of one frame with the Julia set
+fn shape_spectrum(spectrum: &mut [Complex32], state: &mut SpectralChannel, magic_amount: f32) {
+    // Without magic the bins pass untouched, but the smearing keeps track of them so
+    // turning the magic up doesn't start it from silence
+    if magic_amount <= 0.001 {
+        for (bin, magnitude) in spectrum.iter().zip(state.magnitudes.iter_mut()) {
+            *magnitude = bin.norm();
+        }
+        return;
+    }
+
+    // The bins are measured against the loudest one, so the shape doesn't depend on the level
+    let peak = spectrum.iter().fold(0.0f32, |peak, bin| peak.max(bin.norm()));
+    if peak < 1e-9 {
+        state.magnitudes.fill(0.0);
+        return;
+    }
+
+    // c circles the time-domain Julia set's constant
+    state.c_phase = (state.c_phase + C_ORBIT_STEP) % (2.0 * PI);
+    let c_real = 0.285 + 0.01 * state.c_phase.sin();
+    let c_imag = 0.01 + 0.01 * state.c_phase.cos();
+
+    let iterations = 1 + (magic_amount * (MAX_ITERATIONS - 1) as f32) as usize;
+    let smear = magic_amount * MAX_SMEAR;
+    for (bin, previous) in spectrum.iter_mut().zip(state.magnitudes.iter_mut()) {
+        let magnitude = bin.norm();
+        let phase = bin.arg();
+
+        // The loudest bin starts at a radius of 1.5, right where the Julia set frays out.
+        // The square root spreads the quiet bins out, so they don't all pile up in the middle.
+        let radius = 1.5 * (magnitude / peak).sqrt();
+        let (mut z_real, mut z_imag) = (radius * phase.cos(), radius * phase.sin());
+        let mut bounded_for = iterations;
+        for iteration in 0..iterations {
+            let next_real = z_real * z_real - z_imag * z_imag + c_real;
+            z_imag = 2.0 * z_real * z_imag + c_imag;
+            z_real = next_real;
+            if z_real * z_real + z_imag * z_imag > 4.0 {
+                bounded_for = iteration;
+                break;
+            }
+        }
+
+        // From 1 - magic for bins that escape right away to 1 + magic for bins in the set
+        let inside = bounded_for as f32 / iterations as f32;
+        let shaped = magnitude * (1.0 - magic_amount + 2.0 * magic_amount * inside);
+        *previous = shaped + (*previous - shaped) * smear;
+
+        // Where the orbit ended up turns the phase
+        let rotation = magic_amount * z_imag.atan2(z_real);
+        *bin = Complex32::from_polar(*previous, phase + rotation);
+    }
+}
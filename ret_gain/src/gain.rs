@@ -1,17 +1,59 @@
-/// A simple gain processor module
-// This struct is responsible for a single effect: adjusting the volume (gain) of audio samples
-pub struct GainProcessor {}
+// The number of channels we keep separate state for
+use crate::MAX_CHANNELS;
+
+/// How long flipping a channel's polarity takes, in milliseconds
+// An instant flip would click, this fades through silence and back so quickly it can't be heard
+const POLARITY_FADE_MS: f32 = 5.0;
+
+/// The gain stage, with stereo pan, balance and polarity switches for the output
+// The input trim only uses `process()`, which is still a bare multiply. The output goes
+// through `process_frame()` instead, which needs both channels of a frame at once since
+// panning moves signal from one side to the other.
+pub struct GainProcessor {
+    /// The current polarity of every channel, moving between 1.0 (normal) and -1.0 (inverted)
+    // Kept as a smoothed value instead of a bool so flipping it doesn't click
+    polarity: [f32; MAX_CHANNELS],
+
+    /// The polarity every channel is moving towards
+    polarity_targets: [f32; MAX_CHANNELS],
+
+    /// How far the polarity moves per sample while it's flipping
+    polarity_step: f32,
+}
 
 impl GainProcessor {
     /// Create a new gain processor
     // Constructor method that creates a new instance of GainProcessor
     // In Rust, constructors are just regular methods (usually named "new") that return Self
     pub fn new() -> Self {
-        // The empty curly braces {} create a new instance of the struct
-        // Since our struct has no fields, this is an empty struct
-        Self {}
+        let mut gain_processor = Self {
+            polarity: [1.0; MAX_CHANNELS],
+            polarity_targets: [1.0; MAX_CHANNELS],
+            polarity_step: 1.0,
+        };
+        gain_processor.set_sample_rate(44100.0); // Default sample rate, will be updated
+
+        gain_processor
     }
-    
+
+    /// Set the sample rate for the polarity fade
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        // The polarity goes all the way from 1.0 to -1.0 within the fade time
+        self.polarity_step = 2.0 / ((POLARITY_FADE_MS / 1000.0) * sample_rate).max(1.0);
+    }
+
+    /// Set which channels have their polarity inverted
+    pub fn set_phase_invert(&mut self, invert: [bool; MAX_CHANNELS]) {
+        for (target, invert) in self.polarity_targets.iter_mut().zip(invert) {
+            *target = if invert { -1.0 } else { 1.0 };
+        }
+    }
+
+    /// Jump straight to the polarity that's set, without fading
+    pub fn reset(&mut self) {
+        self.polarity = self.polarity_targets;
+    }
+
     /// Process a sample with gain
     // This method applies gain (volume adjustment) to a single audio sample
     // &self means this method doesn't modify the GainProcessor instance
@@ -22,4 +64,50 @@ impl GainProcessor {
         // This is the entire DSP (Digital Signal Processing) algorithm for gain!
         sample * gain
     }
-}
\ No newline at end of file
+
+    /// Process one frame of all channels with gain, pan, balance and the polarity switches
+    // `gain`, `pan` and `balance` should be smoothed values, they're worked out again for
+    // every frame. `pan` and `balance` go from -1.0 (left) to 1.0 (right) and are ignored
+    // for mono signals.
+    pub fn process_frame(&mut self, frame: &mut [f32], gain: f32, pan: f32, balance: f32) {
+        // Move towards the polarity that's set, a few samples at a time
+        for (polarity, target) in self.polarity.iter_mut().zip(self.polarity_targets) {
+            if *polarity < target {
+                *polarity = (*polarity + self.polarity_step).min(target);
+            } else if *polarity > target {
+                *polarity = (*polarity - self.polarity_step).max(target);
+            }
+        }
+
+        for (sample, polarity) in frame.iter_mut().zip(self.polarity) {
+            *sample = self.process(*sample, gain) * polarity;
+        }
+
+        if let [left, right, ..] = frame {
+            (*left, *right) = pan_stereo(*left, *right, pan);
+            (*left, *right) = balance_stereo(*left, *right, balance);
+        }
+    }
+}
+
+/// Pan a stereo signal, moving one channel's signal into the other with a constant-power law
+// Panning right moves the left channel over to the right: at 1.0 both channels come out of
+// the right speaker. The channel that's being moved keeps its power on the way over (cos
+// and sin of the same angle), so it doesn't dip in the middle. At 0.0 nothing changes.
+pub fn pan_stereo(left: f32, right: f32, pan: f32) -> (f32, f32) {
+    let angle = pan.clamp(-1.0, 1.0).abs() * std::f32::consts::FRAC_PI_2;
+    let (moved, stays) = angle.sin_cos();
+    if pan >= 0.0 {
+        (left * stays, right + left * moved)
+    } else {
+        (left + right * moved, right * stays)
+    }
+}
+
+/// Turn one side of a stereo signal down, like the balance knob on a stereo amp
+// Turning it right turns the left channel down, at 1.0 only the right channel is left.
+// Unlike panning nothing is moved over, so the stereo image stays the same.
+pub fn balance_stereo(left: f32, right: f32, balance: f32) -> (f32, f32) {
+    let balance = balance.clamp(-1.0, 1.0);
+    ((1.0 - balance.max(0.0)) * left, (1.0 + balance.min(0.0)) * right)
+}
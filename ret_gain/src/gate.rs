@@ -0,0 +1,105 @@
+// The attack and release times are turned into one-pole coefficients like the envelope follower's
+use crate::envelope::smoothing_coeff;
+// The threshold is set in dB
+use crate::decibels;
+
+/// The threshold at which the gate is switched off, in dB
+// The bottom of the threshold range, nothing real is quieter than this anyway
+pub const GATE_OFF_DB: f32 = -80.0;
+
+/// A stereo-linked noise gate before the effect chain
+// Heavy drive turns the input's noise floor into a loud hiss, so the gate shuts the
+// input off whenever it falls below the threshold. It opens with the attack time as
+// soon as the level gets over the threshold, stays open for the hold time after it
+// drops back below, and then closes with the release time.
+pub struct Gate {
+    /// The level the input has to reach to open the gate, as a linear gain
+    // 0.0 when the gate is switched off
+    threshold: f32,
+
+    /// The gain currently applied to the input (0.0 to 1.0)
+    gain: f32,
+
+    /// How many more samples the gate stays open for
+    hold_counter: usize,
+
+    /// The hold time in samples
+    hold_samples: usize,
+
+    /// Coefficient of the one-pole smoother while the gate opens
+    attack_coeff: f32,
+
+    /// Coefficient of the one-pole smoother while the gate closes
+    release_coeff: f32,
+
+    /// Sample rate for time-based calculations
+    sample_rate: f32,
+}
+
+impl Gate {
+    /// Create a new gate, switched off
+    pub fn new() -> Self {
+        Self {
+            threshold: 0.0,
+            gain: 1.0,
+            hold_counter: 0,
+            hold_samples: 0,
+            attack_coeff: 1.0,
+            release_coeff: 1.0,
+            sample_rate: 44100.0, // Default sample rate, will be updated
+        }
+    }
+
+    /// Set the sample rate for time-based calculations
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Set the threshold in dB, `GATE_OFF_DB` switches the gate off
+    pub fn set_threshold_db(&mut self, threshold_db: f32) {
+        self.threshold = if threshold_db <= GATE_OFF_DB {
+            0.0
+        } else {
+            decibels::db_to_gain(threshold_db)
+        };
+    }
+
+    /// Set the attack, hold and release times in milliseconds
+    pub fn set_times(&mut self, attack_ms: f32, hold_ms: f32, release_ms: f32) {
+        self.attack_coeff = smoothing_coeff(attack_ms, self.sample_rate);
+        self.hold_samples = ((hold_ms / 1000.0) * self.sample_rate).round() as usize;
+        self.release_coeff = smoothing_coeff(release_ms, self.sample_rate);
+    }
+
+    /// Start from an open gate again
+    pub fn reset(&mut self) {
+        self.gain = 1.0;
+        self.hold_counter = 0;
+    }
+
+    /// Feed the level of the next frame and return the gain to apply to it
+    // `level` should be the peak of all channels, so the gate opens and closes on both
+    // sides at once
+    pub fn process(&mut self, level: f32) -> f32 {
+        // The gate is switched off, everything passes
+        if self.threshold <= 0.0 {
+            self.gain = 1.0;
+            return 1.0;
+        }
+
+        if level >= self.threshold {
+            self.hold_counter = self.hold_samples;
+        }
+
+        // Open while above the threshold or holding, closed after that
+        let (target, coeff) = if level >= self.threshold || self.hold_counter > 0 {
+            (1.0, self.attack_coeff)
+        } else {
+            (0.0, self.release_coeff)
+        };
+        self.hold_counter = self.hold_counter.saturating_sub(1);
+        self.gain += (target - self.gain) * coeff;
+
+        self.gain
+    }
+}
@@ -0,0 +1,237 @@
+// Import the NIH-plug prelude for the Enum derive macro
+#[cfg(feature = "plugin")]
+use nih_plug::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+/// The level of the oscillator going into the chain, -6 dBFS
+// Leaves the drive and the fractal some room before everything clips
+const LEVEL: f32 = 0.5;
+
+/// The most notes the generator keeps track of at once
+// Only the latest one sounds, the others are remembered so letting go of it falls back to them
+const MAX_HELD_NOTES: usize = 16;
+
+/// How long the played voice takes to fade in and out, in milliseconds
+// Just long enough to not click
+const GATE_FADE_MS: f32 = 5.0;
+
+/// The scale the chaotic pitch is pulled onto
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum Scale {
+    /// Every pitch in between, the chaos glides freely
+    #[cfg_attr(feature = "plugin", id = "free")]
+    #[cfg_attr(feature = "plugin", name = "Free")]
+    Free,
+    /// Every semitone
+    #[cfg_attr(feature = "plugin", id = "chromatic")]
+    #[cfg_attr(feature = "plugin", name = "Chromatic")]
+    Chromatic,
+    #[cfg_attr(feature = "plugin", id = "major")]
+    #[cfg_attr(feature = "plugin", name = "Major")]
+    Major,
+    /// Natural minor
+    #[cfg_attr(feature = "plugin", id = "minor")]
+    #[cfg_attr(feature = "plugin", name = "Minor")]
+    Minor,
+    /// Minor pentatonic, hard to make sound wrong
+    #[cfg_attr(feature = "plugin", id = "pentatonic")]
+    #[cfg_attr(feature = "plugin", name = "Pentatonic")]
+    Pentatonic,
+}
+
+impl Scale {
+    /// The steps of the scale within an octave, in semitones above the root
+    // `None` means no quantization at all
+    fn steps(self) -> Option<&'static [f32]> {
+        match self {
+            Scale::Free => None,
+            Scale::Chromatic => Some(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0]),
+            Scale::Major => Some(&[0.0, 2.0, 4.0, 5.0, 7.0, 9.0, 11.0]),
+            Scale::Minor => Some(&[0.0, 2.0, 3.0, 5.0, 7.0, 8.0, 10.0]),
+            Scale::Pentatonic => Some(&[0.0, 3.0, 5.0, 7.0, 10.0]),
+        }
+    }
+
+    /// Pull an offset in semitones above the root onto the nearest step of the scale
+    pub fn quantize(self, semitones: f32) -> f32 {
+        let Some(steps) = self.steps() else {
+            return semitones;
+        };
+
+        let octave = (semitones / 12.0).floor();
+        let within_octave = semitones - octave * 12.0;
+        // The root of the next octave up counts as a step too, so the top of the octave rounds up to it
+        let nearest = steps
+            .iter()
+            .copied()
+            .chain(std::iter::once(12.0))
+            .min_by(|a, b| (a - within_octave).abs().total_cmp(&(b - within_octave).abs()))
+            .unwrap_or(0.0);
+
+        octave * 12.0 + nearest
+    }
+}
+
+/// The oscillator's frequency for a base pitch moved by the chaos signal
+// The chaos signal (-1.0 to 1.0) moves the pitch up to `range` semitones either way, and
+// the result is pulled onto the scale, with the base pitch as its root
+#[cfg(feature = "plugin")]
+pub fn chaotic_frequency(base_hz: f32, chaos_signal: f32, range: f32, scale: Scale) -> f32 {
+    let semitones = scale.quantize(chaos_signal.clamp(-1.0, 1.0) * range);
+    base_hz * 2.0f32.powf(semitones / 12.0)
+}
+
+/// A sine oscillator that becomes the sound source in generator mode
+// The plugin feeds the output of its chain back into the oscillator's phase, so with
+// some magic the fractal folds its own output back into the tone and it starts to
+// scream on its own, like a feedback loop through a pedal board
+pub struct Generator {
+    /// The phase of every channel's oscillator, in cycles
+    phases: [f32; MAX_CHANNELS],
+
+    /// The chain's latest output for every channel, fed back into the phase
+    feedback: [f32; MAX_CHANNELS],
+
+    /// Sample rate for converting the frequency to a phase increment
+    sample_rate: f32,
+
+    /// The MIDI notes held down, in the order they were pressed
+    // Only the first `num_held_notes` of them are held, the rest are left over
+    held_notes: [u8; MAX_HELD_NOTES],
+    num_held_notes: usize,
+
+    /// The note the played voice is on its way to, NaN before the first note
+    voice_note: f32,
+
+    /// How many semitones the played pitch is still away from `voice_note`
+    // Decays towards zero instead of moving the pitch towards the note, so the glide
+    // can't get stuck a rounding error away from it
+    glide_offset: f32,
+
+    /// How much of the glide offset is left after every sample
+    glide_decay: f32,
+
+    /// The level of the played voice, fading in while a note is held and out when it's not
+    gate: f32,
+}
+
+impl Generator {
+    /// Create a generator with every oscillator at the start of its cycle
+    pub fn new() -> Self {
+        Self {
+            phases: [0.0; MAX_CHANNELS],
+            feedback: [0.0; MAX_CHANNELS],
+            sample_rate: 44100.0, // Default sample rate, will be updated
+            held_notes: [0; MAX_HELD_NOTES],
+            num_held_notes: 0,
+            voice_note: f32::NAN,
+            glide_offset: 0.0,
+            glide_decay: 0.0,
+            gate: 0.0,
+        }
+    }
+
+    /// Set the sample rate the oscillators run at
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Start every oscillator over and forget the feedback
+    // The held notes are left alone, the keys are still down after all
+    pub fn reset(&mut self) {
+        self.phases = [0.0; MAX_CHANNELS];
+        self.feedback = [0.0; MAX_CHANNELS];
+    }
+
+    /// Forget every held note and silence the played voice
+    pub fn release_notes(&mut self) {
+        self.num_held_notes = 0;
+        self.voice_note = f32::NAN;
+        self.glide_offset = 0.0;
+        self.gate = 0.0;
+    }
+
+    /// Set how long the played pitch takes to glide to a new note, in milliseconds
+    // 0 ms jumps straight to it
+    pub fn set_glide(&mut self, glide_ms: f32) {
+        self.glide_decay = if glide_ms > 0.0 {
+            (-1.0 / ((glide_ms / 1000.0) * self.sample_rate)).exp()
+        } else {
+            0.0
+        };
+    }
+
+    /// A MIDI note was pressed, it becomes the one the voice plays
+    pub fn note_on(&mut self, note: u8) {
+        self.note_off(note);
+        if self.num_held_notes == MAX_HELD_NOTES {
+            // Forget the oldest note to make room
+            self.held_notes.copy_within(1.., 0);
+            self.num_held_notes -= 1;
+        }
+        self.held_notes[self.num_held_notes] = note;
+        self.num_held_notes += 1;
+    }
+
+    /// A MIDI note was let go, the voice falls back to the latest note still held
+    pub fn note_off(&mut self, note: u8) {
+        let held = &self.held_notes[..self.num_held_notes];
+        if let Some(index) = held.iter().position(|held_note| *held_note == note) {
+            self.held_notes.copy_within(index + 1..self.num_held_notes, index);
+            self.num_held_notes -= 1;
+        }
+    }
+
+    /// Move the played voice on by one sample
+    // Returns the voice's frequency in Hz and its level (0.0 to 1.0). Once every note is let go
+    // the pitch stays where it was while the level fades out.
+    pub fn next_voice(&mut self) -> (f32, f32) {
+        let latest_note = self.held_notes[..self.num_held_notes].last().map(|note| *note as f32);
+        if let Some(note) = latest_note.filter(|note| *note != self.voice_note) {
+            // Start the glide from wherever the pitch is now, the very first note has nothing to glide from
+            if !self.voice_note.is_nan() {
+                self.glide_offset += self.voice_note - note;
+            }
+            self.voice_note = note;
+        }
+        self.glide_offset *= self.glide_decay;
+
+        let target_gate = if latest_note.is_some() { 1.0 } else { 0.0 };
+        let gate_step = 1000.0 / (GATE_FADE_MS * self.sample_rate);
+        self.gate = if target_gate > self.gate {
+            (self.gate + gate_step).min(target_gate)
+        } else {
+            (self.gate - gate_step).max(target_gate)
+        };
+
+        if self.voice_note.is_nan() {
+            (0.0, 0.0)
+        } else {
+            // The same tuning as `util::midi_note_to_freq()`, but for the notes in between
+            let pitch = self.voice_note + self.glide_offset;
+            (440.0 * 2.0f32.powf((pitch - 69.0) / 12.0), self.gate)
+        }
+    }
+
+    /// Hand the chain's output of a channel back to the oscillator
+    // Clamped, so a loud chain can't wind the phase around more than half a cycle
+    pub fn feed_back(&mut self, channel: usize, sample: f32) {
+        self.feedback[channel] = sample.clamp(-1.0, 1.0);
+    }
+
+    /// The next sample of a channel's oscillator
+    // `feedback_depth` (0.0 to 1.0) is how far the fed back signal moves the phase, up to
+    // half a cycle either way
+    pub fn process(&mut self, channel: usize, frequency: f32, feedback_depth: f32) -> f32 {
+        let phase = &mut self.phases[channel];
+        let output = (TAU * *phase + self.feedback[channel] * feedback_depth * PI).sin() * LEVEL;
+
+        *phase = (*phase + frequency / self.sample_rate).rem_euclid(1.0);
+
+        output
+    }
+}
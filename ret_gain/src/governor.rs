@@ -0,0 +1,111 @@
+// The two systems the governor supervises
+use crate::chaos::ChaosAttractor;
+use crate::fractal::FractalMagic;
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+/// How long the governor looks back, in seconds
+// Long enough that a single escape of z doesn't count for much, short enough to react
+// within a few cycles of a low note
+const WINDOW_SECONDS: f32 = 0.05;
+
+/// How quickly the feedback and the coupling come back once the systems calm down, in seconds
+// Much slower than the window, so they don't pump straight back into the next blow-up
+const RECOVERY_SECONDS: f32 = 0.5;
+
+/// How quickly the governor steps in, in seconds
+// Fast, but not a jump, which would click in the feedback path
+const ATTACK_SECONDS: f32 = 0.002;
+
+/// The share of samples z may escape on before the governor steps in, and where it takes everything
+// A Julia orbit on the edge of the set gets away now and then on its own, that's part of the sound
+const SAFE_ESCAPE_RATE: f32 = 0.02;
+const CRITICAL_ESCAPE_RATE: f32 = 0.2;
+
+/// How far out the chaotic system may get before the governor steps in, and where it takes everything
+// Relative to the system's limit, the Lorenz attractor stays below about half of it on its own
+const SAFE_MAGNITUDE: f32 = 0.6;
+const CRITICAL_MAGNITUDE: f32 = 0.9;
+
+/// What the governor measured on one channel
+#[derive(Clone, Copy, Default)]
+struct GovernorState {
+    /// The share of the latest samples on which the fractal's z got away
+    escape_rate: f32,
+
+    /// The largest magnitude of the chaotic system within the window
+    magnitude: f32,
+
+    /// How much of the feedback and the coupling is taken away right now, from 0.0 to 1.0
+    cut: f32,
+}
+
+/// Keeps the coupled fractal and chaos effect away from blowing up
+// The fractal's feedback and the coupling between the two effects are what can push the
+// loop over the edge, so instead of waiting until z gets away or the chaotic system hits
+// its limit, the governor watches how often z escapes and how far out the system gets
+// over a short sliding window, and takes the feedback and the coupling back as the two
+// approach their limits. Both come back slowly once things calm down. The rescaling in
+// the fractal and the clamps in the chaotic systems are still there, but only as the
+// last resort for what the input does on its own.
+pub struct StabilityGovernor {
+    /// One state per channel
+    channels: [GovernorState; MAX_CHANNELS],
+
+    /// How much of the previous window every new sample keeps
+    window: f32,
+
+    /// The smoothing while the cut grows, and while it shrinks again
+    attack: f32,
+    recovery: f32,
+}
+
+impl StabilityGovernor {
+    /// Create a new stability governor
+    pub fn new() -> Self {
+        let mut governor = Self {
+            channels: [GovernorState::default(); MAX_CHANNELS],
+            window: 0.0,
+            attack: 0.0,
+            recovery: 0.0,
+        };
+        governor.set_sample_rate(44100.0); // Default sample rate, will be updated
+
+        governor
+    }
+
+    /// Set the sample rate the window and the smoothing depend on
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.window = (-1.0 / (WINDOW_SECONDS * sample_rate)).exp();
+        self.attack = (-1.0 / (ATTACK_SECONDS * sample_rate)).exp();
+        self.recovery = (-1.0 / (RECOVERY_SECONDS * sample_rate)).exp();
+    }
+
+    /// Forget what was measured and give the feedback and the coupling back in full
+    pub fn reset(&mut self) {
+        self.channels = [GovernorState::default(); MAX_CHANNELS];
+    }
+
+    /// Look at where a channel's fractal and chaotic system are, and rein the fractal's feedback in
+    // Called on every sample before the two effects run. Returns the stability, from 1.0
+    // when everything runs freely down to 0.0 when the loop is about to blow up, which
+    // the coupling should be multiplied with.
+    pub fn supervise(&mut self, channel: usize, chaos: &ChaosAttractor, fractal: &mut FractalMagic) -> f32 {
+        let state = &mut self.channels[channel];
+
+        // The escape rate is the average over the window, the magnitude its peak
+        let escaped = if fractal.escaped(channel) { 1.0 } else { 0.0 };
+        state.escape_rate = escaped + (state.escape_rate - escaped) * self.window;
+        state.magnitude = chaos.magnitude(channel).max(state.magnitude * self.window);
+
+        // Nothing is taken until one of the two gets past its safe point, everything once it's critical
+        let pressure = |value: f32, safe: f32, critical: f32| ((value - safe) / (critical - safe)).clamp(0.0, 1.0);
+        let target = pressure(state.escape_rate, SAFE_ESCAPE_RATE, CRITICAL_ESCAPE_RATE)
+            .max(pressure(state.magnitude, SAFE_MAGNITUDE, CRITICAL_MAGNITUDE));
+        let smoothing = if target > state.cut { self.attack } else { self.recovery };
+        state.cut = target + (state.cut - target) * smoothing;
+
+        fractal.set_feedback_cut(channel, state.cut);
+        1.0 - state.cut
+    }
+}
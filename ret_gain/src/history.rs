@@ -0,0 +1,112 @@
+// Snapshots reuse the preset format, which already captures every parameter by ID
+use crate::plugin::RetardedGainParams;
+use crate::presets::Preset;
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+
+/// How many steps can be undone
+// Each step is a full snapshot of a few dozen values, so this costs next to nothing
+const MAX_HISTORY: usize = 100;
+
+/// The complete editable state of the plugin at one point in time
+// Besides the parameters this includes internal state that the host doesn't know about,
+// like the chaos seed, so undo works for those too
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    /// The plain values of all parameters
+    pub values: Preset,
+
+    /// The seed of the chaos effect's starting conditions
+    pub chaos_seed: u32,
+}
+
+impl Snapshot {
+    /// Capture the current state of the parameters
+    pub fn capture(params: &RetardedGainParams) -> Self {
+        Self {
+            values: Preset::from_params(String::from("Undo"), params),
+            chaos_seed: params.chaos_seed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Undo and redo stacks for changes made in the editor
+// This lives on the GUI thread only. Every gesture (dragging a slider, clicking a button,
+// loading a preset) pushes the state from before the gesture, and undoing swaps the
+// current state with the top of the undo stack.
+pub struct History {
+    /// States to go back to, the most recent one at the back
+    undo: VecDeque<Snapshot>,
+
+    /// States that were undone, the most recent one at the back
+    redo: Vec<Snapshot>,
+
+    /// The state from before the gesture that's currently in progress
+    gesture_start: Option<Snapshot>,
+
+    /// How many parameters are currently being changed
+    // A single gesture can change several parameters at once (the XY pad, loading a
+    // preset), those only count as one step once all of them have ended
+    open_gestures: usize,
+}
+
+impl History {
+    /// Create an empty history
+    pub fn new() -> Self {
+        Self {
+            undo: VecDeque::with_capacity(MAX_HISTORY),
+            redo: Vec::new(),
+            gesture_start: None,
+            open_gestures: 0,
+        }
+    }
+
+    /// A parameter gesture started, `current` is the state before the change
+    pub fn begin_gesture(&mut self, current: Snapshot) {
+        if self.open_gestures == 0 {
+            self.gesture_start = Some(current);
+        }
+        self.open_gestures += 1;
+    }
+
+    /// A parameter gesture ended, `current` is the state after the change
+    // Gestures that didn't change anything (clicking a slider without moving it) are ignored
+    pub fn end_gesture(&mut self, current: Snapshot) {
+        self.open_gestures = self.open_gestures.saturating_sub(1);
+        if self.open_gestures > 0 {
+            return;
+        }
+
+        if let Some(start) = self.gesture_start.take() {
+            if start != current {
+                self.push(start);
+            }
+        }
+    }
+
+    /// Remember a state to go back to
+    // Any new change makes the undone states unreachable, so the redo stack is cleared
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.undo.len() == MAX_HISTORY {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(snapshot);
+        self.redo.clear();
+    }
+
+    /// Step back, returning the state to restore
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.undo.pop_back()?;
+        self.redo.push(current);
+
+        Some(snapshot)
+    }
+
+    /// Step forward again after an undo, returning the state to restore
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.redo.pop()?;
+        self.undo.push_back(current);
+
+        Some(snapshot)
+    }
+}
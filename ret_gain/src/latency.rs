@@ -0,0 +1,96 @@
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+// The stages that add latency
+use crate::fractal::FractalMagic;
+use crate::limiter::Limiter;
+use crate::oversampling::Oversampling;
+
+/// How much latency every latency-adding stage of the chain adds, in samples at the host's rate
+// The one place the plugin works its latency out, so the value reported to the host, the dry
+// delay and the bypass delay can't disagree about it. Every stage only counts while it's in
+// the chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyReport {
+    /// The up- and downsampling filters of the oversampler
+    pub oversampling: u32,
+
+    /// The fractal effect, the smoother's lookahead or the spectral engine's frame
+    pub fractal: u32,
+
+    /// The output limiter's lookahead
+    pub limiter: u32,
+}
+
+impl LatencyReport {
+    /// Work out the latency of a chain with this oversampling, fractal effect and limiter
+    // The fractal effect runs inside the oversampler, so its latency is counted in oversampled
+    // samples. Both of its engines' latencies are multiples of every oversampling factor.
+    // `limiter` is `None` while the limiter isn't in the chain.
+    pub fn new(oversampling: Oversampling, fractal_magic: &FractalMagic, limiter: Option<&Limiter>) -> Self {
+        Self {
+            oversampling: oversampling.latency_samples(),
+            fractal: fractal_magic.latency() as u32 / oversampling.factor() as u32,
+            limiter: limiter.map_or(0, Limiter::latency_samples),
+        }
+    }
+
+    /// The latency of the wet signal before the limiter, which the dry signal is delayed by for the mix
+    pub fn wet(&self) -> u32 {
+        self.oversampling + self.fractal
+    }
+
+    /// The latency of the whole chain, which is reported to the host
+    pub fn total(&self) -> u32 {
+        self.wet() + self.limiter
+    }
+}
+
+/// A per-channel delay line used to keep unprocessed signals aligned with latency-adding stages
+// The dry signal for the mix has to be delayed by the same amount as the oversampled wet signal,
+// otherwise blending the two causes comb filtering
+pub struct CompensationDelay {
+    /// Ring buffers for every channel, allocated once up front
+    buffers: [Vec<f32>; MAX_CHANNELS],
+
+    /// The current write position in the ring buffers
+    write_pos: [usize; MAX_CHANNELS],
+
+    /// The current delay in samples
+    delay: usize,
+}
+
+impl CompensationDelay {
+    /// Create a new delay line that can delay signals by up to `max_delay` samples
+    pub fn new(max_delay: usize) -> Self {
+        Self {
+            buffers: std::array::from_fn(|_| vec![0.0; max_delay + 1]),
+            write_pos: [0; MAX_CHANNELS],
+            delay: 0,
+        }
+    }
+
+    /// Set the delay in samples, clamped to the capacity given to `new()`
+    pub fn set_delay(&mut self, delay: usize) {
+        self.delay = delay.min(self.buffers[0].len() - 1);
+    }
+
+    /// Clear the buffered samples
+    pub fn reset(&mut self) {
+        for buffer in self.buffers.iter_mut() {
+            buffer.fill(0.0);
+        }
+    }
+
+    /// Push a sample into the given channel's delay line and return the delayed sample
+    pub fn process(&mut self, channel: usize, sample: f32) -> f32 {
+        let buffer = &mut self.buffers[channel];
+        let len = buffer.len();
+        let write_pos = self.write_pos[channel];
+
+        buffer[write_pos] = sample;
+        let read_pos = (write_pos + len - self.delay) % len;
+        self.write_pos[channel] = (write_pos + 1) % len;
+
+        buffer[read_pos]
+    }
+}
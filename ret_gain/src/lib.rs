@@ -1,24 +1,134 @@
 // Re-export modules
 // These "mod" statements tell Rust to include these files as modules in our crate
 // Similar to JavaScript imports or Python imports, but they define the module structure
+// The plugin, its editor and everything that only exists for them are behind the default
+// `plugin` feature. Without it the processors build without NIH-plug and VIZIA, so a game
+// or an offline renderer can use them (with the `dsp` feature for the slice methods).
+#[cfg(feature = "plugin")]
 mod editor;      // The GUI editor implementation
 mod gain;        // The gain effect processor
+mod gate;        // Noise gate before the effect chain
 mod distortion;  // The distortion effect processor
+mod filter;      // The tone filter after the distortion
 mod fractal;     // The fractal-based effect processor
 mod chaos;       // The chaos/lorenz attractor effect
+mod compressor;  // Feed-forward compressor after the dry/wet mix
+mod crusher;     // Bitcrusher and sample rate reducer after the effect chain
+mod delay;       // Stereo feedback delay after the dry/wet mix
+mod reverb;      // Algorithmic reverb at the end of the chain
+mod tilt;        // Tilt EQ for a quick tonal correction after the reverb
+mod dc_blocker;  // High pass that removes DC offset before the limiter
+mod width;       // Stereo width control with all-pass decorrelation
+mod oversampling; // Oversampling for the non-linear stages
+#[cfg(feature = "plugin")]
+mod presets;     // Factory and user presets
+#[cfg(feature = "plugin")]
+mod analyzer;    // FFT spectrum analyzer shared between the audio thread and the GUI
+#[cfg(feature = "plugin")]
+mod metering;    // True-peak meters shared with the GUI
+mod latency;     // Delay lines for keeping signals aligned with latency-adding stages
+mod limiter;     // Lookahead brickwall output limiter
+mod clipper;     // Hard clipper with a clip indicator at the very end
+#[cfg(feature = "plugin")]
+mod export;      // Renders the chaos and fractal effects to WAV files in the background
+#[cfg(feature = "plugin")]
+mod engine_state; // Snapshots of the chaos and fractal effects, saved with the project
+mod generator;   // The oscillator that replaces the input in generator mode
+mod modulation;  // Modulation sources like the tempo-synced LFO
+mod envelope;    // Envelope follower that lets the input level drive the effects
+#[cfg(feature = "plugin")]
+mod midi;        // MIDI learn for controlling parameters with hardware controllers
+#[cfg(feature = "plugin")]
+mod morph;       // Morphing between two snapshots of the continuous parameters
+#[cfg(feature = "plugin")]
+mod macros;      // Macro knobs that drive several parameters at once
+#[cfg(feature = "plugin")]
+mod migration;   // Versioned plugin state and migrations for old projects
+#[cfg(feature = "plugin")]
+mod history;     // Undo and redo for changes made in the editor
+#[cfg(feature = "plugin")]
+mod randomize;   // Weighted randomization of the main effect parameters
+mod routing;     // The configurable order of the effect chain
+mod ms;          // Mid/side encoding and the per-stage balance
+mod crossover;   // 3-band Linkwitz-Riley crossover for multiband processing
+mod saturation;  // The soft clipper shared by every limiting point in the chain
+mod decibels;    // Conversions between dB and linear gains for the processors
+mod coupling;    // Lets the fractal and the chaos effect drive each other
+mod governor;    // Keeps the coupled fractal and chaos effect from blowing up
+mod quality;     // The Eco, Normal and Ultra quality tiers
+mod precision;   // The 32-bit and 64-bit math of the feedback stages
+mod chain;       // The distortion, fractal and chaos stages the plugin runs on every band
+mod freq_shift;  // Hilbert transform frequency shifter for the chaos effect's FM branch
+#[cfg(feature = "plugin")]
 mod plugin;      // The main plugin structure that combines all effects
+#[cfg(feature = "dsp")]
+pub mod dsp;     // The effect chain on plain slices, for use outside of a plugin
+#[doc(hidden)]
+pub mod test_signals; // Deterministic test signals shared by the tests and the benchmarks
+
+/// The maximum number of audio channels the plugin processes
+// The effects keep separate internal state for each of these channels
+// This matches the largest layout in `AUDIO_IO_LAYOUTS` (stereo)
+// Every processor keeps that state in a fixed-size array of this length instead of a Vec,
+// so no allocations happen on the audio thread
+pub(crate) const MAX_CHANNELS: usize = 2;
 
 // Re-export main types for use in main.rs and elsewhere
 // These "pub use" statements make the specified items available to users of our crate
 // This is like "export" in JavaScript/TypeScript modules - exposing our public API
+#[cfg(feature = "plugin")]
 pub use plugin::RetardedGain;      // Export the main plugin struct
 pub use gain::GainProcessor;       // Export the gain processor
-pub use distortion::Distortion;    // Export the distortion processor
-pub use fractal::FractalMagic;     // Export the fractal effect
-pub use chaos::ChaosAttractor;     // Export the chaos effect
+pub use gate::Gate;                // Export the noise gate
+pub use distortion::{Distortion, DistortionMode, DriveModel}; // Export the distortion processor, its waveshapers and drive models
+pub use filter::{Filter, FilterMode}; // Export the tone filter and its modes
+pub use fractal::{FoldQuality, FractalMagic, FractalSnapshot, MagicEngine, MagicMode, WaveFolder}; // Export the fractal effect, its formulas, engines, wavefolder and snapshots
+pub use chaos::{AttractorSnapshot, ChaosAttractor, ChaosSystem, ChaosType, Evolution, FmMode, Integrator}; // Export the chaos effect, its systems, integrators, evolution modes, FM modes and snapshots
+pub use compressor::Compressor;    // Export the compressor
+pub use crusher::Crusher;          // Export the bitcrusher
+pub use delay::Delay;              // Export the delay effect
+pub use reverb::Reverb;            // Export the reverb effect
+pub use tilt::TiltEq;              // Export the tilt EQ
+pub use dc_blocker::DcBlocker;     // Export the DC blocker
+pub use width::StereoWidth;        // Export the stereo width control
+pub use crossover::Crossover;      // Export the multiband crossover
+pub use oversampling::{Oversampler, Oversampling}; // Export the oversampler and its settings
+#[cfg(feature = "plugin")]
+pub use presets::{AbComparison, AbSlot, Preset, PresetLock, PresetLocks}; // Export the preset snapshot, preset lock and A/B comparison types
+pub use limiter::Limiter;          // Export the output limiter
+pub use clipper::HardClipper;      // Export the hard clipper
+#[cfg(feature = "plugin")]
+pub use metering::{CpuLoadMeter, LoudnessMeter, LoudnessReadings, ProcessingActivity, TruePeakMeter}; // Export the true-peak, loudness and CPU meters and the activity monitor
+pub use modulation::{ChaosModMatrix, Lfo, LfoShape, NoteDivision}; // Export the LFO, its settings and the chaos modulation matrix
+pub use envelope::{Calibration, EnvelopeFollower, InputCalibration}; // Export the envelope follower and the input calibration
+#[cfg(feature = "plugin")]
+pub use midi::MidiLearn;           // Export the MIDI learn state
+#[cfg(feature = "plugin")]
+pub use morph::{MorphSlot, MorphSnapshots}; // Export the morph snapshots
+#[cfg(feature = "plugin")]
+pub use macros::{MacroCurve, MacroMappings}; // Export the macro mapping matrix
+#[cfg(feature = "plugin")]
+pub use migration::STATE_VERSION;  // Export the current state version
+#[cfg(feature = "plugin")]
+pub use history::{History, Snapshot}; // Export the undo history
+#[cfg(feature = "plugin")]
+pub use randomize::{RandomPatch, WeightedRange}; // Export the randomizer types
+pub use routing::{ChannelMode, EffectOrder, Stage, StageSolo}; // Export the effect chain order, the stage solos and channel modes
+pub use generator::{Generator, Scale}; // Export the generator mode's oscillator and its scales
+pub use saturation::soft_clip;     // Export the shared soft clipper
+pub use coupling::couple;          // Export the fractal and chaos coupling
+pub use governor::StabilityGovernor; // Export the stability governor
+pub use quality::Quality;          // Export the quality tiers
+pub use precision::{DspFloat, Precision}; // Export the precisions and the float types they calculate with
+pub use chain::{ChainSettings, EffectChain}; // Export the effect chain and its settings
+pub use freq_shift::FrequencyShifter; // Export the frequency shifter
+pub use latency::LatencyReport;    // Export the latency accounting of the chain
 
 // Export the plugin into the proper formats
 // These are macro invocations that generate the necessary code for VST3 and CLAP plugin formats
+#[cfg(feature = "plugin")]
 use nih_plug::prelude::*;          // Import the NIH-plug framework items
+#[cfg(feature = "plugin")]
 nih_export_clap!(plugin::RetardedGain);  // Generate CLAP plugin export code for RetardedGain
+#[cfg(feature = "plugin")]
 nih_export_vst3!(plugin::RetardedGain);  // Generate VST3 plugin export code for RetardedGain
\ No newline at end of file
@@ -0,0 +1,155 @@
+// VecDeque is used for the sliding window minimum of the gain
+use std::collections::VecDeque;
+
+// The lookahead delay reuses the per-channel compensation delay line
+use crate::latency::CompensationDelay;
+
+/// How far the limiter looks ahead, in milliseconds
+// The gain starts going down this long before a peak arrives, so the peak can't get through
+const LOOKAHEAD_MS: f32 = 1.5;
+
+/// How long the gain takes to recover after a peak, in milliseconds
+const RELEASE_MS: f32 = 100.0;
+
+/// A stereo-linked lookahead brickwall limiter
+// The signal is delayed by the lookahead time while the gain computer looks at the
+// undelayed signal. The required gain reduction is held for the whole lookahead window
+// and then smoothed with a moving average, so by the time a peak leaves the delay line
+// the gain has fully ramped down to (at least) the amount needed to keep it below the ceiling.
+pub struct Limiter {
+    /// The maximum output level as a linear gain
+    ceiling: f32,
+
+    /// The lookahead time in samples
+    lookahead: usize,
+
+    /// Delays the audio by the lookahead time
+    delay: CompensationDelay,
+
+    /// Sliding window minimum of the target gain, as (sample index, gain) pairs
+    // Only values that can still become the minimum are kept, so the front is always the minimum
+    min_window: VecDeque<(usize, f32)>,
+
+    /// The index of the current sample, used to expire old entries from `min_window`
+    sample_index: usize,
+
+    /// The last `lookahead` held gains, used for the moving average
+    average_ring: Vec<f32>,
+
+    /// Where the next value goes in `average_ring`
+    average_pos: usize,
+
+    /// Running sum of `average_ring`
+    // Kept as an f64 so rounding errors don't build up over long sessions
+    average_sum: f64,
+
+    /// Coefficient of the one-pole release smoother
+    release_coeff: f32,
+
+    /// The gain that was applied to the previous frame
+    gain: f32,
+}
+
+impl Limiter {
+    /// Create a new limiter
+    // The buffers are sized for 44.1 kHz until `set_sample_rate()` is called
+    pub fn new() -> Self {
+        let mut limiter = Self {
+            ceiling: 1.0,
+            lookahead: 0,
+            delay: CompensationDelay::new(0),
+            min_window: VecDeque::new(),
+            sample_index: 0,
+            average_ring: Vec::new(),
+            average_pos: 0,
+            average_sum: 0.0,
+            release_coeff: 0.0,
+            gain: 1.0,
+        };
+        limiter.set_sample_rate(44100.0);
+
+        limiter
+    }
+
+    /// Set the sample rate and allocate the lookahead buffers for it
+    // The delay line, the running minimum and the averaging ring are all sized to the 1.5 ms
+    // lookahead at this rate, so they're allocated again here and this has to stay off the
+    // audio thread
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.lookahead = ((LOOKAHEAD_MS / 1000.0) * sample_rate).round().max(1.0) as usize;
+        self.delay = CompensationDelay::new(self.lookahead);
+        self.delay.set_delay(self.lookahead);
+        self.min_window = VecDeque::with_capacity(self.lookahead + 2);
+        self.average_ring = vec![1.0; self.lookahead];
+        self.release_coeff = 1.0 - (-1.0 / ((RELEASE_MS / 1000.0) * sample_rate)).exp();
+        self.reset();
+    }
+
+    /// Set the maximum output level as a linear gain
+    pub fn set_ceiling(&mut self, ceiling: f32) {
+        self.ceiling = ceiling;
+    }
+
+    /// The latency added by the lookahead, in samples
+    pub fn latency_samples(&self) -> u32 {
+        self.lookahead as u32
+    }
+
+    /// Clear the delay line and return to unity gain
+    pub fn reset(&mut self) {
+        self.delay.reset();
+        self.min_window.clear();
+        self.sample_index = 0;
+        self.average_ring.fill(1.0);
+        self.average_pos = 0;
+        self.average_sum = self.lookahead as f64;
+        self.gain = 1.0;
+    }
+
+    /// Limit one frame (one sample for every channel) in place
+    pub fn process(&mut self, frame: &mut [f32]) {
+        self.process_with_amount(frame, 1.0);
+    }
+
+    /// Limit one frame in place, with only `amount` of the gain reduction applied
+    // At 0.0 the frame still goes through the lookahead delay, just without being turned
+    // down. That way the limiter can be faded in and out without the latency changing.
+    pub fn process_with_amount(&mut self, frame: &mut [f32], amount: f32) {
+        // The gain needed to bring the loudest channel down to the ceiling
+        let peak = frame.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        let target_gain = if peak > self.ceiling { self.ceiling / peak } else { 1.0 };
+
+        // Hold the lowest gain seen during the last `lookahead + 1` samples
+        while self.min_window.back().is_some_and(|&(_, gain)| gain >= target_gain) {
+            self.min_window.pop_back();
+        }
+        self.min_window.push_back((self.sample_index, target_gain));
+        while self
+            .min_window
+            .front()
+            .is_some_and(|&(index, _)| index + self.lookahead < self.sample_index)
+        {
+            self.min_window.pop_front();
+        }
+        let held_gain = self.min_window.front().map_or(1.0, |&(_, gain)| gain);
+        self.sample_index += 1;
+
+        // Smooth the attack with a moving average over the lookahead window
+        self.average_sum += (held_gain - self.average_ring[self.average_pos]) as f64;
+        self.average_ring[self.average_pos] = held_gain;
+        self.average_pos = (self.average_pos + 1) % self.lookahead;
+        let attack_gain = (self.average_sum / self.lookahead as f64) as f32;
+
+        // Reduce the gain immediately, but let it recover slowly
+        self.gain = if attack_gain < self.gain {
+            attack_gain
+        } else {
+            self.gain + (attack_gain - self.gain) * self.release_coeff
+        };
+
+        let gain = 1.0 + (self.gain - 1.0) * amount.clamp(0.0, 1.0);
+        for (channel, sample) in frame.iter_mut().enumerate() {
+            *sample = self.delay.process(channel, *sample) * gain;
+        }
+    }
+}
@@ -0,0 +1,204 @@
+// AtomicF32 holds the mapping ranges, so the audio thread can read them without locking
+use atomic_float::AtomicF32;
+// Import the NIH-plug prelude for the PersistentField trait
+use nih_plug::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+// The macros can drive the same smoothed parameters the morph control blends
+use crate::morph::MORPH_TARGETS;
+
+/// The number of macro knobs
+pub const NUM_MACROS: usize = 2;
+
+/// How many parameters every macro knob can drive
+pub const MAPPINGS_PER_MACRO: usize = 3;
+
+/// Marks an empty mapping
+const NONE: usize = usize::MAX;
+
+/// How a mapping follows its macro knob
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MacroCurve {
+    /// Moves the target evenly over the whole turn
+    #[default]
+    Linear,
+    /// Barely moves the target at first, then quickly at the end
+    Exponential,
+    /// Moves the target quickly at first, then barely at the end
+    Logarithmic,
+    /// Slow at both ends and quick in the middle
+    SCurve,
+}
+
+impl MacroCurve {
+    /// All curves in the order the curve button steps through them
+    const ALL: [MacroCurve; 4] = [
+        MacroCurve::Linear,
+        MacroCurve::Exponential,
+        MacroCurve::Logarithmic,
+        MacroCurve::SCurve,
+    ];
+
+    /// Shape a macro position (0.0 to 1.0), the end points stay where they are
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            MacroCurve::Linear => x,
+            MacroCurve::Exponential => x * x,
+            MacroCurve::Logarithmic => 1.0 - (1.0 - x) * (1.0 - x),
+            MacroCurve::SCurve => x * x * (3.0 - 2.0 * x),
+        }
+    }
+
+    /// The curve after this one, wrapping around at the end
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    /// The name shown in the editor
+    pub fn label(self) -> &'static str {
+        match self {
+            MacroCurve::Linear => "LIN",
+            MacroCurve::Exponential => "EXP",
+            MacroCurve::Logarithmic => "LOG",
+            MacroCurve::SCurve => "S",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|curve| *curve == self).unwrap_or(0)
+    }
+}
+
+/// One mapping as it's saved with the plugin state
+// The target is stored by parameter ID, so the mappings survive new morph targets
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MacroMappingState {
+    /// Which macro knob the mapping belongs to
+    pub macro_index: usize,
+    /// Which of the macro's mappings this is
+    pub slot: usize,
+    /// The ID of the target parameter
+    pub target: String,
+    /// See `MacroMappings::range`
+    pub range: f32,
+    /// How the mapping follows the macro knob
+    pub curve: MacroCurve,
+}
+
+/// The mapping matrix from the macro knobs to the parameters they drive, shared between the GUI and the audio thread
+// Every macro has a few mapping slots, each with a target, a range and a curve. The GUI
+// edits the slots, the audio thread reads them every block. Everything is stored in
+// atomics so the audio thread never has to wait for the GUI.
+pub struct MacroMappings {
+    /// The index in `MORPH_TARGETS` of every mapping's target, or `NONE`
+    targets: [[AtomicUsize; MAPPINGS_PER_MACRO]; NUM_MACROS],
+
+    /// How far every mapping moves its target, see `range`
+    ranges: [[AtomicF32; MAPPINGS_PER_MACRO]; NUM_MACROS],
+
+    /// The index of every mapping's curve in `MacroCurve::ALL`
+    curves: [[AtomicU8; MAPPINGS_PER_MACRO]; NUM_MACROS],
+}
+
+impl MacroMappings {
+    /// Create an empty mapping matrix
+    pub fn new() -> Self {
+        Self {
+            targets: std::array::from_fn(|_| std::array::from_fn(|_| AtomicUsize::new(NONE))),
+            ranges: std::array::from_fn(|_| std::array::from_fn(|_| AtomicF32::new(0.5))),
+            curves: std::array::from_fn(|_| std::array::from_fn(|_| AtomicU8::new(0))),
+        }
+    }
+
+    /// The index in `MORPH_TARGETS` of a mapping's target
+    pub fn target(&self, macro_index: usize, slot: usize) -> Option<usize> {
+        Some(self.targets[macro_index][slot].load(Ordering::Relaxed)).filter(|&target| target != NONE)
+    }
+
+    /// Point a mapping at a target, or clear it with `None`
+    pub fn set_target(&self, macro_index: usize, slot: usize, target: Option<usize>) {
+        let target = target.filter(|&target| target < MORPH_TARGETS.len()).unwrap_or(NONE);
+        self.targets[macro_index][slot].store(target, Ordering::Relaxed);
+    }
+
+    /// How far a mapping moves its target with the macro knob all the way up
+    // As a share of the target's whole range, from -1.0 to 1.0. Negative ranges turn the
+    // target down while the macro goes up.
+    pub fn range(&self, macro_index: usize, slot: usize) -> f32 {
+        self.ranges[macro_index][slot].load(Ordering::Relaxed)
+    }
+
+    /// Set how far a mapping moves its target, see `range`
+    pub fn set_range(&self, macro_index: usize, slot: usize, range: f32) {
+        self.ranges[macro_index][slot].store(range.clamp(-1.0, 1.0), Ordering::Relaxed);
+    }
+
+    /// How a mapping follows its macro knob
+    pub fn curve(&self, macro_index: usize, slot: usize) -> MacroCurve {
+        let index = self.curves[macro_index][slot].load(Ordering::Relaxed) as usize;
+        MacroCurve::ALL.get(index).copied().unwrap_or_default()
+    }
+
+    /// Set how a mapping follows its macro knob
+    pub fn set_curve(&self, macro_index: usize, slot: usize, curve: MacroCurve) {
+        self.curves[macro_index][slot].store(curve.index() as u8, Ordering::Relaxed);
+    }
+
+    /// How far all macros together push a target, as a share of its normalized range
+    // `macro_values` are the positions of the macro knobs (0.0 to 1.0). Returns `None` if
+    // no mapping drives the target, so the caller can leave its smoother alone.
+    pub fn offset(&self, target: usize, macro_values: &[f32; NUM_MACROS]) -> Option<f32> {
+        let mut offset = None;
+        for (macro_index, value) in macro_values.iter().enumerate() {
+            for slot in 0..MAPPINGS_PER_MACRO {
+                if self.target(macro_index, slot) == Some(target) {
+                    let shaped = self.curve(macro_index, slot).apply(value.clamp(0.0, 1.0));
+                    *offset.get_or_insert(0.0) += self.range(macro_index, slot) * shaped;
+                }
+            }
+        }
+
+        offset
+    }
+}
+
+// Lets `#[persist]` save the mappings as a readable list, leaving out the empty slots
+impl<'a> PersistentField<'a, Vec<MacroMappingState>> for MacroMappings {
+    fn set(&self, new_value: Vec<MacroMappingState>) {
+        for targets in &self.targets {
+            for target in targets {
+                target.store(NONE, Ordering::Relaxed);
+            }
+        }
+        for mapping in new_value {
+            if mapping.macro_index >= NUM_MACROS || mapping.slot >= MAPPINGS_PER_MACRO {
+                continue;
+            }
+            let target = MORPH_TARGETS.iter().position(|id| *id == mapping.target);
+            self.set_target(mapping.macro_index, mapping.slot, target);
+            self.set_range(mapping.macro_index, mapping.slot, mapping.range);
+            self.set_curve(mapping.macro_index, mapping.slot, mapping.curve);
+        }
+    }
+
+    fn map<F, R>(&self, f: F) -> R
+    where
+        F: Fn(&Vec<MacroMappingState>) -> R,
+    {
+        let mappings = (0..NUM_MACROS)
+            .flat_map(|macro_index| (0..MAPPINGS_PER_MACRO).map(move |slot| (macro_index, slot)))
+            .filter_map(|(macro_index, slot)| {
+                Some(MacroMappingState {
+                    macro_index,
+                    slot,
+                    target: MORPH_TARGETS[self.target(macro_index, slot)?].to_string(),
+                    range: self.range(macro_index, slot),
+                    curve: self.curve(macro_index, slot),
+                })
+            })
+            .collect();
+
+        f(&mappings)
+    }
+}
@@ -0,0 +1,677 @@
+// AtomicF32 lets the audio thread share the meter readings with the GUI
+use atomic_float::AtomicF32;
+// Needed for the Enum derive macro
+use nih_plug::prelude::*;
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+// The CPU meter times every block with the system's monotonic clock
+use std::time::Instant;
+
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+/// How far a digital meter falls back within its decay time, in dB
+// With the default decay time of one second that's the usual 20 dB per second
+const DIGITAL_FALL_DB: f32 = 20.0;
+
+/// The decay time of a digital meter until it's set, in milliseconds
+pub const DEFAULT_DECAY_MS: f32 = 1000.0;
+
+/// How long a PPM takes to rise to a new peak, in seconds
+// The 10 ms integration time of an IEC type II programme meter
+const PPM_RISE_SECONDS: f32 = 0.01;
+
+/// How fast a PPM falls back, in dB per second
+// 24 dB in 2.8 seconds
+const PPM_FALL_DB_PER_SECOND: f32 = 24.0 / 2.8;
+
+/// The time constant of a VU meter, both up and down, in seconds
+const VU_SECONDS: f32 = 0.3;
+
+/// The level above which the clip indicator lights up, as a linear gain
+const CLIP_LEVEL: f32 = 1.0;
+
+/// How the peak meter's reading follows the signal
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeterBallistics {
+    /// Jumps up to every peak right away and falls back 20 dB within the decay time
+    #[id = "digital"]
+    #[name = "Digital"]
+    Digital,
+    /// A programme peak meter, rises within 10 ms and falls back slowly
+    #[id = "ppm"]
+    #[name = "PPM"]
+    Ppm,
+    /// A VU meter, shows the average level with a sluggish 300 ms response
+    #[id = "vu"]
+    #[name = "VU"]
+    Vu,
+}
+
+/// The levels the peak meters show, from the bottom to the top
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeterRange {
+    /// Enough for mixing, the quiet bits are all at the bottom
+    #[id = "standard"]
+    #[name = "-60 to 0 dB"]
+    Standard,
+    /// Shows reverb tails fading out, and how far a hot signal goes over 0 dBFS
+    #[id = "extended"]
+    #[name = "-96 to +6 dB"]
+    Extended,
+}
+
+impl MeterRange {
+    /// The lowest and highest level shown, in dB
+    pub fn db_range(self) -> (f32, f32) {
+        match self {
+            MeterRange::Standard => (-60.0, 0.0),
+            MeterRange::Extended => (-96.0, 6.0),
+        }
+    }
+}
+
+/// How many points the true-peak measurement looks at between two samples
+// 4x is what the ITU-R BS.1770 true-peak meters use
+const OVERSAMPLING: usize = 4;
+
+/// The number of filter taps for every one of those points
+const TAPS_PER_PHASE: usize = 12;
+
+/// Per-channel state of the true-peak measurement
+#[derive(Clone, Copy)]
+struct TruePeakState {
+    /// The most recent samples, newest first
+    history: [f32; TAPS_PER_PHASE],
+}
+
+/// The readings of a peak meter, shared with the GUI
+#[derive(Clone)]
+pub struct PeakReadings {
+    /// The level with the selected ballistics, as a linear gain
+    pub level: Arc<AtomicF32>,
+
+    /// The highest true peak since the GUI last cleared it, as a linear gain
+    // The GUI clears it by storing 0.0, the audio thread only ever raises it
+    pub hold: Arc<AtomicF32>,
+
+    /// Whether a true peak went over 0 dBFS since the GUI last cleared it
+    pub clipped: Arc<AtomicBool>,
+}
+
+impl PeakReadings {
+    /// Clear the hold and the clip indicator
+    // Called from the GUI, the audio thread starts holding again from the next block
+    pub fn clear(&self) {
+        self.hold.store(0.0, Ordering::Relaxed);
+        self.clipped.store(false, Ordering::Relaxed);
+    }
+}
+
+/// A peak meter that catches the peaks between samples
+// A sample peak meter only sees the samples themselves, but the converter in the audio
+// interface draws a smooth curve through them that can overshoot in between. Heavy
+// clipping and folding make those overshoots a lot bigger. This meter interpolates every
+// sample 4x with a windowed sinc filter and reports the highest point it finds, so the
+// reading matches what actually comes out of the speakers.
+// The reading moves with digital, PPM or VU ballistics, but the hold and the clip
+// indicator always see every single peak, so even the shortest over is caught.
+pub struct TruePeakMeter {
+    /// The readings shared with the GUI
+    readings: PeakReadings,
+
+    /// How the reading follows the signal
+    ballistics: MeterBallistics,
+
+    /// How long the digital ballistics take to fall back 20 dB, in seconds
+    decay_seconds: f32,
+
+    /// The current reading, before it's shared with the GUI
+    level: f32,
+
+    /// Sample rate for the ballistics
+    sample_rate: f32,
+
+    /// One state per channel
+    channels: [TruePeakState; MAX_CHANNELS],
+
+    /// The interpolation filter, split into one set of taps for every point between two samples
+    coefficients: [[f32; TAPS_PER_PHASE]; OVERSAMPLING],
+
+    /// The highest peak seen since the last `publish()`
+    block_peak: f32,
+
+    /// The summed absolute value of every sample since the last `publish()`, for the VU ballistics
+    block_sum: f32,
+
+    /// The number of samples in `block_sum`
+    block_samples: usize,
+}
+
+impl TruePeakMeter {
+    /// Create a new true-peak meter, starting from silence
+    pub fn new() -> Self {
+        // A Hann-windowed sinc at a quarter of the oversampled rate
+        let num_taps = OVERSAMPLING * TAPS_PER_PHASE;
+        let center = (num_taps - 1) as f32 / 2.0;
+        let mut coefficients = [[0.0; TAPS_PER_PHASE]; OVERSAMPLING];
+        for (phase, taps) in coefficients.iter_mut().enumerate() {
+            for (tap, coefficient) in taps.iter_mut().enumerate() {
+                let n = (tap * OVERSAMPLING + phase) as f32;
+                let x = (n - center) / OVERSAMPLING as f32;
+                let sinc = if x == 0.0 { 1.0 } else { (PI * x).sin() / (PI * x) };
+                let window = 0.5 - 0.5 * (2.0 * PI * (n + 0.5) / num_taps as f32).cos();
+                *coefficient = sinc * window;
+            }
+
+            // Every point passes DC at unity gain, so a steady level reads the same as a sample meter
+            let sum: f32 = taps.iter().sum();
+            taps.iter_mut().for_each(|coefficient| *coefficient /= sum);
+        }
+
+        Self {
+            readings: PeakReadings {
+                level: Arc::new(AtomicF32::new(0.0)),
+                hold: Arc::new(AtomicF32::new(0.0)),
+                clipped: Arc::new(AtomicBool::new(false)),
+            },
+            ballistics: MeterBallistics::Digital,
+            decay_seconds: DEFAULT_DECAY_MS / 1000.0,
+            level: 0.0,
+            sample_rate: 44100.0, // Default sample rate, will be updated
+            channels: [TruePeakState { history: [0.0; TAPS_PER_PHASE] }; MAX_CHANNELS],
+            coefficients,
+            block_peak: 0.0,
+            block_sum: 0.0,
+            block_samples: 0,
+        }
+    }
+
+    /// Set the sample rate for the ballistics
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Select how the reading follows the signal
+    pub fn set_ballistics(&mut self, ballistics: MeterBallistics) {
+        self.ballistics = ballistics;
+    }
+
+    /// Set how long the digital ballistics take to fall back 20 dB, in milliseconds
+    // PPM and VU meters have standardized timings, so they ignore this
+    pub fn set_decay(&mut self, decay_ms: f32) {
+        self.decay_seconds = decay_ms.max(1.0) / 1000.0;
+    }
+
+    /// The readings, shared with the GUI
+    pub fn readings(&self) -> PeakReadings {
+        self.readings.clone()
+    }
+
+    /// Forget the previous samples
+    pub fn reset(&mut self) {
+        self.channels = [TruePeakState { history: [0.0; TAPS_PER_PHASE] }; MAX_CHANNELS];
+        self.level = 0.0;
+        self.block_peak = 0.0;
+        self.block_sum = 0.0;
+        self.block_samples = 0;
+        self.readings.level.store(0.0, Ordering::Relaxed);
+    }
+
+    /// Add a sample of the given channel to the measurement
+    pub fn push(&mut self, channel: usize, sample: f32) {
+        let state = &mut self.channels[channel];
+        state.history.copy_within(..TAPS_PER_PHASE - 1, 1);
+        state.history[0] = sample;
+
+        for taps in self.coefficients.iter() {
+            let point: f32 = taps.iter().zip(state.history.iter()).map(|(tap, sample)| tap * sample).sum();
+            self.block_peak = self.block_peak.max(point.abs());
+        }
+        self.block_sum += sample.abs();
+        self.block_samples += 1;
+    }
+
+    /// Move the reading on by `num_frames` frames and send it to the GUI
+    // Called once per block with the block's length, so the ballistics take the same time
+    // at every block size. The hold and the clip indicator get the block's highest peak.
+    pub fn publish(&mut self, num_frames: usize) {
+        let elapsed = num_frames as f32 / self.sample_rate;
+        let peak = self.block_peak;
+        self.level = match self.ballistics {
+            MeterBallistics::Digital => {
+                peak.max(self.level * fall_gain(DIGITAL_FALL_DB / self.decay_seconds, elapsed))
+            }
+            MeterBallistics::Ppm => {
+                if peak > self.level {
+                    self.level + (peak - self.level) * (1.0 - (-elapsed / PPM_RISE_SECONDS).exp())
+                } else {
+                    peak.max(self.level * fall_gain(PPM_FALL_DB_PER_SECOND, elapsed))
+                }
+            }
+            MeterBallistics::Vu => {
+                // The average of the rectified signal, like the needle of a real VU meter
+                let average = self.block_sum / self.block_samples.max(1) as f32;
+                self.level + (average - self.level) * (1.0 - (-elapsed / VU_SECONDS).exp())
+            }
+        };
+
+        self.readings.level.store(self.level, Ordering::Relaxed);
+        self.readings.hold.fetch_max(peak, Ordering::Relaxed);
+        if peak > CLIP_LEVEL {
+            self.readings.clipped.store(true, Ordering::Relaxed);
+        }
+
+        self.block_peak = 0.0;
+        self.block_sum = 0.0;
+        self.block_samples = 0;
+    }
+}
+
+/// The gain that lets a level fall by `db_per_second` over `elapsed` seconds
+fn fall_gain(db_per_second: f32, elapsed: f32) -> f32 {
+    10.0f32.powf(-db_per_second * elapsed / 20.0)
+}
+
+/// How quickly the CPU meter follows the load, in seconds
+// Slow enough that a single slow block doesn't make the reading jump around
+const CPU_LOAD_SECONDS: f32 = 0.3;
+
+/// Measures how much of the realtime budget the plugin uses
+// The time spent on a block is compared with how long that block takes to play back. At
+// 100% the plugin only just keeps up, anything above that makes the host drop out. This
+// only counts the plugin itself, the host and the other plugins need their share too.
+pub struct CpuLoadMeter {
+    /// The smoothed load shared with the GUI, 1.0 is the whole budget
+    load: Arc<AtomicF32>,
+
+    /// The current reading, before it's shared with the GUI
+    smoothed: f32,
+
+    /// Sample rate for working out how long a block plays
+    sample_rate: f32,
+
+    /// When the current block started processing
+    block_start: Option<Instant>,
+}
+
+impl CpuLoadMeter {
+    /// Create a new CPU meter, reading 0%
+    pub fn new() -> Self {
+        Self {
+            load: Arc::new(AtomicF32::new(0.0)),
+            smoothed: 0.0,
+            sample_rate: 44100.0, // Default sample rate, will be updated
+            block_start: None,
+        }
+    }
+
+    /// Set the sample rate the blocks play back at
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// The load, shared with the GUI
+    pub fn load(&self) -> Arc<AtomicF32> {
+        self.load.clone()
+    }
+
+    /// Start the reading over from 0%
+    pub fn reset(&mut self) {
+        self.smoothed = 0.0;
+        self.block_start = None;
+        self.load.store(0.0, Ordering::Relaxed);
+    }
+
+    /// Start timing a block, called first thing in `process()`
+    pub fn start(&mut self) {
+        self.block_start = Some(Instant::now());
+    }
+
+    /// Stop timing the block started with `start()` and send the load to the GUI
+    // `num_frames` is the block's length, the smoothing takes the same time at every block size
+    pub fn finish(&mut self, num_frames: usize) {
+        let Some(block_start) = self.block_start.take() else {
+            return;
+        };
+        if num_frames == 0 {
+            return;
+        }
+
+        let budget = num_frames as f32 / self.sample_rate;
+        let load = block_start.elapsed().as_secs_f32() / budget;
+        self.smoothed += (load - self.smoothed) * (1.0 - (-budget / CPU_LOAD_SECONDS).exp());
+        self.load.store(self.smoothed, Ordering::Relaxed);
+    }
+}
+
+/// How long the audio thread may go without a block before the GUI calls it idle, in milliseconds
+// Longer than the biggest buffers hosts use, so a slow block doesn't make the light flicker
+const IDLE_AFTER_MS: u64 = 500;
+
+/// When the audio thread processed its latest block, so the GUI can tell whether its readings are live
+// A host that bypasses the plugin the hard way, or suspends it, simply stops calling
+// `process()`, and nothing tells the GUI. Every block stores how long after the epoch it
+// started, with 0 meaning that none ran since the plugin was deactivated.
+pub struct ProcessingActivity {
+    /// The moment the times are measured from
+    epoch: Instant,
+
+    /// When the latest block started, in milliseconds after the epoch plus one, 0 for none
+    last_block: AtomicU64,
+}
+
+impl ProcessingActivity {
+    /// Create a new activity monitor that hasn't seen a block yet
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            last_block: AtomicU64::new(0),
+        }
+    }
+
+    /// Note that a block is being processed, called first thing in `process()`
+    pub fn mark(&self) {
+        let elapsed = self.epoch.elapsed().as_millis() as u64;
+        self.last_block.store(elapsed + 1, Ordering::Relaxed);
+    }
+
+    /// Forget the latest block, for when the host deactivates the plugin
+    pub fn clear(&self) {
+        self.last_block.store(0, Ordering::Relaxed);
+    }
+
+    /// Whether the audio thread processed a block within the last `IDLE_AFTER_MS`
+    pub fn is_active(&self) -> bool {
+        match self.last_block.load(Ordering::Relaxed) {
+            0 => false,
+            last_block => (self.epoch.elapsed().as_millis() as u64 + 1).saturating_sub(last_block) < IDLE_AFTER_MS,
+        }
+    }
+}
+
+/// How long one step of the loudness measurement is, in seconds
+// The gating blocks and the short-term window are whole numbers of these steps
+const LOUDNESS_STEP_SECONDS: f32 = 0.1;
+
+/// How many steps make up one 400 ms gating block, which overlap by 75%
+const BLOCK_STEPS: usize = 4;
+
+/// How many steps make up the 3 second short-term window
+const SHORT_TERM_STEPS: usize = 30;
+
+/// Gating blocks quieter than this are left out of the integrated loudness, in LUFS
+const ABSOLUTE_GATE_LUFS: f32 = -70.0;
+
+/// Gating blocks this far below the loudness of the rest are left out too, in LU
+const RELATIVE_GATE_LU: f32 = -10.0;
+
+/// The loudest gating block the integrated loudness keeps track of, in LUFS
+const HISTOGRAM_MAX_LUFS: f32 = 5.0;
+
+/// How many bins of the gating block histogram there are for every LU
+const HISTOGRAM_BINS_PER_LU: usize = 10;
+
+/// How many LU every bin of the gating block histogram covers
+const HISTOGRAM_RESOLUTION: f32 = 1.0 / HISTOGRAM_BINS_PER_LU as f32;
+
+/// The number of bins in the gating block histogram, from the absolute gate up to the maximum
+const HISTOGRAM_BINS: usize = (HISTOGRAM_MAX_LUFS - ABSOLUTE_GATE_LUFS) as usize * HISTOGRAM_BINS_PER_LU;
+
+/// Coefficients of one biquad of the K-weighting filter
+#[derive(Clone, Copy, Default)]
+struct BiquadCoefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+/// State of one biquad of the K-weighting filter, in transposed direct form II
+#[derive(Clone, Copy, Default)]
+struct BiquadState {
+    s1: f32,
+    s2: f32,
+}
+
+impl BiquadState {
+    fn process(&mut self, coefficients: &BiquadCoefficients, input: f32) -> f32 {
+        let output = coefficients.b0 * input + self.s1;
+        self.s1 = coefficients.b1 * input - coefficients.a1 * output + self.s2;
+        self.s2 = coefficients.b2 * input - coefficients.a2 * output;
+
+        output
+    }
+}
+
+/// The readings of a loudness meter, shared with the GUI
+#[derive(Clone)]
+pub struct LoudnessReadings {
+    /// The loudness of the last 3 seconds in LUFS, negative infinity for silence
+    pub short_term: Arc<AtomicF32>,
+
+    /// The loudness since the last reset in LUFS, negative infinity until anything was heard
+    pub integrated: Arc<AtomicF32>,
+}
+
+/// A short-term and integrated loudness meter following ITU-R BS.1770
+// Peak meters say nothing about how loud something sounds, and the chaotic stages can
+// change the peaks and the loudness in very different ways. This meter first runs the
+// signal through the K-weighting filter, a high shelf for the way the head boosts the
+// highs and a high pass for the ear's poor bass response, and then averages its power.
+// The short-term loudness is the average over the last 3 seconds. The integrated loudness
+// averages the overlapping 400 ms gating blocks since the last reset, leaving out the
+// silent ones and the ones much quieter than the rest, so pauses don't pull it down.
+pub struct LoudnessMeter {
+    /// The readings shared with the GUI
+    readings: LoudnessReadings,
+
+    /// The K-weighting filter, a high shelf followed by a high pass
+    coefficients: [BiquadCoefficients; 2],
+
+    /// The K-weighting filter state of every channel
+    filters: [[BiquadState; 2]; MAX_CHANNELS],
+
+    /// How many frames one step lasts at the current sample rate
+    step_len: usize,
+
+    /// How many frames of the current step have been measured
+    step_frames: usize,
+
+    /// The summed power of all channels in the current step
+    step_energy: f32,
+
+    /// The mean power of the most recent steps, as a ring buffer
+    steps: [f32; SHORT_TERM_STEPS],
+
+    /// Where the next finished step goes in `steps`
+    step_pos: usize,
+
+    /// How many of the entries in `steps` have been filled since the last reset
+    steps_filled: usize,
+
+    /// How many gating blocks fell into every 0.1 LU wide bin
+    // The integrated loudness needs every block since the reset, which would need an
+    // ever-growing list. Counting them in a histogram is precise enough and never allocates.
+    histogram: [u32; HISTOGRAM_BINS],
+
+    /// The mean power of every histogram bin
+    bin_energies: [f32; HISTOGRAM_BINS],
+
+    /// The summed mean power of every block over the absolute gate
+    gated_energy: f64,
+
+    /// The number of blocks over the absolute gate
+    gated_blocks: u64,
+}
+
+impl LoudnessMeter {
+    /// Create a new loudness meter, starting from silence
+    pub fn new() -> Self {
+        let mut meter = Self {
+            readings: LoudnessReadings {
+                short_term: Arc::new(AtomicF32::new(f32::NEG_INFINITY)),
+                integrated: Arc::new(AtomicF32::new(f32::NEG_INFINITY)),
+            },
+            coefficients: [BiquadCoefficients::default(); 2],
+            filters: [[BiquadState::default(); 2]; MAX_CHANNELS],
+            step_len: 1,
+            step_frames: 0,
+            step_energy: 0.0,
+            steps: [0.0; SHORT_TERM_STEPS],
+            step_pos: 0,
+            steps_filled: 0,
+            histogram: [0; HISTOGRAM_BINS],
+            bin_energies: std::array::from_fn(|bin| {
+                // The loudness in the middle of the bin
+                energy_from_lufs(ABSOLUTE_GATE_LUFS + (bin as f32 + 0.5) * HISTOGRAM_RESOLUTION)
+            }),
+            gated_energy: 0.0,
+            gated_blocks: 0,
+        };
+        meter.set_sample_rate(44100.0); // Default sample rate, will be updated
+
+        meter
+    }
+
+    /// Set the sample rate the K-weighting filter and the steps are designed for
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        // The K-weighting filter from BS.1770 is specified at 48 kHz, these are its analog
+        // prototypes so it comes out the same at every sample rate
+        // The high shelf, +4 dB above around 1.7 kHz
+        let k = (PI * 1681.974 / sample_rate).tan();
+        let q = 0.707_175_2;
+        let vh = 10.0f32.powf(3.999_844 / 20.0);
+        let vb = vh.powf(0.499_666_8);
+        let a0 = 1.0 + k / q + k * k;
+        self.coefficients[0] = BiquadCoefficients {
+            b0: (vh + vb * k / q + k * k) / a0,
+            b1: 2.0 * (k * k - vh) / a0,
+            b2: (vh - vb * k / q + k * k) / a0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+        };
+
+        // The high pass at around 38 Hz
+        let k = (PI * 38.135_47 / sample_rate).tan();
+        let q = 0.500_327;
+        let a0 = 1.0 + k / q + k * k;
+        self.coefficients[1] = BiquadCoefficients {
+            b0: 1.0,
+            b1: -2.0,
+            b2: 1.0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+        };
+
+        self.step_len = ((LOUDNESS_STEP_SECONDS * sample_rate).round() as usize).max(1);
+    }
+
+    /// The readings, shared with the GUI
+    pub fn readings(&self) -> LoudnessReadings {
+        self.readings.clone()
+    }
+
+    /// Start a new measurement from silence
+    pub fn reset(&mut self) {
+        self.filters = [[BiquadState::default(); 2]; MAX_CHANNELS];
+        self.step_frames = 0;
+        self.step_energy = 0.0;
+        self.steps = [0.0; SHORT_TERM_STEPS];
+        self.step_pos = 0;
+        self.steps_filled = 0;
+        self.histogram = [0; HISTOGRAM_BINS];
+        self.gated_energy = 0.0;
+        self.gated_blocks = 0;
+        self.readings.short_term.store(f32::NEG_INFINITY, Ordering::Relaxed);
+        self.readings.integrated.store(f32::NEG_INFINITY, Ordering::Relaxed);
+    }
+
+    /// Add a sample of the given channel to the current frame
+    // Every channel counts the same, BS.1770 only weighs the surround channels differently
+    pub fn push(&mut self, channel: usize, sample: f32) {
+        let [shelf, high_pass] = &mut self.filters[channel];
+        let weighted = shelf.process(&self.coefficients[0], sample);
+        let weighted = high_pass.process(&self.coefficients[1], weighted);
+        self.step_energy += weighted * weighted;
+    }
+
+    /// Finish the current frame, after all of its channels were pushed
+    // Every 100 ms this finishes a step, which updates both readings
+    pub fn end_frame(&mut self) {
+        self.step_frames += 1;
+        if self.step_frames < self.step_len {
+            return;
+        }
+
+        self.steps[self.step_pos] = self.step_energy / self.step_len as f32;
+        self.step_pos = (self.step_pos + 1) % SHORT_TERM_STEPS;
+        self.steps_filled = (self.steps_filled + 1).min(SHORT_TERM_STEPS);
+        self.step_frames = 0;
+        self.step_energy = 0.0;
+
+        // Every step finishes a new gating block from the last four steps
+        if self.steps_filled >= BLOCK_STEPS {
+            let block_energy = self.recent_energy(BLOCK_STEPS);
+            let block_lufs = lufs_from_energy(block_energy);
+            if block_lufs > ABSOLUTE_GATE_LUFS {
+                let bin = ((block_lufs - ABSOLUTE_GATE_LUFS) / HISTOGRAM_RESOLUTION) as usize;
+                self.histogram[bin.min(HISTOGRAM_BINS - 1)] += 1;
+                self.gated_energy += block_energy as f64;
+                self.gated_blocks += 1;
+            }
+        }
+
+        let short_term = lufs_from_energy(self.recent_energy(self.steps_filled));
+        self.readings.short_term.store(short_term, Ordering::Relaxed);
+        self.readings.integrated.store(self.integrated_lufs(), Ordering::Relaxed);
+    }
+
+    /// The mean power of the last `num_steps` steps
+    fn recent_energy(&self, num_steps: usize) -> f32 {
+        let sum: f32 = (1..=num_steps)
+            .map(|age| self.steps[(self.step_pos + SHORT_TERM_STEPS - age) % SHORT_TERM_STEPS])
+            .sum();
+
+        sum / num_steps.max(1) as f32
+    }
+
+    /// The integrated loudness of all gating blocks so far, with both gates applied
+    fn integrated_lufs(&self) -> f32 {
+        if self.gated_blocks == 0 {
+            return f32::NEG_INFINITY;
+        }
+
+        // The relative gate sits 10 LU below the loudness of everything over the absolute gate
+        let relative_gate = lufs_from_energy((self.gated_energy / self.gated_blocks as f64) as f32) + RELATIVE_GATE_LU;
+        let first_bin = ((relative_gate - ABSOLUTE_GATE_LUFS) / HISTOGRAM_RESOLUTION).max(0.0) as usize;
+
+        let mut energy = 0.0;
+        let mut blocks = 0;
+        for (count, bin_energy) in self.histogram.iter().zip(self.bin_energies.iter()).skip(first_bin) {
+            energy += *count as f64 * *bin_energy as f64;
+            blocks += count;
+        }
+        if blocks == 0 {
+            return f32::NEG_INFINITY;
+        }
+
+        lufs_from_energy((energy / blocks as f64) as f32)
+    }
+}
+
+/// The loudness in LUFS of a K-weighted mean power
+fn lufs_from_energy(energy: f32) -> f32 {
+    if energy <= 0.0 {
+        f32::NEG_INFINITY
+    } else {
+        -0.691 + 10.0 * energy.log10()
+    }
+}
+
+/// The K-weighted mean power of a loudness in LUFS
+fn energy_from_lufs(lufs: f32) -> f32 {
+    10.0f32.powf((lufs + 0.691) / 10.0)
+}
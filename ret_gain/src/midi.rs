@@ -0,0 +1,182 @@
+// AtomicF32 holds the latest CC value of every target, waiting for the GUI to pick it up
+use atomic_float::AtomicF32;
+// Import the NIH-plug prelude for FloatParam and the PersistentField trait
+use nih_plug::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::plugin::RetardedGainParams;
+
+/// The number of MIDI CC numbers
+const NUM_CCS: usize = 128;
+
+/// Marks an empty slot in the atomics below
+const NONE: usize = usize::MAX;
+
+/// The IDs of the parameters that can be controlled with MIDI CCs
+// Only smoothed float parameters can be learned: the audio thread can't change a parameter
+// itself, but it can move the parameter's smoother to the CC value, which is what the
+// effects read every sample
+pub const MIDI_TARGETS: [&str; 11] = [
+    "input_gain",
+    "gain",
+    "drive",
+    "filter_cutoff",
+    "filter_resonance",
+    "magic",
+    "chaos",
+    "mix",
+    "lfo_depth",
+    "env_magic_depth",
+    "env_chaos_depth",
+];
+
+/// The index of a parameter ID in `MIDI_TARGETS`
+pub fn target_index(id: &str) -> Option<usize> {
+    MIDI_TARGETS.iter().position(|target| *target == id)
+}
+
+/// The parameter for an index in `MIDI_TARGETS`
+pub fn target_param(params: &RetardedGainParams, index: usize) -> Option<&FloatParam> {
+    match index {
+        0 => Some(&params.input_gain),
+        1 => Some(&params.output_gain),
+        2 => Some(&params.drive),
+        3 => Some(&params.filter_cutoff),
+        4 => Some(&params.filter_resonance),
+        5 => Some(&params.magic),
+        6 => Some(&params.chaos),
+        7 => Some(&params.mix),
+        8 => Some(&params.lfo_depth),
+        9 => Some(&params.env_magic_depth),
+        10 => Some(&params.env_chaos_depth),
+        _ => None,
+    }
+}
+
+/// MIDI learn state and CC to parameter mappings, shared between the GUI and the audio thread
+// Everything is stored in atomics so the audio thread never has to wait for the GUI.
+// The mappings are saved with the plugin state as a map from parameter ID to CC number.
+pub struct MidiLearn {
+    /// The target waiting for a CC to be moved, or `NONE`
+    armed: AtomicUsize,
+
+    /// The target index for every CC number, or `NONE`
+    cc_targets: [AtomicUsize; NUM_CCS],
+
+    /// The latest normalized CC value for every target that the GUI hasn't passed on to the host yet
+    // NaN means nothing is waiting
+    pending: [AtomicF32; MIDI_TARGETS.len()],
+
+    /// Incremented every time a CC is received or learned, so the GUI knows when to look
+    generation: AtomicUsize,
+}
+
+impl MidiLearn {
+    /// Create an empty MIDI learn state without any mappings
+    pub fn new() -> Self {
+        Self {
+            armed: AtomicUsize::new(NONE),
+            cc_targets: std::array::from_fn(|_| AtomicUsize::new(NONE)),
+            pending: std::array::from_fn(|_| AtomicF32::new(f32::NAN)),
+            generation: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait for the next CC and map it to this target
+    pub fn arm(&self, target: usize) {
+        self.armed.store(target, Ordering::Relaxed);
+    }
+
+    /// Stop waiting for a CC
+    pub fn disarm(&self) {
+        self.armed.store(NONE, Ordering::Relaxed);
+    }
+
+    /// The target currently waiting for a CC
+    pub fn armed(&self) -> Option<usize> {
+        Some(self.armed.load(Ordering::Relaxed)).filter(|&target| target != NONE)
+    }
+
+    /// The CC number mapped to a target
+    pub fn mapped_cc(&self, target: usize) -> Option<u8> {
+        self.cc_targets
+            .iter()
+            .position(|cc_target| cc_target.load(Ordering::Relaxed) == target)
+            .map(|cc| cc as u8)
+    }
+
+    /// Remove the mapping of a target
+    pub fn clear(&self, target: usize) {
+        for cc_target in &self.cc_targets {
+            let _ = cc_target.compare_exchange(target, NONE, Ordering::Relaxed, Ordering::Relaxed);
+        }
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A counter that changes whenever a CC is received or learned
+    pub fn generation(&self) -> usize {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// Handle an incoming CC on the audio thread
+    // If a target is armed, the CC gets mapped to it first. Returns the target the CC
+    // controls so the caller can move that parameter's smoother.
+    pub fn handle_cc(&self, cc: u8, value: f32) -> Option<usize> {
+        let cc = cc as usize;
+        if cc >= NUM_CCS {
+            return None;
+        }
+
+        let armed = self.armed.swap(NONE, Ordering::Relaxed);
+        if armed != NONE {
+            // A target can only be controlled by one CC at a time
+            self.clear(armed);
+            self.cc_targets[cc].store(armed, Ordering::Relaxed);
+        }
+
+        let target = self.cc_targets[cc].load(Ordering::Relaxed);
+        let result = if target != NONE {
+            self.pending[target].store(value, Ordering::Relaxed);
+            Some(target)
+        } else {
+            None
+        };
+        self.generation.fetch_add(1, Ordering::Relaxed);
+
+        result
+    }
+
+    /// Take the latest CC value for a target, if one arrived since the last call
+    pub fn take_pending(&self, target: usize) -> Option<f32> {
+        Some(self.pending[target].swap(f32::NAN, Ordering::Relaxed)).filter(|value| !value.is_nan())
+    }
+}
+
+// Lets `#[persist]` save the mappings as a readable map from parameter ID to CC number
+impl<'a> PersistentField<'a, BTreeMap<String, u8>> for MidiLearn {
+    fn set(&self, new_value: BTreeMap<String, u8>) {
+        for cc_target in &self.cc_targets {
+            cc_target.store(NONE, Ordering::Relaxed);
+        }
+        for (id, cc) in new_value {
+            if let (Some(target), Some(cc_target)) = (target_index(&id), self.cc_targets.get(cc as usize)) {
+                cc_target.store(target, Ordering::Relaxed);
+            }
+        }
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn map<F, R>(&self, f: F) -> R
+    where
+        F: Fn(&BTreeMap<String, u8>) -> R,
+    {
+        let mappings = MIDI_TARGETS
+            .iter()
+            .enumerate()
+            .filter_map(|(target, id)| Some((id.to_string(), self.mapped_cc(target)?)))
+            .collect();
+
+        f(&mappings)
+    }
+}
@@ -0,0 +1,115 @@
+// The host hands the saved state over as a `PluginState` before it's applied
+use nih_plug::prelude::*;
+use nih_plug::wrapper::state::{ParamValue, PluginState};
+
+use crate::morph::MorphState;
+use crate::presets::AbComparison;
+
+/// The version of the plugin state this build saves
+// Bump this and add a step to `MIGRATIONS` whenever a parameter gets renamed, removed or
+// changes what its values mean, so old projects still load the way they sounded instead
+// of falling back to the defaults
+pub const STATE_VERSION: u32 = 4;
+
+/// The ID of the persisted field that holds the state version
+pub const STATE_VERSION_KEY: &str = "state-version";
+
+/// A step that turns a state saved by one version into one for the next version
+type Migration = fn(&mut PluginState);
+
+/// Every migration step, `MIGRATIONS[n]` turns a version `n` state into a version `n + 1` state
+// States saved before the version was introduced count as version 0. The steps always run
+// in order, so every step only has to know about the version right before it.
+const MIGRATIONS: [Migration; STATE_VERSION as usize] = [
+    // Version 1 only introduced the version field itself, nothing else changed
+    |_| {},
+    // Version 2 has the drive in dB and a drive model that makes up the level
+    legacy_drive_to_db,
+    // Version 3 anti-aliases the fractal's wavefolder
+    plain_fold,
+    // Version 4 calibrates the level the chaos and fractal effects listen to
+    uncalibrated_influence,
+];
+
+/// Turn the drive into dB everywhere it's saved, and keep old sessions on the legacy drive model
+// Besides the parameter itself, the drive is also stored in the morph snapshots and the
+// inactive A/B slot. Fields that can't be parsed are left alone, they fall back to their
+// defaults when the state is applied anyway.
+fn legacy_drive_to_db(state: &mut PluginState) {
+    let ratio_to_db = |drive: f32| util::gain_to_db(drive.max(1.0));
+
+    if let Some(ParamValue::F32(drive)) = state.params.get_mut("drive") {
+        *drive = ratio_to_db(*drive);
+    }
+    state
+        .params
+        .insert("drive_model".to_string(), ParamValue::String("legacy".to_string()));
+
+    if let Some(field) = state.fields.get_mut("morph-snapshots") {
+        if let Ok(mut morph) = serde_json::from_str::<MorphState>(field) {
+            for snapshot in [&mut morph.a, &mut morph.b] {
+                if let Some(drive) = snapshot.get_mut("drive") {
+                    *drive = ratio_to_db(*drive);
+                }
+            }
+            if let Ok(json) = serde_json::to_string(&morph) {
+                *field = json;
+            }
+        }
+    }
+
+    if let Some(field) = state.fields.get_mut("ab-comparison") {
+        if let Ok(mut ab_comparison) = serde_json::from_str::<AbComparison>(field) {
+            if let Some(preset) = ab_comparison.inactive.as_mut() {
+                preset.upgrade_legacy_drive();
+            }
+            if let Ok(json) = serde_json::to_string(&ab_comparison) {
+                *field = json;
+            }
+        }
+    }
+}
+
+/// Keep old sessions on the plain wavefolder
+// The anti-aliasing takes some of the top end off a heavily folded signal, and the aliasing
+// itself is part of how those sessions sound. Presets without the setting leave it alone
+// like any other missing parameter, so only the parameter itself needs it.
+fn plain_fold(state: &mut PluginState) {
+    state
+        .params
+        .insert("magic_fold_quality".to_string(), ParamValue::String("off".to_string()));
+}
+
+/// Keep old sessions listening to the input at whatever level it comes in
+// Their amounts were set by ear for that level, calibrating it would change how hard the
+// effects respond
+fn uncalibrated_influence(state: &mut PluginState) {
+    state
+        .params
+        .insert("calibration".to_string(), ParamValue::String("off".to_string()));
+}
+
+/// The version a saved state was written by, 0 if it's older than the version field
+pub fn saved_version(state: &PluginState) -> u32 {
+    state
+        .fields
+        .get(STATE_VERSION_KEY)
+        .and_then(|version| serde_json::from_str(version).ok())
+        .unwrap_or(0)
+}
+
+/// Bring a saved state up to the current version before it's loaded
+// States from newer versions are left alone, any parameters this build doesn't know
+// about are skipped when the state is applied anyway
+pub fn migrate(state: &mut PluginState) {
+    let version = saved_version(state);
+    if version > STATE_VERSION {
+        nih_log!("The state was saved by a newer version ({version}), loading it as it is");
+        return;
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(state);
+    }
+    state.fields.insert(STATE_VERSION_KEY.to_string(), STATE_VERSION.to_string());
+}
@@ -0,0 +1,280 @@
+// AtomicF32 shares the modulated values with the GUI without locking
+#[cfg(feature = "plugin")]
+use atomic_float::AtomicF32;
+// Import the NIH-plug prelude for the Enum derive macro
+#[cfg(feature = "plugin")]
+use nih_plug::prelude::*;
+// Import PI constant from the standard library
+use std::f32::consts::PI;
+#[cfg(feature = "plugin")]
+use std::sync::atomic::Ordering;
+
+// The chaos modulation of the output gain is in dB
+use crate::decibels;
+#[cfg(feature = "plugin")]
+use crate::morph::{self, MORPH_TARGETS};
+#[cfg(feature = "plugin")]
+use crate::plugin::RetardedGainParams;
+
+/// The waveform of the LFO
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum LfoShape {
+    #[cfg_attr(feature = "plugin", id = "sine")]
+    #[cfg_attr(feature = "plugin", name = "Sine")]
+    Sine,
+    #[cfg_attr(feature = "plugin", id = "triangle")]
+    #[cfg_attr(feature = "plugin", name = "Triangle")]
+    Triangle,
+    /// A new random value at the start of every cycle
+    #[cfg_attr(feature = "plugin", id = "sample-and-hold")]
+    #[cfg_attr(feature = "plugin", name = "S&H")]
+    SampleAndHold,
+}
+
+/// Musical note lengths the LFO can be synced to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum NoteDivision {
+    #[cfg_attr(feature = "plugin", id = "4/1")]
+    #[cfg_attr(feature = "plugin", name = "4 Bars")]
+    FourBars,
+    #[cfg_attr(feature = "plugin", id = "2/1")]
+    #[cfg_attr(feature = "plugin", name = "2 Bars")]
+    TwoBars,
+    #[cfg_attr(feature = "plugin", id = "1/1")]
+    #[cfg_attr(feature = "plugin", name = "1/1")]
+    Whole,
+    #[cfg_attr(feature = "plugin", id = "1/2")]
+    #[cfg_attr(feature = "plugin", name = "1/2")]
+    Half,
+    #[cfg_attr(feature = "plugin", id = "1/4")]
+    #[cfg_attr(feature = "plugin", name = "1/4")]
+    Quarter,
+    #[cfg_attr(feature = "plugin", id = "1/4d")]
+    #[cfg_attr(feature = "plugin", name = "1/4 Dotted")]
+    DottedQuarter,
+    #[cfg_attr(feature = "plugin", id = "1/4t")]
+    #[cfg_attr(feature = "plugin", name = "1/4 Triplet")]
+    TripletQuarter,
+    #[cfg_attr(feature = "plugin", id = "1/8")]
+    #[cfg_attr(feature = "plugin", name = "1/8")]
+    Eighth,
+    #[cfg_attr(feature = "plugin", id = "1/8d")]
+    #[cfg_attr(feature = "plugin", name = "1/8 Dotted")]
+    DottedEighth,
+    #[cfg_attr(feature = "plugin", id = "1/8t")]
+    #[cfg_attr(feature = "plugin", name = "1/8 Triplet")]
+    TripletEighth,
+    #[cfg_attr(feature = "plugin", id = "1/16")]
+    #[cfg_attr(feature = "plugin", name = "1/16")]
+    Sixteenth,
+}
+
+impl NoteDivision {
+    /// The length of this note division in quarter notes (beats)
+    pub fn beats(self) -> f64 {
+        match self {
+            NoteDivision::FourBars => 16.0,
+            NoteDivision::TwoBars => 8.0,
+            NoteDivision::Whole => 4.0,
+            NoteDivision::Half => 2.0,
+            NoteDivision::Quarter => 1.0,
+            NoteDivision::DottedQuarter => 1.5,
+            NoteDivision::TripletQuarter => 2.0 / 3.0,
+            NoteDivision::Eighth => 0.5,
+            NoteDivision::DottedEighth => 0.75,
+            NoteDivision::TripletEighth => 1.0 / 3.0,
+            NoteDivision::Sixteenth => 0.25,
+        }
+    }
+}
+
+/// A low frequency oscillator used as a modulation source
+// The LFO either runs freely at a rate in Hz, or follows the host's tempo.
+// When the host is playing, synced LFOs lock their phase to the song position
+// so the modulation lands in the same spot every time the song is played.
+pub struct Lfo {
+    /// The waveform to generate
+    shape: LfoShape,
+
+    /// Position within the current cycle (0.0 to 1.0)
+    phase: f32,
+
+    /// How much the phase advances per sample
+    phase_increment: f32,
+
+    /// The current sample & hold value
+    held_value: f32,
+
+    /// State of the random number generator used for sample & hold
+    rng_state: u32,
+
+    /// Sample rate for time-based calculations
+    sample_rate: f32,
+}
+
+impl Lfo {
+    /// Create a new LFO
+    pub fn new() -> Self {
+        Self {
+            shape: LfoShape::Sine,
+            phase: 0.0,
+            phase_increment: 0.0,
+            held_value: 0.0,
+            rng_state: 0x9E37_79B9, // Any non-zero seed works for xorshift
+            sample_rate: 44100.0,   // Default sample rate, will be updated
+        }
+    }
+
+    /// Set the sample rate for time-based calculations
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Set the waveform
+    pub fn set_shape(&mut self, shape: LfoShape) {
+        self.shape = shape;
+    }
+
+    /// Restart the LFO at the beginning of its cycle
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// Run freely at the given rate in Hz
+    pub fn set_free_rate(&mut self, rate_hz: f32) {
+        self.phase_increment = rate_hz / self.sample_rate;
+    }
+
+    /// Sync the LFO to the host's tempo
+    // `tempo` is in beats per minute, `pos_beats` is the song position in quarter notes
+    // if the host is currently playing. Without a song position the LFO keeps its own
+    // phase and only follows the tempo.
+    pub fn set_synced_rate(&mut self, division: NoteDivision, tempo: f64, pos_beats: Option<f64>) {
+        let cycle_beats = division.beats();
+        self.phase_increment = (tempo / 60.0 / cycle_beats) as f32 / self.sample_rate;
+
+        if let Some(pos_beats) = pos_beats {
+            let phase = (pos_beats / cycle_beats).rem_euclid(1.0) as f32;
+            // If the song position wrapped around to a new cycle before our own phase did,
+            // that new cycle also needs a new sample & hold value
+            if phase + 0.5 < self.phase {
+                self.held_value = self.next_random();
+            }
+            self.phase = phase;
+        }
+    }
+
+    /// Produce the next LFO value in the range [-1, 1]
+    pub fn next_value(&mut self) -> f32 {
+        let value = match self.shape {
+            LfoShape::Sine => (self.phase * 2.0 * PI).sin(),
+            // Rises from -1 to 1 during the first half of the cycle and falls back during the second
+            LfoShape::Triangle => 1.0 - 4.0 * (self.phase - 0.5).abs(),
+            LfoShape::SampleAndHold => self.held_value,
+        };
+
+        self.phase += self.phase_increment;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;  // Wrap phase when it exceeds 1.0
+            self.held_value = self.next_random();
+        }
+
+        value
+    }
+
+    /// A pseudo-random value in the range [-1, 1] (xorshift32)
+    fn next_random(&mut self) -> f32 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 17;
+        self.rng_state ^= self.rng_state << 5;
+
+        (self.rng_state as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+}
+
+/// How many octaves a full chaos signal at full depth moves the drive
+const CHAOS_DRIVE_OCTAVES: f32 = 2.0;
+
+/// How many dB a full chaos signal at full depth moves the output gain
+const CHAOS_GAIN_DB: f32 = 12.0;
+
+/// How many octaves a full chaos signal at full depth moves the filter cutoff
+const CHAOS_CUTOFF_OCTAVES: f32 = 3.0;
+
+/// Routes the chaos signal to the drive, the output gain and the filter cutoff
+// A small modulation matrix with one depth per destination, between -1.0 and 1.0.
+// Negative depths move the destination the other way. Drive and cutoff move in octaves
+// and the gain in dB, so the modulation sounds as strong at the bottom of a knob's range
+// as at the top.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChaosModMatrix {
+    pub drive_depth: f32,
+    pub gain_depth: f32,
+    pub cutoff_depth: f32,
+}
+
+impl ChaosModMatrix {
+    /// The drive with the chaos signal applied, kept within the drive knob's range
+    pub fn drive(&self, drive: f32, chaos_signal: f32) -> f32 {
+        (drive * (chaos_signal * self.drive_depth * CHAOS_DRIVE_OCTAVES).exp2()).clamp(1.0, 50.0)
+    }
+
+    /// The output gain with the chaos signal applied, as a gain ratio
+    pub fn gain(&self, gain: f32, chaos_signal: f32) -> f32 {
+        gain * decibels::db_to_gain(chaos_signal * self.gain_depth * CHAOS_GAIN_DB)
+    }
+
+    /// The filter cutoff with the chaos signal applied, kept within the audible range
+    pub fn cutoff(&self, cutoff: f32, chaos_signal: f32) -> f32 {
+        (cutoff * (chaos_signal * self.cutoff_depth * CHAOS_CUTOFF_OCTAVES).exp2()).clamp(20.0, 20_000.0)
+    }
+}
+
+/// The values the audio thread actually used for the parameters it can move on its own
+// The morph, the macros, MIDI learn and the host's modulation all move a parameter's
+// smoother instead of the parameter, and the LFO, envelopes and bursts move the magic and
+// chaos amounts on top of that. The editor draws the difference to the knob's own setting
+// as a ring inside the knob. These are the same parameters the morph blends, since the
+// smoothed float parameters are the only ones that can be moved like this.
+#[cfg(feature = "plugin")]
+pub struct ModulationRings {
+    /// Every parameter the audio thread can move and its latest value, normalized
+    // NaN until the audio thread has published something
+    rings: [(Option<ParamPtr>, AtomicF32); MORPH_TARGETS.len()],
+}
+
+#[cfg(feature = "plugin")]
+impl ModulationRings {
+    pub fn new(params: &RetardedGainParams) -> Self {
+        Self {
+            rings: std::array::from_fn(|target| {
+                (morph::target_param(params, target).map(|param| param.as_ptr()), AtomicF32::new(f32::NAN))
+            }),
+        }
+    }
+
+    /// The ring of a parameter, if the audio thread can move it
+    pub fn index_of(&self, param: ParamPtr) -> Option<usize> {
+        self.rings.iter().position(|(ring_param, _)| *ring_param == Some(param))
+    }
+
+    /// The latest normalized value the audio thread used for a ring's parameter
+    pub fn value(&self, index: usize) -> f32 {
+        self.rings[index].1.load(Ordering::Relaxed)
+    }
+
+    /// Share the normalized value the audio thread used for a ring's parameter
+    pub fn publish(&self, index: usize, normalized: f32) {
+        self.rings[index].1.store(normalized, Ordering::Relaxed);
+    }
+
+    /// Share the normalized value the audio thread used for a parameter
+    // Does nothing for parameters without a ring
+    pub fn publish_param(&self, param: ParamPtr, normalized: f32) {
+        if let Some(index) = self.index_of(param) {
+            self.publish(index, normalized);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+// AtomicF32 holds the snapshot values, so the audio thread can read them without locking
+use atomic_float::AtomicF32;
+// Import the NIH-plug prelude for FloatParam and the PersistentField trait
+use nih_plug::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::plugin::RetardedGainParams;
+
+/// The IDs of the parameters the morph control blends between the two snapshots
+// Only smoothed float parameters can be morphed, for the same reason as with MIDI learn:
+// the audio thread can't change a parameter itself, but it can move the parameter's
+// smoother, which is what the effects read every sample. Modes and switches can't be
+// blended anyway.
+pub const MORPH_TARGETS: [&str; 43] = [
+    "input_gain",
+    "gain",
+    "drive",
+    "filter_cutoff",
+    "filter_resonance",
+    "crusher_bits",
+    "crusher_downsample",
+    "magic",
+    "chaos",
+    "mix",
+    "lfo_depth",
+    "env_magic_depth",
+    "env_chaos_depth",
+    "delay_feedback",
+    "delay_mix",
+    "delay_warble",
+    "reverb_size",
+    "reverb_damping",
+    "reverb_mix",
+    "side_amount",
+    "dist_ms_balance",
+    "magic_ms_balance",
+    "chaos_ms_balance",
+    "crossover_low",
+    "crossover_high",
+    "low_band_amount",
+    "mid_band_amount",
+    "high_band_amount",
+    "burst_amount",
+    "output_pan",
+    "output_balance",
+    "stereo_width",
+    "chaos_to_drive",
+    "chaos_to_gain",
+    "chaos_to_cutoff",
+    "dist_level",
+    "magic_level",
+    "chaos_level",
+    "chaos_color",
+    "generator_pitch",
+    "generator_range",
+    "tilt",
+    "coupling",
+];
+
+/// The parameter for an index in `MORPH_TARGETS`
+pub fn target_param(params: &RetardedGainParams, index: usize) -> Option<&FloatParam> {
+    match index {
+        0 => Some(&params.input_gain),
+        1 => Some(&params.output_gain),
+        2 => Some(&params.drive),
+        3 => Some(&params.filter_cutoff),
+        4 => Some(&params.filter_resonance),
+        5 => Some(&params.crusher_bits),
+        6 => Some(&params.crusher_downsample),
+        7 => Some(&params.magic),
+        8 => Some(&params.chaos),
+        9 => Some(&params.mix),
+        10 => Some(&params.lfo_depth),
+        11 => Some(&params.env_magic_depth),
+        12 => Some(&params.env_chaos_depth),
+        13 => Some(&params.delay_feedback),
+        14 => Some(&params.delay_mix),
+        15 => Some(&params.delay_warble),
+        16 => Some(&params.reverb_size),
+        17 => Some(&params.reverb_damping),
+        18 => Some(&params.reverb_mix),
+        19 => Some(&params.side_amount),
+        20 => Some(&params.dist_ms_balance),
+        21 => Some(&params.magic_ms_balance),
+        22 => Some(&params.chaos_ms_balance),
+        23 => Some(&params.crossover_low),
+        24 => Some(&params.crossover_high),
+        25 => Some(&params.low_band_amount),
+        26 => Some(&params.mid_band_amount),
+        27 => Some(&params.high_band_amount),
+        28 => Some(&params.burst_amount),
+        29 => Some(&params.output_pan),
+        30 => Some(&params.output_balance),
+        31 => Some(&params.stereo_width),
+        32 => Some(&params.chaos_to_drive),
+        33 => Some(&params.chaos_to_gain),
+        34 => Some(&params.chaos_to_cutoff),
+        35 => Some(&params.dist_level),
+        36 => Some(&params.magic_level),
+        37 => Some(&params.chaos_level),
+        38 => Some(&params.chaos_color),
+        39 => Some(&params.generator_pitch),
+        40 => Some(&params.generator_range),
+        41 => Some(&params.tilt),
+        42 => Some(&params.coupling),
+        _ => None,
+    }
+}
+
+/// One of the two snapshots the morph control blends between
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MorphSlot {
+    A,
+    B,
+}
+
+impl MorphSlot {
+    fn index(self) -> usize {
+        match self {
+            MorphSlot::A => 0,
+            MorphSlot::B => 1,
+        }
+    }
+}
+
+/// The two snapshots as they're saved with the plugin state
+// Plain values by parameter ID like in the presets, so the snapshots survive changes to
+// parameter ranges and new morph targets
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MorphState {
+    pub a: BTreeMap<String, f32>,
+    pub b: BTreeMap<String, f32>,
+}
+
+/// The two morph snapshots, shared between the GUI and the audio thread
+// The GUI stores the current settings in a slot, the audio thread reads them while the
+// morph control moves. Everything is stored in atomics so the audio thread never has to
+// wait for the GUI.
+pub struct MorphSnapshots {
+    /// The plain value of every target in both slots
+    // NaN means the slot hasn't been stored yet
+    values: [[AtomicF32; MORPH_TARGETS.len()]; 2],
+
+    /// Incremented every time a slot is stored or loaded, so the audio thread knows to apply them again
+    generation: AtomicUsize,
+}
+
+impl MorphSnapshots {
+    /// Create two empty snapshots
+    pub fn new() -> Self {
+        Self {
+            values: std::array::from_fn(|_| std::array::from_fn(|_| AtomicF32::new(f32::NAN))),
+            generation: AtomicUsize::new(0),
+        }
+    }
+
+    /// Store the current parameter values in a slot
+    pub fn store(&self, slot: MorphSlot, params: &RetardedGainParams) {
+        for (target, value) in self.values[slot.index()].iter().enumerate() {
+            if let Some(param) = target_param(params, target) {
+                value.store(param.unmodulated_plain_value(), Ordering::Relaxed);
+            }
+        }
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Whether the settings have been stored in a slot
+    pub fn is_stored(&self, slot: MorphSlot) -> bool {
+        self.values[slot.index()].iter().any(|value| !value.load(Ordering::Relaxed).is_nan())
+    }
+
+    /// A counter that changes whenever a slot is stored or loaded
+    pub fn generation(&self) -> usize {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// The plain value of a target blended between both slots, `position` 0.0 is A and 1.0 is B
+    // The blend happens on the normalized values, so skewed ranges like the filter cutoff
+    // sweep evenly instead of rushing through the low end. Returns `None` if the target
+    // is missing from either slot.
+    pub fn blend(&self, param: &FloatParam, target: usize, position: f32) -> Option<f32> {
+        let a = self.values[0][target].load(Ordering::Relaxed);
+        let b = self.values[1][target].load(Ordering::Relaxed);
+        if a.is_nan() || b.is_nan() {
+            return None;
+        }
+
+        let a = param.preview_normalized(a);
+        let b = param.preview_normalized(b);
+        Some(param.preview_plain(a + (b - a) * position))
+    }
+}
+
+// Lets `#[persist]` save the snapshots as readable maps from parameter ID to plain value
+impl<'a> PersistentField<'a, MorphState> for MorphSnapshots {
+    fn set(&self, new_value: MorphState) {
+        for (slot, values) in [&new_value.a, &new_value.b].into_iter().enumerate() {
+            for (target, id) in MORPH_TARGETS.iter().enumerate() {
+                let value = values.get(*id).copied().unwrap_or(f32::NAN);
+                self.values[slot][target].store(value, Ordering::Relaxed);
+            }
+        }
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn map<F, R>(&self, f: F) -> R
+    where
+        F: Fn(&MorphState) -> R,
+    {
+        let slot_values = |slot: usize| {
+            MORPH_TARGETS
+                .iter()
+                .zip(&self.values[slot])
+                .map(|(id, value)| (id.to_string(), value.load(Ordering::Relaxed)))
+                .filter(|(_, value)| !value.is_nan())
+                .collect()
+        };
+
+        f(&MorphState {
+            a: slot_values(0),
+            b: slot_values(1),
+        })
+    }
+}
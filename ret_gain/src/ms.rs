@@ -0,0 +1,70 @@
+// Used for the parameter value formatter
+#[cfg(feature = "plugin")]
+use std::sync::Arc;
+
+/// Split a left and right sample into mid and side
+// The mid is what both channels have in common, the side is what's different between them
+#[cfg(feature = "plugin")]
+pub fn encode(left: f32, right: f32) -> (f32, f32) {
+    ((left + right) * 0.5, (left - right) * 0.5)
+}
+
+/// Turn a mid and side sample back into left and right
+// The exact inverse of `encode()`, so encoding and decoding again changes nothing
+#[cfg(feature = "plugin")]
+pub fn decode(mid: f32, side: f32) -> (f32, f32) {
+    (mid + side, mid - side)
+}
+
+/// How much of an effect the mid and side signals get for a given balance
+// -1.0 puts the effect on the mid only, 1.0 on the side only, and at 0.0 both get all of
+// it. Always returns `[mid, side]`, where 1.0 is the fully processed signal and 0.0
+// leaves that signal untouched.
+#[cfg(feature = "plugin")]
+pub fn balance_weights(balance: f32) -> [f32; 2] {
+    let balance = balance.clamp(-1.0, 1.0);
+    [1.0 - balance.max(0.0), 1.0 + balance.min(0.0)]
+}
+
+/// Blend a processed sample with the unprocessed one by a weight from `balance_weights()`
+pub fn apply_weight(unprocessed: f32, processed: f32, weight: f32) -> f32 {
+    unprocessed + (processed - unprocessed) * weight
+}
+
+/// Show a mid/side balance as "Mid 50%", "Center" or "Side 50%"
+#[cfg(feature = "plugin")]
+pub fn v2s_balance() -> Arc<dyn Fn(f32) -> String + Send + Sync> {
+    Arc::new(|balance| {
+        let percent = (balance.abs() * 100.0).round();
+        if percent == 0.0 {
+            String::from("Center")
+        } else if balance < 0.0 {
+            format!("Mid {percent:.0}%")
+        } else {
+            format!("Side {percent:.0}%")
+        }
+    })
+}
+
+/// Parse a mid/side balance typed in as "Mid 50", "Side 25%", "Center" or a plain number
+// Plain numbers go from -100 (mid only) to 100 (side only)
+#[cfg(feature = "plugin")]
+pub fn s2v_balance() -> Arc<dyn Fn(&str) -> Option<f32> + Send + Sync> {
+    Arc::new(|string| {
+        let string = string.trim().to_lowercase();
+        if string.starts_with('c') {
+            return Some(0.0);
+        }
+
+        let (sign, number) = if let Some(number) = string.strip_prefix("mid") {
+            (-1.0, number)
+        } else if let Some(number) = string.strip_prefix("side") {
+            (1.0, number)
+        } else {
+            (1.0, string.as_str())
+        };
+        let number: f32 = number.trim().trim_end_matches('%').trim().parse().ok()?;
+
+        Some((sign * number / 100.0).clamp(-1.0, 1.0))
+    })
+}
@@ -0,0 +1,235 @@
+// Import the NIH-plug prelude for the Enum derive macro
+#[cfg(feature = "plugin")]
+use nih_plug::prelude::*;
+// Import PI constant from the standard library
+use std::f32::consts::PI;
+
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+/// Number of taps of the halfband FIR filter used by every 2x stage
+// Must be of the form 4k + 1 so the filter delay is a whole number of samples at both rates
+const HALFBAND_TAPS: usize = 33;
+
+/// Number of non-zero coefficients in the odd polyphase branch of the halfband filter
+// In a halfband filter every other tap is zero, except for the center tap
+const PHASE_TAPS: usize = (HALFBAND_TAPS - 1) / 2;
+
+/// Delay of the even (center tap) polyphase branch, in samples at the lower rate
+const CENTER_DELAY: usize = PHASE_TAPS / 2;
+
+/// The maximum number of cascaded 2x stages (2^3 = 8x oversampling)
+const MAX_STAGES: usize = 3;
+
+/// The oversampling amount used for the non-linear stages
+// These are the values shown in the GUI and saved with the plugin state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum Oversampling {
+    #[cfg_attr(feature = "plugin", id = "off")]
+    #[cfg_attr(feature = "plugin", name = "Off")]
+    Off,
+    #[cfg_attr(feature = "plugin", id = "2x")]
+    #[cfg_attr(feature = "plugin", name = "2x")]
+    X2,
+    #[cfg_attr(feature = "plugin", id = "4x")]
+    #[cfg_attr(feature = "plugin", name = "4x")]
+    X4,
+    #[cfg_attr(feature = "plugin", id = "8x")]
+    #[cfg_attr(feature = "plugin", name = "8x")]
+    X8,
+}
+
+impl Oversampling {
+    /// The number of cascaded 2x stages needed for this oversampling amount
+    pub fn num_stages(self) -> usize {
+        match self {
+            Oversampling::Off => 0,
+            Oversampling::X2 => 1,
+            Oversampling::X4 => 2,
+            Oversampling::X8 => 3,
+        }
+    }
+
+    /// The factor the sample rate gets multiplied by (1, 2, 4 or 8)
+    pub fn factor(self) -> usize {
+        1 << self.num_stages()
+    }
+
+    /// The latency introduced by the up- and downsampling filters, in samples at the host's rate
+    // Every stage adds its filter delay twice (once going up, once going down), and each stage
+    // runs at twice the rate of the previous one, so its delay counts half as much
+    pub fn latency_samples(self) -> u32 {
+        (0..self.num_stages())
+            .map(|stage| (2 * CENTER_DELAY as u32) >> stage)
+            .sum()
+    }
+}
+
+/// A single 2x up/downsampling stage using a polyphase halfband FIR filter
+// The same filter is used both for removing the images after upsampling and for
+// removing the content above the original Nyquist frequency before downsampling
+#[derive(Clone, Copy)]
+struct HalfbandStage {
+    /// The odd polyphase branch of the halfband filter (the even branch is just the center tap)
+    coeffs: [f32; PHASE_TAPS],
+
+    /// The most recent input samples of the upsampler, newest first
+    up_history: [f32; PHASE_TAPS],
+
+    /// The most recent even-phase samples fed to the downsampler, newest first
+    down_even_history: [f32; CENTER_DELAY + 1],
+
+    /// The most recent odd-phase samples fed to the downsampler, newest first
+    down_odd_history: [f32; PHASE_TAPS + 1],
+}
+
+impl HalfbandStage {
+    /// Create a new stage with a Blackman windowed-sinc halfband filter
+    fn new(coeffs: [f32; PHASE_TAPS]) -> Self {
+        Self {
+            coeffs,
+            up_history: [0.0; PHASE_TAPS],
+            down_even_history: [0.0; CENTER_DELAY + 1],
+            down_odd_history: [0.0; PHASE_TAPS + 1],
+        }
+    }
+
+    /// Clear the filter histories
+    fn reset(&mut self) {
+        *self = Self::new(self.coeffs);
+    }
+
+    /// Turn one input sample into two samples at twice the sample rate
+    fn upsample(&mut self, input: f32) -> [f32; 2] {
+        // Shift the history by one sample and insert the new input at the front
+        self.up_history.copy_within(0..PHASE_TAPS - 1, 1);
+        self.up_history[0] = input;
+
+        // The even output sample only passes through the center tap, which is a plain delay
+        // The factor 2 that compensates for the zero-stuffing cancels out the center tap's 0.5
+        let even = self.up_history[CENTER_DELAY];
+
+        // The odd output sample is the actual interpolation
+        let odd: f32 = self
+            .coeffs
+            .iter()
+            .zip(self.up_history.iter())
+            .map(|(coeff, sample)| coeff * sample)
+            .sum();
+
+        [even, 2.0 * odd]
+    }
+
+    /// Turn two samples at twice the sample rate back into one sample
+    fn downsample(&mut self, even: f32, odd: f32) -> f32 {
+        self.down_even_history.copy_within(0..CENTER_DELAY, 1);
+        self.down_even_history[0] = even;
+        self.down_odd_history.copy_within(0..PHASE_TAPS, 1);
+        self.down_odd_history[0] = odd;
+
+        // The odd branch uses the odd samples from the previous pairs, hence the offset of one
+        let odd_branch: f32 = self
+            .coeffs
+            .iter()
+            .zip(self.down_odd_history[1..].iter())
+            .map(|(coeff, sample)| coeff * sample)
+            .sum();
+
+        0.5 * self.down_even_history[CENTER_DELAY] + odd_branch
+    }
+}
+
+/// Compute the odd polyphase branch of a Blackman windowed-sinc halfband lowpass filter
+// This only runs when the oversampler is created, never on the audio thread
+fn halfband_coefficients() -> [f32; PHASE_TAPS] {
+    let center = (HALFBAND_TAPS - 1) as f32 / 2.0;
+    let mut coeffs = [0.0; PHASE_TAPS];
+    for (i, coeff) in coeffs.iter_mut().enumerate() {
+        // Only the odd taps (relative to the start of the filter) are non-zero
+        let tap = (2 * i + 1) as f32;
+        let x = (tap - center) / 2.0;
+        let sinc = (PI * x).sin() / (PI * x);
+        let window = 0.42 - 0.5 * (2.0 * PI * tap / (HALFBAND_TAPS - 1) as f32).cos()
+            + 0.08 * (4.0 * PI * tap / (HALFBAND_TAPS - 1) as f32).cos();
+        *coeff = 0.5 * sinc * window;
+    }
+
+    // Normalize the branch to 0.5 so the filter has exactly unity gain at DC
+    // (the center tap contributes the other 0.5)
+    let sum: f32 = coeffs.iter().sum();
+    for coeff in coeffs.iter_mut() {
+        *coeff *= 0.5 / sum;
+    }
+
+    coeffs
+}
+
+/// Runs a section of the effect chain at a higher sample rate to reduce aliasing
+// Non-linear processing like tanh distortion and wave folding creates harmonics above
+// the Nyquist frequency, which fold back as inharmonic aliasing. Processing at 2x-8x
+// the sample rate and filtering before going back down removes most of that.
+pub struct Oversampler {
+    /// The currently active oversampling amount
+    oversampling: Oversampling,
+
+    /// The cascaded 2x stages for every channel
+    channels: [[HalfbandStage; MAX_STAGES]; MAX_CHANNELS],
+}
+
+impl Oversampler {
+    /// Create a new oversampler with the given oversampling amount
+    pub fn new(oversampling: Oversampling) -> Self {
+        let stage = HalfbandStage::new(halfband_coefficients());
+
+        Self {
+            oversampling,
+            channels: [[stage; MAX_STAGES]; MAX_CHANNELS],
+        }
+    }
+
+    /// The currently active oversampling amount
+    pub fn oversampling(&self) -> Oversampling {
+        self.oversampling
+    }
+
+    /// Change the oversampling amount
+    // The filter histories are cleared when the amount changes since they no longer line up
+    pub fn set_oversampling(&mut self, oversampling: Oversampling) {
+        if oversampling != self.oversampling {
+            self.oversampling = oversampling;
+            self.reset();
+        }
+    }
+
+    /// Clear the filter state of every channel
+    pub fn reset(&mut self) {
+        for stages in self.channels.iter_mut() {
+            for stage in stages.iter_mut() {
+                stage.reset();
+            }
+        }
+    }
+
+    /// Process a single sample of the given channel through `f` at the oversampled rate
+    // `f` gets called `factor()` times per input sample, so it must be written for
+    // a sample rate of `sample_rate * factor()`
+    pub fn process<F: FnMut(f32) -> f32>(&mut self, channel: usize, sample: f32, mut f: F) -> f32 {
+        let num_stages = self.oversampling.num_stages();
+        process_stages(&mut self.channels[channel][..num_stages], sample, &mut f)
+    }
+}
+
+/// Recursively upsample through every stage, run `f` at the highest rate, and come back down
+fn process_stages<F: FnMut(f32) -> f32>(stages: &mut [HalfbandStage], sample: f32, f: &mut F) -> f32 {
+    match stages.split_first_mut() {
+        // No stages left, so we're at the highest rate
+        None => f(sample),
+        Some((stage, remaining_stages)) => {
+            let [even, odd] = stage.upsample(sample);
+            let even = process_stages(remaining_stages, even, f);
+            let odd = process_stages(remaining_stages, odd, f);
+            stage.downsample(even, odd)
+        }
+    }
+}
@@ -11,14 +11,73 @@ use nih_plug_vizia::ViziaState;
 
 // Import the standard library's Arc (Atomic Reference Counting) type
 // Arc allows multiple ownership of the same data across different threads safely
-use std::sync::Arc;
+// Mutex is only locked by the GUI thread, never by the audio thread
+use std::sync::atomic::AtomicU32;
+use std::sync::{Arc, Mutex, RwLock};
 
 // Import our own modules with editor, effects, etc.
 use crate::editor;  // 'crate' means "from the current crate (package)"
-use crate::distortion::Distortion;  // Import the Distortion struct from distortion.rs
-use crate::fractal::FractalMagic;  // Import the FractalMagic struct from fractal.rs
-use crate::chaos::ChaosAttractor;  // Import the ChaosAttractor struct from chaos.rs
+use crate::editor::layout::LayoutSettings;  // Import the editor's tab settings from editor/layout.rs
+use crate::editor::theme::ThemeSettings;  // Import the editor's theme settings from editor/theme.rs
+use crate::presets::AbComparison;  // Import the A/B comparison state from presets.rs
+use crate::analyzer::{self, AttractorInput, AttractorOutput, CorrelationMeter, LevelDifferenceMeter, ScopeInput, ScopeOutput, SpectrumInput, SpectrumOutput, TextureInput, TextureOutput};  // Import the analyzers from analyzer.rs
+use crate::distortion::{DistortionMode, DriveModel, MAX_DRIVE};  // Import the distortion types from distortion.rs
+use crate::filter::FilterMode;  // Import the tone filter's modes from filter.rs
+use crate::fractal::{self, FoldQuality, MagicEngine, MagicMode};  // Import the fractal effect types from fractal.rs
+use crate::chaos::{self, ChaosType, Evolution, FmMode, Integrator};  // Import the chaos effect types from chaos.rs
+use crate::compressor::Compressor;  // Import the compressor from compressor.rs
+use crate::crusher::Crusher;  // Import the bitcrusher from crusher.rs
+use crate::dc_blocker::DcBlocker;  // Import the DC blocker from dc_blocker.rs
+use crate::metering::{self, CpuLoadMeter, LoudnessMeter, MeterBallistics, MeterRange, ProcessingActivity, TruePeakMeter};  // Import the meters from metering.rs
+use crate::delay::{self, Delay};  // Import the delay effect from delay.rs
+use crate::gate::{self, Gate};  // Import the noise gate from gate.rs
 use crate::gain::GainProcessor;  // Import the GainProcessor struct from gain.rs
+use crate::tilt::{self, TiltEq};  // Import the tilt EQ from tilt.rs
+use crate::width::StereoWidth;  // Import the stereo width control from width.rs
+use crate::MAX_CHANNELS;  // The maximum number of channels the effects keep state for
+use crate::latency::{CompensationDelay, LatencyReport};  // Import the delay line for latency compensation and the latency report from latency.rs
+use crate::limiter::Limiter;  // Import the Limiter struct from limiter.rs
+use crate::clipper::HardClipper;  // Import the hard clipper from clipper.rs
+use crate::export::{self, ExportState, ExportStatus, TextureExport};  // Import the texture export from export.rs
+use crate::generator::{self, Generator, Scale};  // Import the generator mode's oscillator from generator.rs
+use crate::modulation::{ChaosModMatrix, Lfo, LfoShape, ModulationRings, NoteDivision};  // Import the LFO and chaos modulation types from modulation.rs
+use crate::envelope::{self, Calibration, EnvelopeFollower, InputCalibration, TransientDetector};  // Import the envelope follower, transient detector and input calibration from envelope.rs
+use crate::midi::{self, MidiLearn};  // Import MIDI learn from midi.rs
+use crate::morph::{self, MorphSnapshots, MORPH_TARGETS};  // Import the morph snapshots from morph.rs
+use crate::macros::{MacroMappings, NUM_MACROS};  // Import the macro mapping matrix from macros.rs
+use crate::migration::{self, STATE_VERSION};  // Import the state migrations from migration.rs
+use nih_plug::wrapper::state::PluginState;  // The saved state the migrations work on
+use crate::oversampling::{Oversampler, Oversampling};  // Import the oversampling types from oversampling.rs
+use crate::quality::Quality;  // Import the quality tiers from quality.rs
+use crate::precision::Precision;  // Import the precisions of the feedback stages from precision.rs
+use crate::reverb::Reverb;  // Import the reverb from reverb.rs
+use crate::ms;  // Import the mid/side helpers from ms.rs
+use crate::chain::{ChainSettings, EffectChain};  // Import the distortion, fractal and chaos chain from chain.rs
+use crate::crossover::{Crossover, FULL_BAND, NUM_BANDS};  // Import the band splitter from crossover.rs
+use crate::engine_state::EngineState;  // Import the chaos and fractal snapshots from engine_state.rs
+use crate::routing::{ChannelMode, EffectOrder, StageSolo};  // Import the effect order types from routing.rs
+
+/// How long the crossfade between the processed and the bypassed signal takes
+// Long enough to avoid a click, short enough to feel instant
+const BYPASS_FADE_MS: f32 = 10.0;
+
+/// How far the playhead may be off from where it should be before that counts as a jump, in samples
+// Some hosts work the position in samples out from seconds or beats, so it can be a sample
+// or two off from block to block while the transport just carries on
+const TRANSPORT_JUMP_TOLERANCE: i64 = 4;
+
+/// The aux output that carries the chaos and fractal modulation signals as audio
+const MODULATION_OUTPUT_PORTS: &[NonZeroU32] = &[new_nonzero_u32(2)];
+const MODULATION_OUTPUT_NAMES: PortNames = PortNames {
+    aux_outputs: &["Modulation"],
+    ..PortNames::const_default()
+};
+
+/// Work the editor hands off to a background thread
+pub enum Task {
+    /// Render a texture from the chaos and fractal effects and save it as a WAV file
+    ExportTexture(TextureExport),
+}
 
 /// The main plugin structure combining all effects
 // This struct is the central part of our plugin, containing all the data and effect processors
@@ -27,19 +86,198 @@ pub struct RetardedGain {
     // It allows multiple parts of the code to access the same data without copying it
     params: Arc<RetardedGainParams>,  // Hold all parameter data
 
-    /// Needed to normalize the peak meter's response based on the sample rate.
-    // This will be calculated based on the sample rate to make meters decay at a consistent rate
-    peak_meter_decay_weight: f32,
-    
-    /// The current data for the peak meter. Shared between GUI and audio processing.
-    // AtomicF32 allows both audio thread and GUI thread to safely access this value
-    peak_meter: Arc<AtomicF32>,
+    /// The true-peak meter of the output. Its reading is shared between GUI and audio processing.
+    peak_meter: TruePeakMeter,
+
+    /// True-peak meter for the signal going into the effect chain, after the input trim
+    input_peak_meter: TruePeakMeter,
+
+    /// Loudness meter of the untouched input, for level-matching against the output
+    input_loudness: LoudnessMeter,
+
+    /// Loudness meter of the output
+    output_loudness: LoudnessMeter,
+
+    /// The level of the output relative to the input in dB, shared with the GUI
+    // This is the gain the whole chain adds, for level-matching against bypass
+    level_difference: Arc<AtomicF32>,
+
+    /// Measures the level difference between the input and output
+    level_difference_meter: LevelDifferenceMeter,
+
+    /// Measures how much of the realtime budget `process()` takes, shared with the GUI
+    cpu_meter: CpuLoadMeter,
+
+    /// When the latest block was processed, so the GUI can tell when the host stopped calling `process()`
+    activity: Arc<ProcessingActivity>,
+
+    /// The magic amount after envelope modulation, shown in the GUI
+    modulated_magic: Arc<AtomicF32>,
+
+    /// The chaos amount after LFO and envelope modulation, shown in the GUI
+    modulated_chaos: Arc<AtomicF32>,
+
+    /// The lowest stability any stability governor allowed during the latest block, shown in the GUI
+    stability: Arc<AtomicF32>,
+
+    /// The values every smoothed parameter was moved to, for the GUI's modulation rings
+    modulation_rings: Arc<ModulationRings>,
+
+    /// The correlation between the left and right output channels, shown in the GUI
+    correlation: Arc<AtomicF32>,
+
+    /// Measures the correlation of the output
+    correlation_meter: CorrelationMeter,
+
+    /// Records the path of the chaos effect's attractor for the GUI
+    attractor_input: AttractorInput,
+
+    /// The GUI side of the attractor scope, handed to the editor when it opens
+    // Only the GUI thread ever locks this and the other outputs below, the audio thread writes
+    // to their inputs without locking, so drawing them never blocks it
+    attractor_output: Arc<Mutex<AttractorOutput>>,
+
+    /// Records the input and output waveforms for the GUI
+    scope_input: ScopeInput,
+
+    /// The GUI side of the oscilloscope, handed to the editor when it opens
+    scope_output: Arc<Mutex<ScopeOutput>>,
+
+    /// Records the output and the chaos signal over the last few seconds for the GUI
+    texture_input: TextureInput,
+
+    /// The GUI side of the texture history, handed to the editor when it opens
+    texture_output: Arc<Mutex<TextureOutput>>,
+
+    /// The audio thread side of the spectrum analyzer
+    spectrum_input: SpectrumInput,
+
+    /// The GUI side of the spectrum analyzer, handed to the editor when it opens
+    spectrum_output: Arc<Mutex<SpectrumOutput>>,
     
     // The effect processors - each one handles a specific audio effect
     gain_processor: GainProcessor,  // Controls volume
-    distortion: Distortion,  // Adds distortion/saturation 
-    fractal_magic: FractalMagic,  // Applies fractal-based effects
-    chaos_attractor: ChaosAttractor,  // Applies chaos theory algorithms to sound
+    gates: [Gate; MAX_CHANNELS],  // Keep the input's noise floor out of the chain, only the first one is used when linked
+    // The main chain has one set of processors per band, only `FULL_BAND` runs when multiband is off
+    chains: [EffectChain; NUM_BANDS],  // Distortion, fractal and chaos stages, one chain for the full band and one for every band in multiband mode
+    crossover: Crossover,  // Splits the signal into bands for multiband processing
+    crusher: Crusher,  // Lo-fi bit and sample rate reduction after the chain
+    compressors: [Compressor; MAX_CHANNELS],  // Dynamics after the dry/wet mix, only the first one is used when linked
+    delay: Delay,  // Echoes after the dry/wet mix
+    reverb: Reverb,  // Smears everything into a tail at the end of the chain
+    tilt_eq: TiltEq,  // Brightens or darkens everything the chain put out
+    stereo_width: StereoWidth,  // Narrows or widens the output's stereo image
+    dc_blocker: DcBlocker,  // Removes the DC offset the asymmetric effects leave behind
+
+    /// Runs the effect chain at a higher sample rate to reduce aliasing
+    oversampler: Oversampler,
+
+    /// Delays the dry signal by the oversampling latency and the fractal lookahead so the mix stays phase-aligned
+    dry_delay: CompensationDelay,
+
+    /// Safety limiter at the very end of the chain
+    limiter: Limiter,
+
+    /// Hard clipper after the limiter, the very last stage
+    hard_clipper: HardClipper,
+
+    /// How the latest texture export went, shared with the background task and the GUI
+    export_status: Arc<ExportStatus>,
+
+    /// Whether the limiter was enabled during the previous block
+    // Used to clear the limiter's delay line when it gets switched back on
+    limiter_enabled: bool,
+
+    /// Whether the latency was kept constant during the previous block
+    constant_latency: bool,
+
+    /// How far the limiter has faded in
+    // 0.0 passes the signal through the lookahead delay untouched, 1.0 is fully limited.
+    // Only fades with constant latency, otherwise it jumps along with the switch.
+    limiter_fade: f32,
+
+    /// The latency we last reported to the host, in samples
+    reported_latency: u32,
+
+    /// Delays the untouched input by the total latency, so bypassing doesn't shift the timing
+    bypass_delay: CompensationDelay,
+
+    /// How far the crossfade to the bypassed signal has gotten
+    // 0.0 is fully processed, 1.0 is fully bypassed
+    bypass_fade: f32,
+
+    /// Whether `initialize()` has run before, so the next call is a change of the sample rate or layout
+    initialized: bool,
+
+    /// How far the output has faded back in after the plugin was initialized again
+    // 0.0 is silent, 1.0 is the full output. Everything starts over from a clean state
+    // then, and what was playing a moment ago would jump straight to that without the fade.
+    reinit_fade: f32,
+
+    /// Whether the host runs the plugin with a mono input and a stereo output
+    // The input gets copied to both channels first, and the chaos effect runs its
+    // decorrelated attractors, so a mono source like a guitar comes out wide
+    mono_to_stereo: bool,
+
+    /// Modulates the chaos amount
+    lfo: Lfo,
+
+    /// Follow the input level to modulate the magic and chaos amounts
+    // One per channel for dual mono, only the first one is used when the channels are linked
+    envelopes: [EnvelopeFollower; MAX_CHANNELS],
+
+    /// Fires a chaos burst on every hit in the input
+    // Listens to the loudest channel, so a hit on either side moves both
+    transient_detector: TransientDetector,
+
+    /// Measures the input level for calibrating how hard it pushes the chaos and fractal effects
+    input_calibration: InputCalibration,
+
+    /// Where the host's playhead should be at the start of the next block, in samples
+    // `None` while the transport is stopped or the host doesn't report a position
+    expected_pos_samples: Option<i64>,
+
+    /// Where the host's playhead was at the start of the previous block, in samples
+    // Anything before it is a jump back, like a loop, however close to the expected position
+    previous_pos_samples: Option<i64>,
+
+    /// Whether the host's transport was playing during the previous block
+    was_playing: bool,
+
+    /// Whether multiband processing was enabled during the previous block
+    // Used to clear the bands that only run in multiband mode when it gets switched back on
+    multiband_enabled: bool,
+
+    /// The oscillator that replaces the input in generator mode
+    generator: Generator,
+
+    /// Whether the saved engine state still has to be applied
+    // Set by `initialize()`, and applied in the first block once every setting is in place,
+    // since switching the chaos system or the formula would start them over again
+    restore_engine_state: bool,
+
+    /// The engine state the project was opened with
+    // The transport restart goes back here instead of to the seed's starting conditions, so
+    // bounces stay deterministic and still start with the project's texture
+    engine_restart_point: Option<EngineState>,
+
+    /// Whether generator mode was enabled during the previous block
+    // Used to start the oscillator from the top of its cycle when it gets switched on
+    generator_enabled: bool,
+
+    /// The morph position the morph targets were last blended for
+    // NaN until the first block, so the snapshots are applied right away
+    morph_position: f32,
+
+    /// The generation of the morph snapshots the morph targets were last blended with
+    morph_generation: usize,
+
+    /// The plain value the morph blended every morph target to, NaN while it isn't blended
+    morphed_values: [f32; MORPH_TARGETS.len()],
+
+    /// The plain value every morph target's smoother was last sent to by the morph or the macros
+    // The smoothers are only moved again when this changes, so turning a knob still works
+    smoother_targets: [f32; MORPH_TARGETS.len()],
 }
 
 // The #[derive(Params)] macro automatically implements the Params trait for our struct
@@ -51,19 +289,569 @@ pub struct RetardedGainParams {
     #[persist = "editor-state"]
     pub editor_state: Arc<ViziaState>,  // Holds the GUI state
 
+    /// The editor's theme and accent color, saved together with the parameter state
+    // Only the GUI thread ever locks this and the other RwLocks below, so they never block the
+    // audio thread
+    #[persist = "editor-theme"]
+    pub editor_theme: RwLock<ThemeSettings>,
+
+    /// The editor's open tab and how far every tab is scrolled, saved together with the parameter state
+    #[persist = "editor-layout"]
+    pub editor_layout: RwLock<LayoutSettings>,
+
+    /// Which MIDI CCs control which parameters, saved together with the parameter state
+    #[persist = "midi-mappings"]
+    pub midi_learn: MidiLearn,
+
+    /// The parameters that keep their values while browsing presets, saved together with the parameter state
+    #[persist = "preset-locks"]
+    pub preset_locks: RwLock<PresetLocks>,
+
+    /// The A/B comparison slots, saved together with the parameter state
+    #[persist = "ab-comparison"]
+    pub ab_comparison: RwLock<AbComparison>,
+
+    /// The seed for the chaos effect's starting conditions, changed with the re-seed button
+    // Saved with the state so a session always comes back with the same texture
+    #[persist = "chaos-seed"]
+    pub chaos_seed: AtomicU32,
+
+    /// The two snapshots the morph control blends between, saved together with the parameter state
+    #[persist = "morph-snapshots"]
+    pub morph_snapshots: MorphSnapshots,
+
+    /// Which parameters the macro knobs drive, saved together with the parameter state
+    #[persist = "macro-mappings"]
+    pub macro_mappings: MacroMappings,
+
+    /// Where the chaos systems and fractal iterations were, saved together with the parameter state
+    // Only kept up to date while Persist Engine State is on, `None` otherwise
+    #[persist = "engine-state"]
+    pub engine_state: RwLock<Option<EngineState>>,
+
+    /// The version of the state layout, so older projects can be migrated when they're loaded
+    // Always the current version, `migration::migrate()` brings older states up to it
+    #[persist = "state-version"]
+    pub state_version: AtomicU32,
+
     // Parameter definitions - each gets a unique ID and stores a single value
     // Similar to props/state in React or properties in a Python class
+    /// Passes the input through untouched
+    // Marked as the host's bypass parameter, so the host's own bypass button uses it
+    #[id = "bypass"]
+    pub bypass: BoolParam,
+
+    /// Input trim applied before the effect chain
+    // Pulling this down before heavy drive settings keeps the non-linear stages under control
+    #[id = "input_gain"]
+    pub input_gain: FloatParam,
+
+    /// How the level the chaos and fractal effects listen to is calibrated
+    // Only their influence is scaled, the audio keeps its level. Projects from before the
+    // calibration load with it off, see `migration.rs`.
+    #[id = "calibration"]
+    pub calibration: EnumParam<Calibration>,
+
+    /// How much the influence is turned up or down with the manual calibration, in dB
+    #[id = "calibration_trim"]
+    pub calibration_trim: FloatParam,
+
+    /// Output gain applied after the effect chain
+    // The ID is still "gain" so sessions from before the input trim existed keep their gain setting
     #[id = "gain"]  // Unique identifier for this parameter
-    pub gain: FloatParam,  // FloatParam is a special type that handles parameter behaviors
+    pub output_gain: FloatParam,  // FloatParam is a special type that handles parameter behaviors
+
+    /// Turns the output down by as much as the distortion's drive makes the signal louder
+    // Keeps the level roughly the same while sweeping the drive, so louder doesn't fool the ear.
+    // Only does anything with the legacy drive model, the compensated one already does this.
+    #[id = "auto_gain"]
+    pub auto_gain: BoolParam,
+
+    /// Moves the output's stereo image to the left or right
+    // Constant-power: the channel that's moved over keeps its level
+    #[id = "output_pan"]
+    pub output_pan: FloatParam,
+
+    /// Turns one side of the output down
+    #[id = "output_balance"]
+    pub output_balance: FloatParam,
+
+    /// How wide the output's stereo image is
+    // 0% is mono, 100% leaves it alone, above that the side is turned up and a
+    // decorrelated copy of the mid is added to it
+    #[id = "stereo_width"]
+    pub stereo_width: FloatParam,
+
+    /// Flips the polarity of the left output channel
+    #[id = "invert_left"]
+    pub invert_left: BoolParam,
+
+    /// Flips the polarity of the right output channel
+    #[id = "invert_right"]
+    pub invert_right: BoolParam,
     
+    /// The level the input has to reach to open the noise gate, in dB
+    // The bottom of the range switches the gate off
+    #[id = "gate_threshold"]
+    pub gate_threshold: FloatParam,
+
+    /// How quickly the noise gate opens, in milliseconds
+    #[id = "gate_attack"]
+    pub gate_attack: FloatParam,
+
+    /// How long the noise gate stays open after the input falls below the threshold, in milliseconds
+    #[id = "gate_hold"]
+    pub gate_hold: FloatParam,
+
+    /// How quickly the noise gate closes, in milliseconds
+    #[id = "gate_release"]
+    pub gate_release: FloatParam,
+
+    /// How hard the signal is pushed into the waveshaper, in dB
+    // Stored as dB since state version 2, older sessions have the plain ratio and are
+    // converted by the migration
     #[id = "drive"]
     pub drive: FloatParam,
+
+    /// Whether the distortion makes up the level it adds
+    // Old sessions are migrated to the legacy model, so they keep sounding the same
+    #[id = "drive_model"]
+    pub drive_model: EnumParam<DriveModel>,
+
+    /// The waveshaping algorithm of the distortion stage
+    #[id = "dist_mode"]
+    pub dist_mode: EnumParam<DistortionMode>,
+
+    /// The type of the tone filter after the distortion
+    #[id = "filter_mode"]
+    pub filter_mode: EnumParam<FilterMode>,
+
+    /// The cutoff frequency of the tone filter
+    #[id = "filter_cutoff"]
+    pub filter_cutoff: FloatParam,
+
+    /// The resonance of the tone filter
+    #[id = "filter_resonance"]
+    pub filter_resonance: FloatParam,
     
     #[id = "magic"]
     pub magic: FloatParam,
+
+    /// The iteration formula of the fractal effect
+    #[id = "magic_mode"]
+    pub magic_mode: EnumParam<MagicMode>,
+
+    /// Whether the fractal effect works on the samples or on the spectrum
+    // The spectral engine adds a fixed latency instead of the lookahead's
+    #[id = "magic_engine"]
+    pub magic_engine: EnumParam<MagicEngine>,
+
+    /// How the fractal's wavefolder anti-aliases its folds
+    // Projects from before the setting load with the plain fold, see `migration.rs`
+    #[id = "magic_fold_quality"]
+    pub magic_fold_quality: EnumParam<FoldQuality>,
+
+    /// Lets the fractal effect's release smoother see transients coming, in milliseconds
+    // 0 turns it off, anything else adds that much latency
+    #[id = "magic_lookahead"]
+    pub magic_lookahead: FloatParam,
     
     #[id = "chaos"]
     pub chaos: FloatParam,
+
+    /// The chaotic system driving the chaos effect
+    #[id = "chaos_system"]
+    pub chaos_system: EnumParam<ChaosType>,
+
+    /// The numerical method that advances the continuous chaotic systems
+    #[id = "chaos_integrator"]
+    pub chaos_integrator: EnumParam<Integrator>,
+
+    /// How the chaotic systems' parameters drift over time, freely or over a number of bars
+    #[id = "chaos_evolution"]
+    pub chaos_evolution: EnumParam<Evolution>,
+
+    /// How fast the chaotic systems run, as a multiple of their normal speed
+    // From a slow drift well below 1x to audio-rate screaming at the top
+    #[id = "chaos_rate"]
+    pub chaos_rate: FloatParam,
+
+    /// How much of the chaotic signal's bandwidth reaches the audio, from a smooth wobble to harsh noise
+    #[id = "chaos_color"]
+    pub chaos_color: FloatParam,
+
+    /// What the chaos effect's FM branch does: the classic cosine, or a real frequency shift
+    // Classic is how the chaos effect always sounded, so older projects don't change
+    #[id = "chaos_fm_mode"]
+    pub chaos_fm_mode: EnumParam<FmMode>,
+
+    /// How far the chaos signal shifts the input in the Shift mode, in Hz
+    #[id = "chaos_shift"]
+    pub chaos_shift: FloatParam,
+
+    /// How much the fractal and the chaos effect listen to each other
+    // The fractal's z pushes the chaotic system, and the chaos signal moves the fractal's c
+    // 0.0 is the default, where they run independently like they always did
+    #[id = "coupling"]
+    pub coupling: FloatParam,
+
+    /// Whether the plugin ignores its input and plays an oscillator through the chain instead
+    // Turns the chaos and fractal effects into an instrument for an empty track
+    #[id = "generator"]
+    pub generator: BoolParam,
+
+    /// The generator's pitch with the chaos signal at rest, in Hz
+    #[id = "generator_pitch"]
+    pub generator_pitch: FloatParam,
+
+    /// How far the chaos signal moves the generator's pitch either way, in semitones
+    #[id = "generator_range"]
+    pub generator_range: FloatParam,
+
+    /// The scale the generator's pitch is pulled onto
+    #[id = "generator_scale"]
+    pub generator_scale: EnumParam<Scale>,
+
+    /// Whether MIDI notes play the generator, instead of it droning at `generator_pitch`
+    #[id = "generator_midi"]
+    pub generator_midi: BoolParam,
+
+    /// How long the generator's pitch takes to glide from one played note to the next
+    #[id = "generator_glide"]
+    pub generator_glide: FloatParam,
+
+    /// The rate of the chaos effect's secondary phase modulation, in Hz
+    #[id = "chaos_mod_rate"]
+    pub chaos_mod_rate: FloatParam,
+
+    /// Whether the secondary phase modulation follows the host's tempo instead of `chaos_mod_rate`
+    #[id = "chaos_mod_sync"]
+    pub chaos_mod_sync: BoolParam,
+
+    /// The note length of one modulation cycle when synced
+    #[id = "chaos_mod_division"]
+    pub chaos_mod_division: EnumParam<NoteDivision>,
+
+    /// Runs decorrelated Lorenz systems on the left and right channels
+    #[id = "chaos_stereo"]
+    pub chaos_stereo: BoolParam,
+
+    /// Holds the Lorenz systems still, locking the chaos modulation to its current timbre
+    #[id = "chaos_freeze"]
+    pub chaos_freeze: BoolParam,
+
+    /// Restarts the chaos and fractal effects whenever the host's playback starts or jumps
+    // This makes every bounce of the same section sound the same
+    #[id = "transport_reset"]
+    pub transport_reset: BoolParam,
+
+    /// Only lets the fractal LFO and the chaos evolution move while the host is playing
+    // Both start over when playback stops, so a render lines up with what was auditioned
+    #[id = "transport_clock"]
+    pub transport_clock: BoolParam,
+
+    /// Saves where the chaos systems and fractal iterations are with the project
+    // Reopening the project then resumes the exact texture instead of starting them over
+    #[id = "persist_engine_state"]
+    pub persist_engine_state: BoolParam,
+
+    /// Enables the bitcrusher after the effect chain
+    #[id = "crusher"]
+    pub crusher: BoolParam,
+
+    /// The bit depth of the bitcrusher
+    #[id = "crusher_bits"]
+    pub crusher_bits: FloatParam,
+
+    /// How many samples every sample is held for by the bitcrusher
+    #[id = "crusher_downsample"]
+    pub crusher_downsample: FloatParam,
+
+    /// The curve of the soft clippers in the chaos, the fractal and the delay's feedback
+    // 0.0 = tanh, 0.5 = the sinh-based curve, 1.0 = the polynomial, morphing in between
+    #[id = "clip_character"]
+    pub clip_character: FloatParam,
+
+    /// Dry/wet balance for the whole effect chain
+    // 0.0 = only the untouched input, 1.0 = only the processed signal
+    // Anything in between lets the plugin work as a parallel effect
+    #[id = "mix"]
+    pub mix: FloatParam,
+
+    /// Blends all continuous parameters between the two morph snapshots
+    // 0.0 = snapshot A, 1.0 = snapshot B. Only does anything once both snapshots are stored.
+    #[id = "morph"]
+    pub morph: FloatParam,
+
+    /// The first macro knob, drives the parameters mapped to it in the macro matrix
+    #[id = "macro_1"]
+    pub macro_1: FloatParam,
+
+    /// The second macro knob
+    #[id = "macro_2"]
+    pub macro_2: FloatParam,
+
+    /// How much the effect chain is oversampled
+    // Higher settings alias less at high drive, but cost more CPU and add latency
+    #[id = "oversampling"]
+    pub oversampling: EnumParam<Oversampling>,
+
+    /// Trades CPU against fidelity for the oversampling, the chaos integrator and the fold quality at once
+    // Normal is the default and leaves all three as they're set, so older projects sound the same
+    #[id = "quality"]
+    pub quality: EnumParam<Quality>,
+
+    /// The float type the crossover, the tone filter, the fractal formula and the chaos systems are calculated with
+    // Hosts only ever hand NIH-plug 32-bit buffers, so this is a quality switch instead. The
+    // ID is from when it only applied to the fractal formula, so older projects keep their setting.
+    #[id = "magic_precision"]
+    pub precision: EnumParam<Precision>,
+
+    /// The order the distortion, fractal and chaos stages are applied in
+    #[id = "routing"]
+    pub routing: EnumParam<EffectOrder>,
+
+    /// The output level of the distortion stage, after the tone filter
+    #[id = "dist_level"]
+    pub dist_level: FloatParam,
+
+    /// The output level of the fractal stage
+    #[id = "magic_level"]
+    pub magic_level: FloatParam,
+
+    /// The output level of the chaos stage
+    #[id = "chaos_level"]
+    pub chaos_level: FloatParam,
+
+    /// Only hear the distortion stage, and any other soloed stage
+    // The solos are for auditioning, so they're left out of the host's automation
+    #[id = "solo_distortion"]
+    pub solo_distortion: BoolParam,
+
+    /// Only hear the fractal stage, and any other soloed stage
+    #[id = "solo_fractal"]
+    pub solo_fractal: BoolParam,
+
+    /// Only hear the chaos stage, and any other soloed stage
+    #[id = "solo_chaos"]
+    pub solo_chaos: BoolParam,
+
+    /// How the channels go through the chain: linked, dual mono or mid/side
+    #[id = "channel_mode"]
+    pub channel_mode: EnumParam<ChannelMode>,
+
+    /// How much of the drive, magic and chaos amounts the side signal gets in mid/side mode
+    #[id = "side_amount"]
+    pub side_amount: FloatParam,
+
+    /// Whether the distortion goes on the mid, the side or both in mid/side mode
+    #[id = "dist_ms_balance"]
+    pub dist_ms_balance: FloatParam,
+
+    /// Whether the Magic One effect goes on the mid, the side or both in mid/side mode
+    #[id = "magic_ms_balance"]
+    pub magic_ms_balance: FloatParam,
+
+    /// Whether the chaos effect goes on the mid, the side or both in mid/side mode
+    #[id = "chaos_ms_balance"]
+    pub chaos_ms_balance: FloatParam,
+
+    /// Splits the signal into three bands and runs the distortion, fractal and chaos stages on each one
+    #[id = "multiband"]
+    pub multiband: BoolParam,
+
+    /// The crossover frequency between the low and the mid band, in Hz
+    #[id = "crossover_low"]
+    pub crossover_low: FloatParam,
+
+    /// The crossover frequency between the mid and the high band, in Hz
+    #[id = "crossover_high"]
+    pub crossover_high: FloatParam,
+
+    /// How much of the drive, magic and chaos amounts the low band gets in multiband mode
+    #[id = "low_band_amount"]
+    pub low_band_amount: FloatParam,
+
+    /// How much of the drive, magic and chaos amounts the mid band gets in multiband mode
+    #[id = "mid_band_amount"]
+    pub mid_band_amount: FloatParam,
+
+    /// How much of the drive, magic and chaos amounts the high band gets in multiband mode
+    #[id = "high_band_amount"]
+    pub high_band_amount: FloatParam,
+
+    /// The level where the compressor starts working, in dB
+    #[id = "comp_threshold"]
+    pub comp_threshold: FloatParam,
+
+    /// The compression ratio, 1:1 turns the compressor off
+    #[id = "comp_ratio"]
+    pub comp_ratio: FloatParam,
+
+    /// How quickly the compressor reacts to a level over the threshold, in milliseconds
+    #[id = "comp_attack"]
+    pub comp_attack: FloatParam,
+
+    /// How quickly the compressor lets go again, in milliseconds
+    #[id = "comp_release"]
+    pub comp_release: FloatParam,
+
+    /// Lets the chaos signal wobble the compressor's release time
+    #[id = "comp_chaotic_release"]
+    pub comp_chaotic_release: BoolParam,
+
+    /// The delay time when not synced, in milliseconds
+    #[id = "delay_time"]
+    pub delay_time: FloatParam,
+
+    /// Whether the delay time follows the host's tempo instead of `delay_time`
+    #[id = "delay_sync"]
+    pub delay_sync: BoolParam,
+
+    /// The delay time as a note length when synced
+    #[id = "delay_division"]
+    pub delay_division: EnumParam<NoteDivision>,
+
+    /// How much of the echo is fed back into the delay
+    #[id = "delay_feedback"]
+    pub delay_feedback: FloatParam,
+
+    /// How much the chaos signal wobbles the delay time
+    #[id = "delay_warble"]
+    pub delay_warble: FloatParam,
+
+    /// Dry/wet balance of the delay
+    // 0.0 turns the delay off, which is the default
+    #[id = "delay_mix"]
+    pub delay_mix: FloatParam,
+
+    /// How long the reverb tail rings
+    #[id = "reverb_size"]
+    pub reverb_size: FloatParam,
+
+    /// How quickly the high frequencies of the reverb tail die out
+    #[id = "reverb_damping"]
+    pub reverb_damping: FloatParam,
+
+    /// Dry/wet balance of the reverb
+    // 0.0 turns the reverb off, which is the default
+    #[id = "reverb_mix"]
+    pub reverb_mix: FloatParam,
+
+    /// How far the tilt EQ after the reverb turns the highs up and the lows down, in dB
+    // Negative values darken the output, 0.0 leaves it alone, which is the default
+    #[id = "tilt"]
+    pub tilt: FloatParam,
+
+    /// Enables the DC blocker at the end of the chain
+    // Only meant for debugging, so it can't be automated and isn't in the editor
+    #[id = "dc_blocker"]
+    pub dc_blocker: BoolParam,
+
+    /// How the peak meters' readings follow the signal
+    // Only changes what the meters show, so it can't be automated
+    #[id = "meter_ballistics"]
+    pub meter_ballistics: EnumParam<MeterBallistics>,
+
+    /// How long the digital peak meters take to fall back 20 dB, in milliseconds
+    // Only changes what the meters show, so it can't be automated
+    #[id = "meter_decay"]
+    pub meter_decay: FloatParam,
+
+    /// The levels the peak meters show
+    // Only read by the editor
+    #[id = "meter_range"]
+    pub meter_range: EnumParam<MeterRange>,
+
+    /// Enables the lookahead brickwall limiter after the gain stage
+    #[id = "limiter"]
+    pub limiter: BoolParam,
+
+    /// Keeps the latency-adding stages in the chain while they're switched off
+    // They pass the signal through untouched instead, so switching them during playback
+    // doesn't change the latency and shift the timeline. Changing this changes the
+    // latency itself, so it can't be automated.
+    #[id = "constant_latency"]
+    pub constant_latency: BoolParam,
+
+    /// The maximum output level of the limiter
+    #[id = "limiter_ceiling"]
+    pub limiter_ceiling: FloatParam,
+
+    /// Enables the hard clipper at the very end of the chain
+    // Unlike the limiter it adds no latency, but it distorts whatever goes over the ceiling
+    #[id = "hard_clip"]
+    pub hard_clip: BoolParam,
+
+    /// The level the hard clipper cuts everything off at
+    #[id = "hard_clip_ceiling"]
+    pub hard_clip_ceiling: FloatParam,
+
+    /// How many seconds of the chaos and fractal signal the export button renders
+    // Only the export reads it, so the host doesn't get to automate it
+    #[id = "export_length"]
+    pub export_length: IntParam,
+
+    /// How much the LFO moves the chaos amount up and down
+    #[id = "lfo_depth"]
+    pub lfo_depth: FloatParam,
+
+    /// The waveform of the LFO
+    #[id = "lfo_shape"]
+    pub lfo_shape: EnumParam<LfoShape>,
+
+    /// Whether the LFO follows the host's tempo instead of `lfo_rate`
+    #[id = "lfo_sync"]
+    pub lfo_sync: BoolParam,
+
+    /// The free-running LFO rate
+    #[id = "lfo_rate"]
+    pub lfo_rate: FloatParam,
+
+    /// The LFO cycle length when synced to the host's tempo
+    #[id = "lfo_division"]
+    pub lfo_division: EnumParam<NoteDivision>,
+
+    /// How quickly the envelope follower reacts to a rising input level
+    #[id = "env_attack"]
+    pub env_attack: FloatParam,
+
+    /// How quickly the envelope follower falls back when the input gets quieter
+    #[id = "env_release"]
+    pub env_release: FloatParam,
+
+    /// How much the input envelope pushes the magic amount up
+    #[id = "env_magic_depth"]
+    pub env_magic_depth: FloatParam,
+
+    /// How much the input envelope pushes the chaos amount up
+    #[id = "env_chaos_depth"]
+    pub env_chaos_depth: FloatParam,
+
+    /// How far the chaos signal moves the drive, negative depths move it the other way
+    #[id = "chaos_to_drive"]
+    pub chaos_to_drive: FloatParam,
+
+    /// How far the chaos signal moves the output gain
+    #[id = "chaos_to_gain"]
+    pub chaos_to_gain: FloatParam,
+
+    /// How far the chaos signal moves the filter cutoff
+    #[id = "chaos_to_cutoff"]
+    pub chaos_to_cutoff: FloatParam,
+
+    /// How much every hit in the input pushes the chaos amount up
+    // 0.0 switches the bursts off, which is the default
+    #[id = "burst_amount"]
+    pub burst_amount: FloatParam,
+
+    /// How easily the transient detector fires
+    #[id = "burst_sensitivity"]
+    pub burst_sensitivity: FloatParam,
+
+    /// How long a chaos burst takes to fade after a hit, in milliseconds
+    #[id = "burst_decay"]
+    pub burst_decay: FloatParam,
 }
 
 // Implementation block for the RetardedGain struct
@@ -75,23 +863,152 @@ impl Default for RetardedGain {
     fn default() -> Self {
         // Create the parameters with default values
         let params = Arc::new(RetardedGainParams::default());
+
+        // Create both halves of the spectrum analyzer
+        let (spectrum_input, spectrum_output) = analyzer::spectrum_analyzer();
+        let (attractor_input, attractor_output) = analyzer::attractor_scope();
+        let (scope_input, scope_output) = analyzer::oscilloscope();
+        let (texture_input, texture_output) = analyzer::texture_history();
         
         // Create and return a new RetardedGain instance
         // In Rust, the last expression without a semicolon is implicitly returned
         Self {
             // Clone the Arc to increment the reference count (not copying the actual data)
             params: params.clone(),
-            peak_meter_decay_weight: 1.0,
-            // Create a new atomic f32 with negative infinity dB as the initial value
-            peak_meter: Arc::new(AtomicF32::new(util::MINUS_INFINITY_DB)),
+            peak_meter: TruePeakMeter::new(),
+            input_peak_meter: TruePeakMeter::new(),
+            input_loudness: LoudnessMeter::new(),
+            output_loudness: LoudnessMeter::new(),
+            level_difference: Arc::new(AtomicF32::new(0.0)),
+            level_difference_meter: LevelDifferenceMeter::new(),
+            cpu_meter: CpuLoadMeter::new(),
+            activity: Arc::new(ProcessingActivity::new()),
+            modulated_magic: Arc::new(AtomicF32::new(params.magic.default_plain_value())),
+            modulated_chaos: Arc::new(AtomicF32::new(params.chaos.default_plain_value())),
+            stability: Arc::new(AtomicF32::new(1.0)),
+            modulation_rings: Arc::new(ModulationRings::new(&params)),
+            correlation: Arc::new(AtomicF32::new(1.0)),
+            correlation_meter: CorrelationMeter::new(),
+            attractor_input,
+            attractor_output: Arc::new(Mutex::new(attractor_output)),
+            scope_input,
+            scope_output: Arc::new(Mutex::new(scope_output)),
+            texture_input,
+            texture_output: Arc::new(Mutex::new(texture_output)),
+            spectrum_input,
+            spectrum_output: Arc::new(Mutex::new(spectrum_output)),
             // Create each effect processor
             gain_processor: GainProcessor::new(),
-            // Initialize effects with the default parameter values
-            distortion: Distortion::new(params.drive.default_plain_value()),
-            fractal_magic: FractalMagic::new(params.magic.default_plain_value()),
-            chaos_attractor: ChaosAttractor::new(params.chaos.default_plain_value()),
+            gates: std::array::from_fn(|_| Gate::new()),
+            // The effect amounts are passed in with every sample, so they don't need initial values
+            chains: std::array::from_fn(|_| EffectChain::new()),
+            crossover: Crossover::new(),
+            crusher: Crusher::new(),
+            compressors: std::array::from_fn(|_| Compressor::new()),
+            delay: Delay::new(),
+            reverb: Reverb::new(),
+            tilt_eq: TiltEq::new(),
+            stereo_width: StereoWidth::new(),
+            dc_blocker: DcBlocker::new(),
+            oversampler: Oversampler::new(params.oversampling.default_plain_value()),
+            // Allocated in initialize(), since the fractal lookahead depends on the sample rate
+            dry_delay: CompensationDelay::new(0),
+            limiter: Limiter::new(),
+            hard_clipper: HardClipper::new(),
+            export_status: Arc::new(ExportStatus::new()),
+            limiter_enabled: params.limiter.default_plain_value(),
+            constant_latency: params.constant_latency.default_plain_value(),
+            limiter_fade: if params.limiter.default_plain_value() { 1.0 } else { 0.0 },
+            reported_latency: 0,
+            // Allocated in initialize(), since the limiter's latency depends on the sample rate
+            bypass_delay: CompensationDelay::new(0),
+            bypass_fade: if params.bypass.default_plain_value() { 1.0 } else { 0.0 },
+            initialized: false,
+            reinit_fade: 1.0, // The first initialization starts from silence anyway
+            mono_to_stereo: false,
+            lfo: Lfo::new(),
+            envelopes: std::array::from_fn(|_| EnvelopeFollower::new()),
+            transient_detector: TransientDetector::new(),
+            input_calibration: InputCalibration::new(),
+            expected_pos_samples: None,
+            previous_pos_samples: None,
+            was_playing: false,
+            multiband_enabled: params.multiband.default_plain_value(),
+            generator: Generator::new(),
+            restore_engine_state: false,
+            engine_restart_point: None,
+            generator_enabled: params.generator.default_plain_value(),
+            morph_position: f32::NAN,
+            morph_generation: 0,
+            morphed_values: [f32::NAN; MORPH_TARGETS.len()],
+            smoother_targets: [f32::NAN; MORPH_TARGETS.len()],
+        }
+    }
+}
+
+impl RetardedGain {
+    /// Follow the fractal lookahead parameter, the magic engine and the oversampling amount
+    // The dry signal is delayed by both the oversampling latency and the fractal effect's latency
+    fn update_fractal_lookahead(&mut self, sample_rate: f32) {
+        let oversampling = self.oversampler.oversampling();
+        let lookahead = ((self.params.magic_lookahead.value() / 1000.0) * sample_rate).round() as u32;
+        for fractal_magic in self.chains.iter_mut().map(|chain| &mut chain.fractal_magic) {
+            fractal_magic.set_engine(self.params.magic_engine.value());
+            fractal_magic.set_lookahead((lookahead * oversampling.factor() as u32) as usize);
+        }
+        self.dry_delay.set_delay(self.latency().wet() as usize);
+    }
+
+    /// The latency of every currently active latency-adding stage
+    // With constant latency a switched off limiter still counts, since it stays in the chain
+    fn latency(&self) -> LatencyReport {
+        let limiter = (self.limiter_enabled || self.constant_latency).then_some(&self.limiter);
+        LatencyReport::new(self.oversampler.oversampling(), &self.chains[FULL_BAND].fractal_magic, limiter)
+    }
+
+    /// Act on a MIDI event from the host
+    // Learned CCs go to their parameters: the plugin can't change its own parameters, so the
+    // CC moves the parameter's smoother instead, which is what the effects read, and the
+    // editor passes the value on to the host. Notes play the generator, whether or not it's
+    // listening to them right now, so switching MIDI on in the middle of a held note picks it up.
+    fn handle_event(&mut self, event: PluginNoteEvent<Self>, sample_rate: f32) {
+        match event {
+            NoteEvent::MidiCC { cc, value, .. } => {
+                let param = self
+                    .params
+                    .midi_learn
+                    .handle_cc(cc, value)
+                    .and_then(|target| midi::target_param(&self.params, target));
+                if let Some(param) = param {
+                    param.smoothed.set_target(sample_rate, param.preview_plain(value));
+                }
+            }
+            NoteEvent::NoteOn { note, .. } => self.generator.note_on(note),
+            NoteEvent::NoteOff { note, .. } | NoteEvent::Choke { note, .. } => self.generator.note_off(note),
+            _ => (),
         }
     }
+
+    /// Whether the host just started playing or its playhead jumped since the previous block
+    // Keeps track of where the playhead should be after this block of `num_samples`, so this
+    // has to be called exactly once per block
+    fn transport_restarted(&mut self, transport: &Transport, num_samples: usize) -> bool {
+        let pos_samples = transport.pos_samples();
+        let started = transport.playing && !self.was_playing;
+        let jumped = match (pos_samples, self.expected_pos_samples, self.previous_pos_samples) {
+            (Some(pos_samples), Some(expected), Some(previous)) => {
+                transport.playing
+                    && ((pos_samples - expected).abs() > TRANSPORT_JUMP_TOLERANCE || pos_samples < previous)
+            }
+            _ => false,
+        };
+
+        self.was_playing = transport.playing;
+        self.previous_pos_samples = if transport.playing { pos_samples } else { None };
+        self.expected_pos_samples = self.previous_pos_samples.map(|pos_samples| pos_samples + num_samples as i64);
+
+        started || jumped
+    }
 }
 
 // Default implementation for parameters
@@ -102,9 +1019,61 @@ impl Default for RetardedGainParams {
             // Get the default editor state
             editor_state: editor::default_state(),
 
-            // Define the gain parameter
-            gain: FloatParam::new(
-                "Gain",  // Display name
+            // No MIDI mappings until something is learned
+            midi_learn: MidiLearn::new(),
+
+            // Both A/B slots start out with the default settings
+            preset_locks: RwLock::new(PresetLocks::default()),
+            ab_comparison: RwLock::new(AbComparison::default()),
+            engine_state: RwLock::new(None),
+            editor_theme: RwLock::new(ThemeSettings::default()),
+            editor_layout: RwLock::new(LayoutSettings::default()),
+
+            // Seed 0 uses the standard starting conditions
+            chaos_seed: AtomicU32::new(0),
+            state_version: AtomicU32::new(STATE_VERSION),
+
+            // The morph control does nothing until both snapshots are stored
+            morph_snapshots: MorphSnapshots::new(),
+
+            // The macro knobs do nothing until something is mapped to them
+            macro_mappings: MacroMappings::new(),
+
+            // Define the bypass parameter and tell the host it's the bypass switch
+            bypass: BoolParam::new("Bypass", false).make_bypass(),
+
+            // Define the input trim parameter
+            input_gain: FloatParam::new(
+                "Input Gain",
+                util::db_to_gain(0.0),  // Default value (0 dB, no change)
+                FloatRange::Skewed {
+                    min: util::db_to_gain(-30.0),
+                    max: util::db_to_gain(30.0),
+                    factor: FloatRange::gain_skew_factor(-30.0, 30.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
+            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+
+            // New instances calibrate themselves, old sessions are migrated to no calibration
+            calibration: EnumParam::new("Calibration", Calibration::Auto),
+            calibration_trim: FloatParam::new(
+                "Calibration Trim",
+                0.0, // Default value (the input as it comes in)
+                FloatRange::Linear {
+                    min: -envelope::MAX_CALIBRATION_DB,
+                    max: envelope::MAX_CALIBRATION_DB,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            // Define the output gain parameter
+            output_gain: FloatParam::new(
+                "Output Gain",  // Display name
                 util::db_to_gain(0.0),  // Default value (0 dB converted to gain ratio)
                 // Define the range and behavior of the parameter
                 FloatRange::Skewed {
@@ -124,20 +1093,125 @@ impl Default for RetardedGainParams {
             .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
             // Convert user-entered strings to internal values
             .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            // Off by default, so the drive works the way it always has
+            auto_gain: BoolParam::new("Auto Gain", false),
+
+            // Define the stereo parameters of the output stage
+            output_pan: stereo_position_param("Output Pan"),
+            output_balance: stereo_position_param("Output Balance"),
+            stereo_width: FloatParam::new(
+                "Stereo Width",
+                1.0, // Default value, the stereo image stays the way it is
+                FloatRange::Linear {
+                    min: 0.0,    // Mono
+                    max: 2.0,    // Twice as wide
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            invert_left: BoolParam::new("Invert Left", false),
+            invert_right: BoolParam::new("Invert Right", false),
             
+            // Define the noise gate parameters
+            gate_threshold: FloatParam::new(
+                "Gate Threshold",
+                gate::GATE_OFF_DB, // Off by default
+                FloatRange::Linear {
+                    min: gate::GATE_OFF_DB,
+                    max: 0.0,
+                },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(Arc::new(|value| {
+                if value <= gate::GATE_OFF_DB {
+                    String::from("Off")
+                } else {
+                    format!("{value:.1}")
+                }
+            }))
+            .with_string_to_value(Arc::new(|string| match string.trim() {
+                "Off" | "off" => Some(gate::GATE_OFF_DB),
+                string => string.trim_end_matches("dB").trim().parse().ok(),
+            })),
+            gate_attack: FloatParam::new(
+                "Gate Attack",
+                1.0, // Default value in milliseconds
+                FloatRange::Skewed {
+                    min: 0.1,    // Snaps open
+                    max: 50.0,   // Fades in
+                    factor: FloatRange::skew_factor(-2.0), // More room for short times
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            gate_hold: FloatParam::new(
+                "Gate Hold",
+                50.0, // Default value in milliseconds
+                FloatRange::Skewed {
+                    min: 0.0,    // Closes right away
+                    max: 500.0,  // Rides out long pauses
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            gate_release: FloatParam::new(
+                "Gate Release",
+                100.0, // Default value in milliseconds
+                FloatRange::Skewed {
+                    min: 5.0,     // Chops tails off
+                    max: 1000.0,  // Lets tails fade out
+                    factor: FloatRange::skew_factor(-2.0), // More room for short times
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+
             // Define the drive parameter
+            // dB are already spread out evenly by ear, so the range doesn't need a skew
             drive: FloatParam::new(
                 "Drive",
-                1.0, // Default value (no distortion)
+                0.0, // Default value (no distortion)
+                FloatRange::Linear {
+                    min: 0.0,                         // No distortion
+                    max: util::gain_to_db(MAX_DRIVE), // Maximum distortion, about 34 dB
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            // New instances get the compensated model, old sessions are migrated to the legacy one
+            drive_model: EnumParam::new("Drive Model", DriveModel::Compensated),
+            dist_mode: EnumParam::new("Dist Mode", DistortionMode::Tanh),
+
+            // Define the tone filter parameters
+            filter_mode: EnumParam::new("Filter Mode", FilterMode::Off),
+            filter_cutoff: FloatParam::new(
+                "Filter Cutoff",
+                8000.0, // Default value in Hz
                 FloatRange::Skewed {
-                    min: 1.0,    // No distortion
-                    max: 50.0,   // Maximum distortion
-                    factor: 0.5, // Skew towards lower values for more precise control
+                    min: 20.0,      // Bottom of the audible range
+                    max: 20_000.0,  // Top of the audible range
+                    factor: FloatRange::skew_factor(-2.2), // Roughly even spacing per octave
                 },
             )
-            .with_smoother(SmoothingStyle::Logarithmic(50.0))
-            .with_unit("x")
-            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            .with_smoother(SmoothingStyle::Logarithmic(20.0))
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(1))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            filter_resonance: FloatParam::new(
+                "Filter Resonance",
+                0.3, // Default value (a gentle bump at the cutoff)
+                FloatRange::Linear {
+                    min: 0.0,    // No resonance peak
+                    max: 1.0,    // Strong resonance peak
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(20.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
             
             // Define the magic parameter for fractal effects
             magic: FloatParam::new(
@@ -151,6 +1225,37 @@ impl Default for RetardedGainParams {
             .with_smoother(SmoothingStyle::Logarithmic(50.0))
             .with_unit("")
             .with_value_to_string(formatters::v2s_f32_percentage(2)),
+
+            // Define the fractal formula, defaulting to the original Julia set
+            magic_mode: EnumParam::new("Magic Mode", MagicMode::Julia),
+            // On the samples by default, which doesn't add any latency
+            magic_engine: EnumParam::new("Magic Engine", MagicEngine::Time),
+            // First-order anti-aliasing by default, it takes care of most of the aliasing for little CPU
+            magic_fold_quality: EnumParam::new("Fold Quality", FoldQuality::FirstOrder),
+
+            // Define the fractal lookahead parameter
+            // Half millisecond steps, since every change changes the plugin's latency
+            magic_lookahead: FloatParam::new(
+                "Magic Lookahead",
+                0.0, // Off by default, so there's no extra latency
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: fractal::MAX_LOOKAHEAD_MS,
+                },
+            )
+            .with_step_size(0.5)
+            .with_unit(" ms")
+            .with_value_to_string(Arc::new(|value| {
+                if value <= 0.0 {
+                    String::from("Off")
+                } else {
+                    format!("{value:.1}")
+                }
+            }))
+            .with_string_to_value(Arc::new(|string| match string.trim() {
+                "Off" | "off" => Some(0.0),
+                string => string.trim_end_matches("ms").trim().parse().ok(),
+            })),
             
             // Define the chaos parameter
             chaos: FloatParam::new(
@@ -164,8 +1269,622 @@ impl Default for RetardedGainParams {
             .with_smoother(SmoothingStyle::Logarithmic(50.0))
             .with_unit("%")
             .with_value_to_string(formatters::v2s_f32_percentage(1)),
-        }
-    }
+            chaos_system: EnumParam::new("Chaos System", ChaosType::Lorenz),
+            chaos_integrator: EnumParam::new("Chaos Integrator", Integrator::Euler),
+            chaos_evolution: EnumParam::new("Chaos Evolution", Evolution::Free),
+            chaos_rate: FloatParam::new(
+                "Chaos Rate",
+                1.0, // The speed of the original chaos effect
+                FloatRange::Skewed {
+                    min: chaos::MIN_RATE, // A slow drift
+                    max: chaos::MAX_RATE, // Audio-rate screaming
+                    factor: FloatRange::skew_factor(-2.0), // 1x sits near the middle
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_unit("x")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            chaos_color: FloatParam::new(
+                "Chaos Color",
+                1.0, // The full bandwidth of the original chaos effect
+                FloatRange::Linear {
+                    min: 0.0,    // A smooth, slow wobble
+                    max: 1.0,    // Unfiltered, up to audio-rate noise
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            chaos_fm_mode: EnumParam::new("Chaos FM Mode", FmMode::Classic),
+            chaos_shift: FloatParam::new(
+                "Chaos Shift",
+                100.0, // Default value (a clearly audible, still musical shift)
+                FloatRange::Skewed {
+                    min: 0.0,                 // No shift at all
+                    max: chaos::MAX_SHIFT_HZ, // The partials swing a long way up and down
+                    factor: FloatRange::skew_factor(-1.0), // More of the knob for the subtle shifts
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            coupling: FloatParam::new(
+                "Coupling",
+                0.0, // Default value (independent)
+                FloatRange::Linear {
+                    min: 0.0,    // Both effects run on their own
+                    max: 1.0,    // Each one pushes the other as hard as the input does
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // Define the generator mode parameters
+            generator: BoolParam::new("Generator", false),
+            generator_pitch: FloatParam::new(
+                "Generator Pitch",
+                110.0, // A2
+                FloatRange::Skewed {
+                    min: 20.0,   // A low rumble
+                    max: 2000.0, // A whistle
+                    factor: FloatRange::skew_factor(-2.0), // More room for the low notes
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(1))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            generator_range: FloatParam::new(
+                "Generator Range",
+                12.0, // An octave either way
+                FloatRange::Linear {
+                    min: 0.0,  // A steady pitch
+                    max: 48.0, // Four octaves either way
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit(" st")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            generator_scale: EnumParam::new("Generator Scale", Scale::Free),
+            generator_midi: BoolParam::new("Generator MIDI", false),
+            generator_glide: FloatParam::new(
+                "Generator Glide",
+                60.0, // A short slide, like a mono synth's portamento
+                FloatRange::Skewed {
+                    min: 0.0,    // Jumps straight to the new note
+                    max: 2000.0, // A slow swoop
+                    factor: FloatRange::skew_factor(-2.0), // More room for short glides
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            chaos_mod_rate: FloatParam::new(
+                "Chaos Mod Rate",
+                chaos::DEFAULT_MOD_RATE_HZ, // The rate of the original chaos effect
+                FloatRange::Skewed {
+                    min: 0.01,   // One cycle every 100 seconds
+                    max: 20.0,   // Fast shimmer
+                    factor: FloatRange::skew_factor(-2.0), // More room for slow rates
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            chaos_mod_sync: BoolParam::new("Chaos Mod Sync", false),
+            chaos_mod_division: EnumParam::new("Chaos Mod Division", NoteDivision::Whole),
+            chaos_stereo: BoolParam::new("Chaos Stereo", false),
+            chaos_freeze: BoolParam::new("Chaos Freeze", false),
+            transport_reset: BoolParam::new("Transport Reset", true),
+            // Off by default, so the effects keep moving while the host is stopped
+            transport_clock: BoolParam::new("Transport Clock", false),
+            // Off by default, projects start the effects over like they always did
+            persist_engine_state: BoolParam::new("Persist Engine State", false).non_automatable(),
+
+            // Define the bitcrusher parameters
+            // Off by default, it's a very obvious effect
+            crusher: BoolParam::new("Crusher", false),
+            crusher_bits: FloatParam::new(
+                "Crusher Bits",
+                8.0, // Default value, the classic 8-bit crunch
+                FloatRange::Linear {
+                    min: 1.0,    // Only three levels left
+                    max: 16.0,   // CD quality
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit(" bit")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            crusher_downsample: FloatParam::new(
+                "Crusher Downsample",
+                1.0, // Default value (no sample rate reduction)
+                FloatRange::Skewed {
+                    min: 1.0,    // Every sample
+                    max: 32.0,   // Every 32nd sample
+                    factor: FloatRange::skew_factor(-1.5), // More room for small factors
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("x")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            // Define the soft clip character parameter
+            clip_character: FloatParam::new(
+                "Clip Character",
+                0.0, // Default value (tanh, how the chain always clipped)
+                FloatRange::Linear {
+                    min: 0.0,    // Tanh
+                    max: 1.0,    // Polynomial
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // Define the dry/wet mix parameter
+            mix: FloatParam::new(
+                "Mix",
+                1.0, // Default value (fully wet, same as before the parameter existed)
+                FloatRange::Linear {
+                    min: 0.0,    // Dry signal only
+                    max: 1.0,    // Processed signal only
+                },
+            )
+            // Linear smoothing, since logarithmic smoothing can't reach 0.0
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // Define the morph parameter
+            // Not smoothed itself, the parameters it blends have their own smoothers
+            morph: FloatParam::new(
+                "Morph",
+                0.0, // Default value (snapshot A)
+                FloatRange::Linear {
+                    min: 0.0,    // Snapshot A
+                    max: 1.0,    // Snapshot B
+                },
+            )
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // Define the macro knobs
+            // Not smoothed either, for the same reason as the morph control
+            macro_1: macro_param("Macro 1"),
+            macro_2: macro_param("Macro 2"),
+
+            // Define the oversampling parameter
+            // Off by default so the plugin doesn't add latency unless asked to
+            oversampling: EnumParam::new("Oversampling", Oversampling::Off),
+            quality: EnumParam::new("Quality", Quality::Normal),
+            // 32-bit by default, so existing projects keep sounding the same
+            precision: EnumParam::new("Precision", Precision::Single),
+
+            // Define the effect order, defaulting to the original fixed chain
+            routing: EnumParam::new("Effect Order", EffectOrder::DistortionFractalChaos),
+
+            // Define the stage levels and solos, every stage is heard at its own level by default
+            dist_level: stage_level_param("Dist Level"),
+            magic_level: stage_level_param("Magic Level"),
+            chaos_level: stage_level_param("Chaos Level"),
+            solo_distortion: BoolParam::new("Solo Dist", false).non_automatable(),
+            solo_fractal: BoolParam::new("Solo Magic", false).non_automatable(),
+            solo_chaos: BoolParam::new("Solo Chaos", false).non_automatable(),
+
+            // Define the channel mode, linked is how the plugin always worked
+            channel_mode: EnumParam::new("Channel Mode", ChannelMode::Linked),
+            side_amount: FloatParam::new(
+                "Side Amount",
+                1.0, // Default value, the side gets the same amounts as the mid
+                FloatRange::Linear {
+                    min: 0.0,    // The side stays clean
+                    max: 2.0,    // The side gets twice as much
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // Define the mid/side balance of every stage, centered by default so nothing changes
+            dist_ms_balance: ms_balance_param("Dist M/S Balance"),
+            magic_ms_balance: ms_balance_param("Magic M/S Balance"),
+            chaos_ms_balance: ms_balance_param("Chaos M/S Balance"),
+
+            // Define the multiband parameters
+            // Off by default, and every band gets the full amounts until they're turned down
+            multiband: BoolParam::new("Multiband", false),
+            crossover_low: FloatParam::new(
+                "Crossover Low",
+                200.0, // Default value in Hz, keeps the kick and the bass in the low band
+                FloatRange::Skewed {
+                    min: 40.0,    // Only the sub bass
+                    max: 1000.0,  // Up into the low mids
+                    factor: FloatRange::skew_factor(-1.5), // Roughly even spacing per octave
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            crossover_high: FloatParam::new(
+                "Crossover High",
+                3000.0, // Default value in Hz, where the presence range starts
+                FloatRange::Skewed {
+                    min: 1000.0,   // Right above the highest low crossover
+                    max: 12_000.0, // Only the air
+                    factor: FloatRange::skew_factor(-1.5), // Roughly even spacing per octave
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_value_to_string(formatters::v2s_f32_hz_then_khz(1))
+            .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+            low_band_amount: band_amount_param("Low Band Amount"),
+            mid_band_amount: band_amount_param("Mid Band Amount"),
+            high_band_amount: band_amount_param("High Band Amount"),
+
+            // Define the compressor parameters
+            // The ratio defaults to 1:1, so the compressor is off until it's dialed in
+            comp_threshold: FloatParam::new(
+                "Comp Threshold",
+                -12.0, // Default value in dB
+                FloatRange::Linear {
+                    min: -60.0,  // Squashes everything
+                    max: 0.0,    // Only catches full scale peaks
+                },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            comp_ratio: FloatParam::new(
+                "Comp Ratio",
+                1.0, // Default value (no compression)
+                FloatRange::Skewed {
+                    min: 1.0,    // No compression
+                    max: 20.0,   // Basically a limiter
+                    factor: FloatRange::skew_factor(-1.5), // More room for gentle ratios
+                },
+            )
+            .with_unit(":1")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            comp_attack: FloatParam::new(
+                "Comp Attack",
+                10.0, // Default value in milliseconds
+                FloatRange::Skewed {
+                    min: 0.1,    // Grabs every transient
+                    max: 100.0,  // Lets the transients through
+                    factor: FloatRange::skew_factor(-2.0), // More room for short times
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            comp_release: FloatParam::new(
+                "Comp Release",
+                150.0, // Default value in milliseconds
+                FloatRange::Skewed {
+                    min: 10.0,    // Pumps
+                    max: 1000.0,  // Smooth leveling
+                    factor: FloatRange::skew_factor(-2.0), // More room for short times
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            comp_chaotic_release: BoolParam::new("Comp Chaotic Release", false),
+
+            // Define the delay parameters
+            delay_time: FloatParam::new(
+                "Delay Time",
+                375.0, // Default value in milliseconds, a dotted eighth at 120 BPM
+                FloatRange::Skewed {
+                    min: 1.0,    // Comb filter territory
+                    max: delay::MAX_DELAY_MS,
+                    factor: FloatRange::skew_factor(-1.5), // More room for short times
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            delay_sync: BoolParam::new("Delay Sync", false),
+            delay_division: EnumParam::new("Delay Division", NoteDivision::DottedEighth),
+            delay_feedback: FloatParam::new(
+                "Delay Feedback",
+                0.35, // Default value, a handful of repeats
+                FloatRange::Linear {
+                    min: 0.0,    // A single echo
+                    max: 0.95,   // Almost endless repeats
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            delay_warble: FloatParam::new(
+                "Delay Warble",
+                0.0, // Default value (steady delay time)
+                FloatRange::Linear {
+                    min: 0.0,    // Steady delay time
+                    max: 1.0,    // Seasick tape
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            delay_mix: FloatParam::new(
+                "Delay Mix",
+                0.0, // Default value (off)
+                FloatRange::Linear {
+                    min: 0.0,    // No echoes
+                    max: 1.0,    // Only echoes
+                },
+            )
+            // Linear smoothing, since logarithmic smoothing can't reach 0.0
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // Define the reverb parameters
+            reverb_size: FloatParam::new(
+                "Reverb Size",
+                0.6, // Default value, a medium hall
+                FloatRange::Linear {
+                    min: 0.0,    // Small room
+                    max: 1.0,    // Ambient wash
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            reverb_damping: FloatParam::new(
+                "Reverb Damping",
+                0.5, // Default value
+                FloatRange::Linear {
+                    min: 0.0,    // Bright tail
+                    max: 1.0,    // Dark tail
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            reverb_mix: FloatParam::new(
+                "Reverb Mix",
+                0.0, // Default value (off)
+                FloatRange::Linear {
+                    min: 0.0,    // No reverb
+                    max: 1.0,    // Only the reverb tail
+                },
+            )
+            // Linear smoothing, since logarithmic smoothing can't reach 0.0
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // Define the tilt EQ parameter
+            tilt: FloatParam::new(
+                "Tilt",
+                0.0, // Default value (flat)
+                FloatRange::Linear {
+                    min: -tilt::MAX_TILT_DB, // Darker, the lows up and the highs down
+                    max: tilt::MAX_TILT_DB,  // Brighter, the highs up and the lows down
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            // The DC blocker is always on, turning it off shows how much offset the chain adds
+            dc_blocker: BoolParam::new("DC Blocker", true).non_automatable(),
+            meter_ballistics: EnumParam::new("Meter Ballistics", MeterBallistics::Digital).non_automatable(),
+            meter_decay: FloatParam::new(
+                "Meter Decay",
+                metering::DEFAULT_DECAY_MS, // Default value in milliseconds, 20 dB per second
+                FloatRange::Skewed {
+                    min: 100.0,   // Twitchy, follows every transient
+                    max: 5000.0,  // Lazy, easy to read on dense material
+                    factor: FloatRange::skew_factor(-1.0), // More room for short times
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0))
+            .non_automatable(),
+            meter_range: EnumParam::new("Meter Range", MeterRange::Standard).non_automatable(),
+
+            // Define the limiter parameters
+            // The limiter adds latency, so it's off unless the user turns it on
+            limiter: BoolParam::new("Limiter", false),
+            // Off by default, so a switched off limiter doesn't add any latency
+            constant_latency: BoolParam::new("Constant Latency", false).non_automatable(),
+            limiter_ceiling: FloatParam::new(
+                "Limiter Ceiling",
+                -0.3, // Default value in dB, just below full scale
+                FloatRange::Linear {
+                    min: -24.0,  // Very conservative
+                    max: 0.0,    // Full scale
+                },
+            )
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            // Define the hard clipper parameters
+            // Off by default, so nothing gets cut that used to get through
+            hard_clip: BoolParam::new("Hard Clip", false),
+            hard_clip_ceiling: FloatParam::new(
+                "Hard Clip Ceiling",
+                0.0, // Default value in dB, full scale
+                FloatRange::Linear {
+                    min: -6.0,   // Plenty of room below the usual -1 dBTP delivery limit
+                    max: 0.0,    // Full scale
+                },
+            )
+            .with_step_size(0.1)
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+
+            // Define the length of the texture export
+            export_length: IntParam::new(
+                "Export Length",
+                10, // Default value in seconds, enough for a loop or two
+                IntRange::Linear {
+                    min: 1,
+                    max: export::MAX_EXPORT_SECONDS,
+                },
+            )
+            .with_unit(" s")
+            .non_automatable(),
+
+            // Define the LFO parameters
+            lfo_depth: FloatParam::new(
+                "LFO Depth",
+                0.0, // Default value (no modulation)
+                FloatRange::Linear {
+                    min: 0.0,    // No modulation
+                    max: 1.0,    // Sweeps the chaos amount over its full range
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            lfo_shape: EnumParam::new("LFO Shape", LfoShape::Sine),
+            lfo_sync: BoolParam::new("LFO Sync", false),
+            lfo_rate: FloatParam::new(
+                "LFO Rate",
+                1.0, // Default value in Hz
+                FloatRange::Skewed {
+                    min: 0.01,   // One cycle every 100 seconds
+                    max: 20.0,   // Fast wobble
+                    factor: FloatRange::skew_factor(-2.0), // More room for slow rates
+                },
+            )
+            .with_unit(" Hz")
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            lfo_division: EnumParam::new("LFO Division", NoteDivision::Quarter),
+
+            // Define the envelope follower parameters
+            env_attack: FloatParam::new(
+                "Env Attack",
+                10.0, // Default value in milliseconds
+                FloatRange::Skewed {
+                    min: 0.1,    // Catches every transient
+                    max: 200.0,  // Slowly swells up
+                    factor: FloatRange::skew_factor(-2.0), // More room for short times
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            env_release: FloatParam::new(
+                "Env Release",
+                150.0, // Default value in milliseconds
+                FloatRange::Skewed {
+                    min: 5.0,     // Drops right after every hit
+                    max: 2000.0,  // Hangs on for a long time
+                    factor: FloatRange::skew_factor(-2.0), // More room for short times
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+            env_magic_depth: FloatParam::new(
+                "Env > Magic",
+                0.0, // Default value (no modulation)
+                FloatRange::Linear {
+                    min: 0.0,    // No modulation
+                    max: 1.0,    // A full scale input adds the full magic range
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            env_chaos_depth: FloatParam::new(
+                "Env > Chaos",
+                0.0, // Default value (no modulation)
+                FloatRange::Linear {
+                    min: 0.0,    // No modulation
+                    max: 1.0,    // A full scale input adds the full chaos range
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+
+            // Define the transient detector parameters
+            chaos_to_drive: FloatParam::new(
+                "Chaos > Drive",
+                0.0, // Default value (no modulation)
+                FloatRange::Linear {
+                    min: -1.0,   // Two octaves of drive either way, inverted
+                    max: 1.0,    // Two octaves of drive either way
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            chaos_to_gain: FloatParam::new(
+                "Chaos > Gain",
+                0.0, // Default value (no modulation)
+                FloatRange::Linear {
+                    min: -1.0,   // 12 dB either way, inverted
+                    max: 1.0,    // 12 dB either way
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            chaos_to_cutoff: FloatParam::new(
+                "Chaos > Cutoff",
+                0.0, // Default value (no modulation)
+                FloatRange::Linear {
+                    min: -1.0,   // Three octaves either way, inverted
+                    max: 1.0,    // Three octaves either way
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            burst_amount: FloatParam::new(
+                "Burst Amount",
+                0.0, // Default value (no bursts)
+                FloatRange::Linear {
+                    min: 0.0,    // No bursts
+                    max: 1.0,    // Every hit adds the full chaos range
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(50.0))
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            burst_sensitivity: FloatParam::new(
+                "Burst Sensitivity",
+                0.5, // Default value, fires on clear hits like drums
+                FloatRange::Linear {
+                    min: 0.0,    // Only the hardest hits
+                    max: 1.0,    // Every little attack
+                },
+            )
+            .with_unit("%")
+            .with_value_to_string(formatters::v2s_f32_percentage(0))
+            .with_string_to_value(formatters::s2v_f32_percentage()),
+            burst_decay: FloatParam::new(
+                "Burst Decay",
+                150.0, // Default value in milliseconds
+                FloatRange::Skewed {
+                    min: 10.0,    // A short flick on the hit
+                    max: 2000.0,  // Rings on well after the hit
+                    factor: FloatRange::skew_factor(-2.0), // More room for short times
+                },
+            )
+            .with_unit(" ms")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+        }
+    }
 }
 
 // Implement the Plugin trait - this defines how our plugin behaves in a host
@@ -183,6 +1902,8 @@ impl Plugin for RetardedGain {
 
     // Define the audio input/output configurations supported by this plugin
     // We support both mono and stereo processing
+    // Every layout has a stereo aux output with the chaos signal on the left and the fractal's
+    // modulation signal on the right, for modulating other plugins or recording them
     const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
         // Stereo configuration
         AudioIOLayout {
@@ -190,6 +1911,8 @@ impl Plugin for RetardedGain {
             // new() returns an Option, which is like null/undefined but safer
             main_input_channels: NonZeroU32::new(2),  // 2 input channels
             main_output_channels: NonZeroU32::new(2), // 2 output channels
+            aux_output_ports: MODULATION_OUTPUT_PORTS,
+            names: MODULATION_OUTPUT_NAMES,
             // The .. syntax means "all other fields keep their default values"
             ..AudioIOLayout::const_default()
         },
@@ -197,17 +1920,35 @@ impl Plugin for RetardedGain {
         AudioIOLayout {
             main_input_channels: NonZeroU32::new(1),  // 1 input channel
             main_output_channels: NonZeroU32::new(1), // 1 output channel
+            aux_output_ports: MODULATION_OUTPUT_PORTS,
+            names: MODULATION_OUTPUT_NAMES,
+            ..AudioIOLayout::const_default()
+        },
+        // Mono to stereo configuration, for mono sources like a guitar on a stereo track
+        AudioIOLayout {
+            main_input_channels: NonZeroU32::new(1),  // 1 input channel
+            main_output_channels: NonZeroU32::new(2), // 2 output channels
+            aux_output_ports: MODULATION_OUTPUT_PORTS,
+            names: MODULATION_OUTPUT_NAMES,
             ..AudioIOLayout::const_default()
         },
     ];
 
+    // Receive MIDI CCs for MIDI learn, and notes for playing the generator
+    // MidiConfig::Basic only delivers notes, MidiConfig::MidiCCs delivers both
+    const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
+
     // Whether the plugin can handle sample-accurate automation
+    // The wrapper then splits the buffer wherever the host changes a parameter and calls
+    // `process()` for every piece, so the settings that are only read once per block, like the
+    // bypass, the modes and the switches, change at the exact sample too. MIDI events aren't
+    // split on, `process()` handles them at their own sample instead.
     const SAMPLE_ACCURATE_AUTOMATION: bool = true;
 
     // Associated types (a bit like generics in TypeScript)
     // These are placeholders for types that will be used with this trait
     type SysExMessage = ();  // The () type is like void or None - we don't use SysEx
-    type BackgroundTask = (); // We don't use background tasks
+    type BackgroundTask = Task; // The texture export runs on a background thread
 
     // Return the parameters of this plugin
     // This method gives the host access to the plugin's parameters
@@ -215,13 +1956,58 @@ impl Plugin for RetardedGain {
         self.params.clone() // Return a cloned Arc to our parameters
     }
 
+    // Called with a saved state right before it's loaded
+    // Older states get their parameters renamed and converted to what this version expects
+    fn filter_state(state: &mut PluginState) {
+        migration::migrate(state);
+    }
+
+    // Run the work the editor hands off, on a background thread
+    // Rendering a minute of audio on the GUI thread would freeze the editor for a while
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        let export_status = self.export_status.clone();
+        Box::new(move |task| match task {
+            Task::ExportTexture(settings) => {
+                export_status.set(ExportState::Rendering);
+                match export::export_texture(&settings) {
+                    Ok(path) => {
+                        nih_log!("Exported the texture to '{}'", path.display());
+                        export_status.set(ExportState::Done);
+                    }
+                    Err(err) => {
+                        nih_log!("Could not export the texture: {}", err);
+                        export_status.set(ExportState::Failed);
+                    }
+                }
+            }
+        })
+    }
+
     // Create the editor (GUI) for this plugin
     // Returns an Option, which is like null/undefined but type-safe
-    fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
+    fn editor(&mut self, async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
         // Call the create function from the editor module to create the GUI
         editor::create(
             self.params.clone(),
-            self.peak_meter.clone(),
+            self.input_peak_meter.readings(),
+            self.peak_meter.readings(),
+            self.input_loudness.readings(),
+            self.output_loudness.readings(),
+            self.level_difference.clone(),
+            self.modulated_magic.clone(),
+            self.modulated_chaos.clone(),
+            self.stability.clone(),
+            self.modulation_rings.clone(),
+            self.correlation.clone(),
+            self.cpu_meter.load(),
+            self.activity.clone(),
+            self.hard_clipper.clip_indicator(),
+            self.export_status.clone(),
+            async_executor,
+            self.spectrum_output.clone(),
+            self.attractor_output.clone(),
+            self.scope_output.clone(),
+            self.texture_output.clone(),
             self.params.editor_state.clone(),
         )
     }
@@ -230,87 +2016,1087 @@ impl Plugin for RetardedGain {
     // Returns true if initialization was successful
     fn initialize(
         &mut self,
-        _audio_io_layout: &AudioIOLayout,
-        _buffer_config: &BufferConfig,
-        _context: &mut impl InitContext<Self>,
+        audio_io_layout: &AudioIOLayout,
+        buffer_config: &BufferConfig,
+        context: &mut impl InitContext<Self>,
     ) -> bool {
-        // Set a faster decay for the peak meter
-        self.peak_meter_decay_weight = 0.5;
+        // The mono input has to be spread over both outputs
+        self.mono_to_stereo = audio_io_layout.main_input_channels.map(NonZeroU32::get) == Some(1)
+            && audio_io_layout.main_output_channels.map(NonZeroU32::get) == Some(2);
+
+        // The meters start from silence
+        self.input_peak_meter.set_sample_rate(buffer_config.sample_rate);
+        self.input_peak_meter.reset();
+        self.peak_meter.set_sample_rate(buffer_config.sample_rate);
+        self.peak_meter.reset();
+        self.input_loudness.set_sample_rate(buffer_config.sample_rate);
+        self.input_loudness.reset();
+        self.output_loudness.set_sample_rate(buffer_config.sample_rate);
+        self.output_loudness.reset();
+
+        // The spectrum analyzer needs the sample rate to label its frequency axis
+        self.spectrum_input.set_sample_rate(buffer_config.sample_rate);
+        self.texture_input.set_sample_rate(buffer_config.sample_rate);
+        self.correlation_meter.set_sample_rate(buffer_config.sample_rate);
+        self.correlation_meter.reset();
+        self.level_difference_meter.set_sample_rate(buffer_config.sample_rate);
+        self.level_difference_meter.reset();
+        self.cpu_meter.set_sample_rate(buffer_config.sample_rate);
+        self.cpu_meter.reset();
+
+        // The limiter's lookahead buffers depend on the sample rate
+        self.limiter.set_sample_rate(buffer_config.sample_rate);
+        self.limiter_enabled = self.params.limiter.value();
+        self.constant_latency = self.params.constant_latency.value();
+        self.limiter_fade = if self.limiter_enabled { 1.0 } else { 0.0 };
+
+        // The delay's buffers depend on the sample rate
+        self.delay.set_sample_rate(buffer_config.sample_rate);
+        self.delay.reset();
+        self.reverb.set_sample_rate(buffer_config.sample_rate);
+        self.reverb.reset();
+        self.tilt_eq.set_sample_rate(buffer_config.sample_rate);
+        self.tilt_eq.reset();
+        self.stereo_width.set_sample_rate(buffer_config.sample_rate);
+        self.stereo_width.reset();
+
+        // The DC blocker's cutoff depends on the sample rate
+        self.dc_blocker.set_sample_rate(buffer_config.sample_rate);
+        self.dc_blocker.reset();
+
+        // The polarity switches start where they're set, without fading in
+        self.gain_processor.set_sample_rate(buffer_config.sample_rate);
+        self.gain_processor.set_phase_invert([self.params.invert_left.value(), self.params.invert_right.value()]);
+        self.gain_processor.reset();
+
+        // The compressor starts without any gain reduction
+        for compressor in self.compressors.iter_mut() {
+            compressor.set_sample_rate(buffer_config.sample_rate);
+            compressor.reset();
+        }
+
+        // The gate starts open
+        for gate in self.gates.iter_mut() {
+            gate.set_sample_rate(buffer_config.sample_rate);
+            gate.reset();
+        }
+
+        // The envelope follower starts from silence
+        for envelope in self.envelopes.iter_mut() {
+            envelope.set_sample_rate(buffer_config.sample_rate);
+            envelope.reset();
+        }
+        self.transient_detector.set_sample_rate(buffer_config.sample_rate);
+        self.transient_detector.reset();
+        self.input_calibration.set_sample_rate(buffer_config.sample_rate);
+        self.input_calibration.reset();
+
+        // The generator starts at the top of its cycle, with no notes held
+        self.generator.set_sample_rate(buffer_config.sample_rate);
+        self.generator.reset();
+        self.generator.release_notes();
+
+        // The fractal lookahead runs at the oversampled rate, so make room for the highest one
+        let max_lookahead = ((fractal::MAX_LOOKAHEAD_MS / 1000.0) * buffer_config.sample_rate).ceil() as u32;
+        for fractal_magic in self.chains.iter_mut().map(|chain| &mut chain.fractal_magic) {
+            fractal_magic.set_max_lookahead((max_lookahead * Oversampling::X8.factor() as u32) as usize);
+        }
+        // The spectral engine's latency is the longest without oversampling, so the delays
+        // need room for whichever is longer
+        let max_lookahead = max_lookahead.max(fractal::SPECTRAL_LATENCY_SAMPLES as u32);
+        self.dry_delay = CompensationDelay::new((Oversampling::X8.latency_samples() + max_lookahead) as usize);
+
+        // Set up the oversampler and tell the host how much latency everything adds
+        let oversampling = self.params.quality.value().oversampling(self.params.oversampling.value());
+        self.oversampler.set_oversampling(oversampling);
+        self.update_fractal_lookahead(buffer_config.sample_rate);
+        self.reported_latency = self.latency().total();
+        context.set_latency_samples(self.reported_latency);
+
+        // The bypassed signal is delayed by the same amount, with room for every stage at once
+        let max_latency = Oversampling::X8.latency_samples() + max_lookahead + self.limiter.latency_samples();
+        self.bypass_delay = CompensationDelay::new(max_latency as usize);
+        self.bypass_delay.set_delay(self.reported_latency as usize);
+        self.bypass_fade = if self.params.bypass.value() { 1.0 } else { 0.0 };
+
+        // The chain runs at the oversampled rate, and nothing of what ran at the old rate or with
+        // the old layout may carry over. process() keeps the rates up to date, but until its
+        // first block they would still be at the 44.1 kHz the processors start with.
+        let oversampled_rate = buffer_config.sample_rate * oversampling.factor() as f32;
+        for chain in self.chains.iter_mut() {
+            chain.set_sample_rate(oversampled_rate);
+            chain.reset();
+        }
+        self.crossover.set_sample_rate(oversampled_rate);
+        self.crossover.reset();
+        self.oversampler.reset();
+        self.crusher.reset();
+        self.limiter.reset();
+        self.lfo.set_sample_rate(buffer_config.sample_rate);
+        self.lfo.reset();
+
+        // A first initialization starts from silence, a later one cuts off whatever was
+        // playing, so the output fades back in from the clean state
+        self.reinit_fade = if self.initialized { 0.0 } else { 1.0 };
+        self.initialized = true;
+
+        // The chaos and fractal effects pick up where the project left them in the first block
+        self.restore_engine_state = true;
+
         true // Return true to indicate successful initialization
     }
 
+    // Called when the host suspends the plugin, process() doesn't run again until it's initialized
+    // A soft bypass through the bypass parameter keeps the whole chain and every meter running,
+    // this is for hosts that bypass the plugin the hard way. The meters drop to silence
+    // instead of freezing on the last block, and the GUI's activity light goes out.
+    fn deactivate(&mut self) {
+        self.activity.clear();
+        self.input_peak_meter.reset();
+        self.peak_meter.reset();
+        self.input_loudness.reset();
+        self.output_loudness.reset();
+        self.level_difference.store(0.0, std::sync::atomic::Ordering::Relaxed);
+        self.correlation.store(1.0, std::sync::atomic::Ordering::Relaxed);
+        self.cpu_meter.reset();
+        self.modulated_magic.store(self.params.magic.value(), std::sync::atomic::Ordering::Relaxed);
+        self.modulated_chaos.store(self.params.chaos.value(), std::sync::atomic::Ordering::Relaxed);
+        self.stability.store(1.0, std::sync::atomic::Ordering::Relaxed);
+    }
+
     // Process audio - this is where the actual audio processing happens
     // Called repeatedly by the host with new audio buffers
     fn process(
         &mut self,
         buffer: &mut Buffer,  // The audio buffer with input/output samples
-        _aux: &mut AuxiliaryBuffers,  // Additional buffers (not used here)
+        aux: &mut AuxiliaryBuffers,  // Additional buffers, the modulation output
         context: &mut impl ProcessContext<Self>,  // Context with timing, transport info, etc.
     ) -> ProcessStatus {
+        // Time the whole block for the CPU meter
+        self.cpu_meter.start();
+        self.activity.mark();
+
+        // Switch the oversampling amount if it changed since the last block
+        // The dry signal delay and the reported latency have to follow along
+        // The quality tier can override it, which the latency follows just the same
+        let quality = self.params.quality.value();
+        let oversampling = quality.oversampling(self.params.oversampling.value());
+        if oversampling != self.oversampler.oversampling() {
+            self.oversampler.set_oversampling(oversampling);
+        }
+        self.update_fractal_lookahead(context.transport().sample_rate);
+
+        // Start the limiter from a clean state when it gets put back into the chain
+        // With constant latency it never leaves the chain, it fades in and out instead
+        let limiter_enabled = self.params.limiter.value();
+        let constant_latency = self.params.constant_latency.value();
+        let limiter_running = limiter_enabled || constant_latency;
+        if limiter_running && !(self.limiter_enabled || self.constant_latency) {
+            self.limiter.reset();
+        }
+        if !constant_latency {
+            self.limiter_fade = if limiter_enabled { 1.0 } else { 0.0 };
+        }
+        self.limiter_enabled = limiter_enabled;
+        self.constant_latency = constant_latency;
+        self.limiter.set_ceiling(util::db_to_gain(self.params.limiter_ceiling.value()));
+        let hard_clip = self.params.hard_clip.value();
+        self.hard_clipper.set_ceiling(util::db_to_gain(self.params.hard_clip_ceiling.value()));
+
+        // Tell the host if the total latency changed because of the settings above
+        let latency = self.latency().total();
+        if latency != self.reported_latency {
+            self.reported_latency = latency;
+            context.set_latency_samples(latency);
+            self.bypass_delay.set_delay(latency as usize);
+        }
+
+        // Fade towards the bypassed or the processed signal, whichever is selected
+        let bypass_target = if self.params.bypass.value() { 1.0 } else { 0.0 };
+        let bypass_fade_step = 1.0 / ((BYPASS_FADE_MS / 1000.0) * context.transport().sample_rate);
+
         // Update the sample rates for time-based effects
         // Getting the sample rate from the transport info
-        self.fractal_magic.set_sample_rate(context.transport().sample_rate as f32);
-        self.chaos_attractor.set_sample_rate(context.transport().sample_rate as f32);
+        // The fractal and chaos effects run inside the oversampler, so they see the higher rate
+        let sample_rate = context.transport().sample_rate;
+        let oversampled_rate = sample_rate * oversampling.factor() as f32;
+        self.chains.iter_mut().for_each(|chain| chain.set_sample_rate(oversampled_rate));
+        self.crossover.set_sample_rate(oversampled_rate);
+        self.lfo.set_sample_rate(sample_rate);
+        self.generator.set_sample_rate(sample_rate);
+
+        // The MIDI events are handled in the sample loop below, at the sample they happen at
+        self.generator.set_glide(self.params.generator_glide.value());
+        let mut next_event = context.next_event();
+
+        // Blend the morph targets between the two snapshots whenever the morph control moves
+        // or a snapshot changes
+        let morph_position = self.params.morph.value();
+        let morph_generation = self.params.morph_snapshots.generation();
+        if morph_position != self.morph_position || morph_generation != self.morph_generation {
+            self.morph_position = morph_position;
+            self.morph_generation = morph_generation;
+            for (target, morphed) in self.morphed_values.iter_mut().enumerate() {
+                *morphed = morph::target_param(&self.params, target)
+                    .and_then(|param| self.params.morph_snapshots.blend(param, target, morph_position))
+                    .unwrap_or(f32::NAN);
+            }
+        }
+
+        // The macros push their targets away from the knob's setting, or from the morph's blend.
+        // This happens before smoothing: like the MIDI CCs it moves the smoothers, so the knobs
+        // stay where they are, and turning a knob takes it over until the morph or a macro
+        // moves again.
+        let macro_values: [f32; NUM_MACROS] = [self.params.macro_1.value(), self.params.macro_2.value()];
+        let targets = self.morphed_values.iter().zip(self.smoother_targets.iter_mut()).enumerate();
+        for (target, (&morphed, smoother_target)) in targets {
+            let Some(param) = morph::target_param(&self.params, target) else {
+                continue;
+            };
+            let offset = self.params.macro_mappings.offset(target, &macro_values);
+            if offset.is_none() && morphed.is_nan() {
+                // Neither the morph nor a macro drives this one
+                *smoother_target = f32::NAN;
+                continue;
+            }
+
+            let base = if morphed.is_nan() { param.unmodulated_plain_value() } else { morphed };
+            let value = match offset {
+                Some(offset) => param.preview_plain((param.preview_normalized(base) + offset).clamp(0.0, 1.0)),
+                None => base,
+            };
+            if value != *smoother_target {
+                *smoother_target = value;
+                param.smoothed.set_target(sample_rate, value);
+            }
+        }
+        for envelope in self.envelopes.iter_mut() {
+            envelope.set_sample_rate(sample_rate);
+            envelope.set_times(self.params.env_attack.value(), self.params.env_release.value());
+        }
+        self.input_calibration.set_sample_rate(sample_rate);
+        self.transient_detector.set_sample_rate(sample_rate);
+        self.transient_detector.set_params(self.params.burst_sensitivity.value(), self.params.burst_decay.value());
+        for compressor in self.compressors.iter_mut() {
+            compressor.set_sample_rate(sample_rate);
+            compressor.set_curve(self.params.comp_threshold.value(), self.params.comp_ratio.value());
+            compressor.set_times(self.params.comp_attack.value(), self.params.comp_release.value());
+            compressor.set_chaotic_release(self.params.comp_chaotic_release.value());
+        }
+        for gate in self.gates.iter_mut() {
+            gate.set_sample_rate(sample_rate);
+            gate.set_threshold_db(self.params.gate_threshold.value());
+            gate.set_times(
+                self.params.gate_attack.value(),
+                self.params.gate_hold.value(),
+                self.params.gate_release.value(),
+            );
+        }
+
+        // Set up the LFO for this block, either following the host's tempo or running freely
+        // When synced and the host is playing, the LFO phase follows the song position
+        self.lfo.set_shape(self.params.lfo_shape.value());
+        if self.params.lfo_sync.value() {
+            let transport = context.transport();
+            let pos_beats = if transport.playing { transport.pos_beats() } else { None };
+            self.lfo.set_synced_rate(
+                self.params.lfo_division.value(),
+                transport.tempo.unwrap_or(120.0), // Fall back to 120 BPM if the host doesn't say
+                pos_beats,
+            );
+        } else {
+            self.lfo.set_free_rate(self.params.lfo_rate.value());
+        }
+
+        // The chaos effect's secondary phase modulation works the same way as the LFO
+        // It runs inside the oversampler, but the rate in Hz takes care of that
+        if self.params.chaos_mod_sync.value() {
+            let transport = context.transport();
+            let pos_beats = if transport.playing { transport.pos_beats() } else { None };
+            for chaos_attractor in self.chains.iter_mut().map(|chain| &mut chain.chaos_attractor) {
+                chaos_attractor.set_synced_mod_rate(
+                    self.params.chaos_mod_division.value(),
+                    transport.tempo.unwrap_or(120.0), // Fall back to 120 BPM if the host doesn't say
+                    pos_beats,
+                );
+            }
+        } else {
+            for chaos_attractor in self.chains.iter_mut().map(|chain| &mut chain.chaos_attractor) {
+                chaos_attractor.set_mod_rate(self.params.chaos_mod_rate.value());
+            }
+        }
+
+        // The slow drift of the chaotic systems' parameters, free or over a number of bars
+        // A bar is as long as the host's time signature says, 4/4 if it doesn't say
+        if let Some(bars) = self.params.chaos_evolution.value().bars() {
+            let transport = context.transport();
+            let pos_beats = if transport.playing { transport.pos_beats() } else { None };
+            let beats_per_bar = match (transport.time_sig_numerator, transport.time_sig_denominator) {
+                (Some(numerator), Some(denominator)) if denominator > 0 => {
+                    numerator as f64 * 4.0 / denominator as f64
+                }
+                _ => 4.0,
+            };
+            for chaos_attractor in self.chains.iter_mut().map(|chain| &mut chain.chaos_attractor) {
+                chaos_attractor.set_synced_evolution(
+                    bars * beats_per_bar,
+                    transport.tempo.unwrap_or(120.0), // Fall back to 120 BPM if the host doesn't say
+                    pos_beats,
+                );
+            }
+        } else {
+            for chaos_attractor in self.chains.iter_mut().map(|chain| &mut chain.chaos_attractor) {
+                chaos_attractor.set_free_evolution();
+            }
+        }
+
+        // The delay time for this block, synced to the host's tempo or in milliseconds
+        if self.params.delay_sync.value() {
+            let tempo = context.transport().tempo.unwrap_or(120.0); // Fall back to 120 BPM if the host doesn't say
+            self.delay.set_synced_time(self.params.delay_division.value().beats(), tempo);
+        } else {
+            self.delay.set_time_ms(self.params.delay_time.value());
+        }
+
+        // The distortion algorithm for this block
+        // Every band gets the same settings, only the amounts differ between them
+        self.chains.iter_mut().for_each(|chain| chain.distortion.set_mode(self.params.dist_mode.value()));
+        self.chains.iter_mut().for_each(|chain| chain.distortion.set_model(self.params.drive_model.value()));
+        self.chains.iter_mut().for_each(|chain| chain.filter.set_mode(self.params.filter_mode.value()));
+        self.chains.iter_mut().for_each(|chain| chain.fractal_magic.set_mode(self.params.magic_mode.value()));
+        self.chains.iter_mut().for_each(|chain| chain.set_precision(self.params.precision.value()));
+        self.crossover.set_precision(self.params.precision.value());
+        self.chains.iter_mut().for_each(|chain| chain.fractal_magic.set_fold_quality(self.params.magic_fold_quality.value()));
+        self.chains.iter_mut().for_each(|chain| chain.fractal_magic.set_quality(quality));
+        for chaos_attractor in self.chains.iter_mut().map(|chain| &mut chain.chaos_attractor) {
+            chaos_attractor.set_chaos_type(self.params.chaos_system.value());
+            chaos_attractor.set_integrator(self.params.chaos_integrator.value());
+            chaos_attractor.set_fm_mode(self.params.chaos_fm_mode.value());
+            chaos_attractor.set_quality(quality);
+            // A mono input always gets the decorrelated attractors, so both outputs move differently
+            chaos_attractor.set_stereo(self.params.chaos_stereo.value() || self.mono_to_stereo);
+            chaos_attractor.set_frozen(self.params.chaos_freeze.value());
+            chaos_attractor.set_seed(self.params.chaos_seed.load(std::sync::atomic::Ordering::Relaxed));
+        }
+
+        // Restart the chaos and fractal effects when playback starts or the playhead jumps
+        // Otherwise their state carries over from whatever played before, and no two bounces
+        // of the same section would sound the same
+        let transport_stopped = self.was_playing && !context.transport().playing;
+        let transport_restarted = self.transport_restarted(context.transport(), buffer.samples());
+        if transport_restarted && self.params.transport_reset.value() {
+            for chain in self.chains.iter_mut() {
+                chain.chaos_attractor.reset();
+                chain.fractal_magic.reset();
+                chain.governor.reset();
+            }
+            if let Some(restart_point) = &self.engine_restart_point {
+                restart_point.restore(&mut self.chains);
+            }
+        }
+
+        // Following the transport, the slow fractal LFO and the chaos evolution only move
+        // during playback, and move back to the start when it stops
+        let transport_clock = self.params.transport_clock.value();
+        let clock_running = !transport_clock || context.transport().playing;
+        for fractal_magic in self.chains.iter_mut().map(|chain| &mut chain.fractal_magic) {
+            fractal_magic.set_clock_running(clock_running);
+        }
+        for chaos_attractor in self.chains.iter_mut().map(|chain| &mut chain.chaos_attractor) {
+            chaos_attractor.set_clock_running(clock_running);
+        }
+        if transport_clock && transport_stopped {
+            for chain in self.chains.iter_mut() {
+                chain.fractal_magic.reset_clock();
+                chain.chaos_attractor.reset_clock();
+            }
+        }
+
+        // The integrated loudness starts over with every playback, so it measures what's playing now
+        if transport_restarted {
+            self.input_loudness.reset();
+            self.output_loudness.reset();
+        }
+
+        // Start the bands that only run in multiband mode from a clean state when it gets turned on
+        let multiband = self.params.multiband.value();
+        if multiband && !self.multiband_enabled {
+            self.crossover.reset();
+            for band in (0..NUM_BANDS).filter(|band| *band != FULL_BAND) {
+                self.chains[band].reset();
+            }
+        }
+        self.multiband_enabled = multiband;
+
+        // Resume the texture the project was saved with, now that the systems and formulas are set
+        // If the host holds the lock right now, the next block tries again
+        if self.restore_engine_state {
+            if !self.params.persist_engine_state.value() {
+                self.restore_engine_state = false;
+                self.engine_restart_point = None;
+            } else if let Ok(engine_state) = self.params.engine_state.try_read() {
+                self.restore_engine_state = false;
+                self.engine_restart_point = *engine_state;
+                if let Some(restart_point) = &self.engine_restart_point {
+                    restart_point.restore(&mut self.chains);
+                }
+            }
+        }
+
+        // Start the oscillator over when generator mode gets turned on, so it doesn't pick up
+        // the feedback of whatever was playing through the chain before
+        let generator_enabled = self.params.generator.value();
+        if generator_enabled && !self.generator_enabled {
+            self.generator.reset();
+        }
+        self.generator_enabled = generator_enabled;
+        let generator_scale = self.params.generator_scale.value();
+        let generator_midi = self.params.generator_midi.value();
+        let calibration = self.params.calibration.value();
+
+        // Whether the output follows the drive for this block
+        // The compensated drive model already makes up the level, doing it twice would duck the output
+        let auto_gain = self.params.auto_gain.value() && self.params.drive_model.value() == DriveModel::Legacy;
+        self.gain_processor.set_sample_rate(sample_rate);
+        self.gain_processor.set_phase_invert([self.params.invert_left.value(), self.params.invert_right.value()]);
+
+        // The order of the effect stages for this block
+        let order = self.params.routing.value();
+        let solo = StageSolo {
+            distortion: self.params.solo_distortion.value(),
+            fractal: self.params.solo_fractal.value(),
+            chaos: self.params.solo_chaos.value(),
+        };
+
+        // How the channels are processed for this block
+        // Mid/side needs two channels, anything else is just processed as it is
+        let channel_mode = self.params.channel_mode.value();
+        let mid_side = channel_mode == ChannelMode::MidSide && buffer.channels() == 2;
+        let dual_mono = channel_mode == ChannelMode::DualMono;
+
+        // Start the crusher from a clean state when it gets turned back on
+        let crusher_enabled = self.params.crusher.value();
+        if !crusher_enabled {
+            self.crusher.reset();
+        }
+
+        // The DC blocker is only ever turned off for debugging, it restarts clean when turned back on
+        let dc_blocker_enabled = self.params.dc_blocker.value();
+        if !dc_blocker_enabled {
+            self.dc_blocker.reset();
+        }
+
+        // The modulated magic and chaos amounts of the latest frame, shown in the GUI
+        let mut modulated_magic = self.params.magic.value();
+        let mut modulated_chaos = self.params.chaos.value();
+        // The lowest stability of the block, so a short intervention still shows up
+        let mut stability: f32 = 1.0;
         
-        // Variable to track the maximum peak value in this processing block
-        let mut max_peak: f32 = 0.0;
+
+        // The spectrum analyzer, scopes and meters are only fed while the editor is open, since nobody can see them otherwise
+        let analyzer_active = self.params.editor_state.is_open();
+        let meter_ballistics = self.params.meter_ballistics.value();
+        self.input_peak_meter.set_ballistics(meter_ballistics);
+        self.peak_meter.set_ballistics(meter_ballistics);
+        let meter_decay = self.params.meter_decay.value();
+        self.input_peak_meter.set_decay(meter_decay);
+        self.peak_meter.set_decay(meter_decay);
+        let num_channels = buffer.channels();
+
+        // The modulation signals only go out when the host gave us the aux output
+        let mut modulation_output = aux.outputs.get_mut(0).map(|output| output.as_slice());
         
         // Process each set of samples
         // buffer.iter_samples() gives access to all channels of each sample at once
-        for channel_samples in buffer.iter_samples() {
+        for (sample_idx, mut channel_samples) in buffer.iter_samples().enumerate() {
+            // Handle every MIDI event that happens at this sample before anything reads the smoothers
+            while let Some(event) = next_event {
+                if event.timing() > sample_idx as u32 {
+                    break;
+                }
+                self.handle_event(event, sample_rate);
+                next_event = context.next_event();
+            }
+
             // Get the smoothed parameter values
             // Smoothing prevents clicks/pops when changing parameters
-            let gain = self.params.gain.smoothed.next();
-            let drive = self.params.drive.smoothed.next();
+            let input_gain = self.params.input_gain.smoothed.next();
+            let calibration_trim = self.params.calibration_trim.smoothed.next();
+            let output_gain = self.params.output_gain.smoothed.next();
+            let output_pan = self.params.output_pan.smoothed.next();
+            let output_balance = self.params.output_balance.smoothed.next();
+            let stereo_width = self.params.stereo_width.smoothed.next();
+            // Smoothed in dB, everything after this works with the ratio
+            let drive = util::db_to_gain(self.params.drive.smoothed.next());
+            let filter_cutoff = self.params.filter_cutoff.smoothed.next();
+            let filter_resonance = self.params.filter_resonance.smoothed.next();
             let magic = self.params.magic.smoothed.next();
             let chaos = self.params.chaos.smoothed.next();
-            
-            // Update the effect processors with current parameter values
-            self.distortion = Distortion::new(drive);
-            self.fractal_magic = FractalMagic::new(magic);
-            self.chaos_attractor = ChaosAttractor::new(chaos);
+            let dist_level = self.params.dist_level.smoothed.next();
+            let magic_level = self.params.magic_level.smoothed.next();
+            let chaos_level = self.params.chaos_level.smoothed.next();
+            let chaos_rate = self.params.chaos_rate.smoothed.next();
+            let chaos_color = self.params.chaos_color.smoothed.next();
+            let chaos_shift = self.params.chaos_shift.smoothed.next();
+            let coupling = self.params.coupling.smoothed.next();
+            let clip_character = self.params.clip_character.smoothed.next();
+            let generator_pitch = self.params.generator_pitch.smoothed.next();
+            let generator_range = self.params.generator_range.smoothed.next();
+            let chaos_mod = ChaosModMatrix {
+                drive_depth: self.params.chaos_to_drive.smoothed.next(),
+                gain_depth: self.params.chaos_to_gain.smoothed.next(),
+                cutoff_depth: self.params.chaos_to_cutoff.smoothed.next(),
+            };
+            let crusher_bits = self.params.crusher_bits.smoothed.next();
+            let crusher_downsample = self.params.crusher_downsample.smoothed.next();
+            let mix = self.params.mix.smoothed.next();
+            let delay_feedback = self.params.delay_feedback.smoothed.next();
+            let delay_warble = self.params.delay_warble.smoothed.next();
+            let delay_mix = self.params.delay_mix.smoothed.next();
+            let reverb_size = self.params.reverb_size.smoothed.next();
+            let reverb_damping = self.params.reverb_damping.smoothed.next();
+            let reverb_mix = self.params.reverb_mix.smoothed.next();
+            let tilt = self.params.tilt.smoothed.next();
+            let lfo_depth = self.params.lfo_depth.smoothed.next();
+            let env_magic_depth = self.params.env_magic_depth.smoothed.next();
+            let env_chaos_depth = self.params.env_chaos_depth.smoothed.next();
+            let burst_amount = self.params.burst_amount.smoothed.next();
+            let side_amount = self.params.side_amount.smoothed.next();
+            let dist_ms_balance = self.params.dist_ms_balance.smoothed.next();
+            let magic_ms_balance = self.params.magic_ms_balance.smoothed.next();
+            let chaos_ms_balance = self.params.chaos_ms_balance.smoothed.next();
+            let band_amounts = [
+                self.params.low_band_amount.smoothed.next(),
+                self.params.mid_band_amount.smoothed.next(),
+                self.params.high_band_amount.smoothed.next(),
+            ];
+            self.crossover.set_frequencies(
+                self.params.crossover_low.smoothed.next(),
+                self.params.crossover_high.smoothed.next(),
+            );
+            // The chaos rate is picked up on every sample, so sweeping it doesn't step
+            self.chains.iter_mut().for_each(|chain| chain.chaos_attractor.set_rate(chaos_rate));
+            self.chains.iter_mut().for_each(|chain| chain.chaos_attractor.set_color(chaos_color));
+            self.chains.iter_mut().for_each(|chain| chain.chaos_attractor.set_shift_depth(chaos_shift));
+            // Every limiting point in the chain clips with the same curve
+            self.chains.iter_mut().for_each(|chain| chain.chaos_attractor.set_saturation(clip_character));
+            self.chains.iter_mut().for_each(|chain| chain.fractal_magic.set_saturation(clip_character));
+            self.delay.set_saturation(clip_character);
+
+            // Turn the output down by the loudness the drive adds
+            // Only the wet part of the mix is louder, so the compensation follows the mix in dB
+            let output_gain = if auto_gain {
+                output_gain * self.chains[FULL_BAND].distortion.gain_compensation(drive).powf(mix)
+            } else {
+                output_gain
+            };
+
+            // The chaos signal as of the previous sample, for the modulation matrix
+            // The output gain moves both channels together, like the stereo width does
+            let chaos_signals: [f32; MAX_CHANNELS] =
+                std::array::from_fn(|channel| self.chains[FULL_BAND].chaos_attractor.signal(channel));
+            let output_gain = chaos_mod.gain(output_gain, chaos_signals[0]);
+
+            // With a mono input the right channel starts out as a copy of the left one
+            // The host doesn't fill it with anything useful, so the input is copied over it
+            if self.mono_to_stereo {
+                let left = channel_samples.get_mut(0).map_or(0.0, |sample| *sample);
+                if let Some(right) = channel_samples.get_mut(1) {
+                    *right = left;
+                }
+            }
+
+            // The level of the untouched input, for the level difference meter
+            let mut input_power = 0.0;
+
+            // The untouched input, delayed to line up with the processed signal for the bypass
+            let mut bypassed = [0.0; MAX_CHANNELS];
+
+            // Apply the input trim before anything else
+            // The envelope followers track the trimmed input, the loudest channel of it when linked
+            let mut levels = [0.0f32; MAX_CHANNELS];
+            for (channel, sample) in channel_samples.iter_mut().enumerate() {
+                input_power += *sample * *sample;
+                self.input_loudness.push(channel, *sample);
+                bypassed[channel] = self.bypass_delay.process(channel, *sample);
+                *sample = self.gain_processor.process(*sample, input_gain);
+                levels[channel] = sample.abs();
+            }
+            let frame_peak = levels.iter().fold(0.0f32, |peak, level| peak.max(*level));
+            self.input_loudness.end_frame();
+
+            // The noise gate listens to the trimmed input, so the trim doesn't shift the threshold
+            // When linked both channels open and close together, in dual mono each one has its own gate
+            let linked_gate_gain = if dual_mono { 1.0 } else { self.gates[0].process(frame_peak) };
+            for (channel, sample) in channel_samples.iter_mut().enumerate() {
+                let gate_gain = if dual_mono {
+                    self.gates[channel].process(levels[channel])
+                } else {
+                    linked_gate_gain
+                };
+                *sample *= gate_gain;
+                levels[channel] *= gate_gain;
+            }
+
+            // In generator mode the oscillator takes the place of the input
+            // The chaos signal sets its pitch, and the magic amount sets how much of the chain's
+            // output is fed back into it. The pitch stays below Nyquist so it can't alias back down.
+            // With MIDI on the played note takes the place of the pitch knob, and the oscillator
+            // only sounds while a note is held.
+            if generator_enabled {
+                let (base_pitch, voice_level) = if generator_midi {
+                    self.generator.next_voice()
+                } else {
+                    (generator_pitch, 1.0)
+                };
+                for (channel, sample) in channel_samples.iter_mut().enumerate() {
+                    let frequency =
+                        generator::chaotic_frequency(base_pitch, chaos_signals[channel], generator_range, generator_scale)
+                            .min(sample_rate * 0.45);
+                    *sample = self.generator.process(channel, frequency, magic) * voice_level;
+                }
+            }
+            // Track the true peak going into the chain for the input meter
+            // The meters are only read by the GUI, so they're skipped while it's closed
+            if analyzer_active {
+                for (channel, sample) in channel_samples.iter_mut().enumerate() {
+                    self.input_peak_meter.push(channel, *sample);
+                }
+            }
+
+            // Calibrate how hard what goes into the chain pushes the chaos and fractal effects
+            // The level is measured in every mode, so switching to auto doesn't start from scratch
+            let chain_power = channel_samples.iter_mut().map(|sample| *sample * *sample).sum::<f32>()
+                / num_channels.max(1) as f32;
+            let auto_calibration = self.input_calibration.process(chain_power);
+            let influence_gain = match calibration {
+                Calibration::Off => 1.0,
+                Calibration::Auto => auto_calibration,
+                Calibration::Manual => util::db_to_gain(calibration_trim),
+            };
+            self.chains.iter_mut().for_each(|chain| chain.chaos_attractor.set_influence_gain(influence_gain));
+            self.chains.iter_mut().for_each(|chain| chain.fractal_magic.set_influence_gain(influence_gain));
+
+            // The envelope modulation of every channel, all the same unless it's dual mono
+            let mut envelopes = [0.0; MAX_CHANNELS];
+            if dual_mono {
+                for (channel, envelope) in envelopes.iter_mut().enumerate().take(num_channels) {
+                    *envelope = self.envelopes[channel].process(levels[channel]);
+                }
+            } else {
+                let linked_peak = levels.iter().fold(0.0f32, |peak, level| peak.max(*level));
+                envelopes = [self.envelopes[0].process(linked_peak); MAX_CHANNELS];
+            }
+
+            // Every hit in the input fires a chaos burst that fades away after it
+            let burst = self.transient_detector.process(frame_peak) * burst_amount;
+
+            // Louder input pushes the magic amount up, and the LFO, envelope and bursts move the chaos amount
+            let lfo = self.lfo.next_value() * lfo_depth;
+            let mut drives = [drive; MAX_CHANNELS];
+            let mut magics: [f32; MAX_CHANNELS] =
+                std::array::from_fn(|channel| (magic + envelopes[channel] * env_magic_depth).clamp(0.0, 1.0));
+            let mut chaoses: [f32; MAX_CHANNELS] = std::array::from_fn(|channel| {
+                (chaos + lfo + envelopes[channel] * env_chaos_depth + burst).clamp(0.0, 1.0)
+            });
+            modulated_magic = magics[0];
+            modulated_chaos = chaoses[0];
+
+            // In mid/side mode the chain runs on the mid (first) and side (second) signals
+            // The side gets its own amounts, scaled from the main ones
+            if mid_side {
+                let mut frame = channel_samples.iter_mut();
+                if let (Some(left), Some(right)) = (frame.next(), frame.next()) {
+                    (*left, *right) = ms::encode(*left, *right);
+                }
+
+                drives[1] = 1.0 + (drive - 1.0) * side_amount;
+                magics[1] = (magics[1] * side_amount).clamp(0.0, 1.0);
+                chaoses[1] = (chaoses[1] * side_amount).clamp(0.0, 1.0);
+            }
+
+            // The chaos signal moves every channel's drive on its own
+            for (drive, chaos_signal) in drives.iter_mut().zip(chaos_signals) {
+                *drive = chaos_mod.drive(*drive, chaos_signal);
+            }
+
+            // How much of every stage the mid and side signals get, everything gets all of it outside mid/side mode
+            let (dist_weights, magic_weights, chaos_weights) = if mid_side {
+                (
+                    ms::balance_weights(dist_ms_balance),
+                    ms::balance_weights(magic_ms_balance),
+                    ms::balance_weights(chaos_ms_balance),
+                )
+            } else {
+                ([1.0; MAX_CHANNELS], [1.0; MAX_CHANNELS], [1.0; MAX_CHANNELS])
+            };
             
             // Process each sample across all channels
-            for sample in channel_samples {
-                // Apply effects in sequence
+            // enumerate() gives us the channel index so every channel uses its own effect state
+            for (channel, sample) in channel_samples.iter_mut().enumerate() {
+                // Keep a copy of the unprocessed input for the dry/wet mix
+                // It's delayed by the oversampling latency and the fractal lookahead to line up with the wet signal
+                let dry = self.dry_delay.process(channel, *sample);
+
+                // Apply effects in the selected order
                 // Each effect processes the output of the previous effect
-                *sample = self.distortion.process(*sample);    // Apply distortion
-                *sample = self.fractal_magic.process(*sample); // Apply fractal effect
-                *sample = self.chaos_attractor.process(*sample); // Apply chaos effect
-                *sample = self.gain_processor.process(*sample, gain); // Apply gain
-                
-                // Track the peak level for the meter
-                // abs() gets the absolute value, and max() compares with the current max
-                max_peak = max_peak.max(sample.abs());
+                // The whole chain runs at the oversampled rate, since every stage is non-linear
+                // The smoothed amounts for this sample are passed straight to every effect
+                let settings = ChainSettings {
+                    order,
+                    solo,
+                    drive: drives[channel],
+                    filter_cutoff: chaos_mod.cutoff(filter_cutoff, chaos_signals[channel]),
+                    filter_resonance,
+                    magic: magics[channel],
+                    chaos: chaoses[channel],
+                    coupling,
+                    dist_level,
+                    magic_level,
+                    chaos_level,
+                    dist_weight: dist_weights[channel],
+                    magic_weight: magic_weights[channel],
+                    chaos_weight: chaos_weights[channel],
+                };
+                let chains = &mut self.chains;
+                let crossover = &mut self.crossover;
+                // Runs the chain on one band, with the band's own share of the amounts
+                let mut process_band = |band: usize, sample: f32, amount: f32| {
+                    let band_settings = ChainSettings {
+                        drive: 1.0 + (settings.drive - 1.0) * amount,
+                        magic: settings.magic * amount,
+                        chaos: settings.chaos * amount,
+                        ..settings
+                    };
+                    let (sample, band_stability) = chains[band].process(channel, sample, &band_settings);
+                    stability = stability.min(band_stability);
+                    sample
+                };
+                *sample = self.oversampler.process(channel, *sample, |sample| {
+                    if multiband {
+                        // Every band runs through its own chain, and they're added back up afterwards
+                        let bands = crossover.split(channel, sample);
+                        (0..NUM_BANDS)
+                            .map(|band| process_band(band, bands[band], band_amounts[band]))
+                            .sum()
+                    } else {
+                        process_band(FULL_BAND, sample, 1.0)
+                    }
+                });
+                if generator_enabled {
+                    self.generator.feed_back(channel, *sample);
+                }
+
+                // The crusher runs at the plugin's own sample rate, so the downsample factor
+                // doesn't depend on the oversampling setting
+                if crusher_enabled {
+                    *sample = self.crusher.process(channel, *sample, crusher_bits, crusher_downsample);
+                }
+
+                // Blend the dry input with the processed chain output
+                *sample = dry * (1.0 - mix) + *sample * mix;
+            }
+
+            // Back from mid and side to left and right
+            if mid_side {
+                let mut frame = channel_samples.iter_mut();
+                if let (Some(mid), Some(side)) = (frame.next(), frame.next()) {
+                    (*mid, *side) = ms::decode(*mid, *side);
+                }
+            }
+
+            // When linked the compressor sees all channels at once, so the stereo image doesn't shift
+            // In chaotic release mode the chaos signal wobbles the release
+            let mut mixed_peak: f32 = 0.0;
+            for sample in channel_samples.iter_mut() {
+                mixed_peak = mixed_peak.max(sample.abs());
+            }
+            let mut comp_gains = [1.0; MAX_CHANNELS];
+            if dual_mono {
+                for (channel, sample) in channel_samples.iter_mut().enumerate() {
+                    comp_gains[channel] =
+                        self.compressors[channel].process(sample.abs(), self.chains[FULL_BAND].chaos_attractor.signal(channel));
+                }
+            } else {
+                comp_gains = [self.compressors[0].process(mixed_peak, self.chains[FULL_BAND].chaos_attractor.signal(0)); MAX_CHANNELS];
+            }
+
+            for (channel, sample) in channel_samples.iter_mut().enumerate() {
+                *sample *= comp_gains[channel];
+
+                // Echo the mixed signal, with the chaos signal wobbling the delay time
+                let chaos_signal = self.chains[FULL_BAND].chaos_attractor.signal(channel);
+                *sample = self.delay.process(channel, *sample, delay_feedback, delay_mix, delay_warble, chaos_signal);
+
+                // The reverb comes last, so it smears the echoes too
+                *sample = self.reverb.process(channel, *sample, reverb_size, reverb_damping, reverb_mix);
+
+                // Then the tilt EQ shapes the tone of everything before it, the reverb tail included
+                *sample = self.tilt_eq.process(channel, *sample, tilt);
+            }
+
+            // Apply the stereo width, output gain, pan, balance and the polarity switches
+            // These move signal between the channels, so they need the whole frame at once
+            let mut frame = [0.0; MAX_CHANNELS];
+            for (frame_sample, sample) in frame.iter_mut().zip(channel_samples.iter_mut()) {
+                *frame_sample = *sample;
+            }
+            if let [left, right] = &mut frame[..num_channels] {
+                let (mid, side) = ms::encode(*left, *right);
+                let chaos_signal = self.chains[FULL_BAND].chaos_attractor.signal(0);
+                let side = self.stereo_width.process(mid, side, stereo_width, chaos_signal);
+
+                // At 100% the frame is left alone, so not even a rounding error gets in
+                if stereo_width != 1.0 {
+                    (*left, *right) = ms::decode(mid, side);
+                }
+            }
+            self.gain_processor.process_frame(&mut frame[..num_channels], output_gain, output_pan, output_balance);
+            for (channel, (frame_sample, sample)) in frame.iter().zip(channel_samples.iter_mut()).enumerate() {
+                *sample = *frame_sample;
+
+                // Remove any DC offset the chain left behind
+                // This is the last fixed stage, only the limiter comes after it so the ceiling still holds
+                if dc_blocker_enabled {
+                    *sample = self.dc_blocker.process(channel, *sample);
+                }
+            }
+
+            // The limiter needs to see all channels of a frame at once to keep them linked
+            if limiter_running {
+                // Fade the limiting in or out over the same time as the host bypass
+                let limiter_target = if limiter_enabled { 1.0 } else { 0.0 };
+                if limiter_target > self.limiter_fade {
+                    self.limiter_fade = (self.limiter_fade + bypass_fade_step).min(limiter_target);
+                } else {
+                    self.limiter_fade = (self.limiter_fade - bypass_fade_step).max(limiter_target);
+                }
+
+                let mut frame = [0.0; MAX_CHANNELS];
+                for (frame_sample, sample) in frame.iter_mut().zip(channel_samples.iter_mut()) {
+                    *frame_sample = *sample;
+                }
+                self.limiter.process_with_amount(&mut frame[..num_channels], self.limiter_fade);
+                for (frame_sample, sample) in frame.iter().zip(channel_samples.iter_mut()) {
+                    *sample = *frame_sample;
+                }
+            }
+
+            // The very last stage, so nothing gets past its ceiling whatever the stages before did
+            if hard_clip {
+                for sample in channel_samples.iter_mut() {
+                    *sample = self.hard_clipper.process(*sample);
+                }
+            }
+
+            // Equal-power crossfade between the processed and the bypassed signal
+            // Once the fade is done only one of the two is left, so bypass is truly untouched
+            if self.bypass_fade != bypass_target {
+                self.bypass_fade = if bypass_target > self.bypass_fade {
+                    (self.bypass_fade + bypass_fade_step).min(bypass_target)
+                } else {
+                    (self.bypass_fade - bypass_fade_step).max(bypass_target)
+                };
+            }
+            if self.bypass_fade > 0.0 {
+                let angle = self.bypass_fade * std::f32::consts::FRAC_PI_2;
+                let (bypassed_gain, processed_gain) = angle.sin_cos();
+                for (sample, bypassed) in channel_samples.iter_mut().zip(bypassed) {
+                    *sample = if self.bypass_fade >= 1.0 {
+                        bypassed
+                    } else {
+                        *sample * processed_gain + bypassed * bypassed_gain
+                    };
+                }
+            }
+
+            // Fade back in after the plugin was initialized again, over the same time as the bypass
+            if self.reinit_fade < 1.0 {
+                self.reinit_fade = (self.reinit_fade + bypass_fade_step).min(1.0);
+                let gain = (self.reinit_fade * std::f32::consts::FRAC_PI_2).sin();
+                channel_samples.iter_mut().for_each(|sample| *sample *= gain);
+            }
+
+            // Sum of all channels in this frame, used to feed the mono spectrum analyzer
+            let mut frame_sum = 0.0;
+            let mut output_power = 0.0;
+            for (channel, sample) in channel_samples.iter_mut().enumerate() {
+                // Track the true peak for the output meter
+                if analyzer_active {
+                    self.peak_meter.push(channel, *sample);
+                }
+                self.output_loudness.push(channel, *sample);
+                frame_sum += *sample;
+                output_power += *sample * *sample;
+            }
+            self.output_loudness.end_frame();
+
+            // Compare the output level against the untouched input
+            self.level_difference_meter.push(input_power / num_channels as f32, output_power / num_channels as f32);
+
+            // The correlation only means something with two channels
+            if num_channels == 2 {
+                let mut frame = channel_samples.iter_mut();
+                if let (Some(left), Some(right)) = (frame.next(), frame.next()) {
+                    self.correlation_meter.push(*left, *right);
+                }
+            }
+
+            // Feed the spectrum analyzer with the average of all channels
+            // and the attractor scope with the first channel's chaotic system
+            // The oscilloscope compares the output with the delayed input, so both line up in time
+            // The texture history follows the first channel's chaos signal along with the output
+            if analyzer_active {
+                self.spectrum_input.push(frame_sum / num_channels as f32);
+                self.attractor_input.push(self.chains[FULL_BAND].chaos_attractor.system_state(0));
+                let input_sum: f32 = bypassed[..num_channels].iter().sum();
+                self.scope_input.push(input_sum / num_channels as f32, frame_sum / num_channels as f32);
+                self.texture_input.push(frame_sum / num_channels as f32, self.chains[FULL_BAND].chaos_attractor.signal(0));
+            }
+
+            // The raw modulation signals of the first channel, like the scopes show them
+            // They're not affected by the mix, the output gain or the bypass
+            if let Some(modulation_output) = modulation_output.as_mut() {
+                let signals = [
+                    self.chains[FULL_BAND].chaos_attractor.signal(0),
+                    self.chains[FULL_BAND].fractal_magic.modulation(0),
+                ];
+                for (output, signal) in modulation_output.iter_mut().zip(signals) {
+                    output[sample_idx] = signal;
+                }
             }
         }
         
-        // Update the peak meter with smoothing/decay
-        // First, load the current meter value
-        let current_meter = self.peak_meter.load(std::sync::atomic::Ordering::Relaxed);
-        
-        // Calculate the new meter value:
-        // If the new peak is higher, jump to it
-        // Otherwise apply decay to the current value
-        let new_meter = if max_peak > current_meter {
-            max_peak // Jump to new peak if higher
-        } else {
-            current_meter * self.peak_meter_decay_weight // Apply decay
-        };
-        
-        // Store the new meter value atomically
-        // Atomic operations ensure data is safely shared between threads
-        self.peak_meter.store(new_meter, std::sync::atomic::Ordering::Relaxed);
+        // Share the modulated amounts with the GUI
+        self.modulated_magic.store(modulated_magic, std::sync::atomic::Ordering::Relaxed);
+        self.modulated_chaos.store(modulated_chaos, std::sync::atomic::Ordering::Relaxed);
+        self.stability.store(stability, std::sync::atomic::Ordering::Relaxed);
+
+        // Where the smoothers ended up after this block, for the modulation rings around the knobs
+        // The magic and chaos amounts get the LFO, envelope and burst modulation on top
+        if analyzer_active {
+            for target in 0..MORPH_TARGETS.len() {
+                if let Some(param) = morph::target_param(&self.params, target) {
+                    self.modulation_rings.publish(target, param.preview_normalized(param.smoothed.previous_value()));
+                }
+            }
+            self.modulation_rings
+                .publish_param(self.params.magic.as_ptr(), self.params.magic.preview_normalized(modulated_magic));
+            self.modulation_rings
+                .publish_param(self.params.chaos.as_ptr(), self.params.chaos.preview_normalized(modulated_chaos));
+        }
+        self.correlation.store(self.correlation_meter.correlation(), std::sync::atomic::Ordering::Relaxed);
+        self.level_difference.store(self.level_difference_meter.difference_db(), std::sync::atomic::Ordering::Relaxed);
+
+        // Update both peak meters with smoothing/decay, but only when someone's looking at them
+        if analyzer_active {
+            self.input_peak_meter.publish(buffer.samples());
+            self.peak_meter.publish(buffer.samples());
+        }
+        // Keep the saved engine state up to date, or forget it once persisting is switched off
+        // Not before it has been restored, and if the host is saving it right now, the next block updates it
+        if !self.restore_engine_state {
+            let persist_engine_state = self.params.persist_engine_state.value();
+            if let Ok(mut engine_state) = self.params.engine_state.try_write() {
+                *engine_state = persist_engine_state.then(|| EngineState::capture(&self.chains));
+            }
+        }
+        self.cpu_meter.finish(buffer.samples());
 
         // Return normal status to indicate processing completed successfully
-        ProcessStatus::Normal
+        // The generator makes sound out of silence, so the host has to keep calling us then
+        if generator_enabled {
+            ProcessStatus::KeepAlive
+        } else {
+            ProcessStatus::Normal
+        }
     }
 }
 
+/// A mid/side balance parameter, from the mid only (-1.0) to the side only (1.0)
+fn ms_balance_param(name: &str) -> FloatParam {
+    FloatParam::new(
+        name,
+        0.0, // Default value, both the mid and the side get the whole effect
+        FloatRange::Linear {
+            min: -1.0,   // Only the mid gets the effect
+            max: 1.0,    // Only the side gets the effect
+        },
+    )
+    .with_smoother(SmoothingStyle::Linear(50.0))
+    .with_value_to_string(ms::v2s_balance())
+    .with_string_to_value(ms::s2v_balance())
+}
+
+/// How much of the effect amounts one band gets in multiband mode, from none to all of them
+fn band_amount_param(name: &str) -> FloatParam {
+    FloatParam::new(
+        name,
+        1.0, // Default value, the band gets the full amounts
+        FloatRange::Linear {
+            min: 0.0,    // The band stays clean
+            max: 1.0,    // The band gets the full amounts
+        },
+    )
+    .with_smoother(SmoothingStyle::Linear(50.0))
+    .with_unit("%")
+    .with_value_to_string(formatters::v2s_f32_percentage(0))
+    .with_string_to_value(formatters::s2v_f32_percentage())
+}
+
+/// The output level of one of the effect stages, at 0 dB by default
+fn stage_level_param(name: &str) -> FloatParam {
+    FloatParam::new(
+        name,
+        util::db_to_gain(0.0), // Default value, the stage is left at its own level
+        FloatRange::Skewed {
+            min: util::db_to_gain(-24.0),
+            max: util::db_to_gain(12.0),
+            factor: FloatRange::gain_skew_factor(-24.0, 12.0),
+        },
+    )
+    .with_smoother(SmoothingStyle::Logarithmic(50.0))
+    .with_unit(" dB")
+    .with_value_to_string(formatters::v2s_f32_gain_to_db(1))
+    .with_string_to_value(formatters::s2v_f32_gain_to_db())
+}
+
+/// A left/right position for the output's pan and balance, centered by default
+fn stereo_position_param(name: &str) -> FloatParam {
+    FloatParam::new(
+        name,
+        0.0, // Default value, both channels are left alone
+        FloatRange::Linear {
+            min: -1.0,   // All the way to the left
+            max: 1.0,    // All the way to the right
+        },
+    )
+    .with_smoother(SmoothingStyle::Linear(50.0))
+    .with_value_to_string(formatters::v2s_f32_panning())
+    .with_string_to_value(formatters::s2v_f32_panning())
+}
+
+/// A macro knob, it does nothing by itself
+fn macro_param(name: &str) -> FloatParam {
+    FloatParam::new(
+        name,
+        0.0, // Default value, the mapped parameters are where their knobs are
+        FloatRange::Linear {
+            min: 0.0,    // The mapped parameters are where their knobs are
+            max: 1.0,    // The mapped parameters are moved by their whole ranges
+        },
+    )
+    .with_unit("%")
+    .with_value_to_string(formatters::v2s_f32_percentage(0))
+    .with_string_to_value(formatters::s2v_f32_percentage())
+}
+
 // Implementation for CLAP plugin format support
 // CLAP is a newer plugin format with modern features
 impl ClapPlugin for RetardedGain {
     // Define the unique ID for this plugin in CLAP hosts
+    // Hosts find the plugin in saved sessions by this ID, so it must never change. It's the
+    // only plugin the crate exports: `GainProcessor` in gain.rs is just the gain stage.
     const CLAP_ID: &'static str = "com.weblabstudio.retarded-gain";
     
     // Description shown in CLAP hosts
@@ -336,6 +3122,7 @@ impl ClapPlugin for RetardedGain {
 impl Vst3Plugin for RetardedGain {
     // Unique class ID for VST3 - must be 16 bytes
     // The *b prefix creates a byte array from a string
+    // Like the CLAP ID, sessions refer to the plugin by it, so it must never change
     const VST3_CLASS_ID: [u8; 16] = *b"R3T4RD3DG41NWSHU";
     
     // Categories for this plugin in VST3 hosts
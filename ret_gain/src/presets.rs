@@ -0,0 +1,303 @@
+// Import the NIH-plug prelude for the Params trait, ParamPtr and utility functions
+use nih_plug::prelude::*;
+// Serde is used to save user presets as JSON files
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::distortion::DriveModel;
+use crate::plugin::RetardedGainParams;
+
+/// A named snapshot of parameter values
+// Values are stored as plain (not normalized) values keyed by the parameter's ID,
+// so presets stay readable in the JSON files and survive changes to parameter ranges
+// Parameters that are missing from a preset are left untouched when it's loaded
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Preset {
+    /// The name shown in the preset browser
+    pub name: String,
+
+    /// Plain parameter values by parameter ID
+    pub values: BTreeMap<String, f32>,
+}
+
+impl Preset {
+    /// Capture the current values of all parameters into a new preset
+    // Bypass is left out, loading a preset should never silently bypass the plugin
+    pub fn from_params(name: String, params: &RetardedGainParams) -> Self {
+        let values = params
+            .param_map()
+            .into_iter()
+            .filter(|(id, _, _)| id != "bypass")
+            // SAFETY: The parameter pointers come straight from the params object, which outlives this call
+            .map(|(id, param_ptr, _)| (id, unsafe { param_ptr.unmodulated_plain_value() }))
+            .collect();
+
+        Self { name, values }
+    }
+
+    /// Look up the parameters stored in this preset and convert their values to normalized values
+    // The GUI uses this to send the values to the host through its parameter setter
+    pub fn normalized_values(&self, params: &RetardedGainParams) -> Vec<(ParamPtr, f32)> {
+        params
+            .param_map()
+            .into_iter()
+            .filter_map(|(id, param_ptr, _)| {
+                let plain = *self.values.get(&id)?;
+                // SAFETY: See above
+                Some((param_ptr, unsafe { param_ptr.preview_normalized(plain) }))
+            })
+            .collect()
+    }
+
+    /// A copy of the preset without the locked parameters, which then keep their current values
+    pub fn without_locked(&self, locks: PresetLocks) -> Self {
+        let mut preset = self.clone();
+        for lock in PresetLock::ALL {
+            if locks.is_locked(lock) {
+                preset.values.remove(lock.id());
+            }
+        }
+
+        preset
+    }
+
+    /// Bring a preset from before the drive was in dB up to date
+    // Those presets have the drive as a ratio and no drive model, and they were made with
+    // the drive that doesn't make up the level. Presets saved since then always have a
+    // drive model, so they're left alone.
+    pub fn upgrade_legacy_drive(&mut self) {
+        if self.values.contains_key("drive_model") {
+            return;
+        }
+
+        if let Some(drive) = self.values.get_mut("drive") {
+            *drive = util::gain_to_db(drive.max(1.0));
+        }
+        self.values
+            .insert("drive_model".to_string(), DriveModel::Legacy.to_index() as f32);
+    }
+}
+
+/// Build a factory preset from the main effect settings
+// Gain is given in decibels here since that's what the GUI displays. The drive is given as
+// a ratio like it was when the presets were made, and the presets keep the legacy drive
+// model, since their gains were set by ear with the drive making the signal louder.
+fn factory_preset(name: &str, gain_db: f32, drive: f32, magic: f32, chaos: f32, mix: f32) -> Preset {
+    let values = [
+        ("gain", util::db_to_gain(gain_db)),
+        ("drive", util::gain_to_db(drive)),
+        ("drive_model", DriveModel::Legacy.to_index() as f32),
+        ("magic", magic),
+        ("chaos", chaos),
+        ("mix", mix),
+    ]
+    .into_iter()
+    .map(|(id, value)| (id.to_string(), value))
+    .collect();
+
+    Preset {
+        name: name.to_string(),
+        values,
+    }
+}
+
+/// The presets that ship with the plugin
+pub fn factory_presets() -> Vec<Preset> {
+    vec![
+        factory_preset("Init", 0.0, 1.0, 0.0, 0.0, 1.0),
+        factory_preset("Warm Saturation", -2.0, 3.0, 0.0, 0.0, 1.0),
+        factory_preset("Fuzz Wall", -9.0, 35.0, 0.0, 0.0, 1.0),
+        factory_preset("Parallel Crunch", -3.0, 12.0, 0.0, 0.0, 0.4),
+        factory_preset("Fractal Shimmer", -1.0, 1.0, 0.35, 0.0, 1.0),
+        factory_preset("Julia Fold", -4.0, 4.0, 0.8, 0.0, 1.0),
+        factory_preset("Gentle Wobble", 0.0, 1.0, 0.0, 0.2, 1.0),
+        factory_preset("Butterfly Effect", -2.0, 2.0, 0.0, 0.65, 1.0),
+        factory_preset("Strange Attractor", -5.0, 6.0, 0.3, 0.9, 1.0),
+        factory_preset("Evolving Pad Grit", -3.0, 1.5, 0.5, 0.5, 0.6),
+        factory_preset("Broken Radio", -6.0, 20.0, 0.6, 0.7, 1.0),
+        factory_preset("Total Meltdown", -12.0, 50.0, 1.0, 1.0, 1.0),
+        factory_preset("Subtle Glue", 0.0, 1.8, 0.1, 0.1, 0.5),
+    ]
+}
+
+/// The directory user presets are stored in
+// This is a platform-specific config directory, e.g. `~/.config/ret_gain/presets` on Linux
+pub fn user_presets_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ret_gain").join("presets"))
+}
+
+/// Load all user presets from the presets directory
+// Files that can't be read or parsed are skipped with a log message
+pub fn load_user_presets() -> Vec<Preset> {
+    let Some(dir) = user_presets_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        // The directory doesn't exist until the first preset gets saved
+        return Vec::new();
+    };
+
+    let mut presets: Vec<Preset> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .filter_map(|path| {
+            let preset = fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|json| serde_json::from_str(&json).map_err(|err| err.to_string()));
+            match preset {
+                Ok(mut preset) => {
+                    preset.upgrade_legacy_drive();
+                    Some(preset)
+                }
+                Err(err) => {
+                    nih_log!("Could not load preset '{}': {}", path.display(), err);
+                    None
+                }
+            }
+        })
+        .collect();
+    presets.sort_by(|a, b| a.name.cmp(&b.name));
+
+    presets
+}
+
+/// Save a preset to the user presets directory, overwriting any preset with the same name
+// Returns the path of the written file
+pub fn save_user_preset(preset: &Preset) -> std::io::Result<PathBuf> {
+    let dir = user_presets_dir().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "no config directory on this platform")
+    })?;
+    fs::create_dir_all(&dir)?;
+
+    // Only keep characters that are safe to use in file names on every platform
+    let file_name: String = preset
+        .name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == ' ' { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("{file_name}.json"));
+
+    let json = serde_json::to_string_pretty(preset).map_err(std::io::Error::other)?;
+    fs::write(&path, json)?;
+
+    Ok(path)
+}
+
+/// A parameter that can be kept at its current value while browsing presets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresetLock {
+    /// The output gain, so a louder preset doesn't blast the monitors
+    OutputGain,
+    /// The dry/wet mix, so every preset is heard at the same amount
+    Mix,
+}
+
+impl PresetLock {
+    /// Every lockable parameter, in the order the editor shows them
+    pub const ALL: [PresetLock; 2] = [PresetLock::OutputGain, PresetLock::Mix];
+
+    /// The ID of the locked parameter
+    pub fn id(self) -> &'static str {
+        match self {
+            // The output gain still has the ID from before the input trim existed
+            PresetLock::OutputGain => "gain",
+            PresetLock::Mix => "mix",
+        }
+    }
+
+    /// The name shown in the editor
+    pub fn label(self) -> &'static str {
+        match self {
+            PresetLock::OutputGain => "OUT",
+            PresetLock::Mix => "MIX",
+        }
+    }
+}
+
+/// Which parameters keep their current values when a preset is loaded
+// Only loading from the preset browser respects these, switching between the A/B slots
+// still brings back everything, since that's meant to compare the complete settings.
+// Saved together with the plugin state, nothing is locked by default.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PresetLocks {
+    /// Keep the output gain
+    pub output_gain: bool,
+
+    /// Keep the dry/wet mix
+    pub mix: bool,
+}
+
+impl PresetLocks {
+    /// Whether a parameter is locked
+    pub fn is_locked(&self, lock: PresetLock) -> bool {
+        match lock {
+            PresetLock::OutputGain => self.output_gain,
+            PresetLock::Mix => self.mix,
+        }
+    }
+
+    /// Lock a parameter, or unlock it if it was locked
+    pub fn toggle(&mut self, lock: PresetLock) {
+        let locked = match lock {
+            PresetLock::OutputGain => &mut self.output_gain,
+            PresetLock::Mix => &mut self.mix,
+        };
+        *locked = !*locked;
+    }
+}
+
+/// One of the two A/B comparison slots
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AbSlot {
+    #[default]
+    A,
+    B,
+}
+
+impl AbSlot {
+    /// The slot that isn't this one
+    pub fn other(self) -> Self {
+        match self {
+            AbSlot::A => AbSlot::B,
+            AbSlot::B => AbSlot::A,
+        }
+    }
+
+    /// The name shown in the editor
+    pub fn label(self) -> &'static str {
+        match self {
+            AbSlot::A => "A",
+            AbSlot::B => "B",
+        }
+    }
+}
+
+/// Two sets of settings to quickly switch between while mixing
+// The active slot's settings are always the current parameter values, so only the
+// inactive slot needs to be stored here. It's saved together with the plugin state.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AbComparison {
+    /// The slot the current parameter values belong to
+    pub active: AbSlot,
+
+    /// The settings of the other slot
+    // This is empty until the first switch, in which case both slots start out the same
+    pub inactive: Option<Preset>,
+}
+
+impl AbComparison {
+    /// Switch to the other slot, storing the current settings in the slot being left
+    // Returns the settings that should be applied, if the other slot has any yet
+    pub fn switch(&mut self, current: Preset) -> Option<Preset> {
+        self.active = self.active.other();
+        self.inactive.replace(current)
+    }
+
+    /// Overwrite the inactive slot with the current settings
+    pub fn copy_to_inactive(&mut self, current: Preset) {
+        self.inactive = Some(current);
+    }
+}
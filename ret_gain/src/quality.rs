@@ -0,0 +1,57 @@
+// The quality tier is an enum parameter
+#[cfg(feature = "plugin")]
+use nih_plug::prelude::*;
+
+// The settings a tier can override
+use crate::chaos::Integrator;
+use crate::fractal::FoldQuality;
+use crate::oversampling::Oversampling;
+
+/// How much CPU the effect chain may spend on fidelity
+// One switch for the settings that cost the most, so a big session can be made lighter
+// without going through every one of them. Normal leaves them all as they're set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum Quality {
+    /// No oversampling, the plain fold, Euler integration, and the chaotic systems at control rate
+    #[cfg_attr(feature = "plugin", id = "eco")]
+    #[cfg_attr(feature = "plugin", name = "Eco")]
+    Eco,
+    /// Exactly what the oversampling, integrator and fold quality settings say. How projects from before the setting sound.
+    #[cfg_attr(feature = "plugin", id = "normal")]
+    #[cfg_attr(feature = "plugin", name = "Normal")]
+    Normal,
+    /// 8x oversampling, Runge-Kutta integration and second-order anti-aliasing, whatever those settings say
+    #[cfg_attr(feature = "plugin", id = "ultra")]
+    #[cfg_attr(feature = "plugin", name = "Ultra")]
+    Ultra,
+}
+
+impl Quality {
+    /// The oversampling the chain runs with, given the one that's chosen
+    pub fn oversampling(self, chosen: Oversampling) -> Oversampling {
+        match self {
+            Quality::Eco => Oversampling::Off,
+            Quality::Normal => chosen,
+            Quality::Ultra => Oversampling::X8,
+        }
+    }
+
+    /// The integrator the continuous chaotic systems use, given the one that's chosen
+    pub fn integrator(self, chosen: Integrator) -> Integrator {
+        match self {
+            Quality::Eco => Integrator::Euler,
+            Quality::Normal => chosen,
+            Quality::Ultra => Integrator::RungeKutta4,
+        }
+    }
+
+    /// The anti-aliasing of the fractal's wavefolder, given the one that's chosen
+    pub fn fold_quality(self, chosen: FoldQuality) -> FoldQuality {
+        match self {
+            Quality::Eco => FoldQuality::Off,
+            Quality::Normal => chosen,
+            Quality::Ultra => FoldQuality::SecondOrder,
+        }
+    }
+}
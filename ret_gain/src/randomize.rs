@@ -0,0 +1,84 @@
+/// A range a randomized parameter is picked from
+// Uniformly random values are rarely usable: full drive with full chaos is mostly noise.
+// The skew bends the distribution towards the low end of the range, so most rolls land
+// on tasteful settings and only the occasional one goes all the way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightedRange {
+    /// The lowest value that can come out
+    pub min: f32,
+    /// The highest value that can come out
+    pub max: f32,
+    /// 1.0 is uniform, higher values favor the low end of the range
+    pub skew: f32,
+}
+
+impl WeightedRange {
+    /// Map a uniformly random number between 0.0 and 1.0 onto the range
+    pub fn pick(&self, random: f32) -> f32 {
+        self.min + (self.max - self.min) * random.powf(self.skew)
+    }
+}
+
+/// Drive mostly stays in saturation territory, with the odd fuzz wall
+// In dB, which already spreads the saturation settings out, so it needs less skew than the ratio did
+pub const DRIVE_RANGE: WeightedRange = WeightedRange { min: 0.0, max: 34.0, skew: 1.3 };
+
+/// Magic leaves the top of its range alone, where it folds everything into noise
+pub const MAGIC_RANGE: WeightedRange = WeightedRange { min: 0.0, max: 0.8, skew: 1.5 };
+
+/// Chaos favors gentle movement over the wildest settings
+pub const CHAOS_RANGE: WeightedRange = WeightedRange { min: 0.0, max: 0.7, skew: 1.5 };
+
+/// The parameter values of one roll of the dice
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RandomPatch {
+    /// Distortion drive, in the drive parameter's plain range
+    pub drive: f32,
+    /// Magic amount from 0.0 to 1.0
+    pub magic: f32,
+    /// Chaos amount from 0.0 to 1.0
+    pub chaos: f32,
+}
+
+/// Roll the dice with a seed
+// The same seed always gives the same patch, which is what makes a happy accident
+// recallable: write down the seed and type it back in later.
+// Every value is drawn regardless of which parameters are locked, so locking one
+// doesn't change what the others come out as.
+pub fn roll(seed: u32) -> RandomPatch {
+    // xorshift gets stuck at zero, so that seed is nudged
+    let mut state = seed.max(1);
+    let mut next_random = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as f32 / u32::MAX as f32
+    };
+
+    RandomPatch {
+        drive: DRIVE_RANGE.pick(next_random()),
+        magic: MAGIC_RANGE.pick(next_random()),
+        chaos: CHAOS_RANGE.pick(next_random()),
+    }
+}
+
+/// A fresh non-zero seed
+// This only runs on the GUI thread, so the clock is random enough
+pub fn new_seed() -> u32 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |time| time.subsec_nanos());
+
+    // Spread the nanoseconds over all 32 bits
+    nanos.wrapping_mul(0x9E37_79B9).max(1)
+}
+
+/// Show a seed the way the editor displays it
+pub fn format_seed(seed: u32) -> String {
+    format!("{seed:08X}")
+}
+
+/// Read a seed typed into the editor
+pub fn parse_seed(text: &str) -> Option<u32> {
+    u32::from_str_radix(text.trim().trim_start_matches('#'), 16).ok()
+}
@@ -0,0 +1,167 @@
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+/// Delay lengths of the parallel comb filters at 44.1 kHz, in samples
+// These are the tunings from Jezar's Freeverb. They're all mutually prime-ish, so the
+// echoes of the different combs never line up into an audible pattern.
+const COMB_TUNINGS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+
+/// Delay lengths of the series all-pass filters at 44.1 kHz, in samples
+const ALLPASS_TUNINGS: [usize; 4] = [556, 441, 341, 225];
+
+/// How much longer the right channel's delays are, in samples at 44.1 kHz
+// Slightly different delays on both sides give the reverb its width
+const STEREO_SPREAD: usize = 23;
+
+/// How much of the input goes into the combs
+// The eight combs add up to a lot of gain, this keeps the tail around the input level
+const INPUT_GAIN: f32 = 0.015;
+
+/// How loud the reverb tail comes out, undoing `INPUT_GAIN`
+const WET_GAIN: f32 = 3.0;
+
+/// A feedback comb filter with a low pass filter in its feedback path
+// The low pass is what makes the high frequencies die out faster than the lows,
+// like they do in a real room
+struct Comb {
+    buffer: Vec<f32>,
+    pos: usize,
+
+    /// State of the one-pole low pass in the feedback path
+    filter_store: f32,
+}
+
+impl Comb {
+    fn new(len: usize) -> Self {
+        Self {
+            buffer: vec![0.0; len.max(1)],
+            pos: 0,
+            filter_store: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.pos];
+        self.filter_store = output * (1.0 - damping) + self.filter_store * damping;
+        self.buffer[self.pos] = input + self.filter_store * feedback;
+        self.pos = (self.pos + 1) % self.buffer.len();
+
+        output
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.filter_store = 0.0;
+    }
+}
+
+/// A Schroeder all-pass filter, smears the echoes of the combs into a dense tail
+struct Allpass {
+    buffer: Vec<f32>,
+    pos: usize,
+}
+
+impl Allpass {
+    fn new(len: usize) -> Self {
+        Self {
+            buffer: vec![0.0; len.max(1)],
+            pos: 0,
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let buffered = self.buffer[self.pos];
+        self.buffer[self.pos] = input + buffered * 0.5;
+        self.pos = (self.pos + 1) % self.buffer.len();
+
+        buffered - input
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+    }
+}
+
+/// Per-channel state of the reverb
+struct ReverbState {
+    combs: [Comb; COMB_TUNINGS.len()],
+    allpasses: [Allpass; ALLPASS_TUNINGS.len()],
+}
+
+impl ReverbState {
+    /// Create the filters for the given sample rate, with `spread` extra samples (at 44.1 kHz) on every delay
+    fn new(sample_rate: f32, spread: usize) -> Self {
+        // The tunings are for 44.1 kHz, other sample rates need longer or shorter delays
+        // to sound the same
+        let scale = |len: usize| ((len + spread) as f32 * sample_rate / 44100.0).round() as usize;
+
+        Self {
+            combs: std::array::from_fn(|i| Comb::new(scale(COMB_TUNINGS[i]))),
+            allpasses: std::array::from_fn(|i| Allpass::new(scale(ALLPASS_TUNINGS[i]))),
+        }
+    }
+}
+
+/// A small Freeverb-style algorithmic reverb
+// Eight comb filters in parallel build up the echoes, and four all-pass filters in series
+// blur them into a smooth tail. It sits at the end of the chain to smear the chaotic
+// textures into pads.
+pub struct Reverb {
+    /// The filters of every channel, allocated by `set_sample_rate()`
+    channels: [ReverbState; MAX_CHANNELS],
+}
+
+impl Reverb {
+    /// Create a new reverb
+    // The buffers are sized for 44.1 kHz until `set_sample_rate()` is called
+    pub fn new() -> Self {
+        let mut reverb = Self {
+            channels: std::array::from_fn(|_| ReverbState::new(44100.0, 0)),
+        };
+        reverb.set_sample_rate(44100.0);
+
+        reverb
+    }
+
+    /// Set the sample rate and allocate the delay lines for it
+    // The comb and allpass tunings are in samples at 44.1 kHz, so every channel's twelve delay
+    // lines are built again at their scaled lengths, which is only done while the plugin
+    // is initialized
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        // Every channel after the first one is spread a little further
+        self.channels = std::array::from_fn(|channel| ReverbState::new(sample_rate, channel * STEREO_SPREAD));
+    }
+
+    /// Clear the reverb tail
+    pub fn reset(&mut self) {
+        for state in self.channels.iter_mut() {
+            state.combs.iter_mut().for_each(Comb::reset);
+            state.allpasses.iter_mut().for_each(Allpass::reset);
+        }
+    }
+
+    /// Process a single sample of the given channel through the reverb
+    // `size` (0.0 to 1.0) sets how long the tail rings, `damping` (0.0 to 1.0) how quickly
+    // the highs die out, and `mix` blends the input with the tail
+    pub fn process(&mut self, channel: usize, sample: f32, size: f32, damping: f32, mix: f32) -> f32 {
+        // Freeverb's mapping of the controls onto the comb filters
+        let feedback = 0.7 + size * 0.28;
+        let damping = damping * 0.4;
+
+        let state = &mut self.channels[channel];
+        let input = sample * INPUT_GAIN;
+
+        // The combs run in parallel on the same input
+        let mut wet = 0.0;
+        for comb in state.combs.iter_mut() {
+            wet += comb.process(input, feedback, damping);
+        }
+
+        // The all-passes run one after the other
+        for allpass in state.allpasses.iter_mut() {
+            wet = allpass.process(wet);
+        }
+
+        sample * (1.0 - mix) + wet * WET_GAIN * mix
+    }
+}
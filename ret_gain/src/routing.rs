@@ -0,0 +1,96 @@
+// Import the NIH-plug prelude for the Enum derive macro
+#[cfg(feature = "plugin")]
+use nih_plug::prelude::*;
+
+/// One of the reorderable stages of the effect chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// The tanh distortion
+    Distortion,
+    /// The fractal wavefolder ("Magic One")
+    Fractal,
+    /// The Lorenz attractor modulation
+    Chaos,
+}
+
+/// Which stages are soloed, so the others can be left out while auditioning one
+// Like the solo buttons on a mixer: when nothing is soloed every stage is heard, otherwise
+// only the soloed ones are
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StageSolo {
+    pub distortion: bool,
+    pub fractal: bool,
+    pub chaos: bool,
+}
+
+impl StageSolo {
+    /// Whether the output of a stage goes on down the chain
+    pub fn is_heard(self, stage: Stage) -> bool {
+        if !(self.distortion || self.fractal || self.chaos) {
+            return true;
+        }
+
+        match stage {
+            Stage::Distortion => self.distortion,
+            Stage::Fractal => self.fractal,
+            Stage::Chaos => self.chaos,
+        }
+    }
+}
+
+/// The order the distortion, fractal and chaos stages are applied in
+// Every permutation of the three stages is available
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum EffectOrder {
+    #[cfg_attr(feature = "plugin", id = "dist-magic-chaos")]
+    #[cfg_attr(feature = "plugin", name = "Dist > Magic > Chaos")]
+    DistortionFractalChaos,
+    #[cfg_attr(feature = "plugin", id = "dist-chaos-magic")]
+    #[cfg_attr(feature = "plugin", name = "Dist > Chaos > Magic")]
+    DistortionChaosFractal,
+    #[cfg_attr(feature = "plugin", id = "magic-dist-chaos")]
+    #[cfg_attr(feature = "plugin", name = "Magic > Dist > Chaos")]
+    FractalDistortionChaos,
+    #[cfg_attr(feature = "plugin", id = "magic-chaos-dist")]
+    #[cfg_attr(feature = "plugin", name = "Magic > Chaos > Dist")]
+    FractalChaosDistortion,
+    #[cfg_attr(feature = "plugin", id = "chaos-dist-magic")]
+    #[cfg_attr(feature = "plugin", name = "Chaos > Dist > Magic")]
+    ChaosDistortionFractal,
+    #[cfg_attr(feature = "plugin", id = "chaos-magic-dist")]
+    #[cfg_attr(feature = "plugin", name = "Chaos > Magic > Dist")]
+    ChaosFractalDistortion,
+}
+
+impl EffectOrder {
+    /// The stages in the order they should be applied
+    pub fn stages(self) -> [Stage; 3] {
+        match self {
+            EffectOrder::DistortionFractalChaos => [Stage::Distortion, Stage::Fractal, Stage::Chaos],
+            EffectOrder::DistortionChaosFractal => [Stage::Distortion, Stage::Chaos, Stage::Fractal],
+            EffectOrder::FractalDistortionChaos => [Stage::Fractal, Stage::Distortion, Stage::Chaos],
+            EffectOrder::FractalChaosDistortion => [Stage::Fractal, Stage::Chaos, Stage::Distortion],
+            EffectOrder::ChaosDistortionFractal => [Stage::Chaos, Stage::Distortion, Stage::Fractal],
+            EffectOrder::ChaosFractalDistortion => [Stage::Chaos, Stage::Fractal, Stage::Distortion],
+        }
+    }
+}
+
+/// How the two channels of a stereo signal go through the effect chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "plugin", derive(Enum))]
+pub enum ChannelMode {
+    /// Both channels share the gate, envelope follower and compressor, so they move together
+    #[cfg_attr(feature = "plugin", id = "linked")]
+    #[cfg_attr(feature = "plugin", name = "Linked")]
+    Linked,
+    /// Every channel has its own gate, envelope follower and compressor
+    #[cfg_attr(feature = "plugin", id = "dual-mono")]
+    #[cfg_attr(feature = "plugin", name = "Dual Mono")]
+    DualMono,
+    /// The chain runs on the mid and side signals instead of left and right
+    #[cfg_attr(feature = "plugin", id = "mid-side")]
+    #[cfg_attr(feature = "plugin", name = "Mid/Side")]
+    MidSide,
+}
@@ -0,0 +1,43 @@
+// The curves behind every point in the chain that keeps a signal from running away
+use std::f32::consts::{FRAC_2_PI, FRAC_PI_2};
+
+/// Where the polynomial clipper reaches ±1 and flattens out
+// x - 4x³/27 has a slope of 1 at the origin and of 0 here, right where it reaches 1.0
+const POLYNOMIAL_KNEE: f32 = 1.5;
+
+/// Soft clip a sample with a curve between the three clippers
+// `character` (0.0 to 1.0) morphs from tanh at 0.0 over the sinh-based curve at 0.5 to the
+// polynomial at 1.0, crossfading between the two curves on either side in between. All of
+// them leave quiet signals alone with a slope of 1 and never go past ±1, so every mix of
+// them doesn't either.
+pub fn soft_clip(input: f32, character: f32) -> f32 {
+    let character = character.clamp(0.0, 1.0);
+    if character <= 0.5 {
+        let blend = character * 2.0;
+        tanh_clip(input) * (1.0 - blend) + sinh_clip(input) * blend
+    } else {
+        let blend = (character - 0.5) * 2.0;
+        sinh_clip(input) * (1.0 - blend) + polynomial_clip(input) * blend
+    }
+}
+
+/// The hyperbolic tangent, the smooth S-curve the chain always used
+fn tanh_clip(input: f32) -> f32 {
+    input.tanh()
+}
+
+/// The Gudermannian function atan(sinh(x)), scaled to ±1
+// Bends a little earlier than tanh and takes longer to settle, which sounds rounder and
+// a bit darker when it's pushed
+fn sinh_clip(input: f32) -> f32 {
+    // sinh() overflows to infinity for loud inputs, which atan() turns into exactly π/2
+    FRAC_2_PI * (FRAC_PI_2 * input).sinh().atan()
+}
+
+/// A cubic that stays straight for longer and then flattens out at `POLYNOMIAL_KNEE`
+// The clearest of the three below the knee, and the most audible once it's hit, since
+// the curve stops right there instead of bending towards ±1 forever
+fn polynomial_clip(input: f32) -> f32 {
+    let x = input.clamp(-POLYNOMIAL_KNEE, POLYNOMIAL_KNEE);
+    x - 4.0 * x * x * x / 27.0
+}
@@ -0,0 +1,14 @@
+/// Uniform white noise between -amplitude and amplitude
+// A fixed xorshift generator, so every run gets exactly the same noise. The tests and the
+// benchmarks both feed their processors with it, which is the only reason it's public.
+pub fn white_noise(len: usize, amplitude: f32, seed: u32) -> Vec<f32> {
+    let mut state = seed.max(1);
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            amplitude * (state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+        })
+        .collect()
+}
@@ -0,0 +1,97 @@
+// Import PI and the square root of 2 from the standard library
+use std::f32::consts::{PI, SQRT_2};
+
+// The number of channels we keep separate DSP state for
+use crate::MAX_CHANNELS;
+
+/// The frequency the tilt pivots around, in Hz
+// Both shelves sit here, so this frequency keeps its level whichever way the tilt goes
+const PIVOT_HZ: f32 = 800.0;
+
+/// How far the tilt goes either way, in dB
+// The difference between the lowest and the highest frequencies, half of it on either side
+pub const MAX_TILT_DB: f32 = 12.0;
+
+/// The damping of both shelves, 1/Q with Q = 0.707
+// The flattest shelves that don't overshoot, so the two add up to a straight tilt
+const SHELF_DAMPING: f32 = SQRT_2;
+
+/// The two integrator states of one state variable filter
+#[derive(Clone, Copy, Default)]
+struct ShelfState {
+    ic1eq: f32,
+    ic2eq: f32,
+}
+
+impl ShelfState {
+    /// Run the filter and return its band pass and low pass outputs
+    fn process(&mut self, sample: f32, g: f32) -> (f32, f32) {
+        let a1 = 1.0 / (1.0 + g * (g + SHELF_DAMPING));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let v3 = sample - self.ic2eq;
+        let v1 = a1 * self.ic1eq + a2 * v3;
+        let v2 = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        (v1, v2)
+    }
+}
+
+/// A tilt EQ at the end of the chain, for a quick tonal correction of the chaotic output
+// A low shelf and a high shelf at the same pivot frequency, one turned down by half the tilt
+// and the other turned up by the other half. Positive tilts make the output brighter,
+// negative ones darker. Both are the same trapezoidal state variable filter the tone filter
+// uses, so the tilt can move on every sample without zipper noise, and at 0 dB their extra
+// outputs are weighted by exactly zero, which leaves the signal untouched.
+pub struct TiltEq {
+    /// The low shelf and the high shelf state of every channel
+    channels: [[ShelfState; 2]; MAX_CHANNELS],
+
+    /// The prewarped pivot frequency, only depends on the sample rate
+    pivot: f32,
+}
+
+impl TiltEq {
+    /// Create a new tilt EQ
+    pub fn new() -> Self {
+        let mut tilt_eq = Self {
+            channels: [[ShelfState::default(); 2]; MAX_CHANNELS],
+            pivot: 0.0,
+        };
+        tilt_eq.set_sample_rate(44100.0); // Default sample rate, will be updated
+
+        tilt_eq
+    }
+
+    /// Set the sample rate the pivot frequency is prewarped for
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.pivot = (PI * PIVOT_HZ.min(sample_rate * 0.49) / sample_rate).tan();
+    }
+
+    /// Clear the filter state of every channel
+    pub fn reset(&mut self) {
+        self.channels = [[ShelfState::default(); 2]; MAX_CHANNELS];
+    }
+
+    /// Tilt a single sample of the given channel by `tilt` dB
+    // The tilt is passed in with every sample, so its smoothing is sample-accurate
+    pub fn process(&mut self, channel: usize, sample: f32, tilt: f32) -> f32 {
+        // Every shelf gets half of the tilt, and the shelf gain A is the square root of that as a ratio
+        let tilt = tilt.clamp(-MAX_TILT_DB, MAX_TILT_DB);
+        let a = 10.0f32.powf(tilt / 80.0);
+        let sqrt_a = a.sqrt();
+        let [low_shelf, high_shelf] = &mut self.channels[channel];
+
+        // The low shelf turns everything below the pivot down by half the tilt
+        let low_a = 1.0 / a;
+        let (band, low) = low_shelf.process(sample, self.pivot * sqrt_a);
+        let sample = sample + SHELF_DAMPING * (low_a - 1.0) * band + (low_a * low_a - 1.0) * low;
+
+        // The high shelf turns everything above it up by the other half
+        let (band, low) = high_shelf.process(sample, self.pivot * sqrt_a);
+        a * a * sample + SHELF_DAMPING * (1.0 - a) * a * band + (1.0 - a * a) * low
+    }
+}
@@ -0,0 +1,133 @@
+/// The delay times of the all-pass filters that decorrelate the mid, in milliseconds
+// Short and unrelated to each other, so the filters smear the phase without an audible echo
+const ALLPASS_DELAYS_MS: [f32; 3] = [2.3, 3.7, 5.9];
+
+/// How far the chaos signal moves the all-pass delay times, in milliseconds
+// Just enough to keep the phase smear moving, more would start to sound like a chorus
+const MAX_WOBBLE_MS: f32 = 0.3;
+
+/// The feedback gain of the all-pass filters
+const ALLPASS_GAIN: f32 = 0.6;
+
+/// A Schroeder all-pass filter with a fractional delay time
+// Like the reverb's all-pass filters, but the read position is linearly interpolated like
+// in the delay, so the chaos signal can move it smoothly
+struct ModulatedAllpass {
+    /// Ring buffer, allocated by `set_sample_rate()`
+    buffer: Vec<f32>,
+
+    /// The current write position in the ring buffer
+    write_pos: usize,
+
+    /// The delay time without any wobble, in samples
+    delay: f32,
+}
+
+impl ModulatedAllpass {
+    fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            write_pos: 0,
+            delay: 1.0,
+        }
+    }
+
+    /// Allocate the buffer for a delay time plus the wobble on top
+    fn set_delay(&mut self, delay_ms: f32, sample_rate: f32) {
+        let max_samples = ((delay_ms + MAX_WOBBLE_MS) / 1000.0 * sample_rate).ceil() as usize + 2;
+        self.buffer = vec![0.0; max_samples];
+        self.write_pos = 0;
+        self.delay = delay_ms / 1000.0 * sample_rate;
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_pos = 0;
+    }
+
+    /// Pass a sample through the filter, with the delay time moved by `wobble` samples
+    fn process(&mut self, input: f32, wobble: f32) -> f32 {
+        let len = self.buffer.len();
+        let delay = (self.delay + wobble).clamp(1.0, (len - 2) as f32);
+
+        // Read between the two samples around the fractional delay time
+        let read_pos = self.write_pos as f32 + len as f32 - delay;
+        let index = read_pos as usize;
+        let fraction = read_pos - index as f32;
+        let a = self.buffer[index % len];
+        let b = self.buffer[(index + 1) % len];
+        let delayed = a + (b - a) * fraction;
+
+        // Every frequency comes out just as loud as it went in, only the phase changes
+        let written = input + delayed * ALLPASS_GAIN;
+        self.buffer[self.write_pos] = written;
+        self.write_pos = (self.write_pos + 1) % len;
+
+        delayed - written * ALLPASS_GAIN
+    }
+}
+
+/// A stereo width control working on the mid/side signals
+// Below 100% the side is turned down until the signal is mono at 0%. Above 100% the side is
+// turned up, and a decorrelated copy of the mid is added to it so even mono sources spread
+// out. The copy comes from a few short all-pass filters whose delay times the chaos signal
+// keeps moving. Only the side is ever changed, so summing the output to mono always gives
+// the same signal, and at exactly 100% nothing changes at all.
+pub struct StereoWidth {
+    /// The all-pass filters in series that decorrelate the mid
+    allpasses: [ModulatedAllpass; ALLPASS_DELAYS_MS.len()],
+
+    /// Sample rate for the wobble calculations
+    sample_rate: f32,
+}
+
+impl StereoWidth {
+    /// Create a new width control
+    // The buffers are sized for 44.1 kHz until `set_sample_rate()` is called
+    pub fn new() -> Self {
+        let mut width = Self {
+            allpasses: std::array::from_fn(|_| ModulatedAllpass::new()),
+            sample_rate: 44100.0,
+        };
+        width.set_sample_rate(44100.0);
+
+        width
+    }
+
+    /// Set the sample rate and allocate the all-pass buffers for it
+    // Each of the three all-pass buffers is resized to its delay plus the wobble at this rate,
+    // so the plugin only calls this from `initialize()`
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        for (allpass, delay_ms) in self.allpasses.iter_mut().zip(ALLPASS_DELAYS_MS) {
+            allpass.set_delay(delay_ms, sample_rate);
+        }
+    }
+
+    /// Clear the all-pass filters
+    pub fn reset(&mut self) {
+        self.allpasses.iter_mut().for_each(ModulatedAllpass::reset);
+    }
+
+    /// Work out the new side sample for a mid/side pair
+    // `width` goes from 0.0 (mono) over 1.0 (unchanged) to 2.0 (twice as wide).
+    // `chaos_signal` is between -1.0 and 1.0 and wobbles the all-pass filters.
+    pub fn process(&mut self, mid: f32, side: f32, width: f32, chaos_signal: f32) -> f32 {
+        let width = width.clamp(0.0, 2.0);
+
+        // The filters always run, so turning the width up doesn't start them from silence
+        let wobble = chaos_signal * (MAX_WOBBLE_MS / 1000.0) * self.sample_rate;
+        let mut decorrelated = mid;
+        for (i, allpass) in self.allpasses.iter_mut().enumerate() {
+            // Every filter wobbles the other way, so they don't all move in step
+            let direction = if i % 2 == 0 { 1.0 } else { -1.0 };
+            decorrelated = allpass.process(decorrelated, wobble * direction);
+        }
+
+        if width <= 1.0 {
+            side * width
+        } else {
+            side * width + decorrelated * (width - 1.0)
+        }
+    }
+}
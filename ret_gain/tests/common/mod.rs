@@ -0,0 +1,141 @@
+// Shared test signals and checks for the DSP tests
+// Every test file that needs these pulls them in with `mod common;`, so not every
+// helper is used by every one of them
+#![allow(dead_code)]
+
+use std::f32::consts::PI;
+use std::fs;
+use std::path::PathBuf;
+
+/// The sample rate all test signals are generated at
+pub const SAMPLE_RATE: f32 = 44100.0;
+
+/// The length of every test signal, about 93 ms
+pub const SIGNAL_LEN: usize = 4096;
+
+/// A single full scale sample followed by silence
+pub fn impulse(len: usize) -> Vec<f32> {
+    let mut signal = vec![0.0; len];
+    signal[0] = 1.0;
+
+    signal
+}
+
+/// A sine of the given frequency and amplitude
+pub fn sine(len: usize, frequency: f32, amplitude: f32) -> Vec<f32> {
+    (0..len)
+        .map(|i| amplitude * (2.0 * PI * frequency * i as f32 / SAMPLE_RATE).sin())
+        .collect()
+}
+
+/// A sine sweeping exponentially from 20 Hz to 20 kHz at the given amplitude
+pub fn sine_sweep(len: usize, amplitude: f32) -> Vec<f32> {
+    let (start, end) = (20.0f32, 20000.0f32);
+    let duration = len as f32 / SAMPLE_RATE;
+    let rate = (end / start).ln() / duration;
+
+    // The phase is the integral of the exponentially rising frequency
+    (0..len)
+        .map(|i| {
+            let time = i as f32 / SAMPLE_RATE;
+            let phase = 2.0 * PI * start * ((rate * time).exp() - 1.0) / rate;
+            amplitude * phase.sin()
+        })
+        .collect()
+}
+
+// The same noise the benchmarks run on
+pub use ret_gain::test_signals::white_noise;
+
+/// All test signals with their names, for running a processor over each of them
+pub fn test_signals() -> Vec<(&'static str, Vec<f32>)> {
+    vec![
+        ("impulse", impulse(SIGNAL_LEN)),
+        ("sweep", sine_sweep(SIGNAL_LEN, 0.8)),
+        ("noise", white_noise(SIGNAL_LEN, 0.8, 0x1234_5678)),
+    ]
+}
+
+/// Run a signal through a per-sample processing function
+pub fn render(input: &[f32], mut process: impl FnMut(f32) -> f32) -> Vec<f32> {
+    input.iter().map(|&sample| process(sample)).collect()
+}
+
+/// Check that no sample is NaN or infinite, and that none gets louder than `bound`
+pub fn assert_bounded(name: &str, output: &[f32], bound: f32) {
+    for (index, sample) in output.iter().enumerate() {
+        assert!(sample.is_finite(), "{name}: sample {index} is {sample}");
+        assert!(sample.abs() <= bound, "{name}: sample {index} is {sample}, louder than {bound}");
+    }
+}
+
+/// Check that the output matches the input to within `tolerance`
+pub fn assert_transparent(name: &str, input: &[f32], output: &[f32], tolerance: f32) {
+    assert_eq!(input.len(), output.len(), "{name}: lengths differ");
+    for (index, (a, b)) in input.iter().zip(output.iter()).enumerate() {
+        assert!((a - b).abs() <= tolerance, "{name}: sample {index} changed from {a} to {b}");
+    }
+}
+
+/// Share of a periodic signal's power that isn't on the harmonics of `fundamental_bin`, in dB
+// A plain DFT over the whole signal, which has to repeat exactly every `signal.len()`
+// samples so nothing leaks between bins. With a fundamental that doesn't divide the length,
+// the harmonics that fold back over Nyquist land between the real ones, so this is the
+// aliasing floor. DC is left out, it's neither.
+pub fn inharmonic_power_db(signal: &[f32], fundamental_bin: usize) -> f64 {
+    let len = signal.len();
+    let (mut harmonic, mut inharmonic) = (0.0, 0.0);
+    for bin in 1..=len / 2 {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (index, sample) in signal.iter().enumerate() {
+            let angle = -2.0 * std::f64::consts::PI * ((bin * index) % len) as f64 / len as f64;
+            re += *sample as f64 * angle.cos();
+            im += *sample as f64 * angle.sin();
+        }
+        if bin % fundamental_bin == 0 {
+            harmonic += re * re + im * im;
+        } else {
+            inharmonic += re * re + im * im;
+        }
+    }
+
+    10.0 * (inharmonic / (harmonic + inharmonic)).log10()
+}
+
+/// How far a rendered sample may drift from its snapshot
+// Leaves room for different floating point rounding on other platforms and compilers
+const SNAPSHOT_TOLERANCE: f32 = 1e-4;
+
+/// Compare a rendered buffer against its snapshot in `tests/snapshots`
+// The snapshots are committed, so a missing one fails like a changed one would, instead of
+// quietly recording whatever the code puts out now. Run with `UPDATE_SNAPSHOTS=1` to record
+// the snapshots of a new test, or to re-record all of them after an intentional change to
+// the sound.
+pub fn assert_snapshot(name: &str, output: &[f32]) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.txt"));
+    let rendered: String = output.iter().map(|sample| format!("{sample:.6}\n")).collect();
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, rendered).unwrap();
+        return;
+    }
+    assert!(
+        path.exists(),
+        "{name}: there's no snapshot at {}. Run with UPDATE_SNAPSHOTS=1 to record it",
+        path.display()
+    );
+
+    let snapshot = fs::read_to_string(&path).unwrap();
+    let expected: Vec<f32> = snapshot.lines().map(|line| line.parse().unwrap()).collect();
+    assert_eq!(expected.len(), output.len(), "{name}: the snapshot has a different length");
+    for (index, (a, b)) in expected.iter().zip(output.iter()).enumerate() {
+        assert!(
+            (a - b).abs() <= SNAPSHOT_TOLERANCE,
+            "{name}: sample {index} is {b}, the snapshot says {a}. Run with UPDATE_SNAPSHOTS=1 if this was intentional"
+        );
+    }
+}
@@ -0,0 +1,969 @@
+// Runs every processor over the test signals and checks that the output stays sane
+mod common;
+
+use common::{
+    SAMPLE_RATE, SIGNAL_LEN, assert_bounded, assert_transparent, impulse, inharmonic_power_db, render, sine,
+    test_signals,
+};
+use ret_gain::{
+    ChainSettings, ChaosAttractor, ChaosType, Compressor, Crossover, Crusher, DcBlocker, Delay, Distortion, DistortionMode,
+    DriveModel, EffectChain, Filter, FilterMode, FmMode, FoldQuality, FractalMagic, FrequencyShifter, GainProcessor,
+    Gate, Generator, HardClipper, InputCalibration, Integrator, LatencyReport, Limiter, MagicEngine, MagicMode,
+    Oversampler, Oversampling, Precision, Preset, PresetLock, PresetLocks, ProcessingActivity, Quality, Reverb, Scale,
+    StabilityGovernor, StageSolo, StereoWidth, TiltEq, TruePeakMeter, WaveFolder, couple, soft_clip,
+};
+use std::sync::atomic::Ordering;
+
+const DISTORTION_MODES: [DistortionMode; 6] = [
+    DistortionMode::Tanh,
+    DistortionMode::HardClip,
+    DistortionMode::Cubic,
+    DistortionMode::Diode,
+    DistortionMode::Foldback,
+    DistortionMode::Bitcrush,
+];
+
+const MAGIC_MODES: [MagicMode; 4] = [MagicMode::Julia, MagicMode::Mandelbrot, MagicMode::BurningShip, MagicMode::Newton];
+
+const CHAOS_TYPES: [ChaosType; 4] = [ChaosType::Lorenz, ChaosType::Rossler, ChaosType::Chua, ChaosType::Logistic];
+
+#[test]
+fn distortion_is_bounded() {
+    for mode in DISTORTION_MODES {
+        for (signal_name, input) in test_signals() {
+            for drive in [1.0, 10.0, 50.0] {
+                let mut distortion = Distortion::new();
+                distortion.set_sample_rate(SAMPLE_RATE);
+                distortion.set_mode(mode);
+                let output = render(&input, |sample| distortion.process(0, sample, drive));
+
+                // The waveshapers stay within ±1, the DC blocker after them can overshoot a little
+                assert_bounded(&format!("{mode:?} {signal_name} drive {drive}"), &output, 4.0);
+            }
+        }
+    }
+}
+
+#[test]
+fn distortion_keeps_silence_silent() {
+    for mode in DISTORTION_MODES {
+        let mut distortion = Distortion::new();
+        distortion.set_mode(mode);
+        let output = render(&[0.0; SIGNAL_LEN], |sample| distortion.process(0, sample, 50.0));
+        assert_transparent(&format!("{mode:?}"), &[0.0; SIGNAL_LEN], &output, 0.0);
+    }
+}
+
+#[test]
+fn compensated_drive_keeps_the_level_of_a_sine() {
+    // The second half, once the DC blocker has settled
+    let rms = |output: &[f32]| {
+        let settled = &output[SIGNAL_LEN / 2..];
+        (settled.iter().map(|sample| sample * sample).sum::<f32>() / settled.len() as f32).sqrt()
+    };
+    let input = sine(SIGNAL_LEN, 441.0, 0.25);
+    let input_rms = rms(&input);
+
+    for mode in [DistortionMode::Tanh, DistortionMode::HardClip, DistortionMode::Cubic] {
+        for drive in [1.0, 10.0, 50.0] {
+            let mut distortion = Distortion::new();
+            distortion.set_sample_rate(SAMPLE_RATE);
+            distortion.set_mode(mode);
+            distortion.set_model(DriveModel::Compensated);
+            let output = render(&input, |sample| distortion.process(0, sample, drive));
+
+            // Within 1.5 dB of the input
+            let ratio = rms(&output) / input_rms;
+            assert!((0.84..1.19).contains(&ratio), "{mode:?} drive {drive} changed the level by {ratio}x");
+        }
+    }
+}
+
+#[test]
+fn filter_is_bounded_and_transparent_when_off() {
+    for mode in [FilterMode::LowPass, FilterMode::HighPass, FilterMode::BandPass] {
+        for precision in [Precision::Single, Precision::Double] {
+            for (signal_name, input) in test_signals() {
+                let mut filter = Filter::new();
+                filter.set_sample_rate(SAMPLE_RATE);
+                filter.set_mode(mode);
+                filter.set_precision(precision);
+                let output = render(&input, |sample| filter.process(0, sample, 1000.0, 0.5));
+                assert_bounded(&format!("{mode:?} {precision:?} {signal_name}"), &output, 4.0);
+            }
+        }
+    }
+
+    for (signal_name, input) in test_signals() {
+        let mut filter = Filter::new();
+        let output = render(&input, |sample| filter.process(0, sample, 1000.0, 1.0));
+        assert_transparent(signal_name, &input, &output, 0.0);
+    }
+}
+
+#[test]
+fn fractal_is_bounded() {
+    for mode in MAGIC_MODES {
+        for precision in [Precision::Single, Precision::Double] {
+            for (signal_name, input) in test_signals() {
+                let mut fractal = FractalMagic::new();
+                fractal.set_sample_rate(SAMPLE_RATE);
+                fractal.set_mode(mode);
+                fractal.set_precision(precision);
+                let mut modulation = Vec::with_capacity(input.len());
+                let output = render(&input, |sample| {
+                    let output = fractal.process(0, sample, 1.0);
+                    modulation.push(fractal.modulation(0));
+                    output
+                });
+
+                // The output is soft clipped, and the modulation output is a plain audio signal too
+                assert_bounded(&format!("{mode:?} {precision:?} {signal_name}"), &output, 1.0);
+                assert_bounded(&format!("{mode:?} {precision:?} {signal_name} modulation"), &modulation, 1.0);
+            }
+        }
+    }
+}
+
+#[test]
+fn wavefolder_anti_aliasing_lowers_the_aliasing_floor() {
+    // A full scale sine at about 3.3 kHz, on bin 311 of the DFT, folded at 0.4 like full magic does.
+    // It's run for two periods of the DFT and only the second one is looked at, so the
+    // anti-aliasing's history is filled with the sine itself.
+    let fundamental_bin = 311;
+    let input = sine(2 * SIGNAL_LEN, fundamental_bin as f32 * SAMPLE_RATE / SIGNAL_LEN as f32, 1.0);
+    let aliasing_floor = |quality: FoldQuality| {
+        let mut folder = WaveFolder::new();
+        folder.set_quality(quality);
+        let output = render(&input, |sample| folder.process(0, sample, 2.5));
+        inharmonic_power_db(&output[SIGNAL_LEN..], fundamental_bin)
+    };
+
+    let off = aliasing_floor(FoldQuality::Off);
+    let first_order = aliasing_floor(FoldQuality::FirstOrder);
+    let second_order = aliasing_floor(FoldQuality::SecondOrder);
+    assert!(first_order < off - 6.0, "first order {first_order:.1} dB, plain fold {off:.1} dB");
+    assert!(second_order < first_order - 6.0, "second order {second_order:.1} dB, first order {first_order:.1} dB");
+
+    // Both stay bounded and smooth on the test signals too
+    for quality in [FoldQuality::FirstOrder, FoldQuality::SecondOrder] {
+        for (signal_name, input) in test_signals() {
+            let mut folder = WaveFolder::new();
+            folder.set_quality(quality);
+            let output = render(&input, |sample| folder.process(0, sample, 2.5));
+            assert_bounded(&format!("{quality:?} {signal_name}"), &output, 1.0);
+        }
+    }
+}
+
+#[test]
+fn fractal_is_transparent_at_zero() {
+    for (signal_name, input) in test_signals() {
+        let mut fractal = FractalMagic::new();
+        fractal.set_sample_rate(SAMPLE_RATE);
+        let output = render(&input, |sample| fractal.process(0, sample, 0.0));
+        assert_transparent(signal_name, &input, &output, 0.0);
+    }
+}
+
+#[test]
+fn spectral_fractal_is_bounded_and_a_plain_delay_at_zero() {
+    for (signal_name, input) in test_signals() {
+        let mut fractal = FractalMagic::new();
+        fractal.set_sample_rate(SAMPLE_RATE);
+        fractal.set_engine(MagicEngine::Spectral);
+        let output = render(&input, |sample| fractal.process(0, sample, 1.0));
+        assert_bounded(&format!("spectral {signal_name}"), &output, 1.0);
+
+        // Without magic the frames add back up to the input, just late by the reported latency
+        let mut fractal = FractalMagic::new();
+        fractal.set_engine(MagicEngine::Spectral);
+        let latency = fractal.latency();
+        let output = render(&input, |sample| fractal.process(0, sample, 0.0));
+        assert_transparent(signal_name, &input[..SIGNAL_LEN - latency], &output[latency..], 1e-5);
+    }
+}
+
+#[test]
+fn chaos_is_bounded() {
+    for chaos_type in CHAOS_TYPES {
+        for integrator in [Integrator::Euler, Integrator::RungeKutta4] {
+            for precision in [Precision::Single, Precision::Double] {
+                for (signal_name, input) in test_signals() {
+                    let mut chaos = ChaosAttractor::new();
+                    chaos.set_sample_rate(SAMPLE_RATE);
+                    chaos.set_chaos_type(chaos_type);
+                    chaos.set_integrator(integrator);
+                    chaos.set_precision(precision);
+                    let output = render(&input, |sample| chaos.process(0, sample, 1.0));
+
+                    // The output is soft clipped
+                    assert_bounded(&format!("{chaos_type:?} {integrator:?} {precision:?} {signal_name}"), &output, 1.0);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn chaos_is_bounded_at_the_slowest_and_fastest_rates() {
+    for chaos_type in CHAOS_TYPES {
+        for rate in [0.05, 16.0] {
+            for (signal_name, input) in test_signals() {
+                let mut chaos = ChaosAttractor::new();
+                chaos.set_sample_rate(SAMPLE_RATE);
+                chaos.set_chaos_type(chaos_type);
+                chaos.set_rate(rate);
+                let output = render(&input, |sample| chaos.process(0, sample, 1.0));
+                assert_bounded(&format!("{chaos_type:?} {rate}x {signal_name}"), &output, 1.0);
+            }
+        }
+    }
+}
+
+#[test]
+fn engine_snapshots_resume_where_they_were_taken_and_only_fit_their_own_system() {
+    let input = sine(SIGNAL_LEN, 441.0, 0.5);
+    for chaos_type in CHAOS_TYPES {
+        let mut chaos = ChaosAttractor::new();
+        chaos.set_sample_rate(SAMPLE_RATE);
+        chaos.set_chaos_type(chaos_type);
+        render(&input, |sample| chaos.process(0, sample, 1.0));
+        let snapshot = chaos.snapshot();
+
+        // A fresh attractor picks up right where the first one was
+        let mut resumed = ChaosAttractor::new();
+        resumed.set_sample_rate(SAMPLE_RATE);
+        resumed.set_chaos_type(chaos_type);
+        resumed.restore(&snapshot);
+        assert_eq!(resumed.snapshot(), snapshot, "{chaos_type:?}");
+
+        // Another system keeps its own starting conditions
+        let other_type = CHAOS_TYPES[(chaos_type as usize + 1) % CHAOS_TYPES.len()];
+        let mut other = ChaosAttractor::new();
+        other.set_chaos_type(other_type);
+        let untouched = other.snapshot();
+        other.restore(&snapshot);
+        assert_eq!(other.snapshot(), untouched, "{chaos_type:?} into {other_type:?}");
+    }
+
+    for mode in MAGIC_MODES {
+        let mut fractal = FractalMagic::new();
+        fractal.set_sample_rate(SAMPLE_RATE);
+        fractal.set_mode(mode);
+        render(&input, |sample| fractal.process(0, sample, 1.0));
+        let snapshot = fractal.snapshot();
+
+        let mut resumed = FractalMagic::new();
+        resumed.set_mode(mode);
+        resumed.restore(&snapshot);
+        assert_eq!(resumed.snapshot(), snapshot, "{mode:?}");
+
+        let other_mode = MAGIC_MODES[(mode as usize + 1) % MAGIC_MODES.len()];
+        let mut other = FractalMagic::new();
+        other.set_mode(other_mode);
+        let untouched = other.snapshot();
+        other.restore(&snapshot);
+        assert_eq!(other.snapshot(), untouched, "{mode:?} into {other_mode:?}");
+    }
+}
+
+#[test]
+fn chaos_color_smooths_the_chaotic_signal() {
+    // The largest jump of the chaos signal between two samples, at the fastest rate
+    let roughness = |color: f32| {
+        let mut chaos = ChaosAttractor::new();
+        chaos.set_sample_rate(SAMPLE_RATE);
+        chaos.set_rate(16.0);
+        chaos.set_color(color);
+        let mut previous = chaos.signal(0);
+        let mut largest_jump: f32 = 0.0;
+        for sample in sine(SIGNAL_LEN, 441.0, 0.5) {
+            chaos.process(0, sample, 1.0);
+            largest_jump = largest_jump.max((chaos.signal(0) - previous).abs());
+            previous = chaos.signal(0);
+        }
+        largest_jump
+    };
+
+    let (dark, full) = (roughness(0.0), roughness(1.0));
+    assert!(dark.is_finite() && dark < full * 0.1, "the darkest color jumps by {dark}, the full bandwidth by {full}");
+}
+
+#[test]
+fn frequency_shifter_moves_a_sine_up_without_a_mirror_image() {
+    // Frequencies that fall exactly on the bins of a DFT over the second half of the signal,
+    // once the allpass chains have settled
+    let bin_hz = SAMPLE_RATE / (SIGNAL_LEN / 2) as f32;
+    let mut shifter = FrequencyShifter::new();
+    shifter.set_sample_rate(SAMPLE_RATE);
+    let output = render(&sine(SIGNAL_LEN, 46.0 * bin_hz, 0.5), |sample| shifter.process(0, sample, 10.0 * bin_hz));
+    let tail = &output[SIGNAL_LEN / 2..];
+
+    // The amplitude of the tail at a bin
+    let level = |bin: usize| {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (index, sample) in tail.iter().enumerate() {
+            let angle = -2.0 * std::f64::consts::PI * (bin * index) as f64 / tail.len() as f64;
+            re += *sample as f64 * angle.cos();
+            im += *sample as f64 * angle.sin();
+        }
+        (re * re + im * im).sqrt() * 2.0 / tail.len() as f64
+    };
+
+    // All of the sine ends up 10 bins higher, ring modulation would leave half of it 10 bins lower
+    let (down, original, up) = (level(36), level(46), level(56));
+    assert!((up - 0.5).abs() < 0.01, "the shifted sine has an amplitude of {up}");
+    assert!(down < 0.005 && original < 0.005, "{down} is left 10 bins lower and {original} where it was");
+
+    // The chaos effect stays bounded with the shifter in its FM branch, at the deepest shift
+    for (signal_name, input) in test_signals() {
+        let mut chaos = ChaosAttractor::new();
+        chaos.set_sample_rate(SAMPLE_RATE);
+        chaos.set_fm_mode(FmMode::Shift);
+        chaos.set_shift_depth(1000.0);
+        let output = render(&input, |sample| chaos.process(0, sample, 1.0));
+        assert_bounded(&format!("shift {signal_name}"), &output, 1.0);
+    }
+}
+
+#[test]
+fn coupling_changes_nothing_at_zero_and_stays_bounded_at_full() {
+    for mode in MAGIC_MODES {
+        for (signal_name, input) in test_signals() {
+            // The chaos effect into the fractal, coupled as hard as the knob goes
+            let chain = |coupling: Option<f32>| {
+                let mut chaos = ChaosAttractor::new();
+                chaos.set_sample_rate(SAMPLE_RATE);
+                let mut fractal = FractalMagic::new();
+                fractal.set_sample_rate(SAMPLE_RATE);
+                fractal.set_mode(mode);
+                render(&input, |sample| {
+                    if let Some(coupling) = coupling {
+                        couple(&mut chaos, &mut fractal, 0, coupling);
+                    }
+                    fractal.process(0, chaos.process(0, sample, 1.0), 1.0)
+                })
+            };
+
+            let uncoupled = chain(None);
+            let name = format!("{mode:?} {signal_name}");
+            assert_transparent(&name, &uncoupled, &chain(Some(0.0)), 0.0);
+            assert_bounded(&name, &chain(Some(1.0)), 1.0);
+        }
+    }
+}
+
+#[test]
+fn stability_governor_reins_in_a_fractal_that_keeps_escaping_and_lets_go_once_it_calms_down() {
+    let chaos = ChaosAttractor::new();
+    let mut fractal = FractalMagic::new();
+    fractal.set_sample_rate(SAMPLE_RATE);
+    fractal.set_mode(MagicMode::Mandelbrot);
+    let mut governor = StabilityGovernor::new();
+    governor.set_sample_rate(SAMPLE_RATE);
+
+    // The stability at the end of a constant input, a loud one pushes c far outside of the set
+    let mut run = |input: f32, seconds: f32| {
+        let mut stability = 1.0;
+        for _ in 0..(SAMPLE_RATE * seconds) as usize {
+            stability = governor.supervise(0, &chaos, &mut fractal);
+            fractal.process(0, input, 1.0);
+        }
+        stability
+    };
+
+    assert_eq!(run(0.0, 0.5), 1.0, "the governor stepped in on silence");
+    let escaping = run(1.0, 0.5);
+    assert!(escaping < 0.1, "the stability only dropped to {escaping} while z kept escaping");
+    let recovered = run(0.0, 3.0);
+    assert!(recovered > 0.99, "the stability only came back to {recovered}");
+}
+
+#[test]
+fn quality_tiers_override_the_costly_settings_and_eco_chaos_stays_bounded() {
+    // Normal passes every setting through, the other two tiers decide for themselves
+    assert_eq!(Quality::Normal.oversampling(Oversampling::X4), Oversampling::X4);
+    assert_eq!(Quality::Normal.integrator(Integrator::RungeKutta4), Integrator::RungeKutta4);
+    assert_eq!(Quality::Normal.fold_quality(FoldQuality::FirstOrder), FoldQuality::FirstOrder);
+    assert_eq!(Quality::Eco.oversampling(Oversampling::X4), Oversampling::Off);
+    assert_eq!(Quality::Eco.integrator(Integrator::RungeKutta4), Integrator::Euler);
+    assert_eq!(Quality::Eco.fold_quality(FoldQuality::SecondOrder), FoldQuality::Off);
+    assert_eq!(Quality::Ultra.oversampling(Oversampling::Off), Oversampling::X8);
+    assert_eq!(Quality::Ultra.integrator(Integrator::Euler), Integrator::RungeKutta4);
+    assert_eq!(Quality::Ultra.fold_quality(FoldQuality::Off), FoldQuality::SecondOrder);
+
+    // At control rate the systems take four times the steps, which has to stay just as stable
+    for chaos_type in CHAOS_TYPES {
+        for (signal_name, input) in test_signals() {
+            let mut chaos = ChaosAttractor::new();
+            chaos.set_sample_rate(SAMPLE_RATE);
+            chaos.set_chaos_type(chaos_type);
+            chaos.set_quality(Quality::Eco);
+            chaos.set_rate(16.0);
+            let output = render(&input, |sample| chaos.process(0, sample, 1.0));
+            assert_bounded(&format!("{chaos_type:?} {signal_name}"), &output, 1.0);
+        }
+    }
+}
+
+#[test]
+fn chaos_is_transparent_at_zero() {
+    for (signal_name, input) in test_signals() {
+        let mut chaos = ChaosAttractor::new();
+        chaos.set_sample_rate(SAMPLE_RATE);
+        let output = render(&input, |sample| chaos.process(0, sample, 0.0));
+        assert_transparent(signal_name, &input, &output, 0.0);
+    }
+}
+
+#[test]
+fn crusher_is_bounded_and_transparent_at_full_resolution() {
+    for (signal_name, input) in test_signals() {
+        let mut crusher = Crusher::new();
+        let output = render(&input, |sample| crusher.process(0, sample, 1.0, 32.0));
+        assert_bounded(signal_name, &output, 1.0);
+
+        // 16 bits without any downsampling only rounds to the 16-bit grid
+        let mut crusher = Crusher::new();
+        let output = render(&input, |sample| crusher.process(0, sample, 16.0, 1.0));
+        assert_transparent(signal_name, &input, &output, 1.0 / 32768.0);
+    }
+}
+
+#[test]
+fn compressor_only_reduces_gain_and_does_nothing_at_one_to_one() {
+    for (signal_name, input) in test_signals() {
+        let mut compressor = Compressor::new();
+        compressor.set_sample_rate(SAMPLE_RATE);
+        compressor.set_curve(-30.0, 20.0);
+        compressor.set_times(1.0, 50.0);
+        compressor.set_chaotic_release(true);
+        let gains = render(&input, |sample| compressor.process(sample.abs(), 1.0));
+        for (index, gain) in gains.iter().enumerate() {
+            assert!(gain.is_finite() && (0.0..=1.0).contains(gain), "{signal_name}: gain {gain} at {index}");
+        }
+
+        let mut compressor = Compressor::new();
+        compressor.set_sample_rate(SAMPLE_RATE);
+        compressor.set_curve(-30.0, 1.0);
+        let output = render(&input, |sample| sample * compressor.process(sample.abs(), 0.0));
+        assert_transparent(signal_name, &input, &output, 0.0);
+    }
+}
+
+#[test]
+fn gate_is_transparent_when_off() {
+    for (signal_name, input) in test_signals() {
+        let mut gate = Gate::new();
+        gate.set_sample_rate(SAMPLE_RATE);
+        // The bottom of the threshold range switches the gate off
+        gate.set_threshold_db(-80.0);
+        gate.set_times(1.0, 10.0, 50.0);
+        let output = render(&input, |sample| sample * gate.process(sample.abs()));
+        assert_transparent(signal_name, &input, &output, 0.0);
+    }
+}
+
+#[test]
+fn gate_closes_on_silence() {
+    let mut gate = Gate::new();
+    gate.set_sample_rate(SAMPLE_RATE);
+    gate.set_threshold_db(-40.0);
+    gate.set_times(1.0, 1.0, 1.0);
+    let gains = render(&[0.0; SIGNAL_LEN], |level| gate.process(level));
+    assert!(gains[SIGNAL_LEN - 1] < 1e-3, "the gate is still open at {}", gains[SIGNAL_LEN - 1]);
+}
+
+#[test]
+fn delay_is_bounded_and_transparent_without_mix() {
+    for (signal_name, input) in test_signals() {
+        let mut delay = Delay::new();
+        delay.set_sample_rate(SAMPLE_RATE);
+        delay.set_time_ms(10.0);
+        let output = render(&input, |sample| delay.process(0, sample, 0.95, 0.5, 1.0, 1.0));
+        // The feedback is saturated, so the echoes can't run away
+        assert_bounded(signal_name, &output, 2.0);
+
+        let mut delay = Delay::new();
+        delay.set_sample_rate(SAMPLE_RATE);
+        let output = render(&input, |sample| delay.process(0, sample, 0.5, 0.0, 0.0, 0.0));
+        assert_transparent(signal_name, &input, &output, 0.0);
+    }
+}
+
+#[test]
+fn soft_clip_stays_within_one_and_leaves_quiet_signals_alone_with_every_character() {
+    for character in [0.0, 0.25, 0.5, 0.75, 1.0] {
+        let mut previous = f32::NEG_INFINITY;
+        for step in -2000..=2000 {
+            let input = step as f32 * 0.05;
+            let output = soft_clip(input, character);
+            assert!(output.abs() <= 1.0, "character {character}: {input} clipped to {output}");
+            assert!(output >= previous, "character {character}: the curve turns back at {input}");
+            previous = output;
+        }
+        assert_eq!(soft_clip(f32::INFINITY, character), 1.0, "character {character}");
+
+        // Quiet signals go through with a slope of 1
+        let quiet = soft_clip(0.01, character);
+        assert!((quiet - 0.01).abs() < 1e-5, "character {character}: 0.01 clipped to {quiet}");
+
+        let mut chaos = ChaosAttractor::new();
+        chaos.set_sample_rate(SAMPLE_RATE);
+        chaos.set_saturation(character);
+        let mut fractal = FractalMagic::new();
+        fractal.set_sample_rate(SAMPLE_RATE);
+        fractal.set_saturation(character);
+        for (signal_name, input) in test_signals() {
+            let output = render(&input, |sample| chaos.process(0, sample, 1.0));
+            assert_bounded(&format!("chaos {character} {signal_name}"), &output, 1.0);
+            let output = render(&input, |sample| fractal.process(0, sample, 1.0));
+            assert_bounded(&format!("fractal {character} {signal_name}"), &output, 1.0);
+        }
+    }
+
+    // No character is the tanh the chain always clipped with
+    assert_eq!(soft_clip(0.7, 0.0), 0.7f32.tanh());
+}
+
+#[test]
+fn reverb_is_bounded_and_transparent_without_mix() {
+    for (signal_name, input) in test_signals() {
+        let mut reverb = Reverb::new();
+        reverb.set_sample_rate(SAMPLE_RATE);
+        let output = render(&input, |sample| reverb.process(0, sample, 1.0, 0.0, 1.0));
+        // At the largest size the tail rings up on top of the input, but it never runs away
+        assert_bounded(signal_name, &output, 8.0);
+
+        let mut reverb = Reverb::new();
+        let output = render(&input, |sample| reverb.process(0, sample, 1.0, 0.5, 0.0));
+        assert_transparent(signal_name, &input, &output, 0.0);
+    }
+}
+
+#[test]
+fn tilt_eq_is_transparent_when_flat_and_turns_the_ends_of_the_spectrum_by_half_the_tilt() {
+    for (signal_name, input) in test_signals() {
+        let mut tilt_eq = TiltEq::new();
+        tilt_eq.set_sample_rate(SAMPLE_RATE);
+        let output = render(&input, |sample| tilt_eq.process(0, sample, 0.0));
+        assert_transparent(signal_name, &input, &output, 0.0);
+    }
+
+    // The level change of a sine in dB over the second half of a second, once the filters have settled
+    let level_change = |frequency: f32, tilt: f32| {
+        let mut tilt_eq = TiltEq::new();
+        tilt_eq.set_sample_rate(SAMPLE_RATE);
+        let len = SAMPLE_RATE as usize;
+        let input = sine(len, frequency, 0.5);
+        let output = render(&input, |sample| tilt_eq.process(0, sample, tilt));
+        let power = |signal: &[f32]| signal[len / 2..].iter().map(|sample| sample * sample).sum::<f32>();
+        10.0 * (power(&output) / power(&input)).log10()
+    };
+    for tilt in [-12.0, 6.0, 12.0] {
+        assert!((level_change(50.0, tilt) + tilt / 2.0).abs() < 0.25, "{tilt} dB at 50 Hz");
+        assert!((level_change(15000.0, tilt) - tilt / 2.0).abs() < 0.25, "{tilt} dB at 15 kHz");
+        assert!(level_change(800.0, tilt).abs() < 0.25, "{tilt} dB at the pivot");
+    }
+}
+
+#[test]
+fn crossover_bands_add_up_to_a_flat_allpass_and_keep_a_deep_sine_in_the_low_band() {
+    for precision in [Precision::Single, Precision::Double] {
+        for (signal_name, input) in test_signals() {
+            let mut crossover = Crossover::new();
+            crossover.set_sample_rate(SAMPLE_RATE);
+            crossover.set_precision(precision);
+            let bands: Vec<[f32; 3]> = input.iter().map(|&sample| crossover.split(0, sample)).collect();
+            for band in 0..3 {
+                let output: Vec<f32> = bands.iter().map(|bands| bands[band]).collect();
+                assert_bounded(&format!("band {band} of {signal_name} in {precision:?}"), &output, 1.5);
+            }
+        }
+    }
+
+    // The bands of an impulse add up to an allpass, so the magnitude of their sum is flat
+    let mut crossover = Crossover::new();
+    crossover.set_sample_rate(SAMPLE_RATE);
+    let sum = render(&impulse(SIGNAL_LEN), |sample| crossover.split(0, sample).iter().sum());
+    for bin in 1..SIGNAL_LEN / 2 {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (index, sample) in sum.iter().enumerate() {
+            let angle = -2.0 * std::f64::consts::PI * (bin * index) as f64 / SIGNAL_LEN as f64;
+            re += *sample as f64 * angle.cos();
+            im += *sample as f64 * angle.sin();
+        }
+        let magnitude_db = 10.0 * (re * re + im * im).log10();
+        assert!(magnitude_db.abs() < 0.05, "the bands add up to {magnitude_db} dB at bin {bin}");
+    }
+
+    // A sine two and a half octaves below the 200 Hz split hardly reaches the mid and high bands
+    let mut crossover = Crossover::new();
+    crossover.set_sample_rate(SAMPLE_RATE);
+    let len = SAMPLE_RATE as usize;
+    let bands: Vec<[f32; 3]> = sine(len, 35.0, 0.5).iter().map(|&sample| crossover.split(0, sample)).collect();
+    let power = |band: usize| bands[len / 2..].iter().map(|bands| bands[band] * bands[band]).sum::<f32>();
+    for band in 1..3 {
+        let level_db = 10.0 * (power(band) / power(0)).log10();
+        assert!(level_db < -40.0, "band {band} gets the sine at {level_db} dB below the low band");
+    }
+}
+
+#[test]
+fn crossover_in_64_bits_adds_up_flat_at_the_lowest_split_with_8x_oversampling() {
+    // In 32 bits the bands dip by almost 3 dB around 20 Hz here, the poles sit too close to 1.0
+    let sample_rate = SAMPLE_RATE * 8.0;
+    for frequency in [20.0, 40.0, 80.0] {
+        let mut crossover = Crossover::new();
+        crossover.set_sample_rate(sample_rate);
+        crossover.set_precision(Precision::Double);
+        crossover.set_frequencies(40.0, 1000.0);
+        // `sine()` counts in samples at `SAMPLE_RATE`, at 8 times the rate it comes out 8 times as high
+        let input = sine(sample_rate as usize, frequency / 8.0, 0.5);
+        let output = render(&input, |sample| crossover.split(0, sample).iter().sum());
+        let power = |signal: &[f32]| signal[signal.len() / 2..].iter().map(|sample| sample * sample).sum::<f32>();
+        let level_db = 10.0 * (power(&output) / power(&input)).log10();
+        assert!(level_db.abs() < 0.05, "the bands add up to {level_db} dB at {frequency} Hz");
+    }
+}
+
+#[test]
+fn input_calibration_brings_quiet_and_loud_inputs_to_the_same_level_and_holds_through_silence() {
+    // 40 seconds of a steady input at every level, as the power of the frames
+    let calibrated_level = |rms: f32| {
+        let mut calibration = InputCalibration::new();
+        calibration.set_sample_rate(SAMPLE_RATE);
+        let mut gain = 1.0;
+        for _ in 0..(SAMPLE_RATE as usize * 40) {
+            gain = calibration.process(rms * rms);
+        }
+        (calibration, gain * rms)
+    };
+
+    // -38 dBFS and -3 dBFS both end up at -18 dBFS
+    for rms in [0.0126, 0.126, 0.707] {
+        let (_, level) = calibrated_level(rms);
+        assert!((level / 0.126 - 1.0).abs() < 0.01, "{rms} RMS calibrated to {level}");
+    }
+
+    // The gain doesn't go past 24 dB, however quiet the input is
+    let (_, level) = calibrated_level(0.002);
+    assert!(level < 0.002 * 16.0, "0.002 RMS calibrated to {level}");
+
+    // Silence barely moves the gain, so the music comes back at about the level it left off at
+    let (mut calibration, _) = calibrated_level(0.707);
+    let before = calibration.process(0.5);
+    let mut after = before;
+    for _ in 0..(SAMPLE_RATE as usize * 10) {
+        after = calibration.process(0.0);
+    }
+    assert!((after / before - 1.0).abs() < 0.05, "the gain moved from {before} to {after} in silence");
+}
+
+#[test]
+fn dc_blocker_removes_offset() {
+    let mut dc_blocker = DcBlocker::new();
+    dc_blocker.set_sample_rate(SAMPLE_RATE);
+    let input: Vec<f32> = sine(SAMPLE_RATE as usize, 440.0, 0.5).iter().map(|sample| sample + 0.5).collect();
+    let output = render(&input, |sample| dc_blocker.process(0, sample));
+
+    // After a second the offset is gone, the average of the last full cycles is close to zero
+    let tail = &output[output.len() - 1002..];
+    let average = tail.iter().sum::<f32>() / tail.len() as f32;
+    assert!(average.abs() < 0.01, "the DC offset is still {average}");
+}
+
+#[test]
+fn gain_processor_is_transparent_when_centered_and_pans_with_constant_power() {
+    let mut gain_processor = GainProcessor::new();
+    gain_processor.set_sample_rate(SAMPLE_RATE);
+    for (signal_name, input) in test_signals() {
+        let output = render(&input, |sample| {
+            let mut frame = [sample, -sample];
+            gain_processor.process_frame(&mut frame, 1.0, 0.0, 0.0);
+            frame[0]
+        });
+        assert_transparent(signal_name, &input, &output, 1e-6);
+    }
+
+    // Panning the left channel all the way over keeps its power, just on the other side
+    for pan in [0.25, 0.5, 1.0] {
+        let mut frame = [1.0, 0.0];
+        gain_processor.process_frame(&mut frame, 1.0, pan, 0.0);
+        let power = frame[0] * frame[0] + frame[1] * frame[1];
+        assert!((power - 1.0).abs() < 1e-5, "pan {pan}: power {power}");
+    }
+}
+
+#[test]
+fn limiter_holds_the_ceiling() {
+    let ceiling = 0.5;
+    for (signal_name, input) in test_signals() {
+        let mut limiter = Limiter::new();
+        limiter.set_sample_rate(SAMPLE_RATE);
+        limiter.set_ceiling(ceiling);
+        let output = render(&input, |sample| {
+            let mut frame = [sample * 4.0];
+            limiter.process(&mut frame);
+            frame[0]
+        });
+        assert_bounded(signal_name, &output, ceiling + 1e-4);
+    }
+}
+
+#[test]
+fn hard_clipper_holds_the_ceiling_and_latches_its_indicator() {
+    let ceiling = 0.5;
+    for (signal_name, input) in test_signals() {
+        let mut clipper = HardClipper::new();
+        clipper.set_ceiling(ceiling);
+        let clipped = clipper.clip_indicator();
+
+        // Quiet enough to pass untouched, nothing to indicate
+        let output = render(&input, |sample| clipper.process(sample * 0.5));
+        assert_transparent(signal_name, &render(&input, |sample| sample * 0.5), &output, 0.0);
+        assert!(!clipped.load(Ordering::Relaxed), "{signal_name}: clipped without going over");
+
+        let output = render(&input, |sample| clipper.process(sample * 4.0));
+        assert_bounded(signal_name, &output, ceiling);
+        assert!(clipped.load(Ordering::Relaxed), "{signal_name}: the indicator didn't light up");
+    }
+}
+
+#[test]
+fn generator_stays_at_its_level_with_any_feedback_and_steps_onto_the_scale() {
+    for feedback_depth in [0.0, 0.5, 1.0] {
+        let mut generator = Generator::new();
+        generator.set_sample_rate(SAMPLE_RATE);
+
+        // Feeding a folded copy of the output back in, like the fractal would
+        let output = render(&[0.0; SIGNAL_LEN], |_| {
+            let sample = generator.process(0, 440.0, feedback_depth);
+            generator.feed_back(0, (sample * 8.0).sin());
+            sample
+        });
+        assert_bounded(&format!("feedback {feedback_depth}"), &output, 0.5);
+        assert!(output.iter().any(|sample| sample.abs() > 0.4), "feedback {feedback_depth}: too quiet");
+    }
+
+    // Free leaves the offset alone, the scales pull it onto their nearest step
+    assert_eq!(Scale::Free.quantize(1.3), 1.3);
+    assert_eq!(Scale::Chromatic.quantize(1.3), 1.0);
+    assert_eq!(Scale::Major.quantize(5.8), 5.0);
+    assert_eq!(Scale::Minor.quantize(-1.2), -2.0);
+    assert_eq!(Scale::Pentatonic.quantize(11.5), 12.0);
+}
+
+#[test]
+fn generator_glides_to_the_latest_held_note_and_falls_back_when_it_is_let_go() {
+    let mut generator = Generator::new();
+    generator.set_sample_rate(SAMPLE_RATE);
+    generator.set_glide(50.0);
+    let settle = |generator: &mut Generator| {
+        (0..SAMPLE_RATE as usize).for_each(|_| {
+            generator.next_voice();
+        });
+        generator.next_voice()
+    };
+
+    // Nothing plays before the first note, which starts right on its pitch
+    assert_eq!(generator.next_voice(), (0.0, 0.0));
+    generator.note_on(69);
+    assert_eq!(generator.next_voice().0, 440.0);
+
+    generator.note_on(81);
+    let (frequency, level) = settle(&mut generator);
+    assert!((frequency - 880.0).abs() < 0.01 && level == 1.0, "{frequency} Hz at {level}");
+
+    // Letting go of the latest note goes back to the one still held, and then fades out
+    generator.note_off(81);
+    let (frequency, _) = settle(&mut generator);
+    assert!((frequency - 440.0).abs() < 0.01, "{frequency} Hz");
+    generator.note_off(69);
+    assert_eq!(settle(&mut generator).1, 0.0);
+}
+
+#[test]
+fn preset_locks_leave_the_locked_parameters_out_of_a_preset() {
+    let preset = Preset {
+        name: String::from("Loud"),
+        values: [("gain", 2.0), ("mix", 0.5), ("drive", 12.0)]
+            .into_iter()
+            .map(|(id, value)| (id.to_string(), value))
+            .collect(),
+    };
+
+    // Nothing is locked by default, so the whole preset is loaded
+    assert_eq!(preset.without_locked(PresetLocks::default()), preset);
+
+    let mut locks = PresetLocks::default();
+    locks.toggle(PresetLock::OutputGain);
+    let unlocked = preset.without_locked(locks);
+    assert!(!unlocked.values.contains_key("gain"));
+    assert_eq!(unlocked.values.get("mix"), Some(&0.5));
+    assert_eq!(unlocked.values.get("drive"), Some(&12.0));
+
+    locks.toggle(PresetLock::Mix);
+    assert_eq!(preset.without_locked(locks).values.keys().collect::<Vec<_>>(), ["drive"]);
+
+    // Toggling again unlocks
+    locks.toggle(PresetLock::OutputGain);
+    assert!(preset.without_locked(locks).values.contains_key("gain"));
+}
+
+#[test]
+fn oversampler_passes_audio_through_with_its_latency() {
+    for oversampling in [Oversampling::Off, Oversampling::X2, Oversampling::X4, Oversampling::X8] {
+        let input = sine(SIGNAL_LEN, 1000.0, 0.5);
+        let mut oversampler = Oversampler::new(oversampling);
+        let output = render(&input, |sample| oversampler.process(0, sample, |sample| sample));
+
+        // Line the output up with the input, the halfband filters are flat at 1 kHz
+        let latency = oversampling.latency_samples() as usize;
+        assert_transparent(
+            &format!("{oversampling:?}"),
+            &input[..SIGNAL_LEN - latency],
+            &output[latency..],
+            0.01,
+        );
+    }
+}
+
+#[test]
+fn reported_latency_matches_the_measured_delay_of_an_impulse() {
+    // The fractal's lookahead at the longest the parameter goes, in samples at the host's rate
+    let max_lookahead = (0.005 * SAMPLE_RATE).round() as usize;
+    let input: Vec<f32> = impulse(SIGNAL_LEN).iter().map(|sample| sample * 0.5).collect();
+    let fractal_latencies = [(MagicEngine::Time, 0), (MagicEngine::Time, max_lookahead), (MagicEngine::Spectral, 0)];
+
+    for oversampling in [Oversampling::Off, Oversampling::X2, Oversampling::X4, Oversampling::X8] {
+        for (engine, lookahead) in fractal_latencies {
+            for limiter_in_chain in [false, true] {
+                // The latency-adding stages the way the plugin chains them, with nothing else
+                // in between that could move the impulse
+                let mut oversampler = Oversampler::new(oversampling);
+                let mut fractal = FractalMagic::new();
+                fractal.set_sample_rate(SAMPLE_RATE * oversampling.factor() as f32);
+                fractal.set_engine(engine);
+                fractal.set_max_lookahead(max_lookahead * oversampling.factor());
+                fractal.set_lookahead(lookahead * oversampling.factor());
+                let mut limiter = Limiter::new();
+                limiter.set_sample_rate(SAMPLE_RATE);
+
+                let report = LatencyReport::new(oversampling, &fractal, limiter_in_chain.then_some(&limiter));
+                let output = render(&input, |sample| {
+                    let mut frame = [oversampler.process(0, sample, |sample| fractal.process(0, sample, 0.0))];
+                    if limiter_in_chain {
+                        limiter.process(&mut frame);
+                    }
+                    frame[0]
+                });
+
+                // The impulse comes out where its largest sample is
+                let measured = output
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+                    .map(|(index, _)| index as u32);
+                let name = format!("{oversampling:?} {engine:?} {lookahead} limiter {limiter_in_chain}");
+                assert_eq!(measured, Some(report.total()), "{name}");
+                assert_eq!(report.total(), report.wet() + report.limiter, "{name}");
+            }
+        }
+    }
+}
+
+#[test]
+fn stereo_width_leaves_the_side_alone_at_100_percent_and_widens_mono_above() {
+    let input = sine(SIGNAL_LEN, 440.0, 0.5);
+    let mut stereo_width = StereoWidth::new();
+    stereo_width.set_sample_rate(SAMPLE_RATE);
+
+    // A mono signal has no side, at 100% and below it stays that way
+    for width in [0.0, 0.5, 1.0] {
+        let output = render(&input, |mid| stereo_width.process(mid, 0.0, width, 0.3));
+        assert!(output.iter().all(|side| *side == 0.0), "width {width}");
+    }
+
+    // At 200% the decorrelated mid shows up on the side
+    let output = render(&input, |mid| stereo_width.process(mid, 0.0, 2.0, 0.3));
+    assert_bounded("width 200%", &output, 1.5);
+    assert!(output.iter().any(|side| side.abs() > 0.1));
+}
+
+#[test]
+fn digital_meter_falls_20_db_within_the_decay_time_at_any_block_size() {
+    for decay_ms in [500.0, 2000.0] {
+        for block_size in [32, 512] {
+            let mut meter = TruePeakMeter::new();
+            meter.set_sample_rate(SAMPLE_RATE);
+            meter.set_decay(decay_ms);
+            let readings = meter.readings();
+
+            // A full scale block, then silence for the decay time
+            (0..block_size).for_each(|_| meter.push(0, 1.0));
+            meter.publish(block_size);
+            let num_blocks = (decay_ms / 1000.0 * SAMPLE_RATE / block_size as f32).round() as usize;
+            for _ in 0..num_blocks {
+                (0..block_size).for_each(|_| meter.push(0, 0.0));
+                meter.publish(block_size);
+            }
+
+            // The first silent block still sees the end of the full scale block, hence the tolerance
+            let level_db = 20.0 * readings.level.load(Ordering::Relaxed).log10();
+            assert!((level_db + 20.0).abs() < 1.0, "decay {decay_ms} ms, blocks of {block_size}: {level_db} dB");
+        }
+    }
+}
+
+#[test]
+fn activity_monitor_and_meters_go_quiet_when_the_host_stops_processing() {
+    let activity = ProcessingActivity::new();
+    assert!(!activity.is_active(), "active before the first block");
+    activity.mark();
+    assert!(activity.is_active(), "idle right after a block");
+    activity.clear();
+    assert!(!activity.is_active(), "still active after the plugin was deactivated");
+
+    // A meter that gets reset on deactivation doesn't hold on to the last block's level
+    let mut meter = TruePeakMeter::new();
+    meter.set_sample_rate(SAMPLE_RATE);
+    let readings = meter.readings();
+    (0..512).for_each(|_| meter.push(0, 1.0));
+    meter.publish(512);
+    assert!(readings.level.load(Ordering::Relaxed) > 0.5);
+    meter.reset();
+    assert_eq!(readings.level.load(Ordering::Relaxed), 0.0);
+}
+
+#[test]
+fn effect_chain_with_the_distortion_soloed_sounds_like_the_distortion_alone() {
+    // The fractal and the chaos effect still run and push each other around, but only the
+    // distortion and its tone filter are heard
+    let settings = ChainSettings {
+        solo: StageSolo { distortion: true, ..StageSolo::default() },
+        drive: 8.0,
+        filter_cutoff: 5000.0,
+        filter_resonance: 0.3,
+        magic: 0.8,
+        chaos: 0.8,
+        coupling: 1.0,
+        ..ChainSettings::default()
+    };
+    for (name, signal) in test_signals() {
+        let mut chain = EffectChain::new();
+        chain.set_sample_rate(SAMPLE_RATE);
+        chain.filter.set_mode(FilterMode::LowPass);
+        let mut distortion = Distortion::new();
+        distortion.set_sample_rate(SAMPLE_RATE);
+        let mut filter = Filter::new();
+        filter.set_sample_rate(SAMPLE_RATE);
+        filter.set_mode(FilterMode::LowPass);
+
+        let output = render(&signal, |sample| chain.process(0, sample, &settings).0);
+        let expected = render(&signal, |sample| filter.process(0, distortion.process(0, sample, 8.0), 5000.0, 0.3));
+        assert_transparent(name, &expected, &output, 1e-6);
+        assert_bounded(name, &output, 4.0);
+    }
+}
@@ -0,0 +1,80 @@
+// Renders every processor with fixed settings and compares the result to a stored snapshot
+// Any change to the sound of an effect shows up here, so refactors can't change it by accident
+mod common;
+
+use common::{SAMPLE_RATE, assert_snapshot, render, test_signals};
+use ret_gain::{
+    ChaosAttractor, ChaosType, Crusher, Distortion, DistortionMode, Filter, FilterMode, FractalMagic, MagicMode,
+    Reverb,
+};
+
+/// Render every test signal and compare it to the snapshot called `name-signal`
+fn snapshot_all(name: &str, mut make_process: impl FnMut() -> Box<dyn FnMut(f32) -> f32>) {
+    for (signal_name, input) in test_signals() {
+        let output = render(&input, make_process());
+        assert_snapshot(&format!("{name}-{signal_name}"), &output);
+    }
+}
+
+#[test]
+fn distortion_snapshots() {
+    for mode in [DistortionMode::Tanh, DistortionMode::Diode, DistortionMode::Foldback] {
+        snapshot_all(&format!("distortion-{mode:?}").to_lowercase(), || {
+            let mut distortion = Distortion::new();
+            distortion.set_sample_rate(SAMPLE_RATE);
+            distortion.set_mode(mode);
+            Box::new(move |sample| distortion.process(0, sample, 8.0))
+        });
+    }
+}
+
+#[test]
+fn filter_snapshots() {
+    snapshot_all("filter-lowpass", || {
+        let mut filter = Filter::new();
+        filter.set_sample_rate(SAMPLE_RATE);
+        filter.set_mode(FilterMode::LowPass);
+        Box::new(move |sample| filter.process(0, sample, 2000.0, 0.7))
+    });
+}
+
+#[test]
+fn fractal_snapshots() {
+    for mode in [MagicMode::Julia, MagicMode::Newton] {
+        snapshot_all(&format!("fractal-{mode:?}").to_lowercase(), || {
+            let mut fractal = FractalMagic::new();
+            fractal.set_sample_rate(SAMPLE_RATE);
+            fractal.set_mode(mode);
+            Box::new(move |sample| fractal.process(0, sample, 0.7))
+        });
+    }
+}
+
+#[test]
+fn chaos_snapshots() {
+    for chaos_type in [ChaosType::Lorenz, ChaosType::Chua] {
+        snapshot_all(&format!("chaos-{chaos_type:?}").to_lowercase(), || {
+            let mut chaos = ChaosAttractor::new();
+            chaos.set_sample_rate(SAMPLE_RATE);
+            chaos.set_chaos_type(chaos_type);
+            Box::new(move |sample| chaos.process(0, sample, 0.6))
+        });
+    }
+}
+
+#[test]
+fn crusher_snapshots() {
+    snapshot_all("crusher", || {
+        let mut crusher = Crusher::new();
+        Box::new(move |sample| crusher.process(0, sample, 6.0, 4.5))
+    });
+}
+
+#[test]
+fn reverb_snapshots() {
+    snapshot_all("reverb", || {
+        let mut reverb = Reverb::new();
+        reverb.set_sample_rate(SAMPLE_RATE);
+        Box::new(move |sample| reverb.process(0, sample, 0.7, 0.5, 0.4))
+    });
+}